@@ -0,0 +1,12 @@
+CREATE TABLE IngredientPurchase (
+	id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+	creation_date INTEGER NOT NULL DEFAULT (unixepoch() * 1000),
+	purchase_date INTEGER NOT NULL DEFAULT (unixepoch() * 1000),
+	ingredient_id INTEGER NOT NULL REFERENCES Ingredient(id),
+	-- Price in the smallest unit of the currency (cents, for example)
+	price INTEGER NOT NULL,
+	currency TEXT NOT NULL,
+	-- Package weight, in grams
+	package_weight INTEGER NOT NULL,
+	store TEXT
+) STRICT;
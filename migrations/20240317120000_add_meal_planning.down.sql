@@ -0,0 +1,7 @@
+DROP TABLE RecurringMealPlanIngredient;
+DROP TABLE RecurringMealPlanDish;
+DROP TABLE PlannedMealIngredient;
+DROP TABLE PlannedMealDish;
+DROP TABLE PlannedMeal;
+DROP TABLE RecurringMealPlan;
+DROP TABLE NutritionGoal;
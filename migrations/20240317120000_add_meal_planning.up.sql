@@ -0,0 +1,63 @@
+CREATE TABLE NutritionGoal (
+	nutrient TEXT PRIMARY KEY NOT NULL,
+	creation_date INTEGER NOT NULL DEFAULT (unixepoch() * 1000),
+	daily_min REAL,
+	daily_max REAL
+) STRICT;
+
+CREATE TABLE RecurringMealPlan (
+	id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+	creation_date INTEGER NOT NULL DEFAULT (unixepoch() * 1000),
+	description TEXT,
+	-- Date of the first occurrence. Its time of day is used for every occurrence
+	start_date INTEGER NOT NULL,
+	end_date INTEGER,
+	-- Bitmask of the weekdays in which the plan occurs. Monday is 1, Tuesday is 2, Wednesday is 4...
+	weekdays INTEGER NOT NULL,
+	-- Offset of the user's timezone, used to decide which weekday an occurrence falls in
+	utc_offset_minutes INTEGER NOT NULL DEFAULT 0,
+	-- Occurrences were already created up to this date
+	scheduled_until INTEGER NOT NULL
+) STRICT;
+
+CREATE TABLE PlannedMeal (
+	id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+	creation_date INTEGER NOT NULL DEFAULT (unixepoch() * 1000),
+	plan_date INTEGER NOT NULL,
+	description TEXT,
+	recurring_plan_id INTEGER REFERENCES RecurringMealPlan(id),
+	-- The meal created once this plan was confirmed as eaten
+	meal_id INTEGER REFERENCES Meal(id)
+) STRICT;
+
+CREATE TABLE PlannedMealDish (
+	creation_date INTEGER NOT NULL DEFAULT (unixepoch() * 1000),
+	planned_meal_id INTEGER REFERENCES PlannedMeal(id),
+	dish_id INTEGER REFERENCES Dish(id),
+	weight INTEGER NOT NULL,
+	PRIMARY KEY(planned_meal_id, dish_id)
+) STRICT;
+
+CREATE TABLE PlannedMealIngredient (
+	creation_date INTEGER NOT NULL DEFAULT (unixepoch() * 1000),
+	planned_meal_id INTEGER REFERENCES PlannedMeal(id),
+	ingredient_id INTEGER REFERENCES Ingredient(id),
+	weight INTEGER NOT NULL,
+	PRIMARY KEY(planned_meal_id, ingredient_id)
+) STRICT;
+
+CREATE TABLE RecurringMealPlanDish (
+	creation_date INTEGER NOT NULL DEFAULT (unixepoch() * 1000),
+	recurring_plan_id INTEGER REFERENCES RecurringMealPlan(id),
+	dish_id INTEGER REFERENCES Dish(id),
+	weight INTEGER NOT NULL,
+	PRIMARY KEY(recurring_plan_id, dish_id)
+) STRICT;
+
+CREATE TABLE RecurringMealPlanIngredient (
+	creation_date INTEGER NOT NULL DEFAULT (unixepoch() * 1000),
+	recurring_plan_id INTEGER REFERENCES RecurringMealPlan(id),
+	ingredient_id INTEGER REFERENCES Ingredient(id),
+	weight INTEGER NOT NULL,
+	PRIMARY KEY(recurring_plan_id, ingredient_id)
+) STRICT;
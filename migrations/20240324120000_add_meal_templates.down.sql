@@ -0,0 +1,4 @@
+DROP TABLE MealTemplateUsage;
+DROP TABLE MealTemplateIngredient;
+DROP TABLE MealTemplateDish;
+DROP TABLE MealTemplate;
@@ -0,0 +1,30 @@
+CREATE TABLE MealTemplate (
+	id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+	creation_date INTEGER NOT NULL DEFAULT (unixepoch() * 1000),
+	name TEXT NOT NULL UNIQUE,
+	-- Description given to the meals created from this template, such as "Breakfast"
+	description TEXT
+) STRICT;
+
+CREATE TABLE MealTemplateDish (
+	creation_date INTEGER NOT NULL DEFAULT (unixepoch() * 1000),
+	template_id INTEGER REFERENCES MealTemplate(id),
+	dish_id INTEGER REFERENCES Dish(id),
+	weight INTEGER NOT NULL,
+	PRIMARY KEY(template_id, dish_id)
+) STRICT;
+
+CREATE TABLE MealTemplateIngredient (
+	creation_date INTEGER NOT NULL DEFAULT (unixepoch() * 1000),
+	template_id INTEGER REFERENCES MealTemplate(id),
+	ingredient_id INTEGER REFERENCES Ingredient(id),
+	weight INTEGER NOT NULL,
+	PRIMARY KEY(template_id, ingredient_id)
+) STRICT;
+
+CREATE TABLE MealTemplateUsage (
+	creation_date INTEGER NOT NULL DEFAULT (unixepoch() * 1000),
+	template_id INTEGER REFERENCES MealTemplate(id),
+	meal_id INTEGER REFERENCES Meal(id),
+	PRIMARY KEY(template_id, meal_id)
+) STRICT;
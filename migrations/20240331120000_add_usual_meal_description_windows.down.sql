@@ -0,0 +1,3 @@
+ALTER TABLE UsualMealDescriptions DROP COLUMN end_minute;
+ALTER TABLE UsualMealDescriptions DROP COLUMN start_minute;
+ALTER TABLE UsualMealDescriptions DROP COLUMN position;
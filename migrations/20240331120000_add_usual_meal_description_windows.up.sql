@@ -0,0 +1,10 @@
+ALTER TABLE UsualMealDescriptions ADD COLUMN position INTEGER NOT NULL DEFAULT 0;
+-- Typical time window of the meal, in minutes since midnight. The window wraps around midnight
+-- if start_minute is larger than end_minute
+ALTER TABLE UsualMealDescriptions ADD COLUMN start_minute INTEGER;
+ALTER TABLE UsualMealDescriptions ADD COLUMN end_minute INTEGER;
+
+UPDATE UsualMealDescriptions SET start_minute = 1080, end_minute = 1320 WHERE description = 'Dinner';
+UPDATE UsualMealDescriptions SET start_minute = 690, end_minute = 840 WHERE description = 'Work Lunch';
+UPDATE UsualMealDescriptions SET start_minute = 690, end_minute = 870 WHERE description = 'Lunch';
+UPDATE UsualMealDescriptions SET start_minute = 360, end_minute = 600 WHERE description = 'Breakfast';
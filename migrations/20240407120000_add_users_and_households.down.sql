@@ -0,0 +1,81 @@
+CREATE TABLE IngredientPropertiesOld (
+	ingredient_id INTEGER PRIMARY KEY NOT NULL,
+	product_code TEXT UNIQUE NOT NULL,
+	open_food_facts_json JSON NOT NULL,
+
+	kcal_100g INTEGER AS (json_extract(open_food_facts_json, '$.product.nutriments.energy-kcal_100g')) STORED,
+	product_name TEXT AS (json_extract(open_food_facts_json, '$.product.product_name')) VIRTUAL,
+	proteins_100g INTEGER AS (json_extract(open_food_facts_json, '$.product.nutriments.proteins_100g')) VIRTUAL,
+	fat_100g INTEGER AS (json_extract(open_food_facts_json, '$.product.nutriments.fat_100g')) VIRTUAL,
+	carbohydrates_100g INTEGER AS (json_extract(open_food_facts_json, '$.product.nutriments.carbohydrates_100g')) VIRTUAL,
+	FOREIGN KEY (ingredient_id) REFERENCES Ingredient (id)
+);
+INSERT OR IGNORE INTO IngredientPropertiesOld (ingredient_id, product_code, open_food_facts_json)
+SELECT ingredient_id, product_code, open_food_facts_json FROM IngredientProperties;
+DROP TABLE IngredientProperties;
+ALTER TABLE IngredientPropertiesOld RENAME TO IngredientProperties;
+
+CREATE TABLE MealTemplateOld (
+	id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+	creation_date INTEGER NOT NULL DEFAULT (unixepoch() * 1000),
+	name TEXT NOT NULL UNIQUE,
+	description TEXT
+) STRICT;
+CREATE TABLE MealTemplateDishOld (
+	creation_date INTEGER NOT NULL DEFAULT (unixepoch() * 1000),
+	template_id INTEGER REFERENCES MealTemplateOld(id),
+	dish_id INTEGER REFERENCES Dish(id),
+	weight INTEGER NOT NULL,
+	PRIMARY KEY(template_id, dish_id)
+) STRICT;
+CREATE TABLE MealTemplateIngredientOld (
+	creation_date INTEGER NOT NULL DEFAULT (unixepoch() * 1000),
+	template_id INTEGER REFERENCES MealTemplateOld(id),
+	ingredient_id INTEGER REFERENCES Ingredient(id),
+	weight INTEGER NOT NULL,
+	PRIMARY KEY(template_id, ingredient_id)
+) STRICT;
+CREATE TABLE MealTemplateUsageOld (
+	creation_date INTEGER NOT NULL DEFAULT (unixepoch() * 1000),
+	template_id INTEGER REFERENCES MealTemplateOld(id),
+	meal_id INTEGER REFERENCES Meal(id),
+	PRIMARY KEY(template_id, meal_id)
+) STRICT;
+INSERT INTO MealTemplateOld (id, creation_date, name, description)
+SELECT MIN(id), MIN(creation_date), name, MIN(description) FROM MealTemplate GROUP BY name;
+INSERT INTO MealTemplateDishOld SELECT * FROM MealTemplateDish
+WHERE template_id IN (SELECT id FROM MealTemplateOld);
+INSERT INTO MealTemplateIngredientOld SELECT * FROM MealTemplateIngredient
+WHERE template_id IN (SELECT id FROM MealTemplateOld);
+INSERT INTO MealTemplateUsageOld SELECT * FROM MealTemplateUsage
+WHERE template_id IN (SELECT id FROM MealTemplateOld);
+DROP TABLE MealTemplateUsage;
+DROP TABLE MealTemplateIngredient;
+DROP TABLE MealTemplateDish;
+DROP TABLE MealTemplate;
+ALTER TABLE MealTemplateOld RENAME TO MealTemplate;
+ALTER TABLE MealTemplateDishOld RENAME TO MealTemplateDish;
+ALTER TABLE MealTemplateIngredientOld RENAME TO MealTemplateIngredient;
+ALTER TABLE MealTemplateUsageOld RENAME TO MealTemplateUsage;
+
+CREATE TABLE NutritionGoalOld (
+	nutrient TEXT PRIMARY KEY NOT NULL,
+	creation_date INTEGER NOT NULL DEFAULT (unixepoch() * 1000),
+	daily_min REAL,
+	daily_max REAL
+) STRICT;
+INSERT OR IGNORE INTO NutritionGoalOld (nutrient, creation_date, daily_min, daily_max)
+SELECT nutrient, creation_date, daily_min, daily_max FROM NutritionGoal;
+DROP TABLE NutritionGoal;
+ALTER TABLE NutritionGoalOld RENAME TO NutritionGoal;
+
+ALTER TABLE RecurringMealPlan DROP COLUMN user_id;
+ALTER TABLE PlannedMeal DROP COLUMN user_id;
+ALTER TABLE Meal DROP COLUMN user_id;
+ALTER TABLE UsualMealDescriptions DROP COLUMN household_id;
+ALTER TABLE Dish DROP COLUMN household_id;
+ALTER TABLE Ingredient DROP COLUMN household_id;
+
+DROP TABLE Session;
+DROP TABLE User;
+DROP TABLE Household;
@@ -0,0 +1,111 @@
+CREATE TABLE Household (
+	id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+	creation_date INTEGER NOT NULL DEFAULT (unixepoch() * 1000),
+	name TEXT NOT NULL,
+	-- Code other users can register with to join this household
+	invite_code TEXT NOT NULL UNIQUE
+) STRICT;
+
+CREATE TABLE User (
+	id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+	creation_date INTEGER NOT NULL DEFAULT (unixepoch() * 1000),
+	username TEXT NOT NULL UNIQUE,
+	password_hash TEXT NOT NULL,
+	household_id INTEGER NOT NULL REFERENCES Household(id)
+) STRICT;
+
+CREATE TABLE Session (
+	-- Only the SHA-256 of the token is stored
+	token_hash TEXT PRIMARY KEY NOT NULL,
+	creation_date INTEGER NOT NULL DEFAULT (unixepoch() * 1000),
+	user_id INTEGER NOT NULL REFERENCES User(id),
+	expiration_date INTEGER NOT NULL,
+	last_used_date INTEGER
+) STRICT;
+
+-- Rows that existed before users were introduced have no owner. They are given to the first user
+-- that registers.
+ALTER TABLE Ingredient ADD COLUMN household_id INTEGER REFERENCES Household(id);
+ALTER TABLE Dish ADD COLUMN household_id INTEGER REFERENCES Household(id);
+ALTER TABLE UsualMealDescriptions ADD COLUMN household_id INTEGER REFERENCES Household(id);
+ALTER TABLE Meal ADD COLUMN user_id INTEGER REFERENCES User(id);
+ALTER TABLE PlannedMeal ADD COLUMN user_id INTEGER REFERENCES User(id);
+ALTER TABLE RecurringMealPlan ADD COLUMN user_id INTEGER REFERENCES User(id);
+
+-- Goals were unique per nutrient, and now are unique per user and nutrient
+CREATE TABLE NutritionGoalNew (
+	id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+	user_id INTEGER REFERENCES User(id),
+	nutrient TEXT NOT NULL,
+	creation_date INTEGER NOT NULL DEFAULT (unixepoch() * 1000),
+	daily_min REAL,
+	daily_max REAL,
+	UNIQUE(user_id, nutrient)
+) STRICT;
+INSERT INTO NutritionGoalNew (nutrient, creation_date, daily_min, daily_max)
+SELECT nutrient, creation_date, daily_min, daily_max FROM NutritionGoal;
+DROP TABLE NutritionGoal;
+ALTER TABLE NutritionGoalNew RENAME TO NutritionGoal;
+
+-- Template names were globally unique, and now are unique per user. The tables that reference
+-- templates are recreated as well, so no foreign key is left dangling.
+CREATE TABLE MealTemplateNew (
+	id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+	creation_date INTEGER NOT NULL DEFAULT (unixepoch() * 1000),
+	user_id INTEGER REFERENCES User(id),
+	name TEXT NOT NULL,
+	description TEXT,
+	UNIQUE(user_id, name)
+) STRICT;
+CREATE TABLE MealTemplateDishNew (
+	creation_date INTEGER NOT NULL DEFAULT (unixepoch() * 1000),
+	template_id INTEGER REFERENCES MealTemplateNew(id),
+	dish_id INTEGER REFERENCES Dish(id),
+	weight INTEGER NOT NULL,
+	PRIMARY KEY(template_id, dish_id)
+) STRICT;
+CREATE TABLE MealTemplateIngredientNew (
+	creation_date INTEGER NOT NULL DEFAULT (unixepoch() * 1000),
+	template_id INTEGER REFERENCES MealTemplateNew(id),
+	ingredient_id INTEGER REFERENCES Ingredient(id),
+	weight INTEGER NOT NULL,
+	PRIMARY KEY(template_id, ingredient_id)
+) STRICT;
+CREATE TABLE MealTemplateUsageNew (
+	creation_date INTEGER NOT NULL DEFAULT (unixepoch() * 1000),
+	template_id INTEGER REFERENCES MealTemplateNew(id),
+	meal_id INTEGER REFERENCES Meal(id),
+	PRIMARY KEY(template_id, meal_id)
+) STRICT;
+INSERT INTO MealTemplateNew (id, creation_date, name, description)
+SELECT id, creation_date, name, description FROM MealTemplate;
+INSERT INTO MealTemplateDishNew SELECT * FROM MealTemplateDish;
+INSERT INTO MealTemplateIngredientNew SELECT * FROM MealTemplateIngredient;
+INSERT INTO MealTemplateUsageNew SELECT * FROM MealTemplateUsage;
+DROP TABLE MealTemplateUsage;
+DROP TABLE MealTemplateIngredient;
+DROP TABLE MealTemplateDish;
+DROP TABLE MealTemplate;
+ALTER TABLE MealTemplateNew RENAME TO MealTemplate;
+ALTER TABLE MealTemplateDishNew RENAME TO MealTemplateDish;
+ALTER TABLE MealTemplateIngredientNew RENAME TO MealTemplateIngredient;
+ALTER TABLE MealTemplateUsageNew RENAME TO MealTemplateUsage;
+
+-- Product codes were globally unique, which would let a household overwrite another household's
+-- ingredient properties
+CREATE TABLE IngredientPropertiesNew (
+	ingredient_id INTEGER PRIMARY KEY NOT NULL,
+	product_code TEXT NOT NULL,
+	open_food_facts_json JSON NOT NULL,
+
+	kcal_100g INTEGER AS (json_extract(open_food_facts_json, '$.product.nutriments.energy-kcal_100g')) STORED,
+	product_name TEXT AS (json_extract(open_food_facts_json, '$.product.product_name')) VIRTUAL,
+	proteins_100g INTEGER AS (json_extract(open_food_facts_json, '$.product.nutriments.proteins_100g')) VIRTUAL,
+	fat_100g INTEGER AS (json_extract(open_food_facts_json, '$.product.nutriments.fat_100g')) VIRTUAL,
+	carbohydrates_100g INTEGER AS (json_extract(open_food_facts_json, '$.product.nutriments.carbohydrates_100g')) VIRTUAL,
+	FOREIGN KEY (ingredient_id) REFERENCES Ingredient (id)
+);
+INSERT INTO IngredientPropertiesNew (ingredient_id, product_code, open_food_facts_json)
+SELECT ingredient_id, product_code, open_food_facts_json FROM IngredientProperties;
+DROP TABLE IngredientProperties;
+ALTER TABLE IngredientPropertiesNew RENAME TO IngredientProperties;
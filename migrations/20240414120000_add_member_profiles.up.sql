@@ -0,0 +1,2 @@
+-- Name shown to the other members of the household. The username is used when it's not set
+ALTER TABLE User ADD COLUMN display_name TEXT;
@@ -0,0 +1,12 @@
+-- Long lived tokens used by scripts and integrations. They act on behalf of the user that created
+-- them, limited to what their scope allows.
+CREATE TABLE ApiToken (
+	id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+	creation_date INTEGER NOT NULL DEFAULT (unixepoch() * 1000),
+	user_id INTEGER NOT NULL REFERENCES User(id),
+	name TEXT NOT NULL,
+	scope TEXT NOT NULL CHECK (scope IN ('ReadOnly', 'LogMeals', 'Admin')),
+	-- Only the SHA-256 of the token is stored
+	token_hash TEXT NOT NULL UNIQUE,
+	last_used_date INTEGER
+) STRICT;
@@ -0,0 +1,2 @@
+DROP TABLE ShareComment;
+DROP TABLE Share;
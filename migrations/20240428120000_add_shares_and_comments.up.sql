@@ -0,0 +1,33 @@
+-- Read only access to a user's meals in a date range, given through a link to someone outside the
+-- household, such as a dietitian
+CREATE TABLE Share (
+	id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+	creation_date INTEGER NOT NULL DEFAULT (unixepoch() * 1000),
+	user_id INTEGER NOT NULL REFERENCES User(id),
+	-- Who the share is for, such as "Dietitian"
+	name TEXT NOT NULL,
+	-- Only the SHA-256 of the token is stored
+	token_hash TEXT NOT NULL UNIQUE,
+	-- Only meals eaten between these dates are shared
+	start_date INTEGER NOT NULL,
+	end_date INTEGER NOT NULL,
+	-- Hides the free text descriptions of the meals from the guest
+	redact_descriptions INTEGER NOT NULL DEFAULT FALSE,
+	expiration_date INTEGER,
+	last_used_date INTEGER
+) STRICT;
+
+-- Comments left by the guest of a share, or by the owner replying to them. They are attached to
+-- either a meal or a whole day
+CREATE TABLE ShareComment (
+	id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+	creation_date INTEGER NOT NULL DEFAULT (unixepoch() * 1000),
+	share_id INTEGER NOT NULL REFERENCES Share(id),
+	meal_id INTEGER REFERENCES Meal(id),
+	-- Day in the `YYYY-MM-DD` format
+	day TEXT,
+	reply_to_id INTEGER REFERENCES ShareComment(id),
+	author TEXT NOT NULL CHECK (author IN ('Guest', 'Owner')),
+	body TEXT NOT NULL,
+	CHECK ((meal_id IS NULL) <> (day IS NULL))
+) STRICT;
@@ -0,0 +1,40 @@
+DROP TRIGGER MealIngredientSyncDelete;
+DROP TRIGGER MealIngredientSyncUpdate;
+DROP TRIGGER MealIngredientSyncInsert;
+DROP INDEX MealIngredientUuid;
+ALTER TABLE MealIngredient DROP COLUMN uuid;
+
+DROP TRIGGER MealDishSyncDelete;
+DROP TRIGGER MealDishSyncUpdate;
+DROP TRIGGER MealDishSyncInsert;
+DROP INDEX MealDishUuid;
+ALTER TABLE MealDish DROP COLUMN uuid;
+
+DROP TRIGGER DishIngredientSyncDelete;
+DROP TRIGGER DishIngredientSyncUpdate;
+DROP TRIGGER DishIngredientSyncInsert;
+DROP INDEX DishIngredientUuid;
+ALTER TABLE DishIngredient DROP COLUMN uuid;
+
+DROP TRIGGER MealSyncDelete;
+DROP TRIGGER MealSyncUpdate;
+DROP TRIGGER MealSyncInsert;
+DROP INDEX MealUuid;
+ALTER TABLE Meal DROP COLUMN uuid;
+
+DROP TRIGGER DishSyncDelete;
+DROP TRIGGER DishSyncUpdate;
+DROP TRIGGER DishSyncInsert;
+DROP INDEX DishUuid;
+ALTER TABLE Dish DROP COLUMN uuid;
+
+DROP TRIGGER IngredientSyncDelete;
+DROP TRIGGER IngredientSyncUpdate;
+DROP TRIGGER IngredientSyncInsert;
+DROP INDEX IngredientUuid;
+ALTER TABLE Ingredient DROP COLUMN uuid;
+
+DROP VIEW NewUuid;
+
+DROP INDEX SyncChangeEntity;
+DROP TABLE SyncChange;
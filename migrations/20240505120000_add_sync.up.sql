@@ -0,0 +1,173 @@
+-- Every change to the tables that can be synced, so clients can fetch what changed since the
+-- last change they saw. Changes are recorded by the triggers below, whichever endpoint made them.
+CREATE TABLE SyncChange (
+	-- Also used as the cursor of the change feed
+	id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+	creation_date INTEGER NOT NULL DEFAULT (unixepoch() * 1000),
+	household_id INTEGER REFERENCES Household(id),
+	-- Set for changes only the user should see, such as the ones to their meals
+	user_id INTEGER REFERENCES User(id),
+	entity TEXT NOT NULL,
+	entity_uuid TEXT NOT NULL,
+	operation TEXT NOT NULL CHECK (operation IN ('Upsert', 'Delete'))
+) STRICT;
+
+CREATE INDEX SyncChangeEntity ON SyncChange(entity, entity_uuid);
+
+-- Rows created by the server get a random UUID (version 4) on insertion. Clients working offline
+-- generate their own.
+
+-- A new UUID every time it's read from a trigger. Other statements read it once, and use that
+-- same UUID for every row, so existing rows get theirs from a trigger too.
+CREATE VIEW NewUuid AS
+SELECT lower(hex(randomblob(4)) || '-' || hex(randomblob(2)) || '-4' || substr(hex(randomblob(2)), 2) || '-' || substr('89ab', 1 + abs(random()) % 4, 1) || substr(hex(randomblob(2)), 2) || '-' || hex(randomblob(6))) AS uuid;
+
+ALTER TABLE Ingredient ADD COLUMN uuid TEXT;
+CREATE TRIGGER IngredientUuidBackfill AFTER UPDATE OF uuid ON Ingredient WHEN NEW.uuid IS NULL BEGIN
+	UPDATE Ingredient SET uuid = (SELECT uuid FROM NewUuid) WHERE id = NEW.id;
+END;
+UPDATE Ingredient SET uuid = NULL;
+DROP TRIGGER IngredientUuidBackfill;
+CREATE UNIQUE INDEX IngredientUuid ON Ingredient(uuid);
+
+CREATE TRIGGER IngredientSyncInsert AFTER INSERT ON Ingredient BEGIN
+	UPDATE Ingredient SET uuid = (SELECT uuid FROM NewUuid)
+	WHERE id = NEW.id AND uuid IS NULL;
+	INSERT INTO SyncChange (household_id, user_id, entity, entity_uuid, operation)
+	VALUES (NEW.household_id, NULL, 'Ingredient', (SELECT uuid FROM Ingredient WHERE id = NEW.id), 'Upsert');
+END;
+
+CREATE TRIGGER IngredientSyncUpdate AFTER UPDATE ON Ingredient WHEN OLD.uuid IS NOT NULL BEGIN
+	INSERT INTO SyncChange (household_id, user_id, entity, entity_uuid, operation)
+	VALUES (NEW.household_id, NULL, 'Ingredient', NEW.uuid, 'Upsert');
+END;
+
+CREATE TRIGGER IngredientSyncDelete AFTER DELETE ON Ingredient BEGIN
+	INSERT INTO SyncChange (household_id, user_id, entity, entity_uuid, operation)
+	VALUES (OLD.household_id, NULL, 'Ingredient', OLD.uuid, 'Delete');
+END;
+
+ALTER TABLE Dish ADD COLUMN uuid TEXT;
+CREATE TRIGGER DishUuidBackfill AFTER UPDATE OF uuid ON Dish WHEN NEW.uuid IS NULL BEGIN
+	UPDATE Dish SET uuid = (SELECT uuid FROM NewUuid) WHERE id = NEW.id;
+END;
+UPDATE Dish SET uuid = NULL;
+DROP TRIGGER DishUuidBackfill;
+CREATE UNIQUE INDEX DishUuid ON Dish(uuid);
+
+CREATE TRIGGER DishSyncInsert AFTER INSERT ON Dish BEGIN
+	UPDATE Dish SET uuid = (SELECT uuid FROM NewUuid)
+	WHERE id = NEW.id AND uuid IS NULL;
+	INSERT INTO SyncChange (household_id, user_id, entity, entity_uuid, operation)
+	VALUES (NEW.household_id, NULL, 'Dish', (SELECT uuid FROM Dish WHERE id = NEW.id), 'Upsert');
+END;
+
+CREATE TRIGGER DishSyncUpdate AFTER UPDATE ON Dish WHEN OLD.uuid IS NOT NULL BEGIN
+	INSERT INTO SyncChange (household_id, user_id, entity, entity_uuid, operation)
+	VALUES (NEW.household_id, NULL, 'Dish', NEW.uuid, 'Upsert');
+END;
+
+CREATE TRIGGER DishSyncDelete AFTER DELETE ON Dish BEGIN
+	INSERT INTO SyncChange (household_id, user_id, entity, entity_uuid, operation)
+	VALUES (OLD.household_id, NULL, 'Dish', OLD.uuid, 'Delete');
+END;
+
+ALTER TABLE Meal ADD COLUMN uuid TEXT;
+CREATE TRIGGER MealUuidBackfill AFTER UPDATE OF uuid ON Meal WHEN NEW.uuid IS NULL BEGIN
+	UPDATE Meal SET uuid = (SELECT uuid FROM NewUuid) WHERE id = NEW.id;
+END;
+UPDATE Meal SET uuid = NULL;
+DROP TRIGGER MealUuidBackfill;
+CREATE UNIQUE INDEX MealUuid ON Meal(uuid);
+
+CREATE TRIGGER MealSyncInsert AFTER INSERT ON Meal BEGIN
+	UPDATE Meal SET uuid = (SELECT uuid FROM NewUuid)
+	WHERE id = NEW.id AND uuid IS NULL;
+	INSERT INTO SyncChange (household_id, user_id, entity, entity_uuid, operation)
+	VALUES ((SELECT household_id FROM User WHERE id = NEW.user_id), NEW.user_id, 'Meal', (SELECT uuid FROM Meal WHERE id = NEW.id), 'Upsert');
+END;
+
+CREATE TRIGGER MealSyncUpdate AFTER UPDATE ON Meal WHEN OLD.uuid IS NOT NULL BEGIN
+	INSERT INTO SyncChange (household_id, user_id, entity, entity_uuid, operation)
+	VALUES ((SELECT household_id FROM User WHERE id = NEW.user_id), NEW.user_id, 'Meal', NEW.uuid, 'Upsert');
+END;
+
+CREATE TRIGGER MealSyncDelete AFTER DELETE ON Meal BEGIN
+	INSERT INTO SyncChange (household_id, user_id, entity, entity_uuid, operation)
+	VALUES ((SELECT household_id FROM User WHERE id = OLD.user_id), OLD.user_id, 'Meal', OLD.uuid, 'Delete');
+END;
+
+ALTER TABLE DishIngredient ADD COLUMN uuid TEXT;
+CREATE TRIGGER DishIngredientUuidBackfill AFTER UPDATE OF uuid ON DishIngredient WHEN NEW.uuid IS NULL BEGIN
+	UPDATE DishIngredient SET uuid = (SELECT uuid FROM NewUuid) WHERE rowid = NEW.rowid;
+END;
+UPDATE DishIngredient SET uuid = NULL;
+DROP TRIGGER DishIngredientUuidBackfill;
+CREATE UNIQUE INDEX DishIngredientUuid ON DishIngredient(uuid);
+
+CREATE TRIGGER DishIngredientSyncInsert AFTER INSERT ON DishIngredient BEGIN
+	UPDATE DishIngredient SET uuid = (SELECT uuid FROM NewUuid)
+	WHERE rowid = NEW.rowid AND uuid IS NULL;
+	INSERT INTO SyncChange (household_id, user_id, entity, entity_uuid, operation)
+	VALUES ((SELECT household_id FROM Dish WHERE id = NEW.dish_id), NULL, 'DishIngredient', (SELECT uuid FROM DishIngredient WHERE rowid = NEW.rowid), 'Upsert');
+END;
+
+CREATE TRIGGER DishIngredientSyncUpdate AFTER UPDATE ON DishIngredient WHEN OLD.uuid IS NOT NULL BEGIN
+	INSERT INTO SyncChange (household_id, user_id, entity, entity_uuid, operation)
+	VALUES ((SELECT household_id FROM Dish WHERE id = NEW.dish_id), NULL, 'DishIngredient', NEW.uuid, 'Upsert');
+END;
+
+CREATE TRIGGER DishIngredientSyncDelete AFTER DELETE ON DishIngredient BEGIN
+	INSERT INTO SyncChange (household_id, user_id, entity, entity_uuid, operation)
+	VALUES ((SELECT household_id FROM Dish WHERE id = OLD.dish_id), NULL, 'DishIngredient', OLD.uuid, 'Delete');
+END;
+
+ALTER TABLE MealDish ADD COLUMN uuid TEXT;
+CREATE TRIGGER MealDishUuidBackfill AFTER UPDATE OF uuid ON MealDish WHEN NEW.uuid IS NULL BEGIN
+	UPDATE MealDish SET uuid = (SELECT uuid FROM NewUuid) WHERE rowid = NEW.rowid;
+END;
+UPDATE MealDish SET uuid = NULL;
+DROP TRIGGER MealDishUuidBackfill;
+CREATE UNIQUE INDEX MealDishUuid ON MealDish(uuid);
+
+CREATE TRIGGER MealDishSyncInsert AFTER INSERT ON MealDish BEGIN
+	UPDATE MealDish SET uuid = (SELECT uuid FROM NewUuid)
+	WHERE rowid = NEW.rowid AND uuid IS NULL;
+	INSERT INTO SyncChange (household_id, user_id, entity, entity_uuid, operation)
+	VALUES ((SELECT User.household_id FROM Meal JOIN User ON User.id = Meal.user_id WHERE Meal.id = NEW.meal_id), (SELECT user_id FROM Meal WHERE id = NEW.meal_id), 'MealDish', (SELECT uuid FROM MealDish WHERE rowid = NEW.rowid), 'Upsert');
+END;
+
+CREATE TRIGGER MealDishSyncUpdate AFTER UPDATE ON MealDish WHEN OLD.uuid IS NOT NULL BEGIN
+	INSERT INTO SyncChange (household_id, user_id, entity, entity_uuid, operation)
+	VALUES ((SELECT User.household_id FROM Meal JOIN User ON User.id = Meal.user_id WHERE Meal.id = NEW.meal_id), (SELECT user_id FROM Meal WHERE id = NEW.meal_id), 'MealDish', NEW.uuid, 'Upsert');
+END;
+
+CREATE TRIGGER MealDishSyncDelete AFTER DELETE ON MealDish BEGIN
+	INSERT INTO SyncChange (household_id, user_id, entity, entity_uuid, operation)
+	VALUES ((SELECT User.household_id FROM Meal JOIN User ON User.id = Meal.user_id WHERE Meal.id = OLD.meal_id), (SELECT user_id FROM Meal WHERE id = OLD.meal_id), 'MealDish', OLD.uuid, 'Delete');
+END;
+
+ALTER TABLE MealIngredient ADD COLUMN uuid TEXT;
+CREATE TRIGGER MealIngredientUuidBackfill AFTER UPDATE OF uuid ON MealIngredient WHEN NEW.uuid IS NULL BEGIN
+	UPDATE MealIngredient SET uuid = (SELECT uuid FROM NewUuid) WHERE rowid = NEW.rowid;
+END;
+UPDATE MealIngredient SET uuid = NULL;
+DROP TRIGGER MealIngredientUuidBackfill;
+CREATE UNIQUE INDEX MealIngredientUuid ON MealIngredient(uuid);
+
+CREATE TRIGGER MealIngredientSyncInsert AFTER INSERT ON MealIngredient BEGIN
+	UPDATE MealIngredient SET uuid = (SELECT uuid FROM NewUuid)
+	WHERE rowid = NEW.rowid AND uuid IS NULL;
+	INSERT INTO SyncChange (household_id, user_id, entity, entity_uuid, operation)
+	VALUES ((SELECT User.household_id FROM Meal JOIN User ON User.id = Meal.user_id WHERE Meal.id = NEW.meal_id), (SELECT user_id FROM Meal WHERE id = NEW.meal_id), 'MealIngredient', (SELECT uuid FROM MealIngredient WHERE rowid = NEW.rowid), 'Upsert');
+END;
+
+CREATE TRIGGER MealIngredientSyncUpdate AFTER UPDATE ON MealIngredient WHEN OLD.uuid IS NOT NULL BEGIN
+	INSERT INTO SyncChange (household_id, user_id, entity, entity_uuid, operation)
+	VALUES ((SELECT User.household_id FROM Meal JOIN User ON User.id = Meal.user_id WHERE Meal.id = NEW.meal_id), (SELECT user_id FROM Meal WHERE id = NEW.meal_id), 'MealIngredient', NEW.uuid, 'Upsert');
+END;
+
+CREATE TRIGGER MealIngredientSyncDelete AFTER DELETE ON MealIngredient BEGIN
+	INSERT INTO SyncChange (household_id, user_id, entity, entity_uuid, operation)
+	VALUES ((SELECT User.household_id FROM Meal JOIN User ON User.id = Meal.user_id WHERE Meal.id = OLD.meal_id), (SELECT user_id FROM Meal WHERE id = OLD.meal_id), 'MealIngredient', OLD.uuid, 'Delete');
+END;
@@ -0,0 +1,38 @@
+DROP TRIGGER MealIngredientVersionDelete;
+DROP TRIGGER MealIngredientVersionUpdate;
+DROP TRIGGER MealIngredientVersionInsert;
+
+DROP TRIGGER MealDishVersionDelete;
+DROP TRIGGER MealDishVersionUpdate;
+DROP TRIGGER MealDishVersionInsert;
+
+DROP TRIGGER DishIngredientVersionDelete;
+DROP TRIGGER DishIngredientVersionUpdate;
+DROP TRIGGER DishIngredientVersionInsert;
+
+DROP TRIGGER MealVersionUpdate;
+ALTER TABLE Meal DROP COLUMN version;
+
+DROP TRIGGER DishVersionUpdate;
+ALTER TABLE Dish DROP COLUMN version;
+
+DROP TRIGGER IngredientVersionUpdate;
+ALTER TABLE Ingredient DROP COLUMN version;
+
+DROP TRIGGER MealSyncUpdate;
+CREATE TRIGGER MealSyncUpdate AFTER UPDATE ON Meal WHEN OLD.uuid IS NOT NULL BEGIN
+	INSERT INTO SyncChange (household_id, user_id, entity, entity_uuid, operation)
+	VALUES ((SELECT household_id FROM User WHERE id = NEW.user_id), NEW.user_id, 'Meal', NEW.uuid, 'Upsert');
+END;
+
+DROP TRIGGER DishSyncUpdate;
+CREATE TRIGGER DishSyncUpdate AFTER UPDATE ON Dish WHEN OLD.uuid IS NOT NULL BEGIN
+	INSERT INTO SyncChange (household_id, user_id, entity, entity_uuid, operation)
+	VALUES (NEW.household_id, NULL, 'Dish', NEW.uuid, 'Upsert');
+END;
+
+DROP TRIGGER IngredientSyncUpdate;
+CREATE TRIGGER IngredientSyncUpdate AFTER UPDATE ON Ingredient WHEN OLD.uuid IS NOT NULL BEGIN
+	INSERT INTO SyncChange (household_id, user_id, entity, entity_uuid, operation)
+	VALUES (NEW.household_id, NULL, 'Ingredient', NEW.uuid, 'Upsert');
+END;
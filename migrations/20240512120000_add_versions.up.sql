@@ -0,0 +1,86 @@
+-- Version of each entity that can be edited, bumped on every change, so clients can tell when
+-- what they are editing was changed by someone else. Changes to the dishes and ingredients of a
+-- meal, or to the ingredients of a dish, also bump the version of the meal or dish.
+
+-- Bumping the version is part of the change that caused it, so it's not recorded as another
+-- change to sync
+DROP TRIGGER IngredientSyncUpdate;
+CREATE TRIGGER IngredientSyncUpdate AFTER UPDATE ON Ingredient
+WHEN OLD.uuid IS NOT NULL AND NEW.version = OLD.version BEGIN
+	INSERT INTO SyncChange (household_id, user_id, entity, entity_uuid, operation)
+	VALUES (NEW.household_id, NULL, 'Ingredient', NEW.uuid, 'Upsert');
+END;
+
+DROP TRIGGER DishSyncUpdate;
+CREATE TRIGGER DishSyncUpdate AFTER UPDATE ON Dish
+WHEN OLD.uuid IS NOT NULL AND NEW.version = OLD.version BEGIN
+	INSERT INTO SyncChange (household_id, user_id, entity, entity_uuid, operation)
+	VALUES (NEW.household_id, NULL, 'Dish', NEW.uuid, 'Upsert');
+END;
+
+DROP TRIGGER MealSyncUpdate;
+CREATE TRIGGER MealSyncUpdate AFTER UPDATE ON Meal
+WHEN OLD.uuid IS NOT NULL AND NEW.version = OLD.version BEGIN
+	INSERT INTO SyncChange (household_id, user_id, entity, entity_uuid, operation)
+	VALUES ((SELECT household_id FROM User WHERE id = NEW.user_id), NEW.user_id, 'Meal', NEW.uuid, 'Upsert');
+END;
+
+ALTER TABLE Ingredient ADD COLUMN version INTEGER NOT NULL DEFAULT 1;
+
+-- Rows without a uuid are still being inserted
+CREATE TRIGGER IngredientVersionUpdate AFTER UPDATE ON Ingredient
+WHEN NEW.version = OLD.version AND OLD.uuid IS NOT NULL BEGIN
+	UPDATE Ingredient SET version = OLD.version + 1 WHERE id = NEW.id;
+END;
+
+ALTER TABLE Dish ADD COLUMN version INTEGER NOT NULL DEFAULT 1;
+
+-- Rows without a uuid are still being inserted
+CREATE TRIGGER DishVersionUpdate AFTER UPDATE ON Dish
+WHEN NEW.version = OLD.version AND OLD.uuid IS NOT NULL BEGIN
+	UPDATE Dish SET version = OLD.version + 1 WHERE id = NEW.id;
+END;
+
+ALTER TABLE Meal ADD COLUMN version INTEGER NOT NULL DEFAULT 1;
+
+-- Rows without a uuid are still being inserted
+CREATE TRIGGER MealVersionUpdate AFTER UPDATE ON Meal
+WHEN NEW.version = OLD.version AND OLD.uuid IS NOT NULL BEGIN
+	UPDATE Meal SET version = OLD.version + 1 WHERE id = NEW.id;
+END;
+
+CREATE TRIGGER DishIngredientVersionInsert AFTER INSERT ON DishIngredient BEGIN
+	UPDATE Dish SET version = version + 1 WHERE id = NEW.dish_id;
+END;
+
+CREATE TRIGGER DishIngredientVersionUpdate AFTER UPDATE OF weight ON DishIngredient BEGIN
+	UPDATE Dish SET version = version + 1 WHERE id = NEW.dish_id;
+END;
+
+CREATE TRIGGER DishIngredientVersionDelete AFTER DELETE ON DishIngredient BEGIN
+	UPDATE Dish SET version = version + 1 WHERE id = OLD.dish_id;
+END;
+
+CREATE TRIGGER MealDishVersionInsert AFTER INSERT ON MealDish BEGIN
+	UPDATE Meal SET version = version + 1 WHERE id = NEW.meal_id;
+END;
+
+CREATE TRIGGER MealDishVersionUpdate AFTER UPDATE OF weight ON MealDish BEGIN
+	UPDATE Meal SET version = version + 1 WHERE id = NEW.meal_id;
+END;
+
+CREATE TRIGGER MealDishVersionDelete AFTER DELETE ON MealDish BEGIN
+	UPDATE Meal SET version = version + 1 WHERE id = OLD.meal_id;
+END;
+
+CREATE TRIGGER MealIngredientVersionInsert AFTER INSERT ON MealIngredient BEGIN
+	UPDATE Meal SET version = version + 1 WHERE id = NEW.meal_id;
+END;
+
+CREATE TRIGGER MealIngredientVersionUpdate AFTER UPDATE OF weight ON MealIngredient BEGIN
+	UPDATE Meal SET version = version + 1 WHERE id = NEW.meal_id;
+END;
+
+CREATE TRIGGER MealIngredientVersionDelete AFTER DELETE ON MealIngredient BEGIN
+	UPDATE Meal SET version = version + 1 WHERE id = OLD.meal_id;
+END;
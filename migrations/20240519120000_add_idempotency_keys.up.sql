@@ -0,0 +1,18 @@
+-- Responses to requests sent with an `Idempotency-Key` header, replayed when the same request is
+-- retried instead of running it again. Rows are removed once the retention window passes.
+CREATE TABLE IdempotencyKey (
+	id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+	creation_date INTEGER NOT NULL DEFAULT (unixepoch() * 1000),
+	user_id INTEGER NOT NULL REFERENCES User(id),
+	key TEXT NOT NULL,
+	method TEXT NOT NULL,
+	path TEXT NOT NULL,
+	-- SHA-256 of the request body, so a key can't be reused for a different request
+	request_hash TEXT NOT NULL,
+	-- All unset while the first request is still running
+	status_code INTEGER,
+	-- JSON object of the response headers sent again on replays, such as the ETag
+	response_headers TEXT,
+	response_body TEXT,
+	UNIQUE (user_id, key)
+) STRICT;
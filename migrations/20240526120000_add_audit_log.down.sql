@@ -0,0 +1,4 @@
+DROP TRIGGER AuditEntryNoDelete;
+DROP TRIGGER AuditEntryNoUpdate;
+DROP INDEX AuditEntryEntity;
+DROP TABLE AuditEntry;
@@ -0,0 +1,27 @@
+-- Every change made to ingredients, dishes and meals, with what they looked like before and after
+-- it. Changes to the ingredients of a dish, or to the dishes and ingredients of a meal, are
+-- recorded as changes to the dish or meal.
+CREATE TABLE AuditEntry (
+	id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+	creation_date INTEGER NOT NULL DEFAULT (unixepoch() * 1000),
+	household_id INTEGER NOT NULL REFERENCES Household(id),
+	-- The user that made the change
+	user_id INTEGER NOT NULL REFERENCES User(id),
+	entity TEXT NOT NULL CHECK (entity IN ('Ingredient', 'Dish', 'Meal')),
+	-- Not a foreign key, since the entity may have been deleted
+	entity_id INTEGER NOT NULL,
+	operation TEXT NOT NULL CHECK (operation IN ('Create', 'Update', 'Delete', 'Revert')),
+	-- JSON of the entity. Not set before it's created, nor after it's deleted
+	before TEXT,
+	after TEXT
+) STRICT;
+
+CREATE INDEX AuditEntryEntity ON AuditEntry(entity, entity_id);
+
+CREATE TRIGGER AuditEntryNoUpdate BEFORE UPDATE ON AuditEntry BEGIN
+	SELECT RAISE(ABORT, 'The audit log is append-only');
+END;
+
+CREATE TRIGGER AuditEntryNoDelete BEFORE DELETE ON AuditEntry BEGIN
+	SELECT RAISE(ABORT, 'The audit log is append-only');
+END;
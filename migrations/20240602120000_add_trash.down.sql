@@ -0,0 +1,29 @@
+DROP TRIGGER MealSyncUpdate;
+CREATE TRIGGER MealSyncUpdate AFTER UPDATE ON Meal
+WHEN OLD.uuid IS NOT NULL AND NEW.version = OLD.version BEGIN
+	INSERT INTO SyncChange (household_id, user_id, entity, entity_uuid, operation)
+	VALUES ((SELECT household_id FROM User WHERE id = NEW.user_id), NEW.user_id, 'Meal', NEW.uuid, 'Upsert');
+END;
+
+DROP TRIGGER DishSyncUpdate;
+CREATE TRIGGER DishSyncUpdate AFTER UPDATE ON Dish
+WHEN OLD.uuid IS NOT NULL AND NEW.version = OLD.version BEGIN
+	INSERT INTO SyncChange (household_id, user_id, entity, entity_uuid, operation)
+	VALUES (NEW.household_id, NULL, 'Dish', NEW.uuid, 'Upsert');
+END;
+
+DROP INDEX MealDeletionDate;
+DROP INDEX DishDeletionDate;
+
+DELETE FROM MealIngredient WHERE meal_id IN (SELECT id FROM Meal WHERE deletion_date IS NOT NULL);
+DELETE FROM MealDish WHERE meal_id IN (SELECT id FROM Meal WHERE deletion_date IS NOT NULL);
+DELETE FROM MealTemplateUsage WHERE meal_id IN (SELECT id FROM Meal WHERE deletion_date IS NOT NULL);
+DELETE FROM ShareComment WHERE meal_id IN (SELECT id FROM Meal WHERE deletion_date IS NOT NULL);
+UPDATE PlannedMeal SET meal_id = NULL WHERE meal_id IN (SELECT id FROM Meal WHERE deletion_date IS NOT NULL);
+DELETE FROM Meal WHERE deletion_date IS NOT NULL;
+ALTER TABLE Meal DROP COLUMN deletion_date;
+
+DELETE FROM MealDish WHERE dish_id IN (SELECT id FROM Dish WHERE deletion_date IS NOT NULL);
+DELETE FROM DishIngredient WHERE dish_id IN (SELECT id FROM Dish WHERE deletion_date IS NOT NULL);
+DELETE FROM Dish WHERE deletion_date IS NOT NULL;
+ALTER TABLE Dish DROP COLUMN deletion_date;
@@ -0,0 +1,23 @@
+-- Deleted dishes and meals are moved to the trash instead of being removed right away. They can be
+-- restored until they are purged, either explicitly or after the configured retention
+ALTER TABLE Dish ADD COLUMN deletion_date INTEGER;
+ALTER TABLE Meal ADD COLUMN deletion_date INTEGER;
+
+CREATE INDEX DishDeletionDate ON Dish(deletion_date) WHERE deletion_date IS NOT NULL;
+CREATE INDEX MealDeletionDate ON Meal(deletion_date) WHERE deletion_date IS NOT NULL;
+
+-- To other devices, moving something to the trash is the same as deleting it, and restoring it is
+-- the same as creating it again
+DROP TRIGGER DishSyncUpdate;
+CREATE TRIGGER DishSyncUpdate AFTER UPDATE ON Dish
+WHEN OLD.uuid IS NOT NULL AND NEW.version = OLD.version BEGIN
+	INSERT INTO SyncChange (household_id, user_id, entity, entity_uuid, operation)
+	VALUES (NEW.household_id, NULL, 'Dish', NEW.uuid, CASE WHEN NEW.deletion_date IS NULL THEN 'Upsert' ELSE 'Delete' END);
+END;
+
+DROP TRIGGER MealSyncUpdate;
+CREATE TRIGGER MealSyncUpdate AFTER UPDATE ON Meal
+WHEN OLD.uuid IS NOT NULL AND NEW.version = OLD.version BEGIN
+	INSERT INTO SyncChange (household_id, user_id, entity, entity_uuid, operation)
+	VALUES ((SELECT household_id FROM User WHERE id = NEW.user_id), NEW.user_id, 'Meal', NEW.uuid, CASE WHEN NEW.deletion_date IS NULL THEN 'Upsert' ELSE 'Delete' END);
+END;
@@ -0,0 +1,18 @@
+DROP TRIGGER MealIngredientVersionUpdate;
+CREATE TRIGGER MealIngredientVersionUpdate AFTER UPDATE OF weight ON MealIngredient BEGIN
+	UPDATE Meal SET version = version + 1 WHERE id = NEW.meal_id;
+END;
+
+DROP TRIGGER MealDishVersionUpdate;
+CREATE TRIGGER MealDishVersionUpdate AFTER UPDATE OF weight ON MealDish BEGIN
+	UPDATE Meal SET version = version + 1 WHERE id = NEW.meal_id;
+END;
+
+DROP TRIGGER DishIngredientVersionUpdate;
+CREATE TRIGGER DishIngredientVersionUpdate AFTER UPDATE OF weight ON DishIngredient BEGIN
+	UPDATE Dish SET version = version + 1 WHERE id = NEW.dish_id;
+END;
+
+DROP TRIGGER IngredientAliasVersionDelete;
+DROP TRIGGER IngredientAliasVersionInsert;
+DROP TABLE IngredientAlias;
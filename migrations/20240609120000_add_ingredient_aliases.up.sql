@@ -0,0 +1,33 @@
+-- Other names an ingredient is known by, such as the names of the duplicates merged into it
+CREATE TABLE IngredientAlias (
+	id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+	creation_date INTEGER NOT NULL DEFAULT (unixepoch() * 1000),
+	ingredient_id INTEGER NOT NULL REFERENCES Ingredient(id),
+	name TEXT NOT NULL COLLATE NOCASE,
+	UNIQUE(ingredient_id, name)
+) STRICT;
+
+CREATE TRIGGER IngredientAliasVersionInsert AFTER INSERT ON IngredientAlias BEGIN
+	UPDATE Ingredient SET version = version + 1 WHERE id = NEW.ingredient_id;
+END;
+
+CREATE TRIGGER IngredientAliasVersionDelete AFTER DELETE ON IngredientAlias BEGIN
+	UPDATE Ingredient SET version = version + 1 WHERE id = OLD.ingredient_id;
+END;
+
+-- Links are pointed to other dishes and ingredients when merging or reassigning them, which
+-- changes the dish or meal they belong to
+DROP TRIGGER DishIngredientVersionUpdate;
+CREATE TRIGGER DishIngredientVersionUpdate AFTER UPDATE OF weight, ingredient_id ON DishIngredient BEGIN
+	UPDATE Dish SET version = version + 1 WHERE id = NEW.dish_id;
+END;
+
+DROP TRIGGER MealDishVersionUpdate;
+CREATE TRIGGER MealDishVersionUpdate AFTER UPDATE OF weight, dish_id ON MealDish BEGIN
+	UPDATE Meal SET version = version + 1 WHERE id = NEW.meal_id;
+END;
+
+DROP TRIGGER MealIngredientVersionUpdate;
+CREATE TRIGGER MealIngredientVersionUpdate AFTER UPDATE OF weight, ingredient_id ON MealIngredient BEGIN
+	UPDATE Meal SET version = version + 1 WHERE id = NEW.meal_id;
+END;
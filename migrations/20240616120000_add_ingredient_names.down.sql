@@ -0,0 +1,4 @@
+DROP TRIGGER IngredientNameVersionDelete;
+DROP TRIGGER IngredientNameVersionUpdate;
+DROP TRIGGER IngredientNameVersionInsert;
+DROP TABLE IngredientName;
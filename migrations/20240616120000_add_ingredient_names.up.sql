@@ -0,0 +1,31 @@
+-- Name of an ingredient in a language. Requests get the names in the languages of their
+-- `Accept-Language` header, and fall back to `Ingredient.name`
+CREATE TABLE IngredientName (
+	creation_date INTEGER NOT NULL DEFAULT (unixepoch() * 1000),
+	ingredient_id INTEGER NOT NULL REFERENCES Ingredient(id),
+	-- Lowercase language tag, such as "en" or "pt-br"
+	language TEXT NOT NULL,
+	name TEXT NOT NULL,
+	-- Names from Open Food Facts are replaced when the properties are fetched again, names entered
+	-- by hand never are
+	source TEXT NOT NULL CHECK (source IN ('Manual', 'OpenFoodFacts')),
+	PRIMARY KEY(ingredient_id, language)
+) STRICT;
+
+CREATE TRIGGER IngredientNameVersionInsert AFTER INSERT ON IngredientName BEGIN
+	UPDATE Ingredient SET version = version + 1 WHERE id = NEW.ingredient_id;
+END;
+
+CREATE TRIGGER IngredientNameVersionUpdate AFTER UPDATE OF name ON IngredientName BEGIN
+	UPDATE Ingredient SET version = version + 1 WHERE id = NEW.ingredient_id;
+END;
+
+CREATE TRIGGER IngredientNameVersionDelete AFTER DELETE ON IngredientName BEGIN
+	UPDATE Ingredient SET version = version + 1 WHERE id = OLD.ingredient_id;
+END;
+
+-- Seeded from the `product_name_xx` fields of the properties fetched so far
+INSERT INTO IngredientName (ingredient_id, language, name, source)
+SELECT ingredient_id, substr(key, 14), trim(value), 'OpenFoodFacts'
+FROM IngredientProperties, json_each(open_food_facts_json, '$.product')
+WHERE key GLOB 'product_name_[a-z][a-z]' AND type = 'text' AND trim(value) != '';
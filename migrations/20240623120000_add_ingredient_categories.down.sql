@@ -0,0 +1,9 @@
+DROP TRIGGER IngredientTagVersionDelete;
+DROP TRIGGER IngredientTagVersionInsert;
+DROP TRIGGER IngredientCategoryVersionDelete;
+DROP TRIGGER IngredientCategoryVersionInsert;
+DROP TABLE IngredientTag;
+DROP INDEX IngredientCategoryCategory;
+DROP TABLE IngredientCategory;
+DROP INDEX CategoryParent;
+DROP TABLE Category;
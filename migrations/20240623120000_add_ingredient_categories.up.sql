@@ -0,0 +1,89 @@
+-- Tree of categories ingredients are grouped in, such as Grains > Rice > Brown rice
+CREATE TABLE Category (
+	id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+	creation_date INTEGER NOT NULL DEFAULT (unixepoch() * 1000),
+	household_id INTEGER NOT NULL REFERENCES Household(id),
+	-- NULL for top level categories
+	parent_id INTEGER REFERENCES Category(id),
+	name TEXT NOT NULL,
+	-- Tag of the category in Open Food Facts, such as "en:brown-rices", if it was created from there
+	open_food_facts_tag TEXT,
+	UNIQUE(household_id, open_food_facts_tag)
+) STRICT;
+
+CREATE INDEX CategoryParent ON Category(parent_id);
+
+-- Categories an ingredient is in. Ingredients are also in every ancestor of these categories
+CREATE TABLE IngredientCategory (
+	creation_date INTEGER NOT NULL DEFAULT (unixepoch() * 1000),
+	ingredient_id INTEGER NOT NULL REFERENCES Ingredient(id),
+	category_id INTEGER NOT NULL REFERENCES Category(id),
+	-- Categories from Open Food Facts are replaced when the properties are fetched again, categories
+	-- chosen by hand never are
+	source TEXT NOT NULL CHECK (source IN ('Manual', 'OpenFoodFacts')),
+	PRIMARY KEY(ingredient_id, category_id)
+) STRICT;
+
+CREATE INDEX IngredientCategoryCategory ON IngredientCategory(category_id);
+
+-- Free-form labels of an ingredient, such as "breakfast" or "bulk"
+CREATE TABLE IngredientTag (
+	creation_date INTEGER NOT NULL DEFAULT (unixepoch() * 1000),
+	ingredient_id INTEGER NOT NULL REFERENCES Ingredient(id),
+	name TEXT NOT NULL COLLATE NOCASE,
+	PRIMARY KEY(ingredient_id, name)
+) STRICT;
+
+CREATE TRIGGER IngredientCategoryVersionInsert AFTER INSERT ON IngredientCategory BEGIN
+	UPDATE Ingredient SET version = version + 1 WHERE id = NEW.ingredient_id;
+END;
+
+CREATE TRIGGER IngredientCategoryVersionDelete AFTER DELETE ON IngredientCategory BEGIN
+	UPDATE Ingredient SET version = version + 1 WHERE id = OLD.ingredient_id;
+END;
+
+CREATE TRIGGER IngredientTagVersionInsert AFTER INSERT ON IngredientTag BEGIN
+	UPDATE Ingredient SET version = version + 1 WHERE id = NEW.ingredient_id;
+END;
+
+CREATE TRIGGER IngredientTagVersionDelete AFTER DELETE ON IngredientTag BEGIN
+	UPDATE Ingredient SET version = version + 1 WHERE id = OLD.ingredient_id;
+END;
+
+-- Seeded from the `categories_hierarchy` of the properties fetched so far. Each category of the
+-- hierarchy is a child of the one before it, and the ingredient is in the last one
+INSERT OR IGNORE INTO Category (household_id, name, open_food_facts_tag)
+SELECT
+	Ingredient.household_id,
+	upper(substr(replace(substr(value, instr(value, ':') + 1), '-', ' '), 1, 1))
+		|| substr(replace(substr(value, instr(value, ':') + 1), '-', ' '), 2),
+	value
+FROM IngredientProperties
+	JOIN Ingredient ON Ingredient.id = IngredientProperties.ingredient_id,
+	json_each(open_food_facts_json, '$.product.categories_hierarchy')
+WHERE type = 'text'
+ORDER BY IngredientProperties.ingredient_id, key;
+
+UPDATE Category SET parent_id = (
+	SELECT Parent.id
+	FROM IngredientProperties
+		JOIN Ingredient ON Ingredient.id = IngredientProperties.ingredient_id,
+		json_each(open_food_facts_json, '$.product.categories_hierarchy') AS Child,
+		json_each(open_food_facts_json, '$.product.categories_hierarchy') AS ParentTag
+		JOIN Category AS Parent
+			ON Parent.household_id = Ingredient.household_id AND Parent.open_food_facts_tag = ParentTag.value
+	WHERE Ingredient.household_id = Category.household_id
+		AND Child.value = Category.open_food_facts_tag
+		AND ParentTag.key = Child.key - 1
+	ORDER BY IngredientProperties.ingredient_id
+	LIMIT 1
+)
+WHERE open_food_facts_tag IS NOT NULL;
+
+INSERT OR IGNORE INTO IngredientCategory (ingredient_id, category_id, source)
+SELECT IngredientProperties.ingredient_id, Category.id, 'OpenFoodFacts'
+FROM IngredientProperties
+	JOIN Ingredient ON Ingredient.id = IngredientProperties.ingredient_id
+	JOIN Category
+		ON Category.household_id = Ingredient.household_id
+		AND Category.open_food_facts_tag = json_extract(open_food_facts_json, '$.product.categories_hierarchy[#-1]');
@@ -0,0 +1,3 @@
+DROP VIEW IngredientNutrition;
+DROP INDEX IngredientGeneric;
+ALTER TABLE Ingredient DROP COLUMN generic_id;
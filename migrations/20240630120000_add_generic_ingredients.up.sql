@@ -0,0 +1,19 @@
+-- Generic ingredient this one is a variant of, such as "Milk" for a brand of milk. Generic
+-- ingredients can't be variants themselves
+ALTER TABLE Ingredient ADD COLUMN generic_id INTEGER REFERENCES Ingredient(id);
+
+CREATE INDEX IngredientGeneric ON Ingredient(generic_id);
+
+-- Nutrients of the ingredients that have any, per 100g. Variants use the nutrients of their
+-- generic ingredient for the ones their own properties lack
+CREATE VIEW IngredientNutrition AS
+SELECT
+	Ingredient.id AS ingredient_id,
+	coalesce(Own.kcal_100g, Generic.kcal_100g) AS kcal_100g,
+	coalesce(Own.proteins_100g, Generic.proteins_100g) AS proteins_100g,
+	coalesce(Own.fat_100g, Generic.fat_100g) AS fat_100g,
+	coalesce(Own.carbohydrates_100g, Generic.carbohydrates_100g) AS carbohydrates_100g
+FROM Ingredient
+	LEFT JOIN IngredientProperties AS Own ON Own.ingredient_id = Ingredient.id
+	LEFT JOIN IngredientProperties AS Generic ON Generic.ingredient_id = Ingredient.generic_id
+WHERE Own.ingredient_id IS NOT NULL OR Generic.ingredient_id IS NOT NULL;
@@ -0,0 +1,6 @@
+DROP INDEX DietaryRestrictionUnique;
+DROP TABLE DietaryRestriction;
+DROP VIEW IngredientDiet;
+ALTER TABLE IngredientProperties DROP COLUMN ingredients_analysis_tags;
+ALTER TABLE IngredientProperties DROP COLUMN traces_tags;
+ALTER TABLE IngredientProperties DROP COLUMN allergens_tags;
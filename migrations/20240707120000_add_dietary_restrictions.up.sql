@@ -0,0 +1,37 @@
+-- Open Food Facts tags of the product, as JSON arrays. The allergens are tags such as
+-- "en:gluten", and the analysis has tags such as "en:vegan" or "en:palm-oil-free"
+ALTER TABLE IngredientProperties
+ADD COLUMN allergens_tags TEXT AS (iif(json_type(open_food_facts_json, '$.product.allergens_tags') = 'array', json_extract(open_food_facts_json, '$.product.allergens_tags'), NULL)) VIRTUAL;
+
+ALTER TABLE IngredientProperties
+ADD COLUMN traces_tags TEXT AS (iif(json_type(open_food_facts_json, '$.product.traces_tags') = 'array', json_extract(open_food_facts_json, '$.product.traces_tags'), NULL)) VIRTUAL;
+
+ALTER TABLE IngredientProperties
+ADD COLUMN ingredients_analysis_tags TEXT AS (iif(json_type(open_food_facts_json, '$.product.ingredients_analysis_tags') = 'array', json_extract(open_food_facts_json, '$.product.ingredients_analysis_tags'), NULL)) VIRTUAL;
+
+-- Dietary tags of the ingredients that have any. Variants use the tags of their generic
+-- ingredient for the ones their own properties lack
+CREATE VIEW IngredientDiet AS
+SELECT
+	Ingredient.id AS ingredient_id,
+	coalesce(Own.allergens_tags, Generic.allergens_tags) AS allergens_tags,
+	coalesce(Own.traces_tags, Generic.traces_tags) AS traces_tags,
+	coalesce(Own.ingredients_analysis_tags, Generic.ingredients_analysis_tags) AS ingredients_analysis_tags
+FROM Ingredient
+	LEFT JOIN IngredientProperties AS Own ON Own.ingredient_id = Ingredient.id
+	LEFT JOIN IngredientProperties AS Generic ON Generic.ingredient_id = Ingredient.generic_id
+WHERE Own.ingredient_id IS NOT NULL OR Generic.ingredient_id IS NOT NULL;
+
+-- What a household member can't eat. Dishes and meals that go against it come with warnings
+CREATE TABLE DietaryRestriction (
+	id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+	creation_date INTEGER NOT NULL DEFAULT (unixepoch() * 1000),
+	user_id INTEGER NOT NULL REFERENCES User(id),
+	kind TEXT NOT NULL CHECK (kind IN ('Allergen', 'Vegetarian', 'Vegan', 'PalmOilFree')),
+	-- Open Food Facts tag of the allergen to avoid, such as "en:peanuts". Only for the Allergen kind
+	allergen TEXT CHECK ((kind = 'Allergen') = (allergen IS NOT NULL)),
+	-- Whether products that may contain traces of the allergen should be avoided too
+	avoid_traces INTEGER NOT NULL DEFAULT 1
+) STRICT;
+
+CREATE UNIQUE INDEX DietaryRestrictionUnique ON DietaryRestriction(user_id, kind, coalesce(allergen, ''));
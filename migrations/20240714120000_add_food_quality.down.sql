@@ -0,0 +1,4 @@
+DROP VIEW IngredientQuality;
+ALTER TABLE IngredientProperties DROP COLUMN additives_tags;
+ALTER TABLE IngredientProperties DROP COLUMN nova_group;
+ALTER TABLE IngredientProperties DROP COLUMN nutriscore_grade;
@@ -0,0 +1,24 @@
+-- Nutri-Score grade of the product, from "a" (best) to "e" (worst)
+ALTER TABLE IngredientProperties
+ADD COLUMN nutriscore_grade TEXT AS (iif(json_extract(open_food_facts_json, '$.product.nutriscore_grade') IN ('a', 'b', 'c', 'd', 'e'), json_extract(open_food_facts_json, '$.product.nutriscore_grade'), NULL)) VIRTUAL;
+
+-- NOVA group of the product, from 1 (unprocessed) to 4 (ultra-processed)
+ALTER TABLE IngredientProperties
+ADD COLUMN nova_group INTEGER AS (iif(CAST(json_extract(open_food_facts_json, '$.product.nova_group') AS INTEGER) BETWEEN 1 AND 4, CAST(json_extract(open_food_facts_json, '$.product.nova_group') AS INTEGER), NULL)) VIRTUAL;
+
+-- Open Food Facts tags of the additives in the product, such as "en:e330", as a JSON array
+ALTER TABLE IngredientProperties
+ADD COLUMN additives_tags TEXT AS (iif(json_type(open_food_facts_json, '$.product.additives_tags') = 'array', json_extract(open_food_facts_json, '$.product.additives_tags'), NULL)) VIRTUAL;
+
+-- Nutri-Score, NOVA group and additives of the ingredients that have any. Variants use the ones
+-- of their generic ingredient their own properties lack
+CREATE VIEW IngredientQuality AS
+SELECT
+	Ingredient.id AS ingredient_id,
+	coalesce(Own.nutriscore_grade, Generic.nutriscore_grade) AS nutriscore_grade,
+	coalesce(Own.nova_group, Generic.nova_group) AS nova_group,
+	coalesce(Own.additives_tags, Generic.additives_tags) AS additives_tags
+FROM Ingredient
+	LEFT JOIN IngredientProperties AS Own ON Own.ingredient_id = Ingredient.id
+	LEFT JOIN IngredientProperties AS Generic ON Generic.ingredient_id = Ingredient.generic_id
+WHERE Own.ingredient_id IS NOT NULL OR Generic.ingredient_id IS NOT NULL;
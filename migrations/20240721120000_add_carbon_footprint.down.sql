@@ -0,0 +1,3 @@
+DROP VIEW IngredientFootprint;
+ALTER TABLE Ingredient DROP COLUMN co2e_per_kg;
+ALTER TABLE IngredientProperties DROP COLUMN co2e_per_kg;
@@ -0,0 +1,26 @@
+-- Kilograms of CO2 equivalent emitted per kilogram of the product, from its Eco-Score data
+ALTER TABLE IngredientProperties
+ADD COLUMN co2e_per_kg REAL AS (iif(json_type(open_food_facts_json, '$.product.ecoscore_data.agribalyse.co2_total') IN ('integer', 'real'), json_extract(open_food_facts_json, '$.product.ecoscore_data.agribalyse.co2_total'), NULL)) VIRTUAL;
+
+-- Kilograms of CO2 equivalent emitted per kilogram of the ingredient, entered by hand for the
+-- ones without Eco-Score data. Takes precedence over the one of the properties
+ALTER TABLE Ingredient ADD COLUMN co2e_per_kg REAL CHECK (co2e_per_kg >= 0);
+
+-- Footprint per kilogram of the ingredients that have one. Variants without a footprint of their
+-- own use the one of their generic ingredient
+CREATE VIEW IngredientFootprint AS
+SELECT * FROM (
+	SELECT
+		Ingredient.id AS ingredient_id,
+		coalesce(
+			Ingredient.co2e_per_kg,
+			Own.co2e_per_kg,
+			Generic.co2e_per_kg,
+			GenericProperties.co2e_per_kg
+		) AS co2e_per_kg
+	FROM Ingredient
+		LEFT JOIN IngredientProperties AS Own ON Own.ingredient_id = Ingredient.id
+		LEFT JOIN Ingredient AS Generic ON Generic.id = Ingredient.generic_id
+		LEFT JOIN IngredientProperties AS GenericProperties ON GenericProperties.ingredient_id = Ingredient.generic_id
+)
+WHERE co2e_per_kg IS NOT NULL;
@@ -0,0 +1,7 @@
+DROP VIEW IngredientTrackableAmount;
+DROP TRIGGER IngredientTrackableVersionDelete;
+DROP TRIGGER IngredientTrackableVersionUpdate;
+DROP TRIGGER IngredientTrackableVersionInsert;
+DROP INDEX IngredientTrackableTrackable;
+DROP TABLE IngredientTrackable;
+DROP TABLE Trackable;
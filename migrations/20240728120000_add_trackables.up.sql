@@ -0,0 +1,48 @@
+-- Anything worth keeping track of that isn't one of the built-in nutrients, such as caffeine or
+-- alcohol units. Ingredients have an amount of it per 100g
+CREATE TABLE Trackable (
+	id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+	creation_date INTEGER NOT NULL DEFAULT (unixepoch() * 1000),
+	household_id INTEGER NOT NULL REFERENCES Household(id),
+	name TEXT NOT NULL COLLATE NOCASE,
+	-- Unit the amounts are in, such as "mg" or "points"
+	unit TEXT NOT NULL,
+	-- Most that should be had in a day, in the trackable's unit
+	daily_limit REAL CHECK (daily_limit >= 0),
+	UNIQUE(household_id, name)
+) STRICT;
+
+CREATE TABLE IngredientTrackable (
+	creation_date INTEGER NOT NULL DEFAULT (unixepoch() * 1000),
+	ingredient_id INTEGER NOT NULL REFERENCES Ingredient(id),
+	trackable_id INTEGER NOT NULL REFERENCES Trackable(id),
+	-- Amount in 100g of the ingredient, in the trackable's unit
+	amount_100g REAL NOT NULL CHECK (amount_100g >= 0),
+	PRIMARY KEY (ingredient_id, trackable_id)
+) STRICT;
+
+CREATE INDEX IngredientTrackableTrackable ON IngredientTrackable(trackable_id);
+
+CREATE TRIGGER IngredientTrackableVersionInsert AFTER INSERT ON IngredientTrackable BEGIN
+	UPDATE Ingredient SET version = version + 1 WHERE id = NEW.ingredient_id;
+END;
+
+CREATE TRIGGER IngredientTrackableVersionUpdate AFTER UPDATE ON IngredientTrackable BEGIN
+	UPDATE Ingredient SET version = version + 1 WHERE id = NEW.ingredient_id;
+END;
+
+CREATE TRIGGER IngredientTrackableVersionDelete AFTER DELETE ON IngredientTrackable BEGIN
+	UPDATE Ingredient SET version = version + 1 WHERE id = OLD.ingredient_id;
+END;
+
+-- Amounts per 100g of each ingredient. Variants use the amounts of their generic ingredient for
+-- the trackables they have none of their own for
+CREATE VIEW IngredientTrackableAmount AS
+SELECT ingredient_id, trackable_id, amount_100g FROM IngredientTrackable
+UNION ALL
+SELECT Ingredient.id AS ingredient_id, Generic.trackable_id, Generic.amount_100g
+FROM Ingredient JOIN IngredientTrackable AS Generic ON Generic.ingredient_id = Ingredient.generic_id
+WHERE NOT EXISTS (
+	SELECT 1 FROM IngredientTrackable
+	WHERE ingredient_id = Ingredient.id AND trackable_id = Generic.trackable_id
+);
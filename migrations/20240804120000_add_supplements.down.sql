@@ -0,0 +1,11 @@
+DROP TRIGGER SupplementDoseVersionDelete;
+DROP TRIGGER SupplementDoseVersionUpdate;
+DROP TRIGGER SupplementDoseVersionInsert;
+DROP INDEX SupplementDoseOccurrence;
+DROP INDEX SupplementDoseMeal;
+DROP INDEX SupplementDoseUserDate;
+DROP TABLE SupplementDose;
+DROP TABLE SupplementSchedule;
+DROP INDEX SupplementTrackableTrackable;
+DROP TABLE SupplementTrackable;
+DROP TABLE Supplement;
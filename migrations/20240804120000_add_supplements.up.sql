@@ -0,0 +1,76 @@
+-- Vitamins, pills, protein scoops and anything else that is taken by the dose instead of being
+-- weighed. Nutrients are per dose
+CREATE TABLE Supplement (
+	id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+	creation_date INTEGER NOT NULL DEFAULT (unixepoch() * 1000),
+	household_id INTEGER NOT NULL REFERENCES Household(id),
+	name TEXT NOT NULL COLLATE NOCASE,
+	-- What a single dose is, such as "capsule" or "scoop"
+	dose_unit TEXT NOT NULL,
+	kcal REAL CHECK (kcal >= 0),
+	proteins REAL CHECK (proteins >= 0),
+	fat REAL CHECK (fat >= 0),
+	carbohydrates REAL CHECK (carbohydrates >= 0),
+	UNIQUE(household_id, name)
+) STRICT;
+
+CREATE TABLE SupplementTrackable (
+	creation_date INTEGER NOT NULL DEFAULT (unixepoch() * 1000),
+	supplement_id INTEGER NOT NULL REFERENCES Supplement(id),
+	trackable_id INTEGER NOT NULL REFERENCES Trackable(id),
+	-- Amount in a single dose, in the trackable's unit
+	amount REAL NOT NULL CHECK (amount >= 0),
+	PRIMARY KEY (supplement_id, trackable_id)
+) STRICT;
+
+CREATE INDEX SupplementTrackableTrackable ON SupplementTrackable(trackable_id);
+
+-- Doses a user should take, at the same time of day in some weekdays
+CREATE TABLE SupplementSchedule (
+	id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+	creation_date INTEGER NOT NULL DEFAULT (unixepoch() * 1000),
+	user_id INTEGER NOT NULL REFERENCES User(id),
+	supplement_id INTEGER NOT NULL REFERENCES Supplement(id),
+	doses REAL NOT NULL CHECK (doses > 0),
+	-- Date of the first occurrence. Its time of day is used for every occurrence
+	start_date INTEGER NOT NULL,
+	end_date INTEGER,
+	-- Bitmask of the weekdays in which the schedule occurs. Monday is 1, Tuesday is 2, Wednesday is 4...
+	weekdays INTEGER NOT NULL,
+	-- Offset of the user's timezone, used to decide which weekday an occurrence falls in
+	utc_offset_minutes INTEGER NOT NULL DEFAULT 0
+) STRICT;
+
+CREATE TABLE SupplementDose (
+	id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+	creation_date INTEGER NOT NULL DEFAULT (unixepoch() * 1000),
+	user_id INTEGER NOT NULL REFERENCES User(id),
+	supplement_id INTEGER NOT NULL REFERENCES Supplement(id),
+	take_date INTEGER NOT NULL,
+	doses REAL NOT NULL DEFAULT 1 CHECK (doses > 0),
+	-- The meal the dose was taken with, if any
+	meal_id INTEGER REFERENCES Meal(id),
+	-- The occurrence of a schedule the dose was taken for, if any
+	schedule_id INTEGER REFERENCES SupplementSchedule(id),
+	scheduled_date INTEGER,
+	CHECK ((schedule_id IS NULL) = (scheduled_date IS NULL))
+) STRICT;
+
+CREATE INDEX SupplementDoseUserDate ON SupplementDose(user_id, take_date);
+CREATE INDEX SupplementDoseMeal ON SupplementDose(meal_id) WHERE meal_id IS NOT NULL;
+CREATE UNIQUE INDEX SupplementDoseOccurrence ON SupplementDose(schedule_id, scheduled_date)
+WHERE schedule_id IS NOT NULL;
+
+CREATE TRIGGER SupplementDoseVersionInsert AFTER INSERT ON SupplementDose
+WHEN NEW.meal_id IS NOT NULL BEGIN
+	UPDATE Meal SET version = version + 1 WHERE id = NEW.meal_id;
+END;
+
+CREATE TRIGGER SupplementDoseVersionUpdate AFTER UPDATE ON SupplementDose BEGIN
+	UPDATE Meal SET version = version + 1 WHERE id IN (OLD.meal_id, NEW.meal_id);
+END;
+
+CREATE TRIGGER SupplementDoseVersionDelete AFTER DELETE ON SupplementDose
+WHEN OLD.meal_id IS NOT NULL BEGIN
+	UPDATE Meal SET version = version + 1 WHERE id = OLD.meal_id;
+END;
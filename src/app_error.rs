@@ -1,8 +1,13 @@
 use aide::OperationOutput;
-use axum::response::{IntoResponse, Response};
+use axum::{
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
 use schemars::JsonSchema;
 
-use crate::server::ServerResponse;
+use crate::{
+    etag::PreconditionFailed, events::UnknownEventType, period::PeriodError, server::ServerResponse,
+};
 
 pub struct InternalServerError(pub anyhow::Error);
 
@@ -24,6 +29,13 @@ impl OperationOutput for InternalServerError {
 impl IntoResponse for InternalServerError {
     fn into_response(self) -> Response {
         {
+            if self.0.is::<PreconditionFailed>() {
+                return ServerResponse::error_code(self.0, StatusCode::PRECONDITION_FAILED)
+                    .into_response();
+            }
+            if self.0.is::<PeriodError>() || self.0.is::<UnknownEventType>() {
+                return ServerResponse::error_code(self.0, StatusCode::BAD_REQUEST).into_response();
+            }
             ServerResponse::error(self.0).into_response()
         }
     }
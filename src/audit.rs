@@ -0,0 +1,225 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use sqlx::{Executor, Sqlite};
+
+use crate::session::AuthUser;
+
+/// What the audit log keeps track of. Dishes and meals include their components.
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum AuditEntity {
+    Ingredient,
+    Dish,
+    Meal,
+}
+
+impl AuditEntity {
+    pub fn name(self) -> &'static str {
+        match self {
+            AuditEntity::Ingredient => "Ingredient",
+            AuditEntity::Dish => "Dish",
+            AuditEntity::Meal => "Meal",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<AuditEntity> {
+        [
+            AuditEntity::Ingredient,
+            AuditEntity::Dish,
+            AuditEntity::Meal,
+        ]
+        .into_iter()
+        .find(|e| e.name() == name)
+    }
+
+    /// Query that turns the row with id `?1` into the JSON kept in the audit log. Versions are
+    /// left out, so writes that change nothing else are not recorded. The properties of an
+    /// ingredient keep the Open Food Facts product they are computed from, and every entity keeps
+    /// its uuid, to restore them.
+    fn snapshot_sql(self) -> &'static str {
+        match self {
+            AuditEntity::Ingredient => {
+                r#"
+                SELECT json_object(
+                    'uuid', uuid,
+                    'name', name,
+                    'generic_id', generic_id,
+                    'co2e_per_kg', co2e_per_kg,
+                    'aliases', json((
+                        SELECT json_group_array(name)
+                        FROM (SELECT name FROM IngredientAlias WHERE ingredient_id = ?1 ORDER BY name)
+                    )),
+                    'names', json((
+                        SELECT json_group_array(json_object('language', language, 'name', name, 'source', source))
+                        FROM (SELECT * FROM IngredientName WHERE ingredient_id = ?1 ORDER BY language)
+                    )),
+                    'categories', json((
+                        SELECT json_group_array(json_object('category_id', category_id, 'source', source))
+                        FROM (SELECT * FROM IngredientCategory WHERE ingredient_id = ?1 ORDER BY category_id)
+                    )),
+                    'tags', json((
+                        SELECT json_group_array(name)
+                        FROM (SELECT name FROM IngredientTag WHERE ingredient_id = ?1 ORDER BY name)
+                    )),
+                    'trackables', json((
+                        SELECT json_group_array(json_object('trackable_id', trackable_id, 'amount_100g', amount_100g))
+                        FROM (
+                            SELECT trackable_id, amount_100g FROM IngredientTrackable
+                            WHERE ingredient_id = ?1 ORDER BY trackable_id
+                        )
+                    )),
+                    'properties', json((
+                        SELECT json_object(
+                            'product_code', product_code,
+                            'kcal_100g', kcal_100g,
+                            'proteins_100g', proteins_100g,
+                            'fat_100g', fat_100g,
+                            'carbohydrates_100g', carbohydrates_100g,
+                            'open_food_facts_json', json(open_food_facts_json)
+                        )
+                        FROM IngredientProperties WHERE ingredient_id = ?1
+                    ))
+                )
+                FROM Ingredient WHERE id = ?1"#
+            }
+            AuditEntity::Dish => {
+                r#"
+                SELECT json_object(
+                    'uuid', uuid,
+                    'name', name,
+                    'prep_date', prep_date,
+                    'total_weight', total_weight,
+                    'is_finished', is_finished,
+                    'ingredients', json((
+                        SELECT json_group_array(json_object('ingredient_id', ingredient_id, 'weight', weight))
+                        FROM (SELECT * FROM DishIngredient WHERE dish_id = Dish.id ORDER BY ingredient_id)
+                    ))
+                )
+                FROM Dish WHERE id = ?1 AND deletion_date IS NULL"#
+            }
+            AuditEntity::Meal => {
+                r#"
+                SELECT json_object(
+                    'uuid', uuid,
+                    'eat_date', eat_date,
+                    'duration', duration,
+                    'description', description,
+                    'dishes', json((
+                        SELECT json_group_array(json_object('dish_id', dish_id, 'weight', weight))
+                        FROM (SELECT * FROM MealDish WHERE meal_id = Meal.id ORDER BY dish_id)
+                    )),
+                    'ingredients', json((
+                        SELECT json_group_array(json_object('ingredient_id', ingredient_id, 'weight', weight))
+                        FROM (SELECT * FROM MealIngredient WHERE meal_id = Meal.id ORDER BY ingredient_id)
+                    ))
+                )
+                FROM Meal WHERE id = ?1 AND deletion_date IS NULL"#
+            }
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum AuditOperation {
+    Create,
+    Update,
+    Delete,
+    /// The entity was restored to what it looked like after an earlier change
+    Revert,
+}
+
+impl AuditOperation {
+    pub fn name(self) -> &'static str {
+        match self {
+            AuditOperation::Create => "Create",
+            AuditOperation::Update => "Update",
+            AuditOperation::Delete => "Delete",
+            AuditOperation::Revert => "Revert",
+        }
+    }
+}
+
+/// JSON of the entity as the audit log stores it, or `None` if it doesn't exist or is in the
+/// trash. Should be taken before changing it, and given to `record_change` afterwards.
+pub async fn snapshot<'c>(
+    executor: impl Executor<'c, Database = Sqlite>,
+    entity: AuditEntity,
+    id: i64,
+) -> anyhow::Result<Option<String>> {
+    Ok(sqlx::query_scalar::<_, String>(entity.snapshot_sql())
+        .bind(id)
+        .fetch_optional(executor)
+        .await?)
+}
+
+async fn insert_entry<'c>(
+    executor: impl Executor<'c, Database = Sqlite>,
+    user: &AuthUser,
+    entity: AuditEntity,
+    id: i64,
+    before: Option<String>,
+    operation: Option<AuditOperation>,
+) -> anyhow::Result<()> {
+    let sql = format!(
+        r#"
+        WITH Change(before, after) AS (SELECT ?2, ({}))
+        INSERT INTO AuditEntry (household_id, user_id, entity, entity_id, operation, before, after)
+        SELECT
+            ?3,
+            ?4,
+            ?5,
+            ?1,
+            coalesce(?6, CASE
+                WHEN before IS NULL THEN 'Create'
+                WHEN after IS NULL THEN 'Delete'
+                ELSE 'Update'
+            END),
+            before,
+            after
+        FROM Change
+        WHERE before IS NOT after"#,
+        entity.snapshot_sql()
+    );
+    sqlx::query(&sql)
+        .bind(id)
+        .bind(before)
+        .bind(user.household_id)
+        .bind(user.user_id)
+        .bind(entity.name())
+        .bind(operation.map(AuditOperation::name))
+        .execute(executor)
+        .await?;
+
+    Ok(())
+}
+
+/// Records that `user` changed the entity, which looked like `before` until then. Creations are
+/// recorded with no `before`, and deletions once the entity is gone. Nothing is recorded if the
+/// entity didn't change.
+pub async fn record_change<'c>(
+    executor: impl Executor<'c, Database = Sqlite>,
+    user: &AuthUser,
+    entity: AuditEntity,
+    id: i64,
+    before: Option<String>,
+) -> anyhow::Result<()> {
+    insert_entry(executor, user, entity, id, before, None).await
+}
+
+/// Same as `record_change`, for when the entity was reverted to an earlier state.
+pub async fn record_revert<'c>(
+    executor: impl Executor<'c, Database = Sqlite>,
+    user: &AuthUser,
+    entity: AuditEntity,
+    id: i64,
+    before: Option<String>,
+) -> anyhow::Result<()> {
+    insert_entry(
+        executor,
+        user,
+        entity,
+        id,
+        before,
+        Some(AuditOperation::Revert),
+    )
+    .await
+}
@@ -0,0 +1,100 @@
+use axum::{extract::State, Json};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{
+    models::{Household, User},
+    server::{ServerResponse, ServerResponseResult},
+    session::{generate_token, hash_token, verify_password, SESSION_DURATION},
+    state::AppState,
+};
+
+#[derive(Deserialize, JsonSchema)]
+pub struct PostLoginBody {
+    username: String,
+    password: String,
+}
+
+#[derive(Serialize, JsonSchema)]
+pub struct SessionResponse {
+    /// Must be sent in the `Authorization: Bearer <token>` header of every other request
+    pub token: String,
+    pub expiration_date: i64,
+    pub user: User,
+    pub household: Household,
+}
+
+#[derive(Error, Debug)]
+enum PostLoginError {
+    #[error("Invalid username or password")]
+    InvalidCredentials,
+}
+
+/// Creates a new session for the user, returning its token.
+pub async fn create_session(
+    connection: &mut sqlx::SqliteConnection,
+    user_id: i64,
+) -> anyhow::Result<(String, i64)> {
+    let token = generate_token();
+    let token_hash = hash_token(&token);
+    let expiration_date = chrono::Utc::now().timestamp_millis() + SESSION_DURATION;
+
+    sqlx::query!(
+        "INSERT INTO Session (token_hash, user_id, expiration_date) VALUES (?, ?, ?)",
+        token_hash,
+        user_id,
+        expiration_date
+    )
+    .execute(&mut *connection)
+    .await?;
+
+    Ok((token, expiration_date))
+}
+
+pub async fn post_login(
+    State(AppState { connection, .. }): State<AppState>,
+    Json(PostLoginBody { username, password }): Json<PostLoginBody>,
+) -> ServerResponseResult<SessionResponse> {
+    let username = username.trim();
+    let user = sqlx::query!(
+        r#"
+        SELECT id, password_hash
+        FROM User
+        WHERE username = ?"#,
+        username
+    )
+    .fetch_optional(&connection)
+    .await?
+    .filter(|user| verify_password(&password, &user.password_hash))
+    .ok_or(PostLoginError::InvalidCredentials)?;
+
+    let (user, household) = futures::try_join!(
+        sqlx::query_as!(
+            User,
+            "SELECT id, creation_date, username, display_name, household_id FROM User WHERE id = ?",
+            user.id
+        )
+        .fetch_one(&connection),
+        sqlx::query_as!(
+            Household,
+            r#"
+            SELECT Household.id, Household.creation_date, Household.name, Household.invite_code
+            FROM Household JOIN User ON User.household_id = Household.id
+            WHERE User.id = ?"#,
+            user.id
+        )
+        .fetch_one(&connection),
+    )?;
+
+    let mut connection = connection.acquire().await?;
+    let (token, expiration_date) = create_session(&mut connection, user.id).await?;
+
+    Ok(ServerResponse::success(SessionResponse {
+        token,
+        expiration_date,
+        user,
+        household,
+    })
+    .json())
+}
@@ -0,0 +1,25 @@
+use axum::{extract::State, http::HeaderMap};
+
+use crate::{
+    server::{ServerResponse, ServerResponseResult},
+    session::{bearer_token, hash_token, AuthUser},
+    state::AppState,
+};
+
+/// Ends the session used to make this request.
+pub async fn post_logout(
+    State(AppState { connection, .. }): State<AppState>,
+    AuthUser { user_id, .. }: AuthUser,
+    headers: HeaderMap,
+) -> ServerResponseResult<bool> {
+    let token_hash = bearer_token(&headers).map(hash_token);
+    sqlx::query!(
+        "DELETE FROM Session WHERE token_hash = ? AND user_id = ?",
+        token_hash,
+        user_id
+    )
+    .execute(&connection)
+    .await?;
+
+    Ok(ServerResponse::success(true).json())
+}
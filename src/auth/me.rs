@@ -0,0 +1,45 @@
+use axum::extract::State;
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use crate::{
+    models::{Household, User},
+    server::{ServerResponse, ServerResponseResult},
+    session::{AuthScope, AuthUser},
+    state::AppState,
+};
+
+#[derive(Serialize, JsonSchema)]
+pub struct GetMeResponse {
+    user: User,
+    household: Household,
+}
+
+pub async fn get_me(
+    State(AppState { connection, .. }): State<AppState>,
+    AuthUser {
+        user_id,
+        household_id,
+    }: AuthUser,
+    scope: AuthScope,
+) -> ServerResponseResult<GetMeResponse> {
+    let (user, mut household) = futures::try_join!(
+        sqlx::query_as!(
+            User,
+            "SELECT id, creation_date, username, display_name, household_id FROM User WHERE id = ?",
+            user_id
+        )
+        .fetch_one(&connection),
+        sqlx::query_as!(
+            Household,
+            "SELECT id, creation_date, name, invite_code FROM Household WHERE id = ?",
+            household_id
+        )
+        .fetch_one(&connection),
+    )?;
+    if !scope.can_invite() {
+        household.invite_code = None;
+    }
+
+    Ok(ServerResponse::success(GetMeResponse { user, household }).json())
+}
@@ -0,0 +1,20 @@
+use aide::axum::{
+    routing::{get, post},
+    ApiRouter,
+};
+
+use crate::state::AppState;
+
+mod login;
+mod logout;
+mod me;
+mod register;
+
+pub fn route(state: AppState) -> ApiRouter {
+    ApiRouter::new()
+        .api_route("/register", post(register::post_register))
+        .api_route("/login", post(login::post_login))
+        .api_route("/logout", post(logout::post_logout))
+        .api_route("/me", get(me::get_me))
+        .with_state(state)
+}
@@ -0,0 +1,212 @@
+use axum::{extract::State, http::StatusCode, Json};
+use schemars::JsonSchema;
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::{
+    models::{Household, User},
+    server::{ServerResponse, ServerResponseResult},
+    session::{generate_token, hash_password},
+    state::AppState,
+};
+
+use super::login::{create_session, SessionResponse};
+
+/// Smallest password length accepted
+const MIN_PASSWORD_LENGTH: usize = 8;
+
+/// Usual meal descriptions, with their time windows in minutes since midnight, that new
+/// households start with
+const DEFAULT_DESCRIPTIONS: [(&str, Option<i64>, Option<i64>); 5] = [
+    ("Breakfast", Some(360), Some(600)),
+    ("Lunch", Some(690), Some(870)),
+    ("Work Lunch", Some(690), Some(840)),
+    ("Dinner", Some(1080), Some(1320)),
+    ("Snack", None, None),
+];
+
+#[derive(Deserialize, JsonSchema)]
+pub struct PostRegisterBody {
+    username: String,
+    password: String,
+    /// Name shown to the other members of the household
+    display_name: Option<String>,
+    /// Invite code of an existing household to join. A new household is created if not provided
+    household_invite_code: Option<String>,
+    /// Name of the new household. Ignored when joining an existing one
+    household_name: Option<String>,
+}
+
+#[derive(Error, Debug)]
+enum PostRegisterError {
+    #[error("The username should not be empty")]
+    UsernameIsEmpty,
+    #[error("The username \"{0}\" is already taken")]
+    UsernameTaken(String),
+    #[error("The password must have at least {MIN_PASSWORD_LENGTH} characters")]
+    PasswordTooShort,
+    #[error("There is no household with invite code \"{0}\"")]
+    UnknownInviteCode(String),
+}
+
+/// Gives every row created before users existed to the first user that registers.
+async fn claim_unowned_rows(
+    connection: &mut sqlx::SqliteConnection,
+    user_id: i64,
+    household_id: i64,
+) -> anyhow::Result<()> {
+    for table in [
+        "Meal",
+        "PlannedMeal",
+        "RecurringMealPlan",
+        "MealTemplate",
+        "NutritionGoal",
+    ] {
+        sqlx::query(&format!(
+            "UPDATE {table} SET user_id = ? WHERE user_id IS NULL"
+        ))
+        .bind(user_id)
+        .execute(&mut *connection)
+        .await?;
+    }
+    for table in ["Ingredient", "Dish", "UsualMealDescriptions"] {
+        sqlx::query(&format!(
+            "UPDATE {table} SET household_id = ? WHERE household_id IS NULL"
+        ))
+        .bind(household_id)
+        .execute(&mut *connection)
+        .await?;
+    }
+
+    Ok(())
+}
+
+async fn insert_default_descriptions(
+    connection: &mut sqlx::SqliteConnection,
+    household_id: i64,
+) -> anyhow::Result<()> {
+    sqlx::QueryBuilder::new(
+        "INSERT INTO UsualMealDescriptions (household_id, description, position, start_minute, end_minute) ",
+    )
+    .push_values(
+        DEFAULT_DESCRIPTIONS.iter().enumerate(),
+        |mut b, (position, (description, start_minute, end_minute))| {
+            b.push_bind(household_id)
+                .push_bind(*description)
+                .push_bind(position as i64)
+                .push_bind(*start_minute)
+                .push_bind(*end_minute);
+        },
+    )
+    .build()
+    .execute(&mut *connection)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn post_register(
+    State(AppState { connection, .. }): State<AppState>,
+    Json(PostRegisterBody {
+        username,
+        password,
+        display_name,
+        household_invite_code,
+        household_name,
+    }): Json<PostRegisterBody>,
+) -> ServerResponseResult<SessionResponse> {
+    let username = username.trim().to_string();
+    if username.is_empty() {
+        return Err(PostRegisterError::UsernameIsEmpty)?;
+    }
+    if password.chars().count() < MIN_PASSWORD_LENGTH {
+        return Err(PostRegisterError::PasswordTooShort)?;
+    }
+    let password_hash = hash_password(&password)?;
+    let display_name = display_name
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty());
+
+    let mut transaction = connection.begin().await?;
+
+    let username_taken = sqlx::query_scalar!("SELECT id FROM User WHERE username = ?", username)
+        .fetch_optional(&mut *transaction)
+        .await?
+        .is_some();
+    if username_taken {
+        return Err(PostRegisterError::UsernameTaken(username))?;
+    }
+
+    let is_first_user = sqlx::query_scalar!(r#"SELECT COUNT(*) AS "count!: i64" FROM User"#)
+        .fetch_one(&mut *transaction)
+        .await?
+        == 0;
+
+    let (household, is_new_household) = match household_invite_code {
+        Some(invite_code) => (
+            sqlx::query_as!(
+                Household,
+                "SELECT id, creation_date, name, invite_code FROM Household WHERE invite_code = ?",
+                invite_code
+            )
+            .fetch_optional(&mut *transaction)
+            .await?
+            .ok_or(PostRegisterError::UnknownInviteCode(invite_code))?,
+            false,
+        ),
+        None => {
+            let name = household_name
+                .map(|name| name.trim().to_string())
+                .filter(|name| !name.is_empty())
+                .unwrap_or_else(|| format!("{username}'s household"));
+            let invite_code = generate_token();
+            (
+                sqlx::query_as!(
+                    Household,
+                    r#"
+                    INSERT INTO Household (name, invite_code) VALUES (?, ?)
+                    RETURNING id, creation_date, name, invite_code;"#,
+                    name,
+                    invite_code
+                )
+                .fetch_one(&mut *transaction)
+                .await?,
+                true,
+            )
+        }
+    };
+
+    let user = sqlx::query_as!(
+        User,
+        r#"
+        INSERT INTO User (username, password_hash, display_name, household_id) VALUES (?, ?, ?, ?)
+        RETURNING id, creation_date, username, display_name, household_id;"#,
+        username,
+        password_hash,
+        display_name,
+        household.id
+    )
+    .fetch_one(&mut *transaction)
+    .await?;
+
+    if is_first_user {
+        claim_unowned_rows(&mut transaction, user.id, household.id).await?;
+    } else if is_new_household {
+        insert_default_descriptions(&mut transaction, household.id).await?;
+    }
+
+    let (token, expiration_date) = create_session(&mut transaction, user.id).await?;
+
+    transaction.commit().await?;
+
+    Ok(ServerResponse::success_code(
+        SessionResponse {
+            token,
+            expiration_date,
+            user,
+            household,
+        },
+        StatusCode::CREATED,
+    )
+    .json())
+}
@@ -0,0 +1,141 @@
+use std::{collections::HashMap, ops::AddAssign};
+
+use schemars::JsonSchema;
+use serde::Serialize;
+use sqlx::{prelude::FromRow, Sqlite};
+
+use crate::nutrition::{fetch_dish_composition, WeightedComponent};
+
+/// Carbon footprint of some food, based on the Eco-Score data of its ingredients or on the
+/// footprint entered for them.
+#[derive(Serialize, JsonSchema, Default, Clone, Copy, Debug)]
+pub struct Footprint {
+    /// Kilograms of CO2 equivalent
+    pub co2e: f64,
+    /// Grams of ingredients without a footprint, which were left out of `co2e`
+    pub unknown_weight: f64,
+}
+
+impl Footprint {
+    /// Footprint of `grams` of an ingredient that emits `co2e_per_kg`, if it's known.
+    pub fn new(co2e_per_kg: Option<f64>, grams: f64) -> Self {
+        match co2e_per_kg {
+            Some(co2e_per_kg) => Footprint {
+                co2e: co2e_per_kg * grams / 1000.0,
+                unknown_weight: 0.0,
+            },
+            None => Footprint {
+                co2e: 0.0,
+                unknown_weight: grams,
+            },
+        }
+    }
+}
+
+impl AddAssign for Footprint {
+    fn add_assign(&mut self, rhs: Self) {
+        self.co2e += rhs.co2e;
+        self.unknown_weight += rhs.unknown_weight;
+    }
+}
+
+#[derive(FromRow)]
+struct DatabaseIngredientFootprint {
+    ingredient_id: i64,
+    co2e_per_kg: f64,
+}
+
+/// Fetches the kilograms of CO2 equivalent emitted per kilogram of every ingredient in
+/// `ingredient_ids`. Footprints entered by hand take precedence over the ones of the properties,
+/// and variants without one use the one of their generic ingredient. Ingredients without a
+/// footprint are left out of the result.
+pub async fn fetch_ingredient_footprints(
+    connection: &sqlx::Pool<Sqlite>,
+    ingredient_ids: &[i64],
+) -> anyhow::Result<HashMap<i64, f64>> {
+    if ingredient_ids.is_empty() {
+        return Ok(HashMap::new());
+    }
+
+    Ok(sqlx::QueryBuilder::<Sqlite>::new(
+        r#"
+        SELECT ingredient_id, CAST (co2e_per_kg AS FLOAT) AS co2e_per_kg
+        FROM IngredientFootprint
+        WHERE ingredient_id IN "#,
+    )
+    .push_tuples(ingredient_ids.iter(), |mut p, id| {
+        p.push_bind(*id);
+    })
+    .build_query_as::<DatabaseIngredientFootprint>()
+    .fetch_all(connection)
+    .await?
+    .into_iter()
+    .map(|row| (row.ingredient_id, row.co2e_per_kg))
+    .collect())
+}
+
+/// Per kilogram footprints of a set of dishes and ingredients, fetched all at once.
+pub struct FootprintTable {
+    dishes: HashMap<i64, Vec<(i64, f64)>>,
+    ingredients: HashMap<i64, f64>,
+}
+
+impl FootprintTable {
+    pub async fn fetch(
+        connection: &sqlx::Pool<Sqlite>,
+        components: &[WeightedComponent],
+    ) -> anyhow::Result<Self> {
+        let dishes = fetch_dish_composition(
+            connection,
+            &components
+                .iter()
+                .filter_map(|c| c.dish_id)
+                .collect::<Vec<i64>>(),
+        )
+        .await?;
+
+        let ingredients = fetch_ingredient_footprints(
+            connection,
+            &components
+                .iter()
+                .filter_map(|c| c.ingredient_id)
+                .chain(dishes.values().flatten().map(|(id, _)| *id))
+                .collect::<Vec<i64>>(),
+        )
+        .await?;
+
+        Ok(FootprintTable {
+            dishes,
+            ingredients,
+        })
+    }
+
+    /// The grams of each ingredient in `component`. Dishes are made of their ingredients.
+    pub fn ingredient_weights(&self, component: &WeightedComponent) -> Vec<(i64, f64)> {
+        match (component.dish_id, component.ingredient_id) {
+            (Some(dish_id), _) => self
+                .dishes
+                .get(&dish_id)
+                .into_iter()
+                .flatten()
+                .map(|(id, grams)| (*id, grams * component.weight as f64))
+                .collect(),
+            (_, Some(ingredient_id)) => vec![(ingredient_id, component.weight as f64)],
+            (None, None) => vec![],
+        }
+    }
+
+    pub fn ingredient_footprint(&self, ingredient_id: i64, grams: f64) -> Footprint {
+        Footprint::new(self.ingredients.get(&ingredient_id).copied(), grams)
+    }
+
+    pub fn component_footprint(&self, component: &WeightedComponent) -> Footprint {
+        self.ingredient_weights(component).into_iter().fold(
+            Footprint::default(),
+            |mut footprint, (ingredient_id, grams)| {
+                footprint += self.ingredient_footprint(ingredient_id, grams);
+                footprint
+            },
+        )
+    }
+}
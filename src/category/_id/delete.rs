@@ -0,0 +1,72 @@
+use axum::extract::{Path, State};
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+use crate::{
+    audit::{record_change, snapshot, AuditEntity},
+    server::{ServerResponse, ServerResponseResult},
+    session::AuthUser,
+    state::AppState,
+};
+
+#[derive(Deserialize, JsonSchema)]
+pub struct CategoryId {
+    category_id: i64,
+}
+
+/// Deletes the category. Its children are moved to its parent, and its ingredients are no longer
+/// in it, which is recorded in their history.
+pub async fn delete_category(
+    State(AppState { connection, .. }): State<AppState>,
+    user: AuthUser,
+    Path(CategoryId { category_id }): Path<CategoryId>,
+) -> ServerResponseResult<bool> {
+    user.check_category(&connection, category_id).await?;
+
+    let mut transaction = connection.begin().await?;
+    let ingredient_ids = sqlx::query_scalar!(
+        "SELECT ingredient_id FROM IngredientCategory WHERE category_id = ?",
+        category_id
+    )
+    .fetch_all(&mut *transaction)
+    .await?;
+    let mut ingredients_before = vec![];
+    for ingredient_id in &ingredient_ids {
+        ingredients_before
+            .push(snapshot(&mut *transaction, AuditEntity::Ingredient, *ingredient_id).await?);
+    }
+
+    sqlx::query!(
+        r#"
+        UPDATE Category
+        SET parent_id = (SELECT parent_id FROM Category WHERE id = ?1)
+        WHERE parent_id = ?1"#,
+        category_id
+    )
+    .execute(&mut *transaction)
+    .await?;
+    sqlx::query!(
+        "DELETE FROM IngredientCategory WHERE category_id = ?",
+        category_id
+    )
+    .execute(&mut *transaction)
+    .await?;
+    sqlx::query!("DELETE FROM Category WHERE id = ?", category_id)
+        .execute(&mut *transaction)
+        .await?;
+
+    for (ingredient_id, before) in ingredient_ids.into_iter().zip(ingredients_before) {
+        record_change(
+            &mut *transaction,
+            &user,
+            AuditEntity::Ingredient,
+            ingredient_id,
+            before,
+        )
+        .await?;
+    }
+
+    transaction.commit().await?;
+
+    Ok(ServerResponse::success(true).json())
+}
@@ -0,0 +1,69 @@
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+use schemars::JsonSchema;
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::{
+    models::Category,
+    server::{ServerResponse, ServerResponseResult},
+    session::AuthUser,
+    state::AppState,
+    taxonomy::is_in_subtree,
+};
+
+#[derive(Deserialize, JsonSchema)]
+pub struct CategoryId {
+    category_id: i64,
+}
+
+#[derive(Deserialize, JsonSchema)]
+pub struct PostEditCategoryBody {
+    name: String,
+    /// Category this one is inside of. Top level if not provided
+    parent_id: Option<i64>,
+}
+
+#[derive(Error, Debug)]
+enum PostEditCategoryError {
+    #[error("The name of a category should not be empty")]
+    EmptyName,
+    #[error("Category {0} can't be moved inside of itself or of one of its descendants")]
+    ParentInSubtree(i64),
+}
+
+/// Renames the category, or moves it to another place in the tree along with its descendants.
+pub async fn post_edit_category(
+    State(AppState { connection, .. }): State<AppState>,
+    user: AuthUser,
+    Path(CategoryId { category_id }): Path<CategoryId>,
+    Json(PostEditCategoryBody { name, parent_id }): Json<PostEditCategoryBody>,
+) -> ServerResponseResult<Category> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(PostEditCategoryError::EmptyName)?;
+    }
+    user.check_category(&connection, category_id).await?;
+    if let Some(parent_id) = parent_id {
+        user.check_category(&connection, parent_id).await?;
+        if is_in_subtree(&connection, parent_id, category_id).await? {
+            return Err(PostEditCategoryError::ParentInSubtree(category_id))?;
+        }
+    }
+
+    let category = sqlx::query_as!(
+        Category,
+        r#"
+        UPDATE Category SET name = ?, parent_id = ? WHERE id = ?
+        RETURNING id as "id!", creation_date, parent_id, name, open_food_facts_tag;"#,
+        name,
+        parent_id,
+        category_id
+    )
+    .fetch_one(&connection)
+    .await?;
+
+    Ok(ServerResponse::success(category).json())
+}
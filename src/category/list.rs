@@ -0,0 +1,49 @@
+use axum::extract::State;
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use crate::{
+    server::{ServerResponse, ServerResponseResult},
+    session::AuthUser,
+    state::AppState,
+};
+
+#[derive(Serialize, JsonSchema)]
+pub struct ListedCategory {
+    id: i64,
+    creation_date: i64,
+    /// `None` for top level categories
+    parent_id: Option<i64>,
+    name: String,
+    open_food_facts_tag: Option<String>,
+    /// How many ingredients were put in this category, not counting its descendants
+    ingredient_count: i64,
+}
+
+/// Every category of the household. The tree can be rebuilt from their `parent_id`.
+pub async fn list_categories(
+    State(AppState { connection, .. }): State<AppState>,
+    AuthUser { household_id, .. }: AuthUser,
+) -> ServerResponseResult<Vec<ListedCategory>> {
+    let categories = sqlx::query_as!(
+        ListedCategory,
+        r#"
+        SELECT
+            id,
+            creation_date,
+            parent_id,
+            name,
+            open_food_facts_tag,
+            (
+                SELECT COUNT(*) FROM IngredientCategory WHERE category_id = Category.id
+            ) AS "ingredient_count!: i64"
+        FROM Category
+        WHERE household_id = ?
+        ORDER BY name;"#,
+        household_id
+    )
+    .fetch_all(&connection)
+    .await?;
+
+    Ok(ServerResponse::success(categories).json())
+}
@@ -0,0 +1,19 @@
+use aide::axum::{
+    routing::{get, post},
+    ApiRouter,
+};
+
+use crate::state::AppState;
+
+mod _id;
+mod list;
+mod post;
+mod summary;
+
+pub fn route(state: AppState) -> ApiRouter {
+    ApiRouter::new()
+        .api_route("/", post(post::post_category).get(list::list_categories))
+        .api_route("/summary", get(summary::get_category_summary))
+        .nest_api_service("/:category_id", _id::route(state.clone()))
+        .with_state(state)
+}
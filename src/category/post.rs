@@ -0,0 +1,52 @@
+use axum::{extract::State, Json};
+use schemars::JsonSchema;
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::{
+    models::Category,
+    server::{ServerResponse, ServerResponseResult},
+    session::AuthUser,
+    state::AppState,
+};
+
+#[derive(Deserialize, JsonSchema)]
+pub struct PostCategoryBody {
+    name: String,
+    /// Category this one is inside of. Top level if not provided
+    parent_id: Option<i64>,
+}
+
+#[derive(Error, Debug)]
+enum PostCategoryError {
+    #[error("The name of a category should not be empty")]
+    EmptyName,
+}
+
+pub async fn post_category(
+    State(AppState { connection, .. }): State<AppState>,
+    user @ AuthUser { household_id, .. }: AuthUser,
+    Json(PostCategoryBody { name, parent_id }): Json<PostCategoryBody>,
+) -> ServerResponseResult<Category> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(PostCategoryError::EmptyName)?;
+    }
+    if let Some(parent_id) = parent_id {
+        user.check_category(&connection, parent_id).await?;
+    }
+
+    let category = sqlx::query_as!(
+        Category,
+        r#"
+        INSERT INTO Category (household_id, parent_id, name) VALUES (?, ?, ?)
+        RETURNING id, creation_date, parent_id, name, open_food_facts_tag;"#,
+        household_id,
+        parent_id,
+        name
+    )
+    .fetch_one(&connection)
+    .await?;
+
+    Ok(ServerResponse::success(category).json())
+}
@@ -0,0 +1,189 @@
+use std::collections::{BTreeMap, HashMap};
+
+use axum::extract::{Query, State};
+use chrono::{NaiveDate, TimeZone};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use sqlx::prelude::FromRow;
+
+use crate::{
+    models::Category,
+    nutrition::fetch_dish_composition,
+    period::utc_offset,
+    period::{period_start, SummaryPeriod},
+    server::{ServerResponse, ServerResponseResult},
+    session::AuthUser,
+    state::AppState,
+    taxonomy::fetch_ingredient_categories,
+};
+
+#[derive(Deserialize, JsonSchema)]
+pub struct GetCategorySummaryQueryParams {
+    from: i64,
+    to: i64,
+    /// Offset of the household's timezone, used to decide which day each meal belongs to
+    utc_offset_minutes: Option<i32>,
+    period: Option<SummaryPeriod>,
+    /// Household member whose meals should be summarized. Defaults to the current user
+    member_id: Option<i64>,
+    /// Only this category, with the ingredients of its descendants
+    category_id: Option<i64>,
+}
+
+#[derive(Serialize, JsonSchema)]
+pub struct CategoryIntake {
+    category_id: i64,
+    name: String,
+    /// Grams eaten of ingredients in the category or in one of its descendants
+    weight: f64,
+}
+
+#[derive(Serialize, JsonSchema)]
+pub struct CategoryPeriodSummary {
+    /// First day of the period, in the `YYYY-MM-DD` format
+    start: String,
+    /// Heaviest first
+    categories: Vec<CategoryIntake>,
+    /// Grams eaten of ingredients without a category
+    uncategorized: f64,
+}
+
+#[derive(FromRow)]
+struct EatenComponent {
+    date: i64,
+    dish_id: Option<i64>,
+    ingredient_id: Option<i64>,
+    weight: i64,
+}
+
+/// Grams eaten of each category by a household member, day by day or week by week. Dishes count
+/// as the ingredients they are made of, and ingredients count toward their categories and all of
+/// their ancestors.
+pub async fn get_category_summary(
+    State(AppState { connection, .. }): State<AppState>,
+    user @ AuthUser { household_id, .. }: AuthUser,
+    Query(GetCategorySummaryQueryParams {
+        from,
+        to,
+        utc_offset_minutes,
+        period,
+        member_id,
+        category_id,
+    }): Query<GetCategorySummaryQueryParams>,
+) -> ServerResponseResult<Vec<CategoryPeriodSummary>> {
+    let offset = utc_offset(utc_offset_minutes)?;
+    let period = period.unwrap_or_default();
+    let member_id = user.resolve_member(&connection, member_id).await?;
+    if let Some(category_id) = category_id {
+        user.check_category(&connection, category_id).await?;
+    }
+
+    let components = sqlx::query_as::<_, EatenComponent>(
+        r#"
+        SELECT Meal.eat_date AS date, dish_id, NULL AS ingredient_id, weight
+        FROM Meal JOIN MealDish ON MealDish.meal_id = Meal.id
+        WHERE Meal.user_id = ?3 AND Meal.deletion_date IS NULL AND Meal.eat_date >= ?1 AND Meal.eat_date <= ?2
+        UNION ALL
+        SELECT Meal.eat_date AS date, NULL AS dish_id, ingredient_id, weight
+        FROM Meal JOIN MealIngredient ON MealIngredient.meal_id = Meal.id
+        WHERE Meal.user_id = ?3 AND Meal.deletion_date IS NULL AND Meal.eat_date >= ?1 AND Meal.eat_date <= ?2;"#,
+    )
+    .bind(from)
+    .bind(to)
+    .bind(member_id)
+    .fetch_all(&connection)
+    .await?;
+
+    let dishes = fetch_dish_composition(
+        &connection,
+        &components
+            .iter()
+            .filter_map(|c| c.dish_id)
+            .collect::<Vec<i64>>(),
+    )
+    .await?;
+
+    // Grams of each ingredient eaten in each period
+    let mut eaten = BTreeMap::<NaiveDate, HashMap<i64, f64>>::new();
+    for component in &components {
+        let Some(date) = offset.timestamp_millis_opt(component.date).single() else {
+            continue;
+        };
+        let ingredients = eaten
+            .entry(period_start(date.date_naive(), period))
+            .or_default();
+        match (component.dish_id, component.ingredient_id) {
+            (Some(dish_id), _) => {
+                for (ingredient_id, grams) in dishes.get(&dish_id).into_iter().flatten() {
+                    *ingredients.entry(*ingredient_id).or_default() +=
+                        grams * component.weight as f64;
+                }
+            }
+            (_, Some(ingredient_id)) => {
+                *ingredients.entry(ingredient_id).or_default() += component.weight as f64;
+            }
+            (None, None) => {}
+        }
+    }
+
+    let ingredient_categories = fetch_ingredient_categories(
+        &connection,
+        &eaten
+            .values()
+            .flat_map(|ingredients| ingredients.keys().copied())
+            .collect::<Vec<i64>>(),
+    )
+    .await?;
+    let categories = sqlx::query_as!(
+        Category,
+        r#"
+        SELECT id, creation_date, parent_id, name, open_food_facts_tag
+        FROM Category
+        WHERE household_id = ?"#,
+        household_id
+    )
+    .fetch_all(&connection)
+    .await?
+    .into_iter()
+    .map(|category| (category.id, category))
+    .collect::<HashMap<i64, Category>>();
+
+    let summaries = eaten
+        .into_iter()
+        .map(|(start, ingredients)| {
+            let mut uncategorized = 0.0;
+            let mut weights = HashMap::<i64, f64>::new();
+            for (ingredient_id, weight) in ingredients {
+                match ingredient_categories.get(&ingredient_id) {
+                    Some(category_ids) => {
+                        for id in category_ids {
+                            *weights.entry(*id).or_default() += weight;
+                        }
+                    }
+                    None => uncategorized += weight,
+                }
+            }
+
+            let mut intakes = weights
+                .into_iter()
+                .filter(|(id, _)| category_id.is_none_or(|category_id| category_id == *id))
+                .filter_map(|(id, weight)| {
+                    Some(CategoryIntake {
+                        category_id: id,
+                        name: categories.get(&id)?.name.clone(),
+                        weight,
+                    })
+                })
+                .collect::<Vec<CategoryIntake>>();
+            intakes.sort_by(|a, b| b.weight.total_cmp(&a.weight));
+
+            CategoryPeriodSummary {
+                start: start.format("%Y-%m-%d").to_string(),
+                categories: intakes,
+                uncategorized,
+            }
+        })
+        .collect();
+
+    Ok(ServerResponse::success(summaries).json())
+}
@@ -0,0 +1,150 @@
+use chrono::{Duration, NaiveDate, TimeZone, Utc};
+use schemars::JsonSchema;
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::{models::ShareComment, session::GuestShare};
+
+#[derive(Clone, Copy)]
+pub enum CommentAuthor {
+    Guest,
+    Owner,
+}
+
+impl CommentAuthor {
+    pub fn name(self) -> &'static str {
+        match self {
+            CommentAuthor::Guest => "Guest",
+            CommentAuthor::Owner => "Owner",
+        }
+    }
+}
+
+/// A comment about a meal or a day, as received in request bodies. Exactly one of `meal_id`,
+/// `day` and `reply_to_id` must be provided. Replies are attached to the same meal or day as the
+/// comment they reply to.
+#[derive(Deserialize, JsonSchema)]
+pub struct PostCommentBody {
+    pub meal_id: Option<i64>,
+    /// Day in the `YYYY-MM-DD` format
+    pub day: Option<String>,
+    pub reply_to_id: Option<i64>,
+    pub body: String,
+}
+
+#[derive(Error, Debug)]
+pub enum CommentError {
+    #[error("The comment should not be empty")]
+    BodyIsEmpty,
+    #[error("Exactly one of meal_id, day and reply_to_id must be provided")]
+    InvalidTarget,
+    #[error("Invalid day \"{0}\". It must be in the YYYY-MM-DD format")]
+    InvalidDay(String),
+    #[error("The day \"{0}\" is not shared")]
+    DayNotShared(String),
+    #[error("Could not find a shared meal with id \"{0}\"")]
+    MealNotShared(i64),
+    #[error("Could not find comment with id \"{0}\"")]
+    CommentNotFound(i64),
+}
+
+/// Inserts a comment on something shared by `share`, making sure the meal it's about was shared.
+pub async fn insert_comment(
+    connection: &sqlx::Pool<sqlx::Sqlite>,
+    share: &GuestShare,
+    author: CommentAuthor,
+    PostCommentBody {
+        meal_id,
+        day,
+        reply_to_id,
+        body,
+    }: PostCommentBody,
+) -> anyhow::Result<ShareComment> {
+    let body = body.trim();
+    if body.is_empty() {
+        return Err(CommentError::BodyIsEmpty)?;
+    }
+
+    let (meal_id, day) = match (meal_id, day, reply_to_id) {
+        (Some(meal_id), None, None) => {
+            sqlx::query_scalar!(
+                r#"
+                SELECT id FROM Meal
+                WHERE id = ? AND user_id = ? AND deletion_date IS NULL AND eat_date BETWEEN ? AND ?"#,
+                meal_id,
+                share.user_id,
+                share.start_date,
+                share.end_date
+            )
+            .fetch_optional(connection)
+            .await?
+            .ok_or(CommentError::MealNotShared(meal_id))?;
+            (Some(meal_id), None)
+        }
+        (None, Some(day), None) => {
+            let date = NaiveDate::parse_from_str(&day, "%Y-%m-%d")
+                .map_err(|_| CommentError::InvalidDay(day.clone()))?;
+            // Shared days are grouped in the viewer's timezone, so the days at both ends of the
+            // share are accepted whatever their offset from UTC
+            let (Some(start), Some(end)) = (
+                Utc.timestamp_millis_opt(share.start_date).single(),
+                Utc.timestamp_millis_opt(share.end_date).single(),
+            ) else {
+                return Err(CommentError::DayNotShared(day))?;
+            };
+            if date < start.date_naive() - Duration::days(1)
+                || date > end.date_naive() + Duration::days(1)
+            {
+                return Err(CommentError::DayNotShared(day))?;
+            }
+            (None, Some(day))
+        }
+        (None, None, Some(reply_to_id)) => {
+            let comment = sqlx::query!(
+                "SELECT meal_id, day FROM ShareComment WHERE id = ? AND share_id = ?",
+                reply_to_id,
+                share.share_id
+            )
+            .fetch_optional(connection)
+            .await?
+            .ok_or(CommentError::CommentNotFound(reply_to_id))?;
+            (comment.meal_id, comment.day)
+        }
+        _ => return Err(CommentError::InvalidTarget)?,
+    };
+
+    let author = author.name();
+    Ok(sqlx::query_as!(
+        ShareComment,
+        r#"
+        INSERT INTO ShareComment (share_id, meal_id, day, reply_to_id, author, body)
+        VALUES (?, ?, ?, ?, ?, ?)
+        RETURNING id, creation_date, share_id, meal_id, day, reply_to_id, author, body;"#,
+        share.share_id,
+        meal_id,
+        day,
+        reply_to_id,
+        author,
+        body
+    )
+    .fetch_one(connection)
+    .await?)
+}
+
+/// Every comment left on the share, oldest first.
+pub async fn fetch_comments(
+    connection: &sqlx::Pool<sqlx::Sqlite>,
+    share_id: i64,
+) -> anyhow::Result<Vec<ShareComment>> {
+    Ok(sqlx::query_as!(
+        ShareComment,
+        r#"
+        SELECT id, creation_date, share_id, meal_id, day, reply_to_id, author, body
+        FROM ShareComment
+        WHERE share_id = ?
+        ORDER BY creation_date, id;"#,
+        share_id
+    )
+    .fetch_all(connection)
+    .await?)
+}
@@ -0,0 +1,235 @@
+use std::collections::HashMap;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use sqlx::{prelude::FromRow, Sqlite};
+use thiserror::Error;
+
+use crate::{get_missing_items, nutrition::WeightedComponent};
+
+/// A dish or an ingredient, as received in request bodies. Exactly one of `dish_id` and
+/// `ingredient_id` must be provided.
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Debug)]
+pub struct PostComponent {
+    pub weight: i64,
+    pub dish_id: Option<i64>,
+    pub ingredient_id: Option<i64>,
+}
+
+pub struct Component {
+    pub id: i64,
+    pub weight: i64,
+}
+
+/// A dish or ingredient linked to something else, such as a planned meal, with its weight.
+#[derive(Serialize, JsonSchema, FromRow, Clone)]
+pub struct LinkedComponent {
+    #[serde(skip)]
+    pub owner_id: i64,
+    pub dish_id: Option<i64>,
+    pub ingredient_id: Option<i64>,
+    pub name: Option<String>,
+    pub weight: i64,
+}
+
+impl From<&LinkedComponent> for WeightedComponent {
+    fn from(value: &LinkedComponent) -> Self {
+        WeightedComponent {
+            dish_id: value.dish_id,
+            ingredient_id: value.ingredient_id,
+            weight: value.weight,
+        }
+    }
+}
+
+impl From<LinkedComponent> for PostComponent {
+    fn from(value: LinkedComponent) -> Self {
+        PostComponent {
+            weight: value.weight,
+            dish_id: value.dish_id,
+            ingredient_id: value.ingredient_id,
+        }
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum ComponentError {
+    #[error("The following dishes don't exist: {0:?}")]
+    UnknownDishId(Vec<i64>),
+    #[error("The following ingredients don't exist: {0:?}")]
+    UnknownIngredientId(Vec<i64>),
+    #[error("In one of the components provided, there was no dish_id and no ingredient_id")]
+    NoIdProvided,
+    #[error("In one of the components provided, both dish_id and ingredient_id were provided")]
+    DishIdAndIngredientIdProvided,
+    #[error("Weight {0} is invalid. It must be larger than 0")]
+    InvalidWeight(i64),
+}
+
+/// Splits `components` into dishes and ingredients, in that order.
+pub fn split_components(
+    components: impl IntoIterator<Item = PostComponent>,
+) -> Result<(Vec<Component>, Vec<Component>), ComponentError> {
+    components.into_iter().try_fold(
+        (vec![], vec![]),
+        |(mut dishes, mut ingredients),
+         PostComponent {
+             weight,
+             dish_id,
+             ingredient_id,
+         }| {
+            if weight <= 0 {
+                return Err(ComponentError::InvalidWeight(weight));
+            }
+            match (dish_id, ingredient_id) {
+                (None, None) => return Err(ComponentError::NoIdProvided),
+                (None, Some(id)) => ingredients.push(Component { id, weight }),
+                (Some(id), None) => dishes.push(Component { id, weight }),
+                (Some(_), Some(_)) => return Err(ComponentError::DishIdAndIngredientIdProvided),
+            }
+            Ok((dishes, ingredients))
+        },
+    )
+}
+
+/// Ids of the `components` that are not rows of `table` in the household. Dishes in the trash
+/// count as missing.
+pub async fn find_missing<'c>(
+    connection: impl sqlx::Executor<'c, Database = Sqlite>,
+    household_id: i64,
+    table: &str,
+    components: &[Component],
+) -> anyhow::Result<Vec<i64>> {
+    if components.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let mut builder = sqlx::QueryBuilder::new("SELECT id FROM ");
+    builder
+        .push(table)
+        .push(" WHERE household_id = ")
+        .push_bind(household_id);
+    if table == "Dish" {
+        builder.push(" AND deletion_date IS NULL");
+    }
+    let ids_in_database = builder
+        .push(" AND id IN ")
+        .push_tuples(components.iter(), |mut p, component| {
+            p.push_bind(component.id);
+        })
+        .build_query_scalar::<i64>()
+        .fetch_all(connection)
+        .await?;
+
+    Ok(get_missing_items(
+        ids_in_database,
+        components.iter().map(|c| c.id),
+    ))
+}
+
+/// Makes sure every dish and ingredient referenced exists in the household.
+pub async fn check_missing_components(
+    connection: &sqlx::Pool<sqlx::Sqlite>,
+    household_id: i64,
+    dishes: &[Component],
+    ingredients: &[Component],
+) -> anyhow::Result<()> {
+    let (missing_dishes, missing_ingredients) = futures::try_join!(
+        find_missing(connection, household_id, "Dish", dishes),
+        find_missing(connection, household_id, "Ingredient", ingredients),
+    )?;
+
+    if !missing_dishes.is_empty() {
+        return Err(ComponentError::UnknownDishId(missing_dishes))?;
+    }
+    if !missing_ingredients.is_empty() {
+        return Err(ComponentError::UnknownIngredientId(missing_ingredients))?;
+    }
+    Ok(())
+}
+
+/// Inserts `components` in a table that links dishes or ingredients to something else, such as
+/// `MealDish` or `PlannedMealIngredient`. Repeated components have their weights summed.
+pub async fn insert_components(
+    connection: &mut sqlx::SqliteConnection,
+    table: &str,
+    owner_column: &str,
+    owner_id: i64,
+    component_column: &str,
+    components: &[Component],
+) -> anyhow::Result<()> {
+    if components.is_empty() {
+        return Ok(());
+    }
+
+    sqlx::QueryBuilder::new(format!(
+        "INSERT INTO {table} ({owner_column}, {component_column}, weight) "
+    ))
+    .push_values(components.iter(), |mut b, Component { id, weight }| {
+        b.push_bind(owner_id).push_bind(*id).push_bind(*weight);
+    })
+    .push(format!(
+        " ON CONFLICT DO UPDATE SET weight = {table}.weight + excluded.weight"
+    ))
+    .build()
+    .execute(&mut *connection)
+    .await?;
+
+    Ok(())
+}
+
+/// Fetches the dishes and ingredients linked to each id in `owner_ids`, grouped by that id.
+/// `prefix` is the prefix of the link tables, such as `PlannedMeal` for `PlannedMealDish` and
+/// `PlannedMealIngredient`, and `owner_column` is the column that references the owner.
+pub async fn fetch_linked_components(
+    connection: &sqlx::Pool<sqlx::Sqlite>,
+    prefix: &str,
+    owner_column: &str,
+    owner_ids: &[i64],
+) -> anyhow::Result<HashMap<i64, Vec<LinkedComponent>>> {
+    if owner_ids.is_empty() {
+        return Ok(HashMap::new());
+    }
+
+    Ok(sqlx::QueryBuilder::<Sqlite>::new(format!(
+        r#"
+        SELECT
+            {prefix}Dish.{owner_column} AS owner_id,
+            {prefix}Dish.dish_id,
+            NULL AS ingredient_id,
+            Dish.name,
+            {prefix}Dish.weight
+        FROM {prefix}Dish
+            JOIN Dish ON Dish.id = {prefix}Dish.dish_id
+        WHERE {prefix}Dish.{owner_column} IN "#
+    ))
+    .push_tuples(owner_ids.iter(), |mut p, id| {
+        p.push_bind(*id);
+    })
+    .push(format!(
+        r#"
+        UNION ALL
+        SELECT
+            {prefix}Ingredient.{owner_column} AS owner_id,
+            NULL AS dish_id,
+            {prefix}Ingredient.ingredient_id,
+            Ingredient.name,
+            {prefix}Ingredient.weight
+        FROM {prefix}Ingredient
+            JOIN Ingredient ON Ingredient.id = {prefix}Ingredient.ingredient_id
+        WHERE {prefix}Ingredient.{owner_column} IN "#
+    ))
+    .push_tuples(owner_ids.iter(), |mut p, id| {
+        p.push_bind(*id);
+    })
+    .build_query_as::<LinkedComponent>()
+    .fetch_all(connection)
+    .await?
+    .into_iter()
+    .fold(HashMap::new(), |mut dict, component| {
+        dict.entry(component.owner_id)
+            .or_insert_with(Vec::new)
+            .push(component);
+        dict
+    }))
+}
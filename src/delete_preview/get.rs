@@ -0,0 +1,36 @@
+use axum::extract::{Query, State};
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+use crate::{
+    audit::AuditEntity,
+    dependency::{preview, DeletePreview},
+    server::{ServerResponse, ServerResponseResult},
+    session::AuthUser,
+    state::AppState,
+};
+
+#[derive(Deserialize, JsonSchema)]
+pub struct GetDeletePreviewQueryParams {
+    entity: AuditEntity,
+    entity_id: i64,
+}
+
+/// Lists every row that points to an ingredient, dish or meal, or belongs to it, so the user can
+/// pick a delete policy before deleting it.
+pub async fn get_delete_preview(
+    State(AppState { connection, .. }): State<AppState>,
+    user: AuthUser,
+    Query(GetDeletePreviewQueryParams { entity, entity_id }): Query<GetDeletePreviewQueryParams>,
+) -> ServerResponseResult<DeletePreview> {
+    match entity {
+        AuditEntity::Ingredient => user.check_ingredient(&connection, entity_id).await?,
+        AuditEntity::Dish => user.check_dish(&connection, entity_id).await?,
+        AuditEntity::Meal => user.check_meal(&connection, entity_id).await?,
+    }
+
+    let mut connection = connection.acquire().await?;
+    let preview = preview(&mut connection, entity, entity_id).await?;
+
+    Ok(ServerResponse::success(preview).json())
+}
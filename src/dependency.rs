@@ -0,0 +1,523 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use sqlx::SqliteConnection;
+use thiserror::Error;
+
+use crate::{
+    audit::{record_change, snapshot, AuditEntity},
+    session::AuthUser,
+};
+
+/// A table with a column that points to an ingredient, dish or meal.
+struct Reference {
+    table: &'static str,
+    column: &'static str,
+    /// For links with a weight, the other column of their primary key. Links that end up between
+    /// the same rows after a reassign are merged, summing their weights
+    link_column: Option<&'static str>,
+}
+
+const INGREDIENT_REFERENCES: [Reference; 6] = [
+    Reference {
+        table: "DishIngredient",
+        column: "ingredient_id",
+        link_column: Some("dish_id"),
+    },
+    Reference {
+        table: "MealIngredient",
+        column: "ingredient_id",
+        link_column: Some("meal_id"),
+    },
+    Reference {
+        table: "MealTemplateIngredient",
+        column: "ingredient_id",
+        link_column: Some("template_id"),
+    },
+    Reference {
+        table: "PlannedMealIngredient",
+        column: "ingredient_id",
+        link_column: Some("planned_meal_id"),
+    },
+    Reference {
+        table: "RecurringMealPlanIngredient",
+        column: "ingredient_id",
+        link_column: Some("recurring_plan_id"),
+    },
+    Reference {
+        table: "IngredientPurchase",
+        column: "ingredient_id",
+        link_column: None,
+    },
+];
+
+const DISH_REFERENCES: [Reference; 4] = [
+    Reference {
+        table: "MealDish",
+        column: "dish_id",
+        link_column: Some("meal_id"),
+    },
+    Reference {
+        table: "MealTemplateDish",
+        column: "dish_id",
+        link_column: Some("template_id"),
+    },
+    Reference {
+        table: "PlannedMealDish",
+        column: "dish_id",
+        link_column: Some("planned_meal_id"),
+    },
+    Reference {
+        table: "RecurringMealPlanDish",
+        column: "dish_id",
+        link_column: Some("recurring_plan_id"),
+    },
+];
+
+const MEAL_REFERENCES: [Reference; 4] = [
+    Reference {
+        table: "PlannedMeal",
+        column: "meal_id",
+        link_column: None,
+    },
+    Reference {
+        table: "ShareComment",
+        column: "meal_id",
+        link_column: None,
+    },
+    Reference {
+        table: "MealTemplateUsage",
+        column: "meal_id",
+        link_column: None,
+    },
+    Reference {
+        table: "SupplementDose",
+        column: "meal_id",
+        link_column: None,
+    },
+];
+
+/// Rows that point to the entity, and are subject to the delete policy. Rows that belong to it,
+/// such as the ingredients of a dish or the properties of an ingredient, are not.
+fn references(entity: AuditEntity) -> &'static [Reference] {
+    match entity {
+        AuditEntity::Ingredient => &INGREDIENT_REFERENCES,
+        AuditEntity::Dish => &DISH_REFERENCES,
+        AuditEntity::Meal => &MEAL_REFERENCES,
+    }
+}
+
+#[derive(Deserialize, JsonSchema, Clone, Copy)]
+pub enum DeletePolicyKind {
+    /// The delete is rejected while anything points to the entity
+    Restrict,
+    /// Whatever points to the entity is deleted with it. Dishes and meals keep it while they are
+    /// in the trash
+    Cascade,
+    /// Whatever points to the entity is pointed to `reassign_to` instead
+    Reassign,
+}
+
+#[derive(Deserialize, JsonSchema)]
+pub struct DeletePolicyParams {
+    /// See `delete-preview` for what is affected. Defaults to `Cascade` for dishes and meals, and
+    /// to `Restrict` for ingredients
+    policy: Option<DeletePolicyKind>,
+    /// Id of the entity of the same kind to reassign to, with the `Reassign` policy
+    reassign_to: Option<i64>,
+}
+
+pub enum DeletePolicy {
+    Restrict,
+    Cascade,
+    Reassign(i64),
+}
+
+#[derive(Error, Debug)]
+pub enum DeletePolicyError {
+    #[error("The {} is still used by {1} rows. Delete them first, or use the Cascade or Reassign policy", .0.name().to_lowercase())]
+    Restricted(AuditEntity, i64),
+    #[error("The Reassign policy needs the id to reassign to, in \"reassign_to\"")]
+    MissingReassignTarget,
+    #[error("\"reassign_to\" can only be used with the Reassign policy")]
+    UnexpectedReassignTarget,
+    #[error("Can't reassign to the {} being deleted", .0.name().to_lowercase())]
+    ReassignToItself(AuditEntity),
+}
+
+impl DeletePolicyParams {
+    pub fn policy(&self, default: DeletePolicyKind) -> Result<DeletePolicy, DeletePolicyError> {
+        match (self.policy.unwrap_or(default), self.reassign_to) {
+            (DeletePolicyKind::Reassign, Some(id)) => Ok(DeletePolicy::Reassign(id)),
+            (DeletePolicyKind::Reassign, None) => Err(DeletePolicyError::MissingReassignTarget),
+            (_, Some(_)) => Err(DeletePolicyError::UnexpectedReassignTarget),
+            (DeletePolicyKind::Restrict, None) => Ok(DeletePolicy::Restrict),
+            (DeletePolicyKind::Cascade, None) => Ok(DeletePolicy::Cascade),
+        }
+    }
+}
+
+impl DeletePolicy {
+    /// Makes sure the user can reassign what points to the entity `id` to the target of the
+    /// policy.
+    pub async fn check(
+        &self,
+        connection: &mut SqliteConnection,
+        user: &AuthUser,
+        entity: AuditEntity,
+        id: i64,
+    ) -> anyhow::Result<()> {
+        let DeletePolicy::Reassign(target_id) = *self else {
+            return Ok(());
+        };
+        if target_id == id {
+            return Err(DeletePolicyError::ReassignToItself(entity))?;
+        }
+        match entity {
+            AuditEntity::Ingredient => user.check_ingredient(connection, target_id).await,
+            AuditEntity::Dish => user.check_dish(connection, target_id).await,
+            AuditEntity::Meal => user.check_meal(connection, target_id).await,
+        }
+    }
+}
+
+#[derive(Serialize, JsonSchema, sqlx::FromRow)]
+pub struct DishIngredientRow {
+    dish_id: i64,
+    dish_name: Option<String>,
+    ingredient_id: i64,
+    ingredient_name: String,
+    weight: i64,
+}
+
+#[derive(Serialize, JsonSchema, sqlx::FromRow)]
+pub struct MealDishRow {
+    meal_id: i64,
+    meal_description: Option<String>,
+    meal_eat_date: Option<i64>,
+    member_name: String,
+    dish_id: i64,
+    dish_name: Option<String>,
+    weight: i64,
+}
+
+#[derive(Serialize, JsonSchema, sqlx::FromRow)]
+pub struct MealIngredientRow {
+    meal_id: i64,
+    meal_description: Option<String>,
+    meal_eat_date: Option<i64>,
+    member_name: String,
+    ingredient_id: i64,
+    ingredient_name: String,
+    weight: i64,
+}
+
+#[derive(Serialize, JsonSchema, sqlx::FromRow)]
+pub struct IngredientPropertiesRow {
+    ingredient_id: i64,
+    product_code: String,
+    product_name: Option<String>,
+}
+
+#[derive(Serialize, JsonSchema)]
+pub struct ReferenceCount {
+    table: &'static str,
+    count: i64,
+}
+
+/// Everything deleting an ingredient, dish or meal affects. Rows of dishes and meals in the trash
+/// are included, since they still point to the entity.
+#[derive(Serialize, JsonSchema)]
+pub struct DeletePreview {
+    /// How many rows point to the entity. Deleting with the `Restrict` policy is rejected unless
+    /// this is 0
+    dependent_count: i64,
+    /// Dishes using the ingredient, or ingredients of the dish
+    dish_ingredients: Vec<DishIngredientRow>,
+    /// Meals using the dish, or dishes of the meal
+    meal_dishes: Vec<MealDishRow>,
+    /// Meals using the ingredient, or ingredients of the meal
+    meal_ingredients: Vec<MealIngredientRow>,
+    /// Properties fetched for the ingredient. Always deleted with it
+    ingredient_properties: Option<IngredientPropertiesRow>,
+    /// Plans, templates, purchases and comments pointing to the entity, by table
+    other_references: Vec<ReferenceCount>,
+}
+
+async fn fetch_dish_ingredients(
+    connection: &mut SqliteConnection,
+    column: &str,
+    id: i64,
+) -> anyhow::Result<Vec<DishIngredientRow>> {
+    Ok(sqlx::query_as::<_, DishIngredientRow>(&format!(
+        r#"
+        SELECT
+            DishIngredient.dish_id,
+            Dish.name AS dish_name,
+            DishIngredient.ingredient_id,
+            Ingredient.name AS ingredient_name,
+            DishIngredient.weight
+        FROM DishIngredient
+            JOIN Dish ON Dish.id = DishIngredient.dish_id
+            JOIN Ingredient ON Ingredient.id = DishIngredient.ingredient_id
+        WHERE DishIngredient.{column} = ?
+        ORDER BY DishIngredient.dish_id, DishIngredient.ingredient_id;"#
+    ))
+    .bind(id)
+    .fetch_all(connection)
+    .await?)
+}
+
+async fn fetch_meal_dishes(
+    connection: &mut SqliteConnection,
+    column: &str,
+    id: i64,
+) -> anyhow::Result<Vec<MealDishRow>> {
+    Ok(sqlx::query_as::<_, MealDishRow>(&format!(
+        r#"
+        SELECT
+            MealDish.meal_id,
+            Meal.description AS meal_description,
+            Meal.eat_date AS meal_eat_date,
+            COALESCE(User.display_name, User.username) AS member_name,
+            MealDish.dish_id,
+            Dish.name AS dish_name,
+            MealDish.weight
+        FROM MealDish
+            JOIN Meal ON Meal.id = MealDish.meal_id
+            JOIN User ON User.id = Meal.user_id
+            JOIN Dish ON Dish.id = MealDish.dish_id
+        WHERE MealDish.{column} = ?
+        ORDER BY Meal.eat_date DESC NULLS FIRST, MealDish.dish_id;"#
+    ))
+    .bind(id)
+    .fetch_all(connection)
+    .await?)
+}
+
+async fn fetch_meal_ingredients(
+    connection: &mut SqliteConnection,
+    column: &str,
+    id: i64,
+) -> anyhow::Result<Vec<MealIngredientRow>> {
+    Ok(sqlx::query_as::<_, MealIngredientRow>(&format!(
+        r#"
+        SELECT
+            MealIngredient.meal_id,
+            Meal.description AS meal_description,
+            Meal.eat_date AS meal_eat_date,
+            COALESCE(User.display_name, User.username) AS member_name,
+            MealIngredient.ingredient_id,
+            Ingredient.name AS ingredient_name,
+            MealIngredient.weight
+        FROM MealIngredient
+            JOIN Meal ON Meal.id = MealIngredient.meal_id
+            JOIN User ON User.id = Meal.user_id
+            JOIN Ingredient ON Ingredient.id = MealIngredient.ingredient_id
+        WHERE MealIngredient.{column} = ?
+        ORDER BY Meal.eat_date DESC NULLS FIRST, MealIngredient.ingredient_id;"#
+    ))
+    .bind(id)
+    .fetch_all(connection)
+    .await?)
+}
+
+async fn count_references(
+    connection: &mut SqliteConnection,
+    reference: &Reference,
+    id: i64,
+) -> anyhow::Result<i64> {
+    Ok(sqlx::query_scalar::<_, i64>(&format!(
+        "SELECT COUNT(*) FROM {} WHERE {} = ?",
+        reference.table, reference.column
+    ))
+    .bind(id)
+    .fetch_one(connection)
+    .await?)
+}
+
+/// Whether anything subject to the delete policy still points to the entity `id`.
+pub async fn is_referenced(
+    connection: &mut SqliteConnection,
+    entity: AuditEntity,
+    id: i64,
+) -> anyhow::Result<bool> {
+    for reference in references(entity) {
+        if count_references(&mut *connection, reference, id).await? > 0 {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// Lists what deleting the entity `id` affects. The entity must have been checked to belong to
+/// the user already.
+pub async fn preview(
+    connection: &mut SqliteConnection,
+    entity: AuditEntity,
+    id: i64,
+) -> anyhow::Result<DeletePreview> {
+    let mut preview = DeletePreview {
+        dependent_count: 0,
+        dish_ingredients: vec![],
+        meal_dishes: vec![],
+        meal_ingredients: vec![],
+        ingredient_properties: None,
+        other_references: vec![],
+    };
+
+    match entity {
+        AuditEntity::Ingredient => {
+            preview.dish_ingredients =
+                fetch_dish_ingredients(connection, "ingredient_id", id).await?;
+            preview.meal_ingredients =
+                fetch_meal_ingredients(connection, "ingredient_id", id).await?;
+            preview.ingredient_properties = sqlx::query_as::<_, IngredientPropertiesRow>(
+                r#"
+                SELECT ingredient_id, product_code, product_name
+                FROM IngredientProperties
+                WHERE ingredient_id = ?;"#,
+            )
+            .bind(id)
+            .fetch_optional(&mut *connection)
+            .await?;
+        }
+        AuditEntity::Dish => {
+            preview.dish_ingredients = fetch_dish_ingredients(connection, "dish_id", id).await?;
+            preview.meal_dishes = fetch_meal_dishes(connection, "dish_id", id).await?;
+        }
+        AuditEntity::Meal => {
+            preview.meal_dishes = fetch_meal_dishes(connection, "meal_id", id).await?;
+            preview.meal_ingredients = fetch_meal_ingredients(connection, "meal_id", id).await?;
+        }
+    }
+
+    for reference in references(entity) {
+        let count = count_references(connection, reference, id).await?;
+        preview.dependent_count += count;
+        let is_listed = matches!(
+            reference.table,
+            "DishIngredient" | "MealDish" | "MealIngredient"
+        );
+        if count > 0 && !is_listed {
+            preview.other_references.push(ReferenceCount {
+                table: reference.table,
+                count,
+            });
+        }
+    }
+
+    Ok(preview)
+}
+
+/// Dishes and meals whose components change when what points to the ingredient or dish `id` is
+/// reassigned or deleted.
+async fn fetch_affected(
+    connection: &mut SqliteConnection,
+    entity: AuditEntity,
+    id: i64,
+) -> anyhow::Result<Vec<(AuditEntity, i64)>> {
+    let rows = match entity {
+        AuditEntity::Ingredient => {
+            sqlx::query_as::<_, (String, i64)>(
+                r#"
+                SELECT 'Dish', dish_id FROM DishIngredient WHERE ingredient_id = ?1
+                UNION ALL
+                SELECT 'Meal', meal_id FROM MealIngredient WHERE ingredient_id = ?1;"#,
+            )
+            .bind(id)
+            .fetch_all(&mut *connection)
+            .await?
+        }
+        AuditEntity::Dish => {
+            sqlx::query_as::<_, (String, i64)>(
+                "SELECT 'Meal', meal_id FROM MealDish WHERE dish_id = ?",
+            )
+            .bind(id)
+            .fetch_all(&mut *connection)
+            .await?
+        }
+        AuditEntity::Meal => vec![],
+    };
+
+    Ok(rows
+        .into_iter()
+        .filter_map(|(name, id)| Some((AuditEntity::from_name(&name)?, id)))
+        .collect())
+}
+
+/// Applies the policy to what points to the entity `id`, which is about to be deleted. Changes to
+/// the dishes and meals affected are recorded in the audit log. Returns the meals that changed.
+pub async fn resolve_dependents(
+    connection: &mut SqliteConnection,
+    user: &AuthUser,
+    entity: AuditEntity,
+    id: i64,
+    policy: &DeletePolicy,
+) -> anyhow::Result<Vec<i64>> {
+    // Dishes and meals go to the trash, and keep what points to them until they are purged
+    let changes_now = match policy {
+        DeletePolicy::Restrict => {
+            let dependent_count = preview(connection, entity, id).await?.dependent_count;
+            if dependent_count > 0 {
+                return Err(DeletePolicyError::Restricted(entity, dependent_count))?;
+            }
+            false
+        }
+        DeletePolicy::Cascade => entity == AuditEntity::Ingredient,
+        DeletePolicy::Reassign(_) => true,
+    };
+    if !changes_now {
+        return Ok(vec![]);
+    }
+
+    let affected = fetch_affected(connection, entity, id).await?;
+    let mut before = Vec::with_capacity(affected.len());
+    for (affected_entity, affected_id) in &affected {
+        before.push(snapshot(&mut *connection, *affected_entity, *affected_id).await?);
+    }
+
+    for reference in references(entity) {
+        let Reference {
+            table,
+            column,
+            link_column,
+        } = reference;
+        let statements = match (policy, link_column) {
+            (DeletePolicy::Reassign(_), Some(link_column)) => vec![
+                format!(
+                    r#"
+                    UPDATE {table} SET weight = weight + (
+                        SELECT Old.weight FROM {table} AS Old
+                        WHERE Old.{column} = ?1 AND Old.{link_column} = {table}.{link_column}
+                    )
+                    WHERE {column} = ?2
+                        AND {link_column} IN (SELECT {link_column} FROM {table} WHERE {column} = ?1)"#
+                ),
+                format!("UPDATE OR IGNORE {table} SET {column} = ?2 WHERE {column} = ?1"),
+                format!("DELETE FROM {table} WHERE {column} = ?1"),
+            ],
+            (DeletePolicy::Reassign(_), None) => vec![
+                format!("UPDATE OR IGNORE {table} SET {column} = ?2 WHERE {column} = ?1"),
+                format!("DELETE FROM {table} WHERE {column} = ?1"),
+            ],
+            _ => vec![format!("DELETE FROM {table} WHERE {column} = ?1")],
+        };
+        for statement in statements {
+            let mut query = sqlx::query(&statement).bind(id);
+            if let DeletePolicy::Reassign(target_id) = policy {
+                query = query.bind(*target_id);
+            }
+            query.execute(&mut *connection).await?;
+        }
+    }
+
+    let mut meal_ids = vec![];
+    for ((affected_entity, affected_id), before) in affected.into_iter().zip(before) {
+        record_change(&mut *connection, user, affected_entity, affected_id, before).await?;
+        if affected_entity == AuditEntity::Meal && !meal_ids.contains(&affected_id) {
+            meal_ids.push(affected_id);
+        }
+    }
+
+    Ok(meal_ids)
+}
@@ -0,0 +1,371 @@
+use std::collections::{BTreeSet, HashMap};
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use sqlx::{prelude::FromRow, Sqlite};
+
+use crate::nutrition::{fetch_dish_composition, WeightedComponent};
+
+/// Parses one of the tag columns of `IngredientProperties`, which hold JSON arrays.
+pub fn parse_tags(tags: Option<String>) -> Vec<String> {
+    tags.and_then(|tags| serde_json::from_str(&tags).ok())
+        .unwrap_or_default()
+}
+
+/// Whether food fits a diet, according to the ingredients analysis of Open Food Facts. Ordered
+/// from best to worst, so a dish is as bad as its worst ingredient.
+#[derive(Serialize, JsonSchema, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default, Debug)]
+pub enum DietStatus {
+    #[default]
+    Compliant,
+    /// Open Food Facts has no information about it
+    Unknown,
+    /// Some of the product's ingredients may not fit the diet
+    Maybe,
+    NotCompliant,
+}
+
+impl DietStatus {
+    fn from_analysis(tags: &[String], compliant: &str, maybe: &str, not_compliant: &str) -> Self {
+        let has = |tag: &str| tags.iter().any(|t| t == tag);
+        if has(not_compliant) {
+            DietStatus::NotCompliant
+        } else if has(maybe) {
+            DietStatus::Maybe
+        } else if has(compliant) {
+            DietStatus::Compliant
+        } else {
+            DietStatus::Unknown
+        }
+    }
+}
+
+/// Allergens and diets of an ingredient, or of everything in a dish or meal.
+#[derive(Serialize, JsonSchema, Clone, Default, Debug)]
+pub struct DietInfo {
+    /// Open Food Facts tags of the allergens, such as `en:gluten`
+    pub allergens: BTreeSet<String>,
+    /// Allergens there may be traces of
+    pub traces: BTreeSet<String>,
+    pub vegan: DietStatus,
+    pub vegetarian: DietStatus,
+    pub palm_oil_free: DietStatus,
+}
+
+impl DietInfo {
+    /// What is known about ingredients without properties: nothing.
+    fn unknown() -> Self {
+        DietInfo {
+            vegan: DietStatus::Unknown,
+            vegetarian: DietStatus::Unknown,
+            palm_oil_free: DietStatus::Unknown,
+            ..Default::default()
+        }
+    }
+
+    fn from_tags(allergens: Vec<String>, traces: Vec<String>, analysis: Vec<String>) -> Self {
+        DietInfo {
+            allergens: allergens.into_iter().collect(),
+            traces: traces.into_iter().collect(),
+            vegan: DietStatus::from_analysis(
+                &analysis,
+                "en:vegan",
+                "en:maybe-vegan",
+                "en:non-vegan",
+            ),
+            vegetarian: DietStatus::from_analysis(
+                &analysis,
+                "en:vegetarian",
+                "en:maybe-vegetarian",
+                "en:non-vegetarian",
+            ),
+            palm_oil_free: DietStatus::from_analysis(
+                &analysis,
+                "en:palm-oil-free",
+                "en:may-contain-palm-oil",
+                "en:palm-oil",
+            ),
+        }
+    }
+
+    fn add(&mut self, other: &DietInfo) {
+        self.allergens.extend(other.allergens.iter().cloned());
+        self.traces.extend(other.traces.iter().cloned());
+        self.vegan = self.vegan.max(other.vegan);
+        self.vegetarian = self.vegetarian.max(other.vegetarian);
+        self.palm_oil_free = self.palm_oil_free.max(other.palm_oil_free);
+    }
+}
+
+#[derive(FromRow)]
+struct DatabaseIngredientDiet {
+    ingredient_id: i64,
+    allergens_tags: Option<String>,
+    traces_tags: Option<String>,
+    ingredients_analysis_tags: Option<String>,
+}
+
+/// Fetches the allergens and diets of every ingredient in `ingredient_ids`. Variants use the tags
+/// of their generic ingredient for the ones they lack. Ingredients without properties are left
+/// out of the result.
+pub async fn fetch_ingredient_diets(
+    connection: &sqlx::Pool<Sqlite>,
+    ingredient_ids: &[i64],
+) -> anyhow::Result<HashMap<i64, DietInfo>> {
+    if ingredient_ids.is_empty() {
+        return Ok(HashMap::new());
+    }
+
+    Ok(sqlx::QueryBuilder::<Sqlite>::new(
+        r#"
+        SELECT ingredient_id, allergens_tags, traces_tags, ingredients_analysis_tags
+        FROM IngredientDiet
+        WHERE ingredient_id IN "#,
+    )
+    .push_tuples(ingredient_ids.iter(), |mut p, id| {
+        p.push_bind(*id);
+    })
+    .build_query_as::<DatabaseIngredientDiet>()
+    .fetch_all(connection)
+    .await?
+    .into_iter()
+    .map(|row| {
+        (
+            row.ingredient_id,
+            DietInfo::from_tags(
+                parse_tags(row.allergens_tags),
+                parse_tags(row.traces_tags),
+                parse_tags(row.ingredients_analysis_tags),
+            ),
+        )
+    })
+    .collect())
+}
+
+/// Something a household member can't eat.
+#[derive(Serialize, Deserialize, JsonSchema, Clone, PartialEq, Eq, Debug)]
+pub enum Restriction {
+    /// No products with the allergen, such as `en:gluten` or `en:peanuts`
+    Allergen {
+        tag: String,
+        /// Avoid products that may contain traces of it too
+        avoid_traces: bool,
+    },
+    Vegetarian,
+    Vegan,
+    PalmOilFree,
+}
+
+impl Restriction {
+    /// The `kind` and `allergen` columns of `DietaryRestriction`.
+    pub fn kind(&self) -> (&'static str, Option<&str>) {
+        match self {
+            Restriction::Allergen { tag, .. } => ("Allergen", Some(tag)),
+            Restriction::Vegetarian => ("Vegetarian", None),
+            Restriction::Vegan => ("Vegan", None),
+            Restriction::PalmOilFree => ("PalmOilFree", None),
+        }
+    }
+
+    pub fn from_row(kind: &str, allergen: Option<String>, avoid_traces: bool) -> Option<Self> {
+        match (kind, allergen) {
+            ("Allergen", Some(tag)) => Some(Restriction::Allergen { tag, avoid_traces }),
+            ("Vegetarian", _) => Some(Restriction::Vegetarian),
+            ("Vegan", _) => Some(Restriction::Vegan),
+            ("PalmOilFree", _) => Some(Restriction::PalmOilFree),
+            _ => None,
+        }
+    }
+
+    /// How food with `diet` goes against the restriction, if it does. Unknown diets don't count,
+    /// since most products would.
+    fn violation(&self, diet: &DietInfo) -> Option<Violation> {
+        let status = match self {
+            Restriction::Allergen { tag, avoid_traces } => {
+                if diet.allergens.contains(tag) {
+                    return Some(Violation::Contains);
+                }
+                if *avoid_traces && diet.traces.contains(tag) {
+                    return Some(Violation::MayContainTraces);
+                }
+                return None;
+            }
+            Restriction::Vegetarian => diet.vegetarian,
+            Restriction::Vegan => diet.vegan,
+            Restriction::PalmOilFree => diet.palm_oil_free,
+        };
+        match status {
+            DietStatus::NotCompliant => Some(Violation::NotCompliant),
+            DietStatus::Maybe => Some(Violation::MaybeNotCompliant),
+            DietStatus::Compliant | DietStatus::Unknown => None,
+        }
+    }
+}
+
+#[derive(Serialize, JsonSchema, Clone)]
+pub struct DietaryRestriction {
+    pub id: i64,
+    pub creation_date: i64,
+    pub member_id: i64,
+    pub member_name: String,
+    pub restriction: Restriction,
+}
+
+#[derive(FromRow)]
+struct DatabaseRestriction {
+    id: i64,
+    creation_date: i64,
+    member_id: i64,
+    member_name: String,
+    kind: String,
+    allergen: Option<String>,
+    avoid_traces: bool,
+}
+
+/// Fetches the restrictions of a household member, or of everyone in the household if there's no
+/// `member_id`.
+pub async fn fetch_restrictions(
+    connection: &sqlx::Pool<Sqlite>,
+    household_id: i64,
+    member_id: Option<i64>,
+) -> anyhow::Result<Vec<DietaryRestriction>> {
+    Ok(sqlx::query_as::<_, DatabaseRestriction>(
+        r#"
+        SELECT
+            DietaryRestriction.id,
+            DietaryRestriction.creation_date,
+            User.id AS member_id,
+            COALESCE(User.display_name, User.username) AS member_name,
+            kind,
+            allergen,
+            avoid_traces
+        FROM DietaryRestriction JOIN User ON User.id = DietaryRestriction.user_id
+        WHERE User.household_id = ?1 AND (?2 IS NULL OR User.id = ?2)
+        ORDER BY User.id, DietaryRestriction.id"#,
+    )
+    .bind(household_id)
+    .bind(member_id)
+    .fetch_all(connection)
+    .await?
+    .into_iter()
+    .filter_map(|row| {
+        Some(DietaryRestriction {
+            restriction: Restriction::from_row(&row.kind, row.allergen, row.avoid_traces)?,
+            id: row.id,
+            creation_date: row.creation_date,
+            member_id: row.member_id,
+            member_name: row.member_name,
+        })
+    })
+    .collect())
+}
+
+/// Ordered from mildest to worst, for each kind of restriction.
+#[derive(Serialize, JsonSchema, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum Violation {
+    MayContainTraces,
+    Contains,
+    MaybeNotCompliant,
+    NotCompliant,
+}
+
+#[derive(Serialize, JsonSchema)]
+pub struct RestrictionWarning {
+    pub member_id: i64,
+    pub member_name: String,
+    pub restriction: Restriction,
+    /// The worst violation among the ingredients
+    pub violation: Violation,
+    /// Ingredients that go against the restriction
+    pub ingredient_ids: Vec<i64>,
+}
+
+/// Allergens and diets of a dish or meal, with the restrictions it goes against.
+#[derive(Serialize, JsonSchema, Default)]
+pub struct DietCheck {
+    pub diet: DietInfo,
+    pub warnings: Vec<RestrictionWarning>,
+}
+
+/// Checks the ingredients in `ingredient_ids` against the restrictions of a household member, or
+/// of everyone in the household if there's no `member_id`.
+pub async fn check_ingredients(
+    connection: &sqlx::Pool<Sqlite>,
+    household_id: i64,
+    member_id: Option<i64>,
+    ingredient_ids: &[i64],
+) -> anyhow::Result<DietCheck> {
+    let mut ingredient_ids = ingredient_ids.to_vec();
+    ingredient_ids.sort_unstable();
+    ingredient_ids.dedup();
+
+    let (diets, restrictions) = futures::try_join!(
+        fetch_ingredient_diets(connection, &ingredient_ids),
+        fetch_restrictions(connection, household_id, member_id),
+    )?;
+    let unknown = DietInfo::unknown();
+    let ingredient_diets = ingredient_ids
+        .iter()
+        .map(|id| (*id, diets.get(id).unwrap_or(&unknown)))
+        .collect::<Vec<(i64, &DietInfo)>>();
+
+    let diet =
+        ingredient_diets
+            .iter()
+            .fold(DietInfo::default(), |mut diet, (_, ingredient_diet)| {
+                diet.add(ingredient_diet);
+                diet
+            });
+
+    let warnings = restrictions
+        .into_iter()
+        .filter_map(|restriction| {
+            let violations = ingredient_diets
+                .iter()
+                .filter_map(|(id, ingredient_diet)| {
+                    Some((*id, restriction.restriction.violation(ingredient_diet)?))
+                })
+                .collect::<Vec<(i64, Violation)>>();
+            let violation = violations.iter().map(|(_, v)| *v).max()?;
+            Some(RestrictionWarning {
+                member_id: restriction.member_id,
+                member_name: restriction.member_name,
+                restriction: restriction.restriction,
+                violation,
+                ingredient_ids: violations.into_iter().map(|(id, _)| id).collect(),
+            })
+        })
+        .collect();
+
+    Ok(DietCheck { diet, warnings })
+}
+
+/// Same as `check_ingredients`, for the ingredients of a set of dishes and ingredients.
+pub async fn check_components(
+    connection: &sqlx::Pool<Sqlite>,
+    household_id: i64,
+    member_id: Option<i64>,
+    components: &[WeightedComponent],
+) -> anyhow::Result<DietCheck> {
+    let dishes = fetch_dish_composition(
+        connection,
+        &components
+            .iter()
+            .filter_map(|c| c.dish_id)
+            .collect::<Vec<i64>>(),
+    )
+    .await?;
+
+    let ingredient_ids = components
+        .iter()
+        .filter_map(|c| c.ingredient_id)
+        .chain(
+            dishes
+                .values()
+                .flatten()
+                .map(|(ingredient_id, _)| *ingredient_id),
+        )
+        .collect::<Vec<i64>>();
+
+    check_ingredients(connection, household_id, member_id, &ingredient_ids).await
+}
@@ -0,0 +1,43 @@
+use axum::extract::{Path, Query, State};
+use schemars::JsonSchema;
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::{
+    price::{fetch_dish_costs, DishCost, PriceMethod},
+    server::{ServerResponse, ServerResponseResult},
+    session::AuthUser,
+    state::AppState,
+};
+
+#[derive(Error, Debug)]
+enum GetDishCostError {
+    #[error("Could not find dish with id \"{0}\"")]
+    DishNotFound(i64),
+}
+
+#[derive(Deserialize, JsonSchema)]
+pub struct DishId {
+    dish_id: i64,
+}
+
+#[derive(Deserialize, JsonSchema)]
+pub struct GetCostQueryParams {
+    method: Option<PriceMethod>,
+}
+
+pub async fn get_cost(
+    State(AppState { connection, .. }): State<AppState>,
+    user: AuthUser,
+    Path(DishId { dish_id }): Path<DishId>,
+    Query(GetCostQueryParams { method }): Query<GetCostQueryParams>,
+) -> ServerResponseResult<DishCost> {
+    user.check_dish(&connection, dish_id).await?;
+
+    let cost = fetch_dish_costs(&connection, method.unwrap_or_default(), &[dish_id])
+        .await?
+        .remove(&dish_id)
+        .ok_or(GetDishCostError::DishNotFound(dish_id))?;
+
+    Ok(ServerResponse::success(cost).json())
+}
@@ -1,9 +1,14 @@
-use axum::extract::{Path, State};
+use axum::extract::{Path, Query, State};
 use schemars::JsonSchema;
 use serde::Deserialize;
 
 use crate::{
+    audit::{record_change, snapshot, AuditEntity},
+    dependency::{resolve_dependents, DeletePolicyKind, DeletePolicyParams},
+    etag::IfMatch,
+    events::DomainEvent,
     server::{ServerResponse, ServerResponseResult},
+    session::AuthUser,
     state::AppState,
 };
 
@@ -12,19 +17,45 @@ pub struct DishId {
     dish_id: i64,
 }
 
+/// Moves the dish to the trash. With the default `Cascade` policy, meals that already used the
+/// dish keep it until it's purged from the trash.
 pub async fn delete_dish(
-    State(AppState { connection }): State<AppState>,
+    State(AppState {
+        connection, events, ..
+    }): State<AppState>,
+    user: AuthUser,
+    if_match: IfMatch,
     Path(DishId { dish_id }): Path<DishId>,
+    Query(params): Query<DeletePolicyParams>,
 ) -> ServerResponseResult<bool> {
+    user.check_dish(&connection, dish_id).await?;
+    let mut transaction = connection.begin().await?;
+    if_match.check(&mut transaction, "Dish", dish_id).await?;
+    let policy = params.policy(DeletePolicyKind::Cascade)?;
+    policy
+        .check(&mut transaction, &user, AuditEntity::Dish, dish_id)
+        .await?;
+    let before = snapshot(&mut *transaction, AuditEntity::Dish, dish_id).await?;
+
+    let changed_meal_ids =
+        resolve_dependents(&mut transaction, &user, AuditEntity::Dish, dish_id, &policy).await?;
+
+    let now = chrono::Utc::now().timestamp_millis();
     sqlx::query!(
-        r#"
-        DELETE FROM MealDish WHERE dish_id = ?;
-        DELETE FROM Dish WHERE id = ?"#,
-        dish_id,
+        "UPDATE Dish SET deletion_date = ? WHERE id = ?",
+        now,
         dish_id,
     )
-    .execute(&connection)
+    .execute(&mut *transaction)
     .await?;
 
+    record_change(&mut *transaction, &user, AuditEntity::Dish, dish_id, before).await?;
+
+    transaction.commit().await?;
+
+    for meal_id in changed_meal_ids {
+        events.publish(&user, DomainEvent::MealUpdated { meal_id });
+    }
+
     Ok(ServerResponse::success(true).json())
 }
@@ -4,6 +4,7 @@ use serde::{Deserialize, Serialize};
 
 use crate::{
     server::{ServerResponse, ServerResponseResult},
+    session::AuthUser,
     state::AppState,
 };
 
@@ -11,6 +12,8 @@ use crate::{
 pub struct GetDeleteWarningResult {
     meal_description: Option<String>,
     meal_eat_date: Option<i64>,
+    /// Household member that ate the meal
+    member_name: String,
     weight: i64,
 }
 
@@ -20,20 +23,25 @@ pub struct DishId {
 }
 
 pub async fn get_delete_warning(
-    State(AppState { connection }): State<AppState>,
+    State(AppState { connection, .. }): State<AppState>,
+    user: AuthUser,
     Path(DishId { dish_id }): Path<DishId>,
 ) -> ServerResponseResult<Vec<GetDeleteWarningResult>> {
+    user.check_dish(&connection, dish_id).await?;
+
     let results = sqlx::query_as!(
         GetDeleteWarningResult,
         r#"
         SELECT
             Meal.description as meal_description,
             Meal.eat_date as meal_eat_date,
+            COALESCE(User.display_name, User.username) as "member_name!: String",
             MealDish.weight as weight
         FROM Dish
         JOIN MealDish ON MealDish.dish_id = Dish.id
         JOIN Meal ON MealDish.meal_id = Meal.id
-        WHERE Dish.id = ?;"#,
+        JOIN User ON User.id = Meal.user_id
+        WHERE Dish.id = ? AND Meal.deletion_date IS NULL;"#,
         dish_id
     )
     .fetch_all(&connection)
@@ -4,8 +4,13 @@ use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
 use crate::{
+    carbon::{Footprint, FootprintTable},
+    diet::{check_ingredients, DietCheck, DietInfo, RestrictionWarning},
+    etag::{Versioned, VersionedResult},
     models::Dish,
-    server::{ServerResponse, ServerResponseResult},
+    nutrition::{TrackableAmount, TrackableAmounts, TrackableTable, Trackables, WeightedComponent},
+    server::ServerResponse,
+    session::AuthUser,
     state::AppState,
 };
 
@@ -13,6 +18,9 @@ use crate::{
 pub struct UsedAt {
     meal_description: Option<String>,
     meal_id: i64,
+    /// Household member that ate the meal
+    member_id: i64,
+    member_name: String,
     eat_date: Option<i64>,
     weight: Option<i64>,
 }
@@ -23,17 +31,43 @@ pub struct AddedIngredient {
     weight: i64,
     ingredient_name: String,
     ingredient_id: i64,
+    /// Variants of the ingredient, if it's a generic one. It can be resolved to the variant that
+    /// was actually used
+    variant_count: i64,
     kcal: Option<i64>,
     fat: Option<i64>,
     proteins: Option<i64>,
     carbohydrates: Option<i64>,
 }
 
+/// How much of the dish a household member ate
+#[derive(Serialize, JsonSchema)]
+pub struct Portion {
+    member_id: i64,
+    member_name: String,
+    weight: i64,
+}
+
 #[derive(Serialize, JsonSchema)]
 pub struct GetDishResponse {
     dish: Dish,
     added_ingredients: Vec<AddedIngredient>,
+    /// Every meal the dish was eaten in, by any household member
     used_at: Vec<UsedAt>,
+    portions: Vec<Portion>,
+    /// Weight eaten by all household members, in grams
+    eaten_weight: i64,
+    /// Weight left of the dish, in grams. Uses the sum of the ingredients' weights if the dish
+    /// has no total weight
+    remaining_weight: i64,
+    /// Allergens and diets of the dish's ingredients
+    diet: DietInfo,
+    /// Restrictions of household members the dish goes against
+    warnings: Vec<RestrictionWarning>,
+    /// Carbon footprint of all of the dish's ingredients
+    footprint: Footprint,
+    /// Amounts of each of the household's trackables in all of the dish's ingredients
+    trackables: Vec<TrackableAmount>,
 }
 
 #[derive(Error, Debug)]
@@ -48,9 +82,10 @@ pub struct DishId {
 }
 
 pub async fn get_dish(
-    State(AppState { connection }): State<AppState>,
+    State(AppState { connection, .. }): State<AppState>,
+    AuthUser { household_id, .. }: AuthUser,
     Path(DishId { dish_id: id }): Path<DishId>,
-) -> ServerResponseResult<GetDishResponse> {
+) -> VersionedResult<GetDishResponse> {
     let dish = sqlx::query_as!(
         Dish,
         r#"
@@ -60,16 +95,18 @@ pub async fn get_dish(
             name,
             prep_date,
             total_weight,
-            is_finished
+            is_finished,
+            version
         FROM Dish
-        WHERE Dish.id = ?"#,
-        id
+        WHERE Dish.id = ? AND Dish.household_id = ? AND Dish.deletion_date IS NULL"#,
+        id,
+        household_id
     )
     .fetch_optional(&connection)
     .await?
     .ok_or(GetDishError::DishNotFound(id))?;
 
-    let added_ingredients = sqlx::query_as!(
+    let added_ingredients: Vec<AddedIngredient> = sqlx::query_as!(
         AddedIngredient,
         r#"
         SELECT 
@@ -77,17 +114,21 @@ pub async fn get_dish(
             ingredient.name as ingredient_name,
             DishIngredient.creation_date as addition_date,
             DishIngredient.ingredient_id,
-            iif(kcal_100g IS NOT NULL, CAST((kcal_100g * weight / 100) AS INTEGER), NULL) as kcal,
-            iif(proteins_100g IS NOT NULL, CAST((proteins_100g * weight / 100) AS INTEGER), NULL) as proteins,
-            iif(fat_100g IS NOT NULL, CAST((fat_100g * weight / 100) AS INTEGER), NULL) as fat,
-            iif(carbohydrates_100g IS NOT NULL, CAST((carbohydrates_100g * weight / 100) AS INTEGER), NULL) as carbohydrates
+            (
+                SELECT COUNT(*) FROM Ingredient AS Variant WHERE Variant.generic_id = Ingredient.id
+            ) AS "variant_count!: i64",
+            iif(kcal_100g IS NOT NULL, CAST((kcal_100g * weight / 100) AS INTEGER), NULL) as "kcal: i64",
+            iif(proteins_100g IS NOT NULL, CAST((proteins_100g * weight / 100) AS INTEGER), NULL) as "proteins: i64",
+            iif(fat_100g IS NOT NULL, CAST((fat_100g * weight / 100) AS INTEGER), NULL) as "fat: i64",
+            iif(carbohydrates_100g IS NOT NULL, CAST((carbohydrates_100g * weight / 100) AS INTEGER), NULL) as "carbohydrates: i64"
         FROM Dish
             JOIN DishIngredient ON Dish.id = DishIngredient.dish_id
             JOIN Ingredient on DishIngredient.ingredient_id = Ingredient.id
-            LEFT JOIN IngredientProperties on IngredientProperties.ingredient_id = Ingredient.id
-        WHERE Dish.id = ?;
+            LEFT JOIN IngredientNutrition on IngredientNutrition.ingredient_id = Ingredient.id
+        WHERE Dish.id = ? AND Dish.household_id = ? AND Dish.deletion_date IS NULL;
         "#,
-        id
+        id,
+        household_id
     )
     .fetch_all(&connection)
     .await?
@@ -101,19 +142,83 @@ pub async fn get_dish(
             MealDish.meal_id,
             MealDish.weight as weight,
             Meal.eat_date,
-            Meal.description as meal_description
-        FROM MealDish JOIN Meal ON Meal.id = MealDish.meal_id
-        WHERE dish_id = ?
+            Meal.description as meal_description,
+            User.id as member_id,
+            COALESCE(User.display_name, User.username) as "member_name!: String"
+        FROM MealDish
+            JOIN Meal ON Meal.id = MealDish.meal_id
+            JOIN User ON User.id = Meal.user_id
+        WHERE dish_id = ? AND Meal.deletion_date IS NULL
         ORDER BY Meal.eat_date DESC NULLS FIRST;
         "#,
         id
     )
     .fetch_all(&connection)
     .await?;
-    Ok(ServerResponse::success(GetDishResponse {
-        dish,
-        added_ingredients,
-        used_at,
-    })
-    .json())
+
+    let portions = used_at
+        .iter()
+        .fold(Vec::<Portion>::new(), |mut portions, used| {
+            let weight = used.weight.unwrap_or_default();
+            match portions.iter_mut().find(|p| p.member_id == used.member_id) {
+                Some(portion) => portion.weight += weight,
+                None => portions.push(Portion {
+                    member_id: used.member_id,
+                    member_name: used.member_name.clone(),
+                    weight,
+                }),
+            }
+            portions
+        });
+
+    let ingredient_ids = added_ingredients
+        .iter()
+        .map(|i| i.ingredient_id)
+        .collect::<Vec<i64>>();
+    let DietCheck { diet, warnings } =
+        check_ingredients(&connection, household_id, None, &ingredient_ids).await?;
+
+    let components = added_ingredients
+        .iter()
+        .map(|ingredient| WeightedComponent {
+            dish_id: None,
+            ingredient_id: Some(ingredient.ingredient_id),
+            weight: ingredient.weight,
+        })
+        .collect::<Vec<WeightedComponent>>();
+    let (footprint_table, trackable_table, trackables) = futures::try_join!(
+        FootprintTable::fetch(&connection, &components),
+        TrackableTable::fetch(&connection, &components),
+        Trackables::fetch(&connection, household_id),
+    )?;
+    let mut footprint = Footprint::default();
+    let mut amounts = TrackableAmounts::new();
+    for component in &components {
+        footprint += footprint_table.component_footprint(component);
+        trackable_table.add_component(&mut amounts, component);
+    }
+
+    let eaten_weight = portions.iter().map(|p| p.weight).sum::<i64>();
+    let total_weight = if dish.total_weight > 0 {
+        dish.total_weight
+    } else {
+        added_ingredients.iter().map(|i| i.weight).sum()
+    };
+
+    Ok(Versioned(
+        dish.version,
+        ServerResponse::success(GetDishResponse {
+            dish,
+            added_ingredients,
+            used_at,
+            portions,
+            eaten_weight,
+            remaining_weight: total_weight - eaten_weight,
+            diet,
+            warnings,
+            footprint,
+            trackables: trackables.amounts(&amounts),
+        })
+        .json(),
+    ))
 }
@@ -3,7 +3,10 @@ use schemars::JsonSchema;
 use serde::Deserialize;
 
 use crate::{
+    audit::{record_change, snapshot, AuditEntity},
+    etag::IfMatch,
     server::{ServerResponse, ServerResponseResult},
+    session::AuthUser,
     state::AppState,
 };
 
@@ -18,18 +21,29 @@ pub struct IngredientId {
 }
 
 pub async fn delete_ingredient(
-    State(AppState { connection }): State<AppState>,
+    State(AppState { connection, .. }): State<AppState>,
+    user: AuthUser,
+    if_match: IfMatch,
     Path(DishId { dish_id }): Path<DishId>,
     Path(IngredientId { ingredient_id }): Path<IngredientId>,
 ) -> ServerResponseResult<bool> {
+    user.check_dish(&connection, dish_id).await?;
+    let mut transaction = connection.begin().await?;
+    if_match.check(&mut transaction, "Dish", dish_id).await?;
+    let before = snapshot(&mut *transaction, AuditEntity::Dish, dish_id).await?;
+
     sqlx::query!(
         r#"
         DELETE FROM DishIngredient WHERE dish_id = ? AND ingredient_id = ?"#,
         dish_id,
         ingredient_id,
     )
-    .execute(&connection)
+    .execute(&mut *transaction)
     .await?;
 
+    record_change(&mut *transaction, &user, AuditEntity::Dish, dish_id, before).await?;
+
+    transaction.commit().await?;
+
     Ok(ServerResponse::success(true).json())
 }
@@ -7,10 +7,12 @@ use crate::state::AppState;
 
 mod delete;
 mod post;
+mod resolve;
 
 pub fn route(state: AppState) -> ApiRouter {
     ApiRouter::new()
         .api_route("/", post(post::post_ingredient))
         .api_route("/:ingredient_id", delete(delete::delete_ingredient))
+        .api_route("/:ingredient_id/resolve", post(resolve::post_resolve))
         .with_state(state)
 }
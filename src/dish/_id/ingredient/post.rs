@@ -6,7 +6,11 @@ use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 use crate::{
+    audit::{record_change, snapshot, AuditEntity},
+    etag::IfMatch,
+    events::DomainEvent,
     server::{ServerResponse, ServerResponseResult},
+    session::AuthUser,
     state::AppState,
 };
 
@@ -30,13 +34,25 @@ pub struct PostIngredientResult {
 }
 
 pub async fn post_ingredient(
-    State(AppState { connection }): State<AppState>,
+    State(AppState {
+        connection, events, ..
+    }): State<AppState>,
+    user: AuthUser,
+    if_match: IfMatch,
     Path(DishId { dish_id }): Path<DishId>,
     Json(PostIngredientBody {
         weight,
         ingredient_id,
     }): Json<PostIngredientBody>,
 ) -> ServerResponseResult<PostIngredientResult> {
+    futures::try_join!(
+        user.check_dish(&connection, dish_id),
+        user.check_ingredient(&connection, ingredient_id),
+    )?;
+    let mut transaction = connection.begin().await?;
+    if_match.check(&mut transaction, "Dish", dish_id).await?;
+    let before = snapshot(&mut *transaction, AuditEntity::Dish, dish_id).await?;
+
     let data = sqlx::query_as!(
         PostIngredientResult,
         r#"
@@ -51,8 +67,21 @@ pub async fn post_ingredient(
         ingredient_id,
         weight
     )
-    .fetch_one(&connection)
+    .fetch_one(&mut *transaction)
     .await?;
 
+    record_change(&mut *transaction, &user, AuditEntity::Dish, dish_id, before).await?;
+
+    transaction.commit().await?;
+
+    events.publish(
+        &user,
+        DomainEvent::DishIngredientAdded {
+            dish_id,
+            ingredient_id,
+            weight,
+        },
+    );
+
     Ok(ServerResponse::success(data).json())
 }
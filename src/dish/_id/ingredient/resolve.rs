@@ -0,0 +1,97 @@
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+use schemars::JsonSchema;
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::{
+    audit::{record_change, snapshot, AuditEntity},
+    etag::IfMatch,
+    models::DishIngredient,
+    server::{ServerResponse, ServerResponseResult},
+    session::AuthUser,
+    state::AppState,
+};
+
+#[derive(Deserialize, JsonSchema)]
+pub struct DishId {
+    dish_id: i64,
+}
+
+#[derive(Deserialize, JsonSchema)]
+pub struct IngredientId {
+    ingredient_id: i64,
+}
+
+#[derive(Deserialize, JsonSchema)]
+pub struct PostResolveBody {
+    /// Variant of the ingredient that was actually used
+    variant_id: i64,
+}
+
+#[derive(Error, Debug)]
+enum PostResolveError {
+    #[error("Dish {0} has no ingredient {1}")]
+    IngredientNotInDish(i64, i64),
+    #[error("Ingredient {0} is not a variant of ingredient {1}")]
+    NotAVariant(i64, i64),
+}
+
+/// Replaces a generic ingredient of the dish, such as "Milk", with the variant that was actually
+/// used, such as a specific brand. If the dish already has the variant, the weights are summed.
+pub async fn post_resolve(
+    State(AppState { connection, .. }): State<AppState>,
+    user: AuthUser,
+    if_match: IfMatch,
+    Path(DishId { dish_id }): Path<DishId>,
+    Path(IngredientId { ingredient_id }): Path<IngredientId>,
+    Json(PostResolveBody { variant_id }): Json<PostResolveBody>,
+) -> ServerResponseResult<DishIngredient> {
+    futures::try_join!(
+        user.check_dish(&connection, dish_id),
+        user.check_ingredient(&connection, variant_id),
+    )?;
+    sqlx::query_scalar!(
+        "SELECT id FROM Ingredient WHERE id = ? AND generic_id = ?",
+        variant_id,
+        ingredient_id
+    )
+    .fetch_optional(&connection)
+    .await?
+    .ok_or(PostResolveError::NotAVariant(variant_id, ingredient_id))?;
+    let mut transaction = connection.begin().await?;
+    if_match.check(&mut transaction, "Dish", dish_id).await?;
+    let before = snapshot(&mut *transaction, AuditEntity::Dish, dish_id).await?;
+
+    let dish_ingredient = sqlx::query_as!(
+        DishIngredient,
+        r#"
+        INSERT INTO DishIngredient (dish_id, ingredient_id, weight)
+        SELECT dish_id, ?3, weight FROM DishIngredient WHERE dish_id = ?1 AND ingredient_id = ?2
+        ON CONFLICT DO UPDATE SET weight = DishIngredient.weight + excluded.weight
+        RETURNING dish_id, ingredient_id, weight, creation_date;"#,
+        dish_id,
+        ingredient_id,
+        variant_id
+    )
+    .fetch_optional(&mut *transaction)
+    .await?
+    .ok_or(PostResolveError::IngredientNotInDish(
+        dish_id,
+        ingredient_id,
+    ))?;
+    sqlx::query!(
+        "DELETE FROM DishIngredient WHERE dish_id = ? AND ingredient_id = ?",
+        dish_id,
+        ingredient_id
+    )
+    .execute(&mut *transaction)
+    .await?;
+    record_change(&mut *transaction, &user, AuditEntity::Dish, dish_id, before).await?;
+
+    transaction.commit().await?;
+
+    Ok(ServerResponse::success(dish_ingredient).json())
+}
@@ -2,6 +2,7 @@ use aide::axum::{routing::get, ApiRouter};
 
 use crate::state::AppState;
 
+mod cost;
 mod delete;
 mod delete_warning;
 mod get;
@@ -18,6 +19,7 @@ pub fn route(state: AppState) -> ApiRouter {
                 .post(post::post_edit_dish),
         )
         .api_route("/delete-warning", get(delete_warning::get_delete_warning))
+        .nest_api_service("/cost", cost::route(state.clone()))
         .nest_api_service("/weight", weight::route(state.clone()))
         .nest_api_service("/ingredient", ingredient::route(state.clone()))
         .with_state(state)
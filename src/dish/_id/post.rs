@@ -7,9 +7,13 @@ use serde::Deserialize;
 use thiserror::Error;
 
 use crate::{
+    audit::{record_change, snapshot, AuditEntity},
+    etag::{fetch_version, IfMatch, Versioned, VersionedResult},
+    events::DomainEvent,
     get_missing_items,
     models::{Dish, DishIngredient},
-    server::{ServerResponse, ServerResponseResult},
+    server::ServerResponse,
+    session::AuthUser,
     state::AppState,
 };
 
@@ -40,7 +44,11 @@ pub struct DishId {
 }
 
 pub async fn post_edit_dish(
-    State(AppState { connection }): State<AppState>,
+    State(AppState {
+        connection, events, ..
+    }): State<AppState>,
+    user: AuthUser,
+    if_match: IfMatch,
     Path(DishId { dish_id: id }): Path<DishId>,
     Json(PostDish {
         total_weight,
@@ -49,7 +57,12 @@ pub async fn post_edit_dish(
         dish_ingredients,
         is_finished,
     }): Json<PostDish>,
-) -> ServerResponseResult<(Dish, Vec<DishIngredient>)> {
+) -> VersionedResult<(Dish, Vec<DishIngredient>)> {
+    user.check_dish(&connection, id).await?;
+    let mut transaction = connection.begin().await?;
+    if_match.check(&mut transaction, "Dish", id).await?;
+    let before = snapshot(&mut *transaction, AuditEntity::Dish, id).await?;
+
     let dish_ingredients = dish_ingredients.unwrap_or_default();
 
     if !dish_ingredients.is_empty() {
@@ -61,11 +74,13 @@ pub async fn post_edit_dish(
                 .join(", ");
 
             let ingredients_in_database =
-                sqlx::QueryBuilder::new("SELECT id FROM Ingredient WHERE id IN (")
+                sqlx::QueryBuilder::new("SELECT id FROM Ingredient WHERE household_id = ")
+                    .push_bind(user.household_id)
+                    .push(" AND id IN (")
                     .push(ids)
                     .push(")")
                     .build_query_scalar::<i64>()
-                    .fetch_all(&connection)
+                    .fetch_all(&mut *transaction)
                     .await?;
 
             get_missing_items(
@@ -79,11 +94,14 @@ pub async fn post_edit_dish(
         }
     }
 
-    let transaction = connection.begin().await?;
+    let was_finished = sqlx::query_scalar!("SELECT is_finished FROM Dish WHERE id = ?", id)
+        .fetch_one(&mut *transaction)
+        .await?
+        != 0;
 
     let total_weight = total_weight.unwrap_or(0);
 
-    let new_dish = sqlx::query_as!(
+    let mut new_dish = sqlx::query_as!(
         Dish,
         r#"UPDATE Dish SET
             name = ?,
@@ -97,7 +115,8 @@ pub async fn post_edit_dish(
             prep_date,
             name,
             total_weight,
-            is_finished;
+            is_finished,
+            version;
         "#,
         name,
         prep_date,
@@ -105,7 +124,7 @@ pub async fn post_edit_dish(
         total_weight,
         id
     )
-    .fetch_one(&connection)
+    .fetch_one(&mut *transaction)
     .await?;
 
     let new_dish_ingredients = if dish_ingredients.is_empty() {
@@ -132,11 +151,22 @@ pub async fn post_edit_dish(
             RETURNING dish_id, ingredient_id, weight, creation_date;"#,
         )
         .build_query_as::<DishIngredient>()
-        .fetch_all(&connection)
+        .fetch_all(&mut *transaction)
         .await?
     };
 
+    record_change(&mut *transaction, &user, AuditEntity::Dish, id, before).await?;
+
     transaction.commit().await?;
 
-    Ok(ServerResponse::success((new_dish, new_dish_ingredients)).json())
+    new_dish.version = fetch_version(&connection, "Dish", id).await?;
+
+    if new_dish.is_finished != 0 && !was_finished {
+        events.publish(&user, DomainEvent::DishFinished { dish_id: id });
+    }
+
+    Ok(Versioned(
+        new_dish.version,
+        ServerResponse::success((new_dish, new_dish_ingredients)).json(),
+    ))
 }
@@ -7,7 +7,10 @@ use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
 use crate::{
+    audit::{record_change, snapshot, AuditEntity},
+    etag::IfMatch,
     server::{ServerResponse, ServerResponseResult},
+    session::AuthUser,
     state::AppState,
 };
 
@@ -34,26 +37,37 @@ enum PostDishError {
     InvalidWeight(i64),
 }
 pub async fn post_weight(
-    State(AppState { connection }): State<AppState>,
+    State(AppState { connection, .. }): State<AppState>,
+    user @ AuthUser { household_id, .. }: AuthUser,
+    if_match: IfMatch,
     Path(DishId { dish_id: id }): Path<DishId>,
     Json(PostTotalWeight { total_weight }): Json<PostTotalWeight>,
 ) -> ServerResponseResult<TotalWeightResponse> {
     if total_weight < 0 {
         return Err(PostDishError::InvalidWeight(total_weight))?;
     }
+    user.check_dish(&connection, id).await?;
+    let mut transaction = connection.begin().await?;
+    if_match.check(&mut transaction, "Dish", id).await?;
+    let before = snapshot(&mut *transaction, AuditEntity::Dish, id).await?;
 
     let total_weight = sqlx::query_scalar!(
         r#"
         UPDATE Dish
         SET total_weight = ?
-        WHERE Dish.id = ?
+        WHERE Dish.id = ? AND Dish.household_id = ?
         RETURNING total_weight;"#,
         total_weight,
-        id
+        id,
+        household_id
     )
-    .fetch_optional(&connection)
+    .fetch_optional(&mut *transaction)
     .await?
     .ok_or(PostDishError::UnknownDishId(id))?;
 
+    record_change(&mut *transaction, &user, AuditEntity::Dish, id, before).await?;
+
+    transaction.commit().await?;
+
     Ok(ServerResponse::success(TotalWeightResponse { total_weight }).json())
 }
@@ -5,6 +5,7 @@ use sqlx::FromRow;
 
 use crate::{
     server::{ServerResponse, ServerResponseResult},
+    session::AuthUser,
     state::AppState,
 };
 
@@ -15,6 +16,8 @@ pub struct ListDishResponse {
     prep_date: Option<i64>,
     creation_date: i64,
     is_finished: i64,
+    /// Weight left after the portions eaten by every household member, in grams
+    remaining_weight: i64,
 }
 
 #[derive(JsonSchema, Deserialize)]
@@ -23,7 +26,8 @@ pub struct ListDishQueryParams {
 }
 
 pub async fn list_dish(
-    State(AppState { connection }): State<AppState>,
+    State(AppState { connection, .. }): State<AppState>,
+    AuthUser { household_id, .. }: AuthUser,
     Query(query_params): Query<ListDishQueryParams>,
 ) -> ServerResponseResult<Vec<ListDishResponse>> {
     let mut queries = sqlx::QueryBuilder::new(
@@ -33,14 +37,25 @@ pub async fn list_dish(
             name,
             creation_date,
             prep_date,
-            is_finished
+            is_finished,
+            CAST ((
+                CASE
+                    WHEN total_weight > 0 THEN total_weight
+                    ELSE (SELECT TOTAL(weight) FROM DishIngredient WHERE dish_id = Dish.id)
+                END - (
+                    SELECT TOTAL(MealDish.weight)
+                    FROM MealDish JOIN Meal ON Meal.id = MealDish.meal_id
+                    WHERE dish_id = Dish.id AND Meal.deletion_date IS NULL
+                )
+            ) AS INTEGER) AS remaining_weight
         FROM Dish
-        "#,
+        WHERE deletion_date IS NULL AND household_id = "#,
     );
+    queries.push_bind(household_id).push("\n");
 
     if let Some(is_finished) = query_params.is_finished {
         queries
-            .push("WHERE is_finished = ")
+            .push("AND is_finished = ")
             .push(is_finished)
             .push("\n");
     }
@@ -5,9 +5,14 @@ use sqlx::QueryBuilder;
 use thiserror::Error;
 
 use crate::{
+    audit::{record_change, AuditEntity},
+    diet::{check_ingredients, RestrictionWarning},
+    etag::fetch_version,
+    events::DomainEvent,
     get_missing_items,
     models::{Dish, DishIngredient},
     server::{ServerResponse, ServerResponseResult},
+    session::AuthUser,
     state::AppState,
 };
 
@@ -31,14 +36,17 @@ enum PostDishError {
     UnknownIngredientId(Vec<i64>),
 }
 pub async fn post_dish(
-    State(AppState { connection }): State<AppState>,
+    State(AppState {
+        connection, events, ..
+    }): State<AppState>,
+    user @ AuthUser { household_id, .. }: AuthUser,
     Json(PostDish {
         total_weight,
         name,
         prep_date,
         dish_ingredients,
     }): Json<PostDish>,
-) -> ServerResponseResult<(Dish, Vec<DishIngredient>)> {
+) -> ServerResponseResult<(Dish, Vec<DishIngredient>, Vec<RestrictionWarning>)> {
     let dish_ingredients = dish_ingredients.unwrap_or_default();
 
     if !dish_ingredients.is_empty() {
@@ -50,7 +58,9 @@ pub async fn post_dish(
                 .join(", ");
 
             let ingredients_in_database =
-                sqlx::QueryBuilder::new("SELECT id FROM Ingredient WHERE id IN (")
+                sqlx::QueryBuilder::new("SELECT id FROM Ingredient WHERE household_id = ")
+                    .push_bind(household_id)
+                    .push(" AND id IN (")
                     .push(ids)
                     .push(")")
                     .build_query_scalar::<i64>()
@@ -68,22 +78,23 @@ pub async fn post_dish(
         }
     }
 
-    let transaction = connection.begin().await?;
+    let mut transaction = connection.begin().await?;
 
     let total_weight = total_weight.unwrap_or(0);
 
-    let new_dish = sqlx::query_as!(
+    let mut new_dish = sqlx::query_as!(
         Dish,
         "INSERT INTO Dish
-            (name, prep_date, total_weight)
+            (name, prep_date, total_weight, household_id)
         VALUES
-            (?, ?, ?)
-        RETURNING id, creation_date, prep_date, name, total_weight, is_finished;",
+            (?, ?, ?, ?)
+        RETURNING id, creation_date, prep_date, name, total_weight, is_finished, version;",
         name,
         prep_date,
-        total_weight
+        total_weight,
+        household_id
     )
-    .fetch_one(&connection)
+    .fetch_one(&mut *transaction)
     .await?;
 
     let new_dish_ingredients = if !dish_ingredients.is_empty() {
@@ -100,13 +111,49 @@ pub async fn post_dish(
             RETURNING dish_id, ingredient_id, weight, creation_date;"#,
             )
             .build_query_as::<DishIngredient>()
-            .fetch_all(&connection)
+            .fetch_all(&mut *transaction)
             .await?
     } else {
         vec![]
     };
 
+    record_change(
+        &mut *transaction,
+        &user,
+        AuditEntity::Dish,
+        new_dish.id,
+        None,
+    )
+    .await?;
+
     transaction.commit().await?;
 
-    Ok(ServerResponse::success((new_dish, new_dish_ingredients)).json())
+    // Adding the components bumped the version
+    new_dish.version = fetch_version(&connection, "Dish", new_dish.id).await?;
+
+    // Anyone in the household may eat the dish
+    let warnings = check_ingredients(
+        &connection,
+        household_id,
+        None,
+        &new_dish_ingredients
+            .iter()
+            .map(|i| i.ingredient_id)
+            .collect::<Vec<i64>>(),
+    )
+    .await?
+    .warnings;
+
+    for dish_ingredient in &new_dish_ingredients {
+        events.publish(
+            &user,
+            DomainEvent::DishIngredientAdded {
+                dish_id: new_dish.id,
+                ingredient_id: dish_ingredient.ingredient_id,
+                weight: dish_ingredient.weight,
+            },
+        );
+    }
+
+    Ok(ServerResponse::success((new_dish, new_dish_ingredients, warnings)).json())
 }
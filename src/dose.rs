@@ -0,0 +1,235 @@
+use std::collections::HashMap;
+
+use schemars::JsonSchema;
+use serde::Serialize;
+use sqlx::{prelude::FromRow, Sqlite};
+
+use crate::{
+    models::SupplementSchedule,
+    nutrition::{add_amounts, Nutrients, TrackableAmounts},
+    period::weekday_occurrences,
+};
+
+/// Supplement doses taken by a household member.
+#[derive(FromRow)]
+pub struct TakenDose {
+    pub user_id: i64,
+    pub date: i64,
+    pub meal_id: Option<i64>,
+    pub supplement_id: i64,
+    pub doses: f64,
+}
+
+/// Fetches the doses taken between `from` and `to` by the members of the household, or only by
+/// `member_id` if given. Doses taken with a meal that is in the trash are left out.
+pub async fn fetch_taken_doses(
+    connection: &sqlx::Pool<Sqlite>,
+    household_id: i64,
+    member_id: Option<i64>,
+    from: i64,
+    to: i64,
+) -> anyhow::Result<Vec<TakenDose>> {
+    Ok(sqlx::query_as::<_, TakenDose>(
+        r#"
+        SELECT SupplementDose.user_id, take_date AS date, meal_id, supplement_id, doses
+        FROM SupplementDose JOIN User ON User.id = SupplementDose.user_id
+        WHERE User.household_id = ?3
+            AND (?4 IS NULL OR SupplementDose.user_id = ?4)
+            AND take_date >= ?1 AND take_date <= ?2
+            AND (meal_id IS NULL OR meal_id IN (SELECT id FROM Meal WHERE deletion_date IS NULL));"#,
+    )
+    .bind(from)
+    .bind(to)
+    .bind(household_id)
+    .bind(member_id)
+    .fetch_all(connection)
+    .await?)
+}
+
+#[derive(FromRow)]
+struct DatabaseSupplementNutrients {
+    id: i64,
+    kcal: Option<f64>,
+    proteins: Option<f64>,
+    fat: Option<f64>,
+    carbohydrates: Option<f64>,
+}
+
+#[derive(FromRow)]
+struct DatabaseSupplementTrackable {
+    supplement_id: i64,
+    trackable_id: i64,
+    amount: f64,
+}
+
+/// Per dose nutrients and trackables of a set of supplements, fetched all at once.
+pub struct DoseTable {
+    nutrients: HashMap<i64, Nutrients>,
+    trackables: HashMap<i64, TrackableAmounts>,
+}
+
+impl DoseTable {
+    pub async fn fetch(
+        connection: &sqlx::Pool<Sqlite>,
+        supplement_ids: &[i64],
+    ) -> anyhow::Result<Self> {
+        if supplement_ids.is_empty() {
+            return Ok(DoseTable {
+                nutrients: HashMap::new(),
+                trackables: HashMap::new(),
+            });
+        }
+
+        let nutrients = sqlx::QueryBuilder::<Sqlite>::new(
+            r#"
+            SELECT id, kcal, proteins, fat, carbohydrates
+            FROM Supplement
+            WHERE id IN "#,
+        )
+        .push_tuples(supplement_ids.iter(), |mut p, id| {
+            p.push_bind(*id);
+        })
+        .build_query_as::<DatabaseSupplementNutrients>()
+        .fetch_all(connection)
+        .await?
+        .into_iter()
+        .map(|supplement| {
+            (
+                supplement.id,
+                Nutrients {
+                    kcal: supplement.kcal.unwrap_or_default(),
+                    proteins: supplement.proteins.unwrap_or_default(),
+                    fat: supplement.fat.unwrap_or_default(),
+                    carbohydrates: supplement.carbohydrates.unwrap_or_default(),
+                },
+            )
+        })
+        .collect();
+
+        let trackables = sqlx::QueryBuilder::<Sqlite>::new(
+            r#"
+            SELECT supplement_id, trackable_id, amount
+            FROM SupplementTrackable
+            WHERE supplement_id IN "#,
+        )
+        .push_tuples(supplement_ids.iter(), |mut p, id| {
+            p.push_bind(*id);
+        })
+        .build_query_as::<DatabaseSupplementTrackable>()
+        .fetch_all(connection)
+        .await?
+        .into_iter()
+        .fold(HashMap::new(), |mut dict, row| {
+            dict.entry(row.supplement_id)
+                .or_insert_with(TrackableAmounts::new)
+                .insert(row.trackable_id, row.amount);
+            dict
+        });
+
+        Ok(DoseTable {
+            nutrients,
+            trackables,
+        })
+    }
+
+    /// The nutrients in `doses` doses of the supplement. Unknown nutrients count as zero.
+    pub fn dose_nutrients(&self, supplement_id: i64, doses: f64) -> Nutrients {
+        self.nutrients
+            .get(&supplement_id)
+            .map(|n| n.scale(doses))
+            .unwrap_or_default()
+    }
+
+    /// Adds the amounts of trackables in `doses` doses of the supplement to `amounts`.
+    pub fn add_dose(&self, amounts: &mut TrackableAmounts, supplement_id: i64, doses: f64) {
+        if let Some(per_dose) = self.trackables.get(&supplement_id) {
+            add_amounts(amounts, per_dose, doses);
+        }
+    }
+}
+
+/// Occurrences of schedules are not listed more than this many days into the future
+const MAX_SCHEDULE_DAYS: i64 = 366;
+
+/// An occurrence of a supplement schedule, and the dose taken for it if it was.
+#[derive(Serialize, JsonSchema)]
+pub struct ScheduledDose {
+    pub schedule_id: i64,
+    pub supplement_id: i64,
+    pub scheduled_date: i64,
+    pub doses: f64,
+    pub dose_id: Option<i64>,
+}
+
+/// Lists the occurrences of the user's supplement schedules between `from` and `to`, oldest
+/// first.
+pub async fn fetch_scheduled_doses(
+    connection: &sqlx::Pool<Sqlite>,
+    user_id: i64,
+    from: i64,
+    to: i64,
+) -> anyhow::Result<Vec<ScheduledDose>> {
+    let limit = chrono::Utc::now().timestamp_millis() + MAX_SCHEDULE_DAYS * 24 * 60 * 60 * 1000;
+    let to = to.min(limit);
+
+    let schedules = sqlx::query_as!(
+        SupplementSchedule,
+        r#"
+        SELECT
+            id,
+            creation_date,
+            supplement_id,
+            doses,
+            start_date,
+            end_date,
+            weekdays,
+            utc_offset_minutes
+        FROM SupplementSchedule
+        WHERE user_id = ? AND start_date <= ? AND (end_date IS NULL OR end_date >= ?);"#,
+        user_id,
+        to,
+        from
+    )
+    .fetch_all(connection)
+    .await?;
+
+    let taken = sqlx::query!(
+        r#"
+        SELECT id, schedule_id AS "schedule_id!", scheduled_date AS "scheduled_date!"
+        FROM SupplementDose
+        WHERE user_id = ? AND schedule_id IS NOT NULL AND scheduled_date >= ? AND scheduled_date <= ?;"#,
+        user_id,
+        from,
+        to
+    )
+    .fetch_all(connection)
+    .await?
+    .into_iter()
+    .map(|dose| ((dose.schedule_id, dose.scheduled_date), dose.id))
+    .collect::<HashMap<(i64, i64), i64>>();
+
+    let mut occurrences = schedules
+        .into_iter()
+        .flat_map(|schedule| {
+            weekday_occurrences(
+                schedule.start_date,
+                schedule.end_date,
+                schedule.weekdays,
+                schedule.utc_offset_minutes,
+                from.saturating_sub(1),
+                to,
+            )
+            .into_iter()
+            .map(|scheduled_date| ScheduledDose {
+                schedule_id: schedule.id,
+                supplement_id: schedule.supplement_id,
+                scheduled_date,
+                doses: schedule.doses,
+                dose_id: taken.get(&(schedule.id, scheduled_date)).copied(),
+            })
+            .collect::<Vec<ScheduledDose>>()
+        })
+        .collect::<Vec<ScheduledDose>>();
+    occurrences.sort_by_key(|occurrence| occurrence.scheduled_date);
+    Ok(occurrences)
+}
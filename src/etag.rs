@@ -0,0 +1,250 @@
+use aide::{
+    gen::GenContext,
+    openapi::{Operation, Response as ApiResponse},
+    OperationInput, OperationOutput,
+};
+use axum::{
+    async_trait,
+    body::{boxed, Full},
+    extract::FromRequestParts,
+    http::{header, request::Parts, HeaderValue, Method, Request, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use sha2::{Digest, Sha256};
+use sqlx::{Pool, Sqlite, SqliteConnection};
+use thiserror::Error;
+
+use crate::{app_error::InternalServerError, server::ServerResponse};
+
+/// The response of an endpoint that returns a single entity, with that entity's version. The
+/// version ends up in the response's `ETag` header.
+pub struct Versioned<T>(pub i64, pub T);
+
+pub type VersionedResult<T> = Result<Versioned<Json<ServerResponse<T>>>, InternalServerError>;
+
+/// Version of the entity in a response, read by `etag_middleware`.
+#[derive(Clone, Copy, Debug)]
+struct EntityVersion(i64);
+
+impl<T: IntoResponse> IntoResponse for Versioned<T> {
+    fn into_response(self) -> Response {
+        let mut response = self.1.into_response();
+        response.extensions_mut().insert(EntityVersion(self.0));
+        response
+    }
+}
+
+impl<T: OperationOutput> OperationOutput for Versioned<T> {
+    type Inner = T::Inner;
+
+    fn operation_response(ctx: &mut GenContext, operation: &mut Operation) -> Option<ApiResponse> {
+        T::operation_response(ctx, operation)
+    }
+
+    fn inferred_responses(
+        ctx: &mut GenContext,
+        operation: &mut Operation,
+    ) -> Vec<(Option<u16>, ApiResponse)> {
+        T::inferred_responses(ctx, operation)
+    }
+}
+
+#[derive(Error, Debug)]
+#[error(
+    "The {entity} with id \"{id}\" was changed since it was fetched. Its version is now {version}"
+)]
+pub struct PreconditionFailed {
+    pub entity: &'static str,
+    pub id: i64,
+    pub version: i64,
+}
+
+/// Version in an entity tag sent by us, which looks like `"v3-<hash>"` or `"v3"`.
+fn tag_version(tag: &str) -> Option<i64> {
+    let tag = tag.trim();
+    let tag = tag.strip_prefix("W/").unwrap_or(tag);
+    let tag = tag
+        .strip_prefix('"')?
+        .strip_suffix('"')?
+        .strip_prefix('v')?;
+    tag.split('-').next()?.parse().ok()
+}
+
+fn tag_list_matches(list: &str, etag: &str) -> bool {
+    list.split(',')
+        .map(str::trim)
+        .any(|tag| tag == "*" || tag.strip_prefix("W/").unwrap_or(tag) == etag)
+}
+
+/// The `If-Match` header. When sent, updates and deletes fail with `412 Precondition Failed` if
+/// the entity changed since the client fetched it.
+pub struct IfMatch(Option<String>);
+
+impl OperationInput for IfMatch {}
+
+#[async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for IfMatch {
+    type Rejection = std::convert::Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        Ok(IfMatch(
+            parts
+                .headers
+                .get(header::IF_MATCH)
+                .and_then(|value| value.to_str().ok())
+                .map(str::to_string),
+        ))
+    }
+}
+
+impl IfMatch {
+    /// Makes sure the row with `id` in `table` is still at the version the client expects. Must
+    /// run on the transaction that makes the change, which holds the write lock from here on so the
+    /// version can't change before the change is committed. Rows that don't exist are left for the
+    /// ownership checks to report.
+    pub async fn check(
+        &self,
+        connection: &mut SqliteConnection,
+        table: &'static str,
+        id: i64,
+    ) -> anyhow::Result<()> {
+        let Some(list) = &self.0 else {
+            return Ok(());
+        };
+        if list.split(',').any(|tag| tag.trim() == "*") {
+            return Ok(());
+        }
+
+        // Any write takes the lock. This one changes no row, so the version is only bumped once,
+        // by the change itself
+        sqlx::query(&format!("UPDATE {table} SET version = version WHERE FALSE"))
+            .execute(&mut *connection)
+            .await?;
+        let version =
+            sqlx::query_scalar::<_, i64>(&format!("SELECT version FROM {table} WHERE id = ?"))
+                .bind(id)
+                .fetch_optional(&mut *connection)
+                .await?;
+        match version {
+            Some(version)
+                if !list
+                    .split(',')
+                    .filter_map(tag_version)
+                    .any(|v| v == version) =>
+            {
+                Err(PreconditionFailed {
+                    entity: table,
+                    id,
+                    version,
+                })?
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Current version of the row with `id` in `table`. Used after changing the links of a meal or
+/// dish, which bump its version after it was returned.
+pub async fn fetch_version(
+    connection: &Pool<Sqlite>,
+    table: &'static str,
+    id: i64,
+) -> anyhow::Result<i64> {
+    Ok(
+        sqlx::query_scalar::<_, i64>(&format!("SELECT version FROM {table} WHERE id = ?"))
+            .bind(id)
+            .fetch_one(connection)
+            .await?,
+    )
+}
+
+/// Adds an `ETag` header to successful JSON responses. On reads it's a hash of the body, prefixed
+/// by the entity's version when there is one, and `304 Not Modified` is returned if it matches
+/// the request's `If-None-Match`, so clients can poll cheaply. On writes it's only the version.
+pub async fn etag_middleware<B>(request: Request<B>, next: Next<B>) -> Response {
+    let is_read = matches!(*request.method(), Method::GET | Method::HEAD);
+    let if_none_match = request
+        .headers()
+        .get(header::IF_NONE_MATCH)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string);
+
+    let mut response = next.run(request).await;
+
+    let version = response.extensions().get::<EntityVersion>().copied();
+    // Event streams never end, so their body can't be hashed
+    let is_json = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .is_some_and(|value| value.as_bytes().starts_with(b"application/json"));
+    if response.status() != StatusCode::OK || !is_json {
+        return response;
+    }
+
+    if !is_read {
+        if let Some(EntityVersion(version)) = version {
+            if let Ok(value) = HeaderValue::from_str(&format!("\"v{version}\"")) {
+                response.headers_mut().insert(header::ETAG, value);
+            }
+        }
+        return response;
+    }
+
+    let (mut parts, body) = response.into_parts();
+    let bytes = match hyper::body::to_bytes(body).await {
+        Ok(bytes) => bytes,
+        Err(error) => return InternalServerError(error.into()).into_response(),
+    };
+
+    let hash = format!("{:x}", Sha256::digest(&bytes));
+    let hash = &hash[..16];
+    let etag = match version {
+        Some(EntityVersion(version)) => format!("\"v{version}-{hash}\""),
+        None => format!("\"{hash}\""),
+    };
+
+    if if_none_match.is_some_and(|list| tag_list_matches(&list, &etag)) {
+        return (StatusCode::NOT_MODIFIED, [(header::ETAG, etag)]).into_response();
+    }
+
+    if let Ok(value) = HeaderValue::from_str(&etag) {
+        parts.headers.insert(header::ETAG, value);
+    }
+    Response::from_parts(parts, boxed(Full::from(bytes)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tag_version_reads_our_tags() {
+        assert_eq!(tag_version("\"v3\""), Some(3));
+        assert_eq!(tag_version("\"v12-0123456789abcdef\""), Some(12));
+        assert_eq!(tag_version(" W/\"v7-0123456789abcdef\" "), Some(7));
+    }
+
+    #[test]
+    fn tag_version_rejects_other_tags() {
+        assert_eq!(tag_version("\"0123456789abcdef\""), None);
+        assert_eq!(tag_version("v3"), None);
+        assert_eq!(tag_version("\"v\""), None);
+        assert_eq!(tag_version("\"vthree\""), None);
+        assert_eq!(tag_version("*"), None);
+    }
+
+    #[test]
+    fn tag_list_matches_any_tag_of_the_list() {
+        let etag = "\"v2-0123456789abcdef\"";
+        assert!(tag_list_matches(etag, etag));
+        assert!(tag_list_matches(
+            "\"v1-fedcba9876543210\", W/\"v2-0123456789abcdef\"",
+            etag
+        ));
+        assert!(tag_list_matches("*", etag));
+        assert!(!tag_list_matches("\"v1-fedcba9876543210\"", etag));
+        assert!(!tag_list_matches("\"v2\"", etag));
+    }
+}
@@ -0,0 +1,14 @@
+use aide::axum::ApiRouter;
+use axum::routing::get;
+
+use crate::state::AppState;
+
+mod stream;
+
+pub fn route(state: AppState) -> ApiRouter {
+    // Server-sent event streams can't be described by the OpenAPI document, so this route is
+    // left out of it
+    ApiRouter::new()
+        .route("/", get(stream::get_event_stream))
+        .with_state(state)
+}
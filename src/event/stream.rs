@@ -0,0 +1,129 @@
+use std::{convert::Infallible, sync::Arc, time::Duration};
+
+use axum::{
+    extract::{Query, State},
+    response::sse::{Event, KeepAlive, Sse},
+};
+use futures::Stream;
+use schemars::JsonSchema;
+use serde::Deserialize;
+use tokio::sync::broadcast::{error::RecvError, Receiver};
+
+use crate::{
+    app_error::InternalServerError,
+    events::{DomainEventType, EventEnvelope, UnknownEventType},
+    session::AuthUser,
+    state::AppState,
+};
+
+#[derive(Deserialize, JsonSchema)]
+pub struct GetEventStreamQueryParams {
+    /// Comma separated event types to receive, such as `MealCreated,DishFinished`. Every type is
+    /// received if not provided
+    types: Option<String>,
+    /// Only receive events about this meal
+    meal_id: Option<i64>,
+    /// Only receive events about this dish
+    dish_id: Option<i64>,
+    /// Only receive events about this ingredient
+    ingredient_id: Option<i64>,
+    /// Only receive events caused by this household member
+    member_id: Option<i64>,
+}
+
+struct EventFilter {
+    household_id: i64,
+    types: Option<Vec<DomainEventType>>,
+    meal_id: Option<i64>,
+    dish_id: Option<i64>,
+    ingredient_id: Option<i64>,
+    member_id: Option<i64>,
+}
+
+impl EventFilter {
+    fn matches(&self, envelope: &EventEnvelope) -> bool {
+        let event = &envelope.event;
+        envelope.household_id == self.household_id
+            && self
+                .types
+                .as_ref()
+                .is_none_or(|types| types.contains(&event.event_type()))
+            && self.meal_id.is_none_or(|id| event.meal_id() == Some(id))
+            && self.dish_id.is_none_or(|id| event.dish_id() == Some(id))
+            && self
+                .ingredient_id
+                .is_none_or(|id| event.ingredient_id() == Some(id))
+            && self.member_id.is_none_or(|id| envelope.member_id == id)
+    }
+}
+
+/// Waits for the next event that passes `filter`. `None` means the stream should end.
+async fn next_event(
+    receiver: &mut Receiver<Arc<EventEnvelope>>,
+    filter: &EventFilter,
+) -> Option<Event> {
+    loop {
+        match receiver.recv().await {
+            Ok(envelope) if filter.matches(&envelope) => {
+                return Some(
+                    Event::default()
+                        .event(envelope.event.event_type().name())
+                        .json_data(envelope.as_ref())
+                        .unwrap_or_else(|_| Event::default().event("error")),
+                );
+            }
+            Ok(_) => continue,
+            // The client should refetch whatever it is showing, since some events were lost
+            Err(RecvError::Lagged(skipped)) => {
+                return Some(Event::default().event("lagged").data(skipped.to_string()))
+            }
+            Err(RecvError::Closed) => return None,
+        }
+    }
+}
+
+/// Streams the household's events as server-sent events. Each event is named after its type,
+/// and its data is the JSON of the event, with the member that caused it. A `lagged` event, with
+/// the number of events missed, is sent when the client is too slow to keep up.
+pub async fn get_event_stream(
+    State(AppState { events, .. }): State<AppState>,
+    AuthUser { household_id, .. }: AuthUser,
+    Query(GetEventStreamQueryParams {
+        types,
+        meal_id,
+        dish_id,
+        ingredient_id,
+        member_id,
+    }): Query<GetEventStreamQueryParams>,
+) -> Result<Sse<impl Stream<Item = Result<Event, Infallible>>>, InternalServerError> {
+    let types = types
+        .map(|types| {
+            types
+                .split(',')
+                .map(str::trim)
+                .map(|name| {
+                    DomainEventType::from_name(name)
+                        .ok_or_else(|| UnknownEventType(name.to_string()))
+                })
+                .collect::<Result<Vec<DomainEventType>, UnknownEventType>>()
+        })
+        .transpose()?;
+    let filter = EventFilter {
+        household_id,
+        types,
+        meal_id,
+        dish_id,
+        ingredient_id,
+        member_id,
+    };
+
+    let stream = futures::stream::unfold(
+        (events.subscribe(), filter),
+        |(mut receiver, filter)| async move {
+            let event = next_event(&mut receiver, &filter).await?;
+            Some((Ok(event), (receiver, filter)))
+        },
+    );
+
+    Ok(Sse::new(stream).keep_alive(KeepAlive::new().interval(Duration::from_secs(15))))
+}
@@ -0,0 +1,159 @@
+use std::sync::Arc;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tokio::sync::broadcast;
+
+use crate::session::AuthUser;
+
+/// How many events are kept for subscribers that fall behind. Slower subscribers miss events,
+/// and are told so.
+const EVENT_BUFFER_SIZE: usize = 256;
+
+/// Something that happened in a household, published to the event stream.
+#[derive(Serialize, JsonSchema, Clone, Debug)]
+#[serde(tag = "type")]
+pub enum DomainEvent {
+    MealCreated {
+        meal_id: i64,
+    },
+    /// The meal's fields, dishes or ingredients changed
+    MealUpdated {
+        meal_id: i64,
+    },
+    DishIngredientAdded {
+        dish_id: i64,
+        ingredient_id: i64,
+        weight: i64,
+    },
+    DishFinished {
+        dish_id: i64,
+    },
+    /// The ingredient's nutritional properties were fetched from Open Food Facts
+    IngredientPropertiesFetched {
+        ingredient_id: i64,
+    },
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum DomainEventType {
+    MealCreated,
+    MealUpdated,
+    DishIngredientAdded,
+    DishFinished,
+    IngredientPropertiesFetched,
+}
+
+/// An event type that doesn't exist, answered with a bad request since a misspelled type would
+/// silently filter out every event.
+#[derive(Error, Debug)]
+#[error("Unknown event type \"{0}\"")]
+pub struct UnknownEventType(pub String);
+
+impl DomainEventType {
+    pub fn name(self) -> &'static str {
+        match self {
+            DomainEventType::MealCreated => "MealCreated",
+            DomainEventType::MealUpdated => "MealUpdated",
+            DomainEventType::DishIngredientAdded => "DishIngredientAdded",
+            DomainEventType::DishFinished => "DishFinished",
+            DomainEventType::IngredientPropertiesFetched => "IngredientPropertiesFetched",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<DomainEventType> {
+        [
+            DomainEventType::MealCreated,
+            DomainEventType::MealUpdated,
+            DomainEventType::DishIngredientAdded,
+            DomainEventType::DishFinished,
+            DomainEventType::IngredientPropertiesFetched,
+        ]
+        .into_iter()
+        .find(|t| t.name() == name)
+    }
+}
+
+impl DomainEvent {
+    pub fn event_type(&self) -> DomainEventType {
+        match self {
+            DomainEvent::MealCreated { .. } => DomainEventType::MealCreated,
+            DomainEvent::MealUpdated { .. } => DomainEventType::MealUpdated,
+            DomainEvent::DishIngredientAdded { .. } => DomainEventType::DishIngredientAdded,
+            DomainEvent::DishFinished { .. } => DomainEventType::DishFinished,
+            DomainEvent::IngredientPropertiesFetched { .. } => {
+                DomainEventType::IngredientPropertiesFetched
+            }
+        }
+    }
+
+    pub fn meal_id(&self) -> Option<i64> {
+        match self {
+            DomainEvent::MealCreated { meal_id } | DomainEvent::MealUpdated { meal_id } => {
+                Some(*meal_id)
+            }
+            _ => None,
+        }
+    }
+
+    pub fn dish_id(&self) -> Option<i64> {
+        match self {
+            DomainEvent::DishIngredientAdded { dish_id, .. }
+            | DomainEvent::DishFinished { dish_id } => Some(*dish_id),
+            _ => None,
+        }
+    }
+
+    pub fn ingredient_id(&self) -> Option<i64> {
+        match self {
+            DomainEvent::DishIngredientAdded { ingredient_id, .. }
+            | DomainEvent::IngredientPropertiesFetched { ingredient_id } => Some(*ingredient_id),
+            _ => None,
+        }
+    }
+}
+
+/// An event, with who caused it.
+#[derive(Serialize, JsonSchema, Clone, Debug)]
+pub struct EventEnvelope {
+    #[serde(skip)]
+    pub household_id: i64,
+    /// The household member that caused the event
+    pub member_id: i64,
+    pub date: i64,
+    pub event: DomainEvent,
+}
+
+/// Broadcasts events to every open event stream. Cloning it gives another handle to the same
+/// channel.
+#[derive(Clone, Debug)]
+pub struct EventBus(broadcast::Sender<Arc<EventEnvelope>>);
+
+impl EventBus {
+    pub fn new() -> Self {
+        EventBus(broadcast::channel(EVENT_BUFFER_SIZE).0)
+    }
+
+    /// Publishes `event` to the streams of `user`'s household. Should only be called once the
+    /// change is committed.
+    pub fn publish(&self, user: &AuthUser, event: DomainEvent) {
+        // Sending only fails when no one is listening
+        let _ = self.0.send(Arc::new(EventEnvelope {
+            household_id: user.household_id,
+            member_id: user.user_id,
+            date: chrono::Utc::now().timestamp_millis(),
+            event,
+        }));
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<Arc<EventEnvelope>> {
+        self.0.subscribe()
+    }
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        EventBus::new()
+    }
+}
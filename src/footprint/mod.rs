@@ -0,0 +1,13 @@
+use aide::axum::{routing::get, ApiRouter};
+
+use crate::state::AppState;
+
+mod month;
+mod ranking;
+
+pub fn route(state: AppState) -> ApiRouter {
+    ApiRouter::new()
+        .api_route("/month", get(month::get_monthly_footprint))
+        .api_route("/ranking", get(ranking::get_footprint_ranking))
+        .with_state(state)
+}
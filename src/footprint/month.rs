@@ -0,0 +1,117 @@
+use std::collections::BTreeMap;
+
+use axum::extract::{Query, State};
+use chrono::{Datelike, NaiveDate, TimeZone};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use sqlx::prelude::FromRow;
+
+use crate::{
+    carbon::{Footprint, FootprintTable},
+    nutrition::WeightedComponent,
+    period::utc_offset,
+    server::{ServerResponse, ServerResponseResult},
+    session::AuthUser,
+    state::AppState,
+};
+
+#[derive(Deserialize, JsonSchema)]
+pub struct GetMonthlyFootprintQueryParams {
+    from: i64,
+    to: i64,
+    /// Offset of the household's timezone, used to decide which month each meal belongs to
+    utc_offset_minutes: Option<i32>,
+    /// Household member whose meals should be summarized. Defaults to the current user
+    member_id: Option<i64>,
+}
+
+#[derive(Serialize, JsonSchema)]
+pub struct MonthFootprint {
+    /// Month in the `YYYY-MM` format
+    month: String,
+    /// Grams eaten
+    weight: i64,
+    footprint: Footprint,
+}
+
+#[derive(FromRow)]
+struct EatenComponent {
+    date: i64,
+    dish_id: Option<i64>,
+    ingredient_id: Option<i64>,
+    weight: i64,
+}
+
+impl From<&EatenComponent> for WeightedComponent {
+    fn from(value: &EatenComponent) -> Self {
+        WeightedComponent {
+            dish_id: value.dish_id,
+            ingredient_id: value.ingredient_id,
+            weight: value.weight,
+        }
+    }
+}
+
+/// Carbon footprint of what a household member ate, month by month.
+pub async fn get_monthly_footprint(
+    State(AppState { connection, .. }): State<AppState>,
+    user: AuthUser,
+    Query(GetMonthlyFootprintQueryParams {
+        from,
+        to,
+        utc_offset_minutes,
+        member_id,
+    }): Query<GetMonthlyFootprintQueryParams>,
+) -> ServerResponseResult<Vec<MonthFootprint>> {
+    let offset = utc_offset(utc_offset_minutes)?;
+    let member_id = user.resolve_member(&connection, member_id).await?;
+
+    let components = sqlx::query_as::<_, EatenComponent>(
+        r#"
+        SELECT Meal.eat_date AS date, dish_id, NULL AS ingredient_id, weight
+        FROM Meal JOIN MealDish ON MealDish.meal_id = Meal.id
+        WHERE Meal.user_id = ?3 AND Meal.deletion_date IS NULL AND Meal.eat_date >= ?1 AND Meal.eat_date <= ?2
+        UNION ALL
+        SELECT Meal.eat_date AS date, NULL AS dish_id, ingredient_id, weight
+        FROM Meal JOIN MealIngredient ON MealIngredient.meal_id = Meal.id
+        WHERE Meal.user_id = ?3 AND Meal.deletion_date IS NULL AND Meal.eat_date >= ?1 AND Meal.eat_date <= ?2;"#,
+    )
+    .bind(from)
+    .bind(to)
+    .bind(member_id)
+    .fetch_all(&connection)
+    .await?;
+
+    let table = FootprintTable::fetch(
+        &connection,
+        &components
+            .iter()
+            .map(WeightedComponent::from)
+            .collect::<Vec<WeightedComponent>>(),
+    )
+    .await?;
+
+    let mut months = BTreeMap::<NaiveDate, (i64, Footprint)>::new();
+    for component in &components {
+        let Some(date) = offset.timestamp_millis_opt(component.date).single() else {
+            continue;
+        };
+        let Some(month) = date.date_naive().with_day(1) else {
+            continue;
+        };
+        let (weight, footprint) = months.entry(month).or_default();
+        *weight += component.weight;
+        *footprint += table.component_footprint(&WeightedComponent::from(component));
+    }
+
+    let months = months
+        .into_iter()
+        .map(|(month, (weight, footprint))| MonthFootprint {
+            month: month.format("%Y-%m").to_string(),
+            weight,
+            footprint,
+        })
+        .collect();
+
+    Ok(ServerResponse::success(months).json())
+}
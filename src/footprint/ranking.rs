@@ -0,0 +1,133 @@
+use std::collections::HashMap;
+
+use axum::extract::{Query, State};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use sqlx::prelude::FromRow;
+
+use crate::{
+    carbon::FootprintTable,
+    nutrition::WeightedComponent,
+    server::{ServerResponse, ServerResponseResult},
+    session::AuthUser,
+    state::AppState,
+};
+
+#[derive(Deserialize, JsonSchema)]
+pub struct GetFootprintRankingQueryParams {
+    from: i64,
+    to: i64,
+    /// Household member whose meals should be ranked. Defaults to the current user
+    member_id: Option<i64>,
+    /// How many ingredients to return. Defaults to all of them
+    limit: Option<usize>,
+}
+
+#[derive(Serialize, JsonSchema)]
+pub struct RankedIngredient {
+    ingredient_id: i64,
+    name: String,
+    /// Grams eaten, including in dishes
+    weight: f64,
+    /// Kilograms of CO2 equivalent
+    co2e: f64,
+    /// Share of the footprint of everything eaten, from 0 to 1
+    share: f64,
+}
+
+#[derive(FromRow)]
+struct EatenComponent {
+    dish_id: Option<i64>,
+    ingredient_id: Option<i64>,
+    weight: i64,
+}
+
+impl From<&EatenComponent> for WeightedComponent {
+    fn from(value: &EatenComponent) -> Self {
+        WeightedComponent {
+            dish_id: value.dish_id,
+            ingredient_id: value.ingredient_id,
+            weight: value.weight,
+        }
+    }
+}
+
+/// Ingredients a household member ate, ranked by how much they added to their carbon footprint.
+/// Ingredients without a footprint are left out.
+pub async fn get_footprint_ranking(
+    State(AppState { connection, .. }): State<AppState>,
+    user @ AuthUser { household_id, .. }: AuthUser,
+    Query(GetFootprintRankingQueryParams {
+        from,
+        to,
+        member_id,
+        limit,
+    }): Query<GetFootprintRankingQueryParams>,
+) -> ServerResponseResult<Vec<RankedIngredient>> {
+    let member_id = user.resolve_member(&connection, member_id).await?;
+
+    let components = sqlx::query_as::<_, EatenComponent>(
+        r#"
+        SELECT dish_id, NULL AS ingredient_id, weight
+        FROM Meal JOIN MealDish ON MealDish.meal_id = Meal.id
+        WHERE Meal.user_id = ?3 AND Meal.deletion_date IS NULL AND Meal.eat_date >= ?1 AND Meal.eat_date <= ?2
+        UNION ALL
+        SELECT NULL AS dish_id, ingredient_id, weight
+        FROM Meal JOIN MealIngredient ON MealIngredient.meal_id = Meal.id
+        WHERE Meal.user_id = ?3 AND Meal.deletion_date IS NULL AND Meal.eat_date >= ?1 AND Meal.eat_date <= ?2;"#,
+    )
+    .bind(from)
+    .bind(to)
+    .bind(member_id)
+    .fetch_all(&connection)
+    .await?
+    .iter()
+    .map(WeightedComponent::from)
+    .collect::<Vec<WeightedComponent>>();
+
+    let table = FootprintTable::fetch(&connection, &components).await?;
+
+    // Grams eaten and kilograms of CO2 equivalent of each ingredient with a footprint
+    let mut ingredients = HashMap::<i64, (f64, f64)>::new();
+    for component in &components {
+        for (ingredient_id, grams) in table.ingredient_weights(component) {
+            let footprint = table.ingredient_footprint(ingredient_id, grams);
+            if footprint.unknown_weight > 0.0 {
+                continue;
+            }
+            let (weight, co2e) = ingredients.entry(ingredient_id).or_default();
+            *weight += grams;
+            *co2e += footprint.co2e;
+        }
+    }
+    let total = ingredients.values().map(|(_, co2e)| co2e).sum::<f64>();
+
+    let names = sqlx::query!(
+        "SELECT id, name FROM Ingredient WHERE household_id = ?",
+        household_id
+    )
+    .fetch_all(&connection)
+    .await?
+    .into_iter()
+    .map(|ingredient| (ingredient.id, ingredient.name))
+    .collect::<HashMap<i64, String>>();
+
+    let mut ranking = ingredients
+        .into_iter()
+        .filter_map(|(ingredient_id, (weight, co2e))| {
+            Some(RankedIngredient {
+                ingredient_id,
+                name: names.get(&ingredient_id)?.clone(),
+                weight,
+                co2e,
+                share: if total > 0.0 { co2e / total } else { 0.0 },
+            })
+        })
+        .collect::<Vec<RankedIngredient>>();
+    ranking.sort_by(|a, b| b.co2e.total_cmp(&a.co2e));
+    if let Some(limit) = limit {
+        ranking.truncate(limit);
+    }
+
+    Ok(ServerResponse::success(ranking).json())
+}
@@ -0,0 +1,32 @@
+use axum::extract::{Path, State};
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+use crate::{
+    nutrition::Nutrient,
+    server::{ServerResponse, ServerResponseResult},
+    session::AuthUser,
+    state::AppState,
+};
+
+#[derive(Deserialize, JsonSchema)]
+pub struct NutrientPath {
+    nutrient: Nutrient,
+}
+
+pub async fn delete_goal(
+    State(AppState { connection, .. }): State<AppState>,
+    AuthUser { user_id, .. }: AuthUser,
+    Path(NutrientPath { nutrient }): Path<NutrientPath>,
+) -> ServerResponseResult<bool> {
+    let nutrient = nutrient.name();
+    sqlx::query!(
+        "DELETE FROM NutritionGoal WHERE nutrient = ? AND user_id = ?",
+        nutrient,
+        user_id
+    )
+    .execute(&connection)
+    .await?;
+
+    Ok(ServerResponse::success(true).json())
+}
@@ -0,0 +1,42 @@
+use axum::extract::{Query, State};
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+use crate::{
+    models::NutritionGoal,
+    server::{ServerResponse, ServerResponseResult},
+    session::AuthUser,
+    state::AppState,
+};
+
+#[derive(Deserialize, JsonSchema)]
+pub struct ListGoalsQueryParams {
+    /// Household member whose goals should be listed. Defaults to the current user
+    member_id: Option<i64>,
+}
+
+pub async fn list_goals(
+    State(AppState { connection, .. }): State<AppState>,
+    user: AuthUser,
+    Query(ListGoalsQueryParams { member_id }): Query<ListGoalsQueryParams>,
+) -> ServerResponseResult<Vec<NutritionGoal>> {
+    let member_id = user.resolve_member(&connection, member_id).await?;
+
+    let goals = sqlx::query_as!(
+        NutritionGoal,
+        r#"
+        SELECT
+            nutrient,
+            creation_date,
+            daily_min,
+            daily_max
+        FROM NutritionGoal
+        WHERE user_id = ?
+        ORDER BY nutrient;"#,
+        member_id
+    )
+    .fetch_all(&connection)
+    .await?;
+
+    Ok(ServerResponse::success(goals).json())
+}
@@ -0,0 +1,17 @@
+use aide::axum::{
+    routing::{delete, post},
+    ApiRouter,
+};
+
+use crate::state::AppState;
+
+mod delete;
+mod get;
+mod post;
+
+pub fn route(state: AppState) -> ApiRouter {
+    ApiRouter::new()
+        .api_route("/", post(post::post_goal).get(get::list_goals))
+        .api_route("/:nutrient", delete(delete::delete_goal))
+        .with_state(state)
+}
@@ -0,0 +1,64 @@
+use axum::{extract::State, Json};
+use schemars::JsonSchema;
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::{
+    models::NutritionGoal,
+    nutrition::Nutrient,
+    server::{ServerResponse, ServerResponseResult},
+    session::AuthUser,
+    state::AppState,
+};
+
+#[derive(Deserialize, JsonSchema)]
+pub struct PostGoalBody {
+    nutrient: Nutrient,
+    /// Minimum amount that should be eaten in a day, in grams (or kcal)
+    daily_min: Option<f64>,
+    /// Maximum amount that should be eaten in a day, in grams (or kcal)
+    daily_max: Option<f64>,
+}
+
+#[derive(Error, Debug)]
+enum PostGoalError {
+    #[error("Either daily_min or daily_max must be provided")]
+    NoLimitProvided,
+    #[error("daily_min ({0}) must not be larger than daily_max ({1})")]
+    MinLargerThanMax(f64, f64),
+}
+
+pub async fn post_goal(
+    State(AppState { connection, .. }): State<AppState>,
+    AuthUser { user_id, .. }: AuthUser,
+    Json(PostGoalBody {
+        nutrient,
+        daily_min,
+        daily_max,
+    }): Json<PostGoalBody>,
+) -> ServerResponseResult<NutritionGoal> {
+    match (daily_min, daily_max) {
+        (None, None) => return Err(PostGoalError::NoLimitProvided)?,
+        (Some(min), Some(max)) if min > max => {
+            return Err(PostGoalError::MinLargerThanMax(min, max))?
+        }
+        _ => {}
+    }
+
+    let nutrient = nutrient.name();
+    let goal = sqlx::query_as!(
+        NutritionGoal,
+        r#"
+        INSERT OR REPLACE INTO NutritionGoal (user_id, nutrient, daily_min, daily_max)
+        VALUES (?, ?, ?, ?)
+        RETURNING nutrient, creation_date, daily_min, daily_max;"#,
+        user_id,
+        nutrient,
+        daily_min,
+        daily_max
+    )
+    .fetch_one(&connection)
+    .await?;
+
+    Ok(ServerResponse::success(goal).json())
+}
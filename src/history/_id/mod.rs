@@ -0,0 +1,11 @@
+use aide::axum::{routing::post, ApiRouter};
+
+use crate::state::AppState;
+
+mod revert;
+
+pub fn route(state: AppState) -> ApiRouter {
+    ApiRouter::new()
+        .api_route("/revert", post(revert::post_revert))
+        .with_state(state)
+}
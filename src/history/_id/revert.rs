@@ -0,0 +1,451 @@
+use axum::extract::{Path, State};
+use schemars::JsonSchema;
+use serde::{Deserialize, Deserializer, Serialize};
+use sqlx::SqliteConnection;
+use thiserror::Error;
+
+use crate::{
+    audit::{record_revert, snapshot, AuditEntity},
+    component::{find_missing, insert_components, split_components, Component, PostComponent},
+    events::DomainEvent,
+    server::{ServerResponse, ServerResponseResult},
+    session::AuthUser,
+    state::AppState,
+};
+
+#[derive(Deserialize, JsonSchema)]
+pub struct EntryId {
+    entry_id: i64,
+}
+
+#[derive(Serialize, JsonSchema)]
+pub struct PostRevertResult {
+    /// What the entity looks like now
+    state: serde_json::Value,
+    /// Dishes that were deleted since, and were left out
+    skipped_dish_ids: Vec<i64>,
+    /// Ingredients that were deleted since, and were left out
+    skipped_ingredient_ids: Vec<i64>,
+}
+
+#[derive(Error, Debug)]
+enum PostRevertError {
+    #[error("Could not find history entry with id \"{0}\"")]
+    EntryNotFound(i64),
+    #[error("History entry \"{0}\" deleted the entity, so there is nothing to revert to")]
+    NothingToRevertTo(i64),
+}
+
+#[derive(Deserialize)]
+struct IngredientState {
+    /// Missing from entries recorded before the uuid was kept in the history
+    uuid: Option<String>,
+    name: String,
+    /// Missing from entries recorded before ingredients could be variants, and `Some(None)` for
+    /// ingredients that are not
+    #[serde(default, deserialize_with = "present")]
+    generic_id: Option<Option<i64>>,
+    /// Missing from entries recorded before footprints could be entered by hand
+    #[serde(default, deserialize_with = "present")]
+    co2e_per_kg: Option<Option<f64>>,
+    /// Missing from entries recorded before ingredients had aliases
+    aliases: Option<Vec<String>>,
+    /// Missing from entries recorded before ingredients had names per language
+    names: Option<Vec<IngredientNameState>>,
+    /// Missing from entries recorded before ingredients had categories and tags
+    categories: Option<Vec<IngredientCategoryState>>,
+    tags: Option<Vec<String>>,
+    /// Missing from entries recorded before ingredients had trackables
+    trackables: Option<Vec<IngredientTrackableState>>,
+    /// Missing from entries recorded before properties were kept in the history, and `Some(None)`
+    /// for ingredients without properties
+    #[serde(default, deserialize_with = "present")]
+    properties: Option<Option<IngredientPropertiesState>>,
+}
+
+#[derive(Deserialize)]
+struct IngredientPropertiesState {
+    product_code: String,
+    /// The nutrients and everything else in the properties are computed from it
+    open_food_facts_json: serde_json::Value,
+}
+
+#[derive(Deserialize)]
+struct IngredientCategoryState {
+    category_id: i64,
+    source: String,
+}
+
+#[derive(Deserialize)]
+struct IngredientTrackableState {
+    trackable_id: i64,
+    amount_100g: f64,
+}
+
+/// Tells a field set to `null` apart from a missing one, which is left as `None` by `default`.
+fn present<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    T::deserialize(deserializer).map(Some)
+}
+
+#[derive(Deserialize)]
+struct IngredientNameState {
+    language: String,
+    name: String,
+    source: String,
+}
+
+#[derive(Deserialize)]
+struct DishState {
+    /// Missing from entries recorded before the uuid was kept in the history
+    uuid: Option<String>,
+    name: Option<String>,
+    prep_date: Option<i64>,
+    total_weight: i64,
+    is_finished: i64,
+    ingredients: Vec<PostComponent>,
+}
+
+#[derive(Deserialize)]
+struct MealState {
+    /// Missing from entries recorded before the uuid was kept in the history
+    uuid: Option<String>,
+    eat_date: Option<i64>,
+    duration: Option<i64>,
+    description: Option<String>,
+    dishes: Vec<PostComponent>,
+    ingredients: Vec<PostComponent>,
+}
+
+/// Replaces the components linked to `owner_id` in `table`, leaving out the ones that were
+/// deleted since. Returns the ids left out.
+async fn replace_components(
+    connection: &mut SqliteConnection,
+    household_id: i64,
+    (table, owner_column, owner_id): (&str, &str, i64),
+    (component_table, component_column): (&str, &str),
+    components: Vec<Component>,
+) -> anyhow::Result<Vec<i64>> {
+    let missing =
+        find_missing(&mut *connection, household_id, component_table, &components).await?;
+    let components = components
+        .into_iter()
+        .filter(|component| !missing.contains(&component.id))
+        .collect::<Vec<Component>>();
+
+    sqlx::query(&format!("DELETE FROM {table} WHERE {owner_column} = ?"))
+        .bind(owner_id)
+        .execute(&mut *connection)
+        .await?;
+    insert_components(
+        connection,
+        table,
+        owner_column,
+        owner_id,
+        component_column,
+        &components,
+    )
+    .await?;
+
+    Ok(missing)
+}
+
+/// Restores an ingredient, dish or meal to what it looked like right after the change in the
+/// history entry, creating it again or taking it out of the trash if it was deleted since. Dishes
+/// and ingredients that were deleted since are left out of its components. The revert is recorded
+/// in the history too.
+pub async fn post_revert(
+    State(AppState {
+        connection, events, ..
+    }): State<AppState>,
+    user: AuthUser,
+    Path(EntryId { entry_id }): Path<EntryId>,
+) -> ServerResponseResult<PostRevertResult> {
+    let entry = sqlx::query!(
+        r#"
+        SELECT entity, entity_id, after
+        FROM AuditEntry
+        WHERE id = ? AND household_id = ? AND (entity != 'Meal' OR user_id = ?)"#,
+        entry_id,
+        user.household_id,
+        user.user_id
+    )
+    .fetch_optional(&connection)
+    .await?
+    .ok_or(PostRevertError::EntryNotFound(entry_id))?;
+    let entity =
+        AuditEntity::from_name(&entry.entity).ok_or(PostRevertError::EntryNotFound(entry_id))?;
+    let after = entry
+        .after
+        .ok_or(PostRevertError::NothingToRevertTo(entry_id))?;
+    let id = entry.entity_id;
+
+    let mut transaction = connection.begin().await?;
+
+    let before = snapshot(&mut *transaction, entity, id).await?;
+    let existed = before.is_some();
+
+    let mut skipped_dish_ids = vec![];
+    let mut skipped_ingredient_ids = vec![];
+    match entity {
+        AuditEntity::Ingredient => {
+            let state: IngredientState = serde_json::from_str(&after)?;
+            sqlx::query!(
+                r#"
+                INSERT INTO Ingredient (id, uuid, household_id, name) VALUES (?, ?, ?, ?)
+                ON CONFLICT (id) DO UPDATE SET name = excluded.name"#,
+                id,
+                state.uuid,
+                user.household_id,
+                state.name
+            )
+            .execute(&mut *transaction)
+            .await?;
+            if let Some(aliases) = state.aliases {
+                sqlx::query!("DELETE FROM IngredientAlias WHERE ingredient_id = ?", id)
+                    .execute(&mut *transaction)
+                    .await?;
+                for alias in aliases {
+                    sqlx::query!(
+                        "INSERT INTO IngredientAlias (ingredient_id, name) VALUES (?, ?)",
+                        id,
+                        alias
+                    )
+                    .execute(&mut *transaction)
+                    .await?;
+                }
+            }
+            if let Some(generic_id) = state.generic_id {
+                // The generic ingredient may have been deleted or become a variant since, and the
+                // ingredient may have variants of its own now
+                sqlx::query!(
+                    r#"
+                    UPDATE Ingredient
+                    SET generic_id = (
+                        SELECT id FROM Ingredient
+                        WHERE id = ?1 AND id != ?2 AND household_id = ?3 AND generic_id IS NULL
+                            AND NOT EXISTS (SELECT 1 FROM Ingredient WHERE generic_id = ?2)
+                    )
+                    WHERE id = ?2"#,
+                    generic_id,
+                    id,
+                    user.household_id
+                )
+                .execute(&mut *transaction)
+                .await?;
+            }
+            if let Some(co2e_per_kg) = state.co2e_per_kg {
+                sqlx::query!(
+                    "UPDATE Ingredient SET co2e_per_kg = ? WHERE id = ?",
+                    co2e_per_kg,
+                    id
+                )
+                .execute(&mut *transaction)
+                .await?;
+            }
+            if let Some(names) = state.names {
+                sqlx::query!("DELETE FROM IngredientName WHERE ingredient_id = ?", id)
+                    .execute(&mut *transaction)
+                    .await?;
+                for name in names {
+                    sqlx::query!(
+                        r#"
+                        INSERT INTO IngredientName (ingredient_id, language, name, source)
+                        VALUES (?, ?, ?, ?)"#,
+                        id,
+                        name.language,
+                        name.name,
+                        name.source
+                    )
+                    .execute(&mut *transaction)
+                    .await?;
+                }
+            }
+            if let Some(categories) = state.categories {
+                sqlx::query!("DELETE FROM IngredientCategory WHERE ingredient_id = ?", id)
+                    .execute(&mut *transaction)
+                    .await?;
+                // Categories deleted since are left out
+                for category in categories {
+                    sqlx::query!(
+                        r#"
+                        INSERT INTO IngredientCategory (ingredient_id, category_id, source)
+                        SELECT ?, id, ? FROM Category WHERE id = ? AND household_id = ?"#,
+                        id,
+                        category.source,
+                        category.category_id,
+                        user.household_id
+                    )
+                    .execute(&mut *transaction)
+                    .await?;
+                }
+            }
+            if let Some(tags) = state.tags {
+                sqlx::query!("DELETE FROM IngredientTag WHERE ingredient_id = ?", id)
+                    .execute(&mut *transaction)
+                    .await?;
+                for tag in tags {
+                    sqlx::query!(
+                        "INSERT INTO IngredientTag (ingredient_id, name) VALUES (?, ?)",
+                        id,
+                        tag
+                    )
+                    .execute(&mut *transaction)
+                    .await?;
+                }
+            }
+            if let Some(trackables) = state.trackables {
+                sqlx::query!(
+                    "DELETE FROM IngredientTrackable WHERE ingredient_id = ?",
+                    id
+                )
+                .execute(&mut *transaction)
+                .await?;
+                // Trackables deleted since are left out
+                for trackable in trackables {
+                    sqlx::query!(
+                        r#"
+                        INSERT INTO IngredientTrackable (ingredient_id, trackable_id, amount_100g)
+                        SELECT ?, id, ? FROM Trackable WHERE id = ? AND household_id = ?"#,
+                        id,
+                        trackable.amount_100g,
+                        trackable.trackable_id,
+                        user.household_id
+                    )
+                    .execute(&mut *transaction)
+                    .await?;
+                }
+            }
+            match state.properties {
+                Some(Some(properties)) => {
+                    let open_food_facts_json = properties.open_food_facts_json.to_string();
+                    sqlx::query!(
+                        r#"
+                        INSERT INTO IngredientProperties (ingredient_id, product_code, open_food_facts_json)
+                        VALUES (?, ?, ?)
+                        ON CONFLICT (ingredient_id) DO UPDATE SET
+                            product_code = excluded.product_code,
+                            open_food_facts_json = excluded.open_food_facts_json"#,
+                        id,
+                        properties.product_code,
+                        open_food_facts_json
+                    )
+                    .execute(&mut *transaction)
+                    .await?;
+                }
+                Some(None) => {
+                    sqlx::query!(
+                        "DELETE FROM IngredientProperties WHERE ingredient_id = ?",
+                        id
+                    )
+                    .execute(&mut *transaction)
+                    .await?;
+                }
+                None => {}
+            }
+        }
+        AuditEntity::Dish => {
+            let state: DishState = serde_json::from_str(&after)?;
+            sqlx::query!(
+                r#"
+                INSERT INTO Dish (id, uuid, household_id, name, prep_date, total_weight, is_finished)
+                VALUES (?, ?, ?, ?, ?, ?, ?)
+                ON CONFLICT (id) DO UPDATE SET
+                    name = excluded.name,
+                    prep_date = excluded.prep_date,
+                    total_weight = excluded.total_weight,
+                    is_finished = excluded.is_finished,
+                    deletion_date = NULL"#,
+                id,
+                state.uuid,
+                user.household_id,
+                state.name,
+                state.prep_date,
+                state.total_weight,
+                state.is_finished
+            )
+            .execute(&mut *transaction)
+            .await?;
+
+            let (_, ingredients) = split_components(state.ingredients)?;
+            skipped_ingredient_ids = replace_components(
+                &mut transaction,
+                user.household_id,
+                ("DishIngredient", "dish_id", id),
+                ("Ingredient", "ingredient_id"),
+                ingredients,
+            )
+            .await?;
+        }
+        AuditEntity::Meal => {
+            let state: MealState = serde_json::from_str(&after)?;
+            sqlx::query!(
+                r#"
+                INSERT INTO Meal (id, uuid, user_id, eat_date, duration, description)
+                VALUES (?, ?, ?, ?, ?, ?)
+                ON CONFLICT (id) DO UPDATE SET
+                    eat_date = excluded.eat_date,
+                    duration = excluded.duration,
+                    description = excluded.description,
+                    deletion_date = NULL"#,
+                id,
+                state.uuid,
+                user.user_id,
+                state.eat_date,
+                state.duration,
+                state.description
+            )
+            .execute(&mut *transaction)
+            .await?;
+
+            let (dishes, _) = split_components(state.dishes)?;
+            let (_, ingredients) = split_components(state.ingredients)?;
+            skipped_dish_ids = replace_components(
+                &mut transaction,
+                user.household_id,
+                ("MealDish", "meal_id", id),
+                ("Dish", "dish_id"),
+                dishes,
+            )
+            .await?;
+            skipped_ingredient_ids = replace_components(
+                &mut transaction,
+                user.household_id,
+                ("MealIngredient", "meal_id", id),
+                ("Ingredient", "ingredient_id"),
+                ingredients,
+            )
+            .await?;
+        }
+    }
+
+    record_revert(&mut *transaction, &user, entity, id, before).await?;
+    let state = snapshot(&mut *transaction, entity, id)
+        .await?
+        .map(|state| serde_json::from_str(&state))
+        .transpose()?
+        .unwrap_or_default();
+
+    transaction.commit().await?;
+
+    if entity == AuditEntity::Meal {
+        events.publish(
+            &user,
+            if existed {
+                DomainEvent::MealUpdated { meal_id: id }
+            } else {
+                DomainEvent::MealCreated { meal_id: id }
+            },
+        );
+    }
+
+    Ok(ServerResponse::success(PostRevertResult {
+        state,
+        skipped_dish_ids,
+        skipped_ingredient_ids,
+    })
+    .json())
+}
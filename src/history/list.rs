@@ -0,0 +1,84 @@
+use axum::extract::{Query, State};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    audit::AuditEntity,
+    server::{ServerResponse, ServerResponseResult},
+    session::AuthUser,
+    state::AppState,
+};
+
+#[derive(Deserialize, JsonSchema)]
+pub struct ListHistoryQueryParams {
+    entity: AuditEntity,
+    entity_id: i64,
+}
+
+#[derive(Serialize, JsonSchema)]
+pub struct HistoryEntry {
+    id: i64,
+    creation_date: i64,
+    /// The household member that made the change
+    member_id: i64,
+    member_name: String,
+    /// One of `Create`, `Update`, `Delete` or `Revert`
+    operation: String,
+    /// The entity before the change. Not set for creations
+    before: Option<serde_json::Value>,
+    /// The entity after the change. Not set for deletions
+    after: Option<serde_json::Value>,
+}
+
+/// Every change made to an ingredient, dish or meal, oldest first. Meals only have the history
+/// of their owner's changes.
+pub async fn list_history(
+    State(AppState { connection, .. }): State<AppState>,
+    AuthUser {
+        user_id,
+        household_id,
+    }: AuthUser,
+    Query(ListHistoryQueryParams { entity, entity_id }): Query<ListHistoryQueryParams>,
+) -> ServerResponseResult<Vec<HistoryEntry>> {
+    let entity = entity.name();
+    let rows = sqlx::query!(
+        r#"
+        SELECT
+            AuditEntry.id,
+            AuditEntry.creation_date,
+            AuditEntry.user_id,
+            COALESCE(User.display_name, User.username) AS "member_name!: String",
+            AuditEntry.operation,
+            AuditEntry.before,
+            AuditEntry.after
+        FROM AuditEntry
+            JOIN User ON User.id = AuditEntry.user_id
+        WHERE AuditEntry.household_id = ?
+            AND AuditEntry.entity = ?
+            AND AuditEntry.entity_id = ?
+            AND (AuditEntry.entity != 'Meal' OR AuditEntry.user_id = ?)
+        ORDER BY AuditEntry.id;"#,
+        household_id,
+        entity,
+        entity_id,
+        user_id
+    )
+    .fetch_all(&connection)
+    .await?;
+
+    let parse = |json: Option<String>| json.and_then(|json| serde_json::from_str(&json).ok());
+    let history = rows
+        .into_iter()
+        .map(|row| HistoryEntry {
+            id: row.id,
+            creation_date: row.creation_date,
+            member_id: row.user_id,
+            member_name: row.member_name,
+            operation: row.operation,
+            before: parse(row.before),
+            after: parse(row.after),
+        })
+        .collect();
+
+    Ok(ServerResponse::success(history).json())
+}
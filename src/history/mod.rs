@@ -0,0 +1,13 @@
+use aide::axum::{routing::get, ApiRouter};
+
+use crate::state::AppState;
+
+mod _id;
+mod list;
+
+pub fn route(state: AppState) -> ApiRouter {
+    ApiRouter::new()
+        .api_route("/", get(list::list_history))
+        .nest_api_service("/:entry_id", _id::route(state.clone()))
+        .with_state(state)
+}
@@ -0,0 +1,46 @@
+use axum::extract::State;
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use crate::{
+    models::{Household, User},
+    server::{ServerResponse, ServerResponseResult},
+    session::{AuthScope, AuthUser},
+    state::AppState,
+};
+
+#[derive(Serialize, JsonSchema)]
+pub struct GetHouseholdResponse {
+    household: Household,
+    members: Vec<User>,
+}
+
+pub async fn get_household(
+    State(AppState { connection, .. }): State<AppState>,
+    AuthUser { household_id, .. }: AuthUser,
+    scope: AuthScope,
+) -> ServerResponseResult<GetHouseholdResponse> {
+    let (mut household, members) = futures::try_join!(
+        sqlx::query_as!(
+            Household,
+            "SELECT id, creation_date, name, invite_code FROM Household WHERE id = ?",
+            household_id
+        )
+        .fetch_one(&connection),
+        sqlx::query_as!(
+            User,
+            r#"
+            SELECT id, creation_date, username, display_name, household_id
+            FROM User
+            WHERE household_id = ?
+            ORDER BY creation_date;"#,
+            household_id
+        )
+        .fetch_all(&connection),
+    )?;
+    if !scope.can_invite() {
+        household.invite_code = None;
+    }
+
+    Ok(ServerResponse::success(GetHouseholdResponse { household, members }).json())
+}
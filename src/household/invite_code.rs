@@ -0,0 +1,28 @@
+use axum::extract::State;
+
+use crate::{
+    models::Household,
+    server::{ServerResponse, ServerResponseResult},
+    session::{generate_token, AuthUser},
+    state::AppState,
+};
+
+/// Replaces the household's invite code, so the old one can no longer be used to join it.
+pub async fn post_invite_code(
+    State(AppState { connection, .. }): State<AppState>,
+    AuthUser { household_id, .. }: AuthUser,
+) -> ServerResponseResult<Household> {
+    let invite_code = generate_token();
+    let household = sqlx::query_as!(
+        Household,
+        r#"
+        UPDATE Household SET invite_code = ? WHERE id = ?
+        RETURNING id as "id!", creation_date, name, invite_code;"#,
+        invite_code,
+        household_id
+    )
+    .fetch_one(&connection)
+    .await?;
+
+    Ok(ServerResponse::success(household).json())
+}
@@ -0,0 +1,21 @@
+use aide::axum::{
+    routing::{get, post},
+    ApiRouter,
+};
+
+use crate::state::AppState;
+
+mod get;
+mod invite_code;
+mod post;
+mod profile;
+mod summary;
+
+pub fn route(state: AppState) -> ApiRouter {
+    ApiRouter::new()
+        .api_route("/", get(get::get_household).post(post::post_household))
+        .api_route("/invite_code", post(invite_code::post_invite_code))
+        .api_route("/profile", post(profile::post_profile))
+        .api_route("/summary", get(summary::get_summary))
+        .with_state(state)
+}
@@ -0,0 +1,46 @@
+use axum::{extract::State, Json};
+use schemars::JsonSchema;
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::{
+    models::Household,
+    server::{ServerResponse, ServerResponseResult},
+    session::AuthUser,
+    state::AppState,
+};
+
+#[derive(Deserialize, JsonSchema)]
+pub struct PostHouseholdBody {
+    name: String,
+}
+
+#[derive(Error, Debug)]
+enum PostHouseholdError {
+    #[error("The household name should not be empty")]
+    NameIsEmpty,
+}
+
+pub async fn post_household(
+    State(AppState { connection, .. }): State<AppState>,
+    AuthUser { household_id, .. }: AuthUser,
+    Json(PostHouseholdBody { name }): Json<PostHouseholdBody>,
+) -> ServerResponseResult<Household> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(PostHouseholdError::NameIsEmpty)?;
+    }
+
+    let household = sqlx::query_as!(
+        Household,
+        r#"
+        UPDATE Household SET name = ? WHERE id = ?
+        RETURNING id as "id!", creation_date, name, invite_code;"#,
+        name,
+        household_id
+    )
+    .fetch_one(&connection)
+    .await?;
+
+    Ok(ServerResponse::success(household).json())
+}
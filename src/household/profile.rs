@@ -0,0 +1,39 @@
+use axum::{extract::State, Json};
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+use crate::{
+    models::User,
+    server::{ServerResponse, ServerResponseResult},
+    session::AuthUser,
+    state::AppState,
+};
+
+#[derive(Deserialize, JsonSchema)]
+pub struct PostProfileBody {
+    /// Name shown to the other members of the household. Empty or missing to use the username
+    display_name: Option<String>,
+}
+
+pub async fn post_profile(
+    State(AppState { connection, .. }): State<AppState>,
+    AuthUser { user_id, .. }: AuthUser,
+    Json(PostProfileBody { display_name }): Json<PostProfileBody>,
+) -> ServerResponseResult<User> {
+    let display_name = display_name
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty());
+
+    let user = sqlx::query_as!(
+        User,
+        r#"
+        UPDATE User SET display_name = ? WHERE id = ?
+        RETURNING id as "id!", creation_date, username, display_name, household_id;"#,
+        display_name,
+        user_id
+    )
+    .fetch_one(&connection)
+    .await?;
+
+    Ok(ServerResponse::success(user).json())
+}
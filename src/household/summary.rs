@@ -0,0 +1,267 @@
+use std::collections::{BTreeMap, HashMap};
+
+use axum::extract::{Query, State};
+use chrono::TimeZone;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use sqlx::prelude::FromRow;
+
+use crate::{
+    dose::{fetch_taken_doses, DoseTable},
+    models::{NutritionGoal, User},
+    nutrition::{
+        GoalStatus, Nutrient, NutrientTable, Nutrients, TrackableAmount, TrackableAmounts,
+        TrackableTable, Trackables, WeightedComponent,
+    },
+    period::utc_offset,
+    server::{ServerResponse, ServerResponseResult},
+    session::AuthUser,
+    state::AppState,
+};
+
+#[derive(Deserialize, JsonSchema)]
+pub struct GetSummaryQueryParams {
+    from: i64,
+    to: i64,
+    /// Offset of the household's timezone, used to decide which day each meal belongs to
+    utc_offset_minutes: Option<i32>,
+}
+
+#[derive(Serialize, JsonSchema)]
+pub struct MemberGoalProgress {
+    nutrient: Nutrient,
+    daily_min: Option<f64>,
+    daily_max: Option<f64>,
+    eaten: f64,
+    status: GoalStatus,
+}
+
+/// How much of a trackable a member had in a day, compared to its daily limit
+#[derive(Serialize, JsonSchema)]
+pub struct MemberTrackableProgress {
+    trackable_id: i64,
+    name: String,
+    unit: String,
+    daily_limit: Option<f64>,
+    eaten: f64,
+    status: GoalStatus,
+}
+
+impl From<TrackableAmount> for MemberTrackableProgress {
+    fn from(value: TrackableAmount) -> Self {
+        MemberTrackableProgress {
+            status: GoalStatus::from_limits(None, value.daily_limit, value.amount),
+            trackable_id: value.trackable_id,
+            name: value.name,
+            unit: value.unit,
+            daily_limit: value.daily_limit,
+            eaten: value.amount,
+        }
+    }
+}
+
+#[derive(Serialize, JsonSchema)]
+pub struct MemberDay {
+    /// Day in the `YYYY-MM-DD` format
+    date: String,
+    eaten: Nutrients,
+    goals: Vec<MemberGoalProgress>,
+    /// Every trackable of the household
+    trackables: Vec<MemberTrackableProgress>,
+}
+
+#[derive(Serialize, JsonSchema)]
+pub struct MemberSummary {
+    member: User,
+    /// Sum of the nutrients eaten by the member in the whole period
+    total: Nutrients,
+    /// Sum of the trackables had by the member in the whole period
+    total_trackables: Vec<TrackableAmount>,
+    days: Vec<MemberDay>,
+}
+
+#[derive(FromRow)]
+struct MemberComponent {
+    user_id: i64,
+    date: i64,
+    dish_id: Option<i64>,
+    ingredient_id: Option<i64>,
+    weight: i64,
+}
+
+impl From<&MemberComponent> for WeightedComponent {
+    fn from(value: &MemberComponent) -> Self {
+        WeightedComponent {
+            dish_id: value.dish_id,
+            ingredient_id: value.ingredient_id,
+            weight: value.weight,
+        }
+    }
+}
+
+fn goal_progress(goal: &NutritionGoal, eaten: &Nutrients) -> Option<MemberGoalProgress> {
+    let nutrient = Nutrient::from_name(&goal.nutrient)?;
+    let eaten = eaten.get(nutrient);
+    Some(MemberGoalProgress {
+        nutrient,
+        daily_min: goal.daily_min,
+        daily_max: goal.daily_max,
+        eaten,
+        status: GoalStatus::new(goal, eaten),
+    })
+}
+
+/// Nutrients and trackables eaten by every member of the household, day by day, compared to each
+/// member's own goals and the trackables' daily limits. Supplement doses count in the day they
+/// were taken.
+pub async fn get_summary(
+    State(AppState { connection, .. }): State<AppState>,
+    AuthUser { household_id, .. }: AuthUser,
+    Query(GetSummaryQueryParams {
+        from,
+        to,
+        utc_offset_minutes,
+    }): Query<GetSummaryQueryParams>,
+) -> ServerResponseResult<Vec<MemberSummary>> {
+    let offset = utc_offset(utc_offset_minutes)?;
+
+    let members = sqlx::query_as!(
+        User,
+        r#"
+        SELECT id, creation_date, username, display_name, household_id
+        FROM User
+        WHERE household_id = ?
+        ORDER BY creation_date;"#,
+        household_id
+    )
+    .fetch_all(&connection)
+    .await?;
+
+    let components = sqlx::query_as::<_, MemberComponent>(
+        r#"
+        SELECT Meal.user_id, Meal.eat_date AS date, dish_id, NULL AS ingredient_id, weight
+        FROM Meal
+            JOIN User ON User.id = Meal.user_id
+            JOIN MealDish ON MealDish.meal_id = Meal.id
+        WHERE User.household_id = ?3 AND Meal.deletion_date IS NULL AND Meal.eat_date >= ?1 AND Meal.eat_date <= ?2
+        UNION ALL
+        SELECT Meal.user_id, Meal.eat_date AS date, NULL AS dish_id, ingredient_id, weight
+        FROM Meal
+            JOIN User ON User.id = Meal.user_id
+            JOIN MealIngredient ON MealIngredient.meal_id = Meal.id
+        WHERE User.household_id = ?3 AND Meal.deletion_date IS NULL AND Meal.eat_date >= ?1 AND Meal.eat_date <= ?2;"#,
+    )
+    .bind(from)
+    .bind(to)
+    .bind(household_id)
+    .fetch_all(&connection)
+    .await?;
+
+    let mut goals = sqlx::query!(
+        r#"
+        SELECT NutritionGoal.user_id AS "user_id!", nutrient, NutritionGoal.creation_date, daily_min, daily_max
+        FROM NutritionGoal JOIN User ON User.id = NutritionGoal.user_id
+        WHERE User.household_id = ?;"#,
+        household_id
+    )
+    .fetch_all(&connection)
+    .await?
+    .into_iter()
+    .fold(
+        HashMap::<i64, Vec<NutritionGoal>>::new(),
+        |mut dict, goal| {
+            dict.entry(goal.user_id).or_default().push(NutritionGoal {
+                nutrient: goal.nutrient,
+                creation_date: goal.creation_date,
+                daily_min: goal.daily_min,
+                daily_max: goal.daily_max,
+            });
+            dict
+        },
+    );
+
+    let doses = fetch_taken_doses(&connection, household_id, None, from, to).await?;
+
+    let weighted_components = components
+        .iter()
+        .map(WeightedComponent::from)
+        .collect::<Vec<WeightedComponent>>();
+    let supplement_ids = doses
+        .iter()
+        .map(|dose| dose.supplement_id)
+        .collect::<Vec<i64>>();
+    let (table, trackable_table, dose_table, trackables) = futures::try_join!(
+        NutrientTable::fetch(&connection, &weighted_components),
+        TrackableTable::fetch(&connection, &weighted_components),
+        DoseTable::fetch(&connection, &supplement_ids),
+        Trackables::fetch(&connection, household_id),
+    )?;
+
+    let mut member_days = HashMap::<i64, BTreeMap<String, (Nutrients, TrackableAmounts)>>::new();
+    for component in &components {
+        let Some(date) = offset.timestamp_millis_opt(component.date).single() else {
+            continue;
+        };
+        let (nutrients, amounts) = member_days
+            .entry(component.user_id)
+            .or_default()
+            .entry(date.format("%Y-%m-%d").to_string())
+            .or_default();
+        let component = WeightedComponent::from(component);
+        *nutrients += table.component_nutrients(&component);
+        trackable_table.add_component(amounts, &component);
+    }
+    for dose in &doses {
+        let Some(date) = offset.timestamp_millis_opt(dose.date).single() else {
+            continue;
+        };
+        let (nutrients, amounts) = member_days
+            .entry(dose.user_id)
+            .or_default()
+            .entry(date.format("%Y-%m-%d").to_string())
+            .or_default();
+        *nutrients += dose_table.dose_nutrients(dose.supplement_id, dose.doses);
+        dose_table.add_dose(amounts, dose.supplement_id, dose.doses);
+    }
+
+    let summaries = members
+        .into_iter()
+        .map(|member| {
+            let goals = goals.remove(&member.id).unwrap_or_default();
+            let mut total = Nutrients::default();
+            let mut total_amounts = TrackableAmounts::new();
+            let days = member_days
+                .remove(&member.id)
+                .unwrap_or_default()
+                .into_iter()
+                .map(|(date, (eaten, amounts))| {
+                    total += eaten;
+                    for (trackable_id, amount) in &amounts {
+                        *total_amounts.entry(*trackable_id).or_default() += amount;
+                    }
+                    MemberDay {
+                        date,
+                        eaten,
+                        goals: goals
+                            .iter()
+                            .filter_map(|goal| goal_progress(goal, &eaten))
+                            .collect(),
+                        trackables: trackables
+                            .amounts(&amounts)
+                            .into_iter()
+                            .map(MemberTrackableProgress::from)
+                            .collect(),
+                    }
+                })
+                .collect();
+            MemberSummary {
+                member,
+                total,
+                total_trackables: trackables.amounts(&total_amounts),
+                days,
+            }
+        })
+        .collect();
+
+    Ok(ServerResponse::success(summaries).json())
+}
@@ -0,0 +1,265 @@
+use std::collections::BTreeMap;
+
+use axum::{
+    body::{boxed, Body, Full},
+    extract::State,
+    http::{header, HeaderName, HeaderValue, Method, Request, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use sha2::{Digest, Sha256};
+use sqlx::{Pool, Sqlite};
+use thiserror::Error;
+
+use crate::{server::ServerResponse, session::AuthUser, state::AppState};
+
+/// How long responses are kept for, in milliseconds. Retries after that run the request again
+pub const IDEMPOTENCY_KEY_RETENTION: i64 = 24 * 60 * 60 * 1000;
+
+/// Longest key accepted
+const MAX_KEY_LENGTH: usize = 255;
+
+const IDEMPOTENCY_KEY: &str = "idempotency-key";
+
+/// Set on responses that were replayed instead of running the request again
+const IDEMPOTENCY_REPLAYED: HeaderName = HeaderName::from_static("idempotency-replayed");
+
+/// Response headers stored with the body, so replays carry them too
+const STORED_HEADERS: [HeaderName; 3] = [header::CONTENT_TYPE, header::ETAG, header::LOCATION];
+
+#[derive(Error, Debug)]
+pub enum IdempotencyError {
+    #[error("The Idempotency-Key header must have between 1 and {MAX_KEY_LENGTH} characters")]
+    InvalidKey,
+    #[error("A request with the same Idempotency-Key is still being processed")]
+    InProgress,
+    #[error("The Idempotency-Key was already used for a different request")]
+    KeyReused,
+    #[error(transparent)]
+    Database(#[from] sqlx::Error),
+    #[error(transparent)]
+    Internal(#[from] anyhow::Error),
+}
+
+impl IntoResponse for IdempotencyError {
+    fn into_response(self) -> Response {
+        let status_code = match self {
+            IdempotencyError::InvalidKey => StatusCode::BAD_REQUEST,
+            IdempotencyError::InProgress => StatusCode::CONFLICT,
+            IdempotencyError::KeyReused => StatusCode::UNPROCESSABLE_ENTITY,
+            IdempotencyError::Database(_) | IdempotencyError::Internal(_) => {
+                StatusCode::INTERNAL_SERVER_ERROR
+            }
+        };
+        ServerResponse::error_code(self, status_code).into_response()
+    }
+}
+
+struct StoredRequest {
+    method: String,
+    path: String,
+    request_hash: String,
+    status_code: Option<i64>,
+    response_headers: Option<String>,
+    response_body: Option<String>,
+}
+
+/// A claimed key whose response was not stored yet. Dropping it releases the key, so that it can
+/// be retried if the request fails in a way that never gets back to the middleware, such as a
+/// panicking handler.
+struct PendingKey {
+    connection: Pool<Sqlite>,
+    id: i64,
+    done: bool,
+}
+
+impl PendingKey {
+    /// Releases the key right away, for requests that failed.
+    async fn release(mut self) -> Result<(), IdempotencyError> {
+        self.done = true;
+        sqlx::query!("DELETE FROM IdempotencyKey WHERE id = ?", self.id)
+            .execute(&self.connection)
+            .await?;
+        Ok(())
+    }
+}
+
+impl Drop for PendingKey {
+    fn drop(&mut self) {
+        if self.done {
+            return;
+        }
+        let connection = self.connection.clone();
+        let id = self.id;
+        tokio::spawn(async move {
+            let result = sqlx::query!(
+                "DELETE FROM IdempotencyKey WHERE id = ? AND status_code IS NULL",
+                id
+            )
+            .execute(&connection)
+            .await;
+            if let Err(error) = result {
+                println!("Could not release idempotency key {id}: {error}");
+            }
+        });
+    }
+}
+
+fn replay(status_code: i64, headers: Option<String>, body: String) -> Response {
+    let status_code = u16::try_from(status_code)
+        .ok()
+        .and_then(|code| StatusCode::from_u16(code).ok())
+        .unwrap_or(StatusCode::OK);
+    let headers = headers
+        .and_then(|headers| serde_json::from_str::<BTreeMap<String, String>>(&headers).ok())
+        .unwrap_or_default();
+
+    let mut response = (status_code, body).into_response();
+    let response_headers = response.headers_mut();
+    // Replaced by the stored content type, if there is one
+    response_headers.insert(
+        header::CONTENT_TYPE,
+        HeaderValue::from_static("application/json"),
+    );
+    for (name, value) in headers {
+        if let (Ok(name), Ok(value)) = (HeaderName::try_from(name), HeaderValue::try_from(value)) {
+            response_headers.insert(name, value);
+        }
+    }
+    response_headers.insert(IDEMPOTENCY_REPLAYED, HeaderValue::from_static("true"));
+    response
+}
+
+async fn run_idempotent(
+    connection: &Pool<Sqlite>,
+    user_id: i64,
+    key: String,
+    request: Request<Body>,
+    next: Next<Body>,
+) -> Result<Response, IdempotencyError> {
+    if key.is_empty() || key.len() > MAX_KEY_LENGTH {
+        return Err(IdempotencyError::InvalidKey);
+    }
+
+    let now = chrono::Utc::now().timestamp_millis();
+    let expired_before = now - IDEMPOTENCY_KEY_RETENTION;
+    sqlx::query!(
+        "DELETE FROM IdempotencyKey WHERE creation_date < ?",
+        expired_before
+    )
+    .execute(connection)
+    .await?;
+
+    let (parts, body) = request.into_parts();
+    let body = hyper::body::to_bytes(body)
+        .await
+        .map_err(anyhow::Error::from)?;
+    let method = parts.method.to_string();
+    let path = parts.uri.path().to_string();
+    let request_hash = format!("{:x}", Sha256::digest(&body));
+
+    // Claiming the key before running the request makes concurrent retries wait for the first one
+    let claimed_id = sqlx::query_scalar!(
+        r#"
+        INSERT INTO IdempotencyKey (user_id, key, method, path, request_hash)
+        VALUES (?, ?, ?, ?, ?)
+        ON CONFLICT DO NOTHING
+        RETURNING id;"#,
+        user_id,
+        key,
+        method,
+        path,
+        request_hash
+    )
+    .fetch_optional(connection)
+    .await?;
+
+    let Some(claimed_id) = claimed_id else {
+        let stored = sqlx::query_as!(
+            StoredRequest,
+            r#"
+            SELECT method, path, request_hash, status_code, response_headers, response_body
+            FROM IdempotencyKey
+            WHERE user_id = ? AND key = ?"#,
+            user_id,
+            key
+        )
+        .fetch_one(connection)
+        .await?;
+
+        if stored.method != method || stored.path != path || stored.request_hash != request_hash {
+            return Err(IdempotencyError::KeyReused);
+        }
+        return match (stored.status_code, stored.response_body) {
+            (Some(status_code), Some(body)) => {
+                Ok(replay(status_code, stored.response_headers, body))
+            }
+            _ => Err(IdempotencyError::InProgress),
+        };
+    };
+
+    let mut pending = PendingKey {
+        connection: connection.clone(),
+        id: claimed_id,
+        done: false,
+    };
+
+    let response = next.run(Request::from_parts(parts, Body::from(body))).await;
+
+    // Failed requests can be retried with the same key
+    if !response.status().is_success() {
+        pending.release().await?;
+        return Ok(response);
+    }
+
+    let (parts, body) = response.into_parts();
+    let body = hyper::body::to_bytes(body)
+        .await
+        .map_err(anyhow::Error::from)?;
+    let status_code = i64::from(parts.status.as_u16());
+    let response_headers = STORED_HEADERS
+        .iter()
+        .filter_map(|name| Some((name.as_str(), parts.headers.get(name)?.to_str().ok()?)))
+        .collect::<BTreeMap<&str, &str>>();
+    let response_headers = serde_json::to_string(&response_headers).map_err(anyhow::Error::from)?;
+    let response_body = String::from_utf8_lossy(&body).to_string();
+    sqlx::query!(
+        r#"
+        UPDATE IdempotencyKey SET status_code = ?, response_headers = ?, response_body = ?
+        WHERE id = ?"#,
+        status_code,
+        response_headers,
+        response_body,
+        claimed_id
+    )
+    .execute(connection)
+    .await?;
+    pending.done = true;
+
+    Ok(Response::from_parts(parts, boxed(Full::from(body))))
+}
+
+/// Makes `POST` requests sent with an `Idempotency-Key` header safe to retry. The first successful
+/// response for a key is stored for `IDEMPOTENCY_KEY_RETENTION`, and retries of the same request
+/// with that key get it back instead of creating everything again. Keys are scoped to the user.
+pub async fn idempotency_middleware(
+    State(AppState { connection, .. }): State<AppState>,
+    request: Request<Body>,
+    next: Next<Body>,
+) -> Response {
+    let key = request
+        .headers()
+        .get(IDEMPOTENCY_KEY)
+        .and_then(|value| value.to_str().ok())
+        .map(|value| value.trim().to_string());
+    let user = request.extensions().get::<AuthUser>().copied();
+
+    match (key, user) {
+        (Some(key), Some(AuthUser { user_id, .. })) if request.method() == Method::POST => {
+            run_idempotent(&connection, user_id, key, request, next)
+                .await
+                .unwrap_or_else(|error| error.into_response())
+        }
+        _ => next.run(request).await,
+    }
+}
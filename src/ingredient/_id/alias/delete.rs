@@ -0,0 +1,59 @@
+use axum::extract::{Path, State};
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+use crate::{
+    audit::{record_change, snapshot, AuditEntity},
+    etag::IfMatch,
+    server::{ServerResponse, ServerResponseResult},
+    session::AuthUser,
+    state::AppState,
+};
+
+#[derive(Deserialize, JsonSchema)]
+pub struct IngredientId {
+    ingredient_id: i64,
+}
+
+#[derive(Deserialize, JsonSchema)]
+pub struct AliasId {
+    alias_id: i64,
+}
+
+pub async fn delete_alias(
+    State(AppState { connection, .. }): State<AppState>,
+    user: AuthUser,
+    if_match: IfMatch,
+    Path(IngredientId { ingredient_id }): Path<IngredientId>,
+    Path(AliasId { alias_id }): Path<AliasId>,
+) -> ServerResponseResult<bool> {
+    user.check_ingredient(&connection, ingredient_id).await?;
+    let mut transaction = connection.begin().await?;
+    if_match
+        .check(&mut transaction, "Ingredient", ingredient_id)
+        .await?;
+    let before = snapshot(&mut *transaction, AuditEntity::Ingredient, ingredient_id).await?;
+
+    let deleted = sqlx::query!(
+        "DELETE FROM IngredientAlias WHERE id = ? AND ingredient_id = ?",
+        alias_id,
+        ingredient_id
+    )
+    .execute(&mut *transaction)
+    .await?
+    .rows_affected()
+        > 0;
+
+    record_change(
+        &mut *transaction,
+        &user,
+        AuditEntity::Ingredient,
+        ingredient_id,
+        before,
+    )
+    .await?;
+
+    transaction.commit().await?;
+
+    Ok(ServerResponse::success(deleted).json())
+}
@@ -0,0 +1,16 @@
+use aide::axum::{
+    routing::{delete, post},
+    ApiRouter,
+};
+
+use crate::state::AppState;
+
+mod delete;
+mod post;
+
+pub fn route(state: AppState) -> ApiRouter {
+    ApiRouter::new()
+        .api_route("/", post(post::post_alias))
+        .api_route("/:alias_id", delete(delete::delete_alias))
+        .with_state(state)
+}
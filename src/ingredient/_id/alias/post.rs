@@ -0,0 +1,94 @@
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+use schemars::JsonSchema;
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::{
+    audit::{record_change, snapshot, AuditEntity},
+    etag::IfMatch,
+    models::IngredientAlias,
+    server::{ServerResponse, ServerResponseResult},
+    session::AuthUser,
+    state::AppState,
+};
+
+#[derive(Deserialize, JsonSchema)]
+pub struct IngredientId {
+    ingredient_id: i64,
+}
+
+#[derive(Deserialize, JsonSchema)]
+pub struct PostAliasBody {
+    name: String,
+}
+
+#[derive(Error, Debug)]
+enum PostAliasError {
+    #[error("The alias should not be empty")]
+    EmptyName,
+    #[error("The alias \"{0}\" is already the name of the ingredient")]
+    SameAsName(String),
+    #[error("The ingredient already has the alias \"{0}\"")]
+    AliasExists(String),
+}
+
+/// Adds another name the ingredient can be found by. Aliases ignore case.
+pub async fn post_alias(
+    State(AppState { connection, .. }): State<AppState>,
+    user: AuthUser,
+    if_match: IfMatch,
+    Path(IngredientId { ingredient_id }): Path<IngredientId>,
+    Json(PostAliasBody { name }): Json<PostAliasBody>,
+) -> ServerResponseResult<IngredientAlias> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(PostAliasError::EmptyName)?;
+    }
+    user.check_ingredient(&connection, ingredient_id).await?;
+    let mut transaction = connection.begin().await?;
+    if_match
+        .check(&mut transaction, "Ingredient", ingredient_id)
+        .await?;
+
+    let is_name = sqlx::query_scalar!(
+        r#"SELECT name = ? COLLATE NOCASE AS "is_name!: bool" FROM Ingredient WHERE id = ?"#,
+        name,
+        ingredient_id
+    )
+    .fetch_one(&mut *transaction)
+    .await?;
+    if is_name {
+        return Err(PostAliasError::SameAsName(name.to_string()))?;
+    }
+
+    let before = snapshot(&mut *transaction, AuditEntity::Ingredient, ingredient_id).await?;
+
+    let alias = sqlx::query_as!(
+        IngredientAlias,
+        r#"
+        INSERT INTO IngredientAlias (ingredient_id, name) VALUES (?, ?)
+        ON CONFLICT DO NOTHING
+        RETURNING id, creation_date, ingredient_id, name;"#,
+        ingredient_id,
+        name
+    )
+    .fetch_optional(&mut *transaction)
+    .await?
+    .ok_or(PostAliasError::AliasExists(name.to_string()))?;
+
+    record_change(
+        &mut *transaction,
+        &user,
+        AuditEntity::Ingredient,
+        ingredient_id,
+        before,
+    )
+    .await?;
+
+    transaction.commit().await?;
+
+    Ok(ServerResponse::success(alias).json())
+}
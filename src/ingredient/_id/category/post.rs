@@ -0,0 +1,66 @@
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+use crate::{
+    audit::{record_change, snapshot, AuditEntity},
+    etag::IfMatch,
+    server::{ServerResponse, ServerResponseResult},
+    session::AuthUser,
+    state::AppState,
+};
+
+#[derive(Deserialize, JsonSchema)]
+pub struct IngredientId {
+    ingredient_id: i64,
+}
+
+#[derive(Deserialize, JsonSchema)]
+pub struct PostIngredientCategoryBody {
+    category_id: i64,
+}
+
+/// Puts the ingredient in the category. A category it got from Open Food Facts is kept from then
+/// on, as if it had been chosen by hand.
+pub async fn post_ingredient_category(
+    State(AppState { connection, .. }): State<AppState>,
+    user: AuthUser,
+    if_match: IfMatch,
+    Path(IngredientId { ingredient_id }): Path<IngredientId>,
+    Json(PostIngredientCategoryBody { category_id }): Json<PostIngredientCategoryBody>,
+) -> ServerResponseResult<bool> {
+    user.check_ingredient(&connection, ingredient_id).await?;
+    user.check_category(&connection, category_id).await?;
+    let mut transaction = connection.begin().await?;
+    if_match
+        .check(&mut transaction, "Ingredient", ingredient_id)
+        .await?;
+    let before = snapshot(&mut *transaction, AuditEntity::Ingredient, ingredient_id).await?;
+
+    sqlx::query!(
+        r#"
+        INSERT INTO IngredientCategory (ingredient_id, category_id, source)
+        VALUES (?, ?, 'Manual')
+        ON CONFLICT DO UPDATE SET source = excluded.source"#,
+        ingredient_id,
+        category_id
+    )
+    .execute(&mut *transaction)
+    .await?;
+
+    record_change(
+        &mut *transaction,
+        &user,
+        AuditEntity::Ingredient,
+        ingredient_id,
+        before,
+    )
+    .await?;
+
+    transaction.commit().await?;
+
+    Ok(ServerResponse::success(true).json())
+}
@@ -0,0 +1,140 @@
+use axum::extract::{Path, Query, State};
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+use crate::{
+    audit::{record_change, snapshot, AuditEntity},
+    dependency::{resolve_dependents, DeletePolicyKind, DeletePolicyParams},
+    etag::IfMatch,
+    events::DomainEvent,
+    server::{ServerResponse, ServerResponseResult},
+    session::AuthUser,
+    state::AppState,
+};
+
+#[derive(Deserialize, JsonSchema)]
+pub struct IngredientId {
+    ingredient_id: i64,
+}
+
+/// Deletes the ingredient, its properties, aliases, names, categories, tags and trackable amounts.
+/// Its variants stand on their own from then on. By default, it's rejected while dishes, meals,
+/// plans, templates or purchases use the ingredient.
+pub async fn delete_ingredient(
+    State(AppState {
+        connection, events, ..
+    }): State<AppState>,
+    user: AuthUser,
+    if_match: IfMatch,
+    Path(IngredientId { ingredient_id }): Path<IngredientId>,
+    Query(params): Query<DeletePolicyParams>,
+) -> ServerResponseResult<bool> {
+    user.check_ingredient(&connection, ingredient_id).await?;
+    let mut transaction = connection.begin().await?;
+    if_match
+        .check(&mut transaction, "Ingredient", ingredient_id)
+        .await?;
+    let policy = params.policy(DeletePolicyKind::Restrict)?;
+    policy
+        .check(
+            &mut transaction,
+            &user,
+            AuditEntity::Ingredient,
+            ingredient_id,
+        )
+        .await?;
+    let before = snapshot(&mut *transaction, AuditEntity::Ingredient, ingredient_id).await?;
+    let variant_ids = sqlx::query_scalar!(
+        "SELECT id FROM Ingredient WHERE generic_id = ?",
+        ingredient_id
+    )
+    .fetch_all(&mut *transaction)
+    .await?;
+    let mut variants_before = vec![];
+    for variant_id in &variant_ids {
+        variants_before
+            .push(snapshot(&mut *transaction, AuditEntity::Ingredient, *variant_id).await?);
+    }
+
+    let changed_meal_ids = resolve_dependents(
+        &mut transaction,
+        &user,
+        AuditEntity::Ingredient,
+        ingredient_id,
+        &policy,
+    )
+    .await?;
+
+    sqlx::query!(
+        "DELETE FROM IngredientProperties WHERE ingredient_id = ?",
+        ingredient_id
+    )
+    .execute(&mut *transaction)
+    .await?;
+    sqlx::query!(
+        "DELETE FROM IngredientAlias WHERE ingredient_id = ?",
+        ingredient_id
+    )
+    .execute(&mut *transaction)
+    .await?;
+    sqlx::query!(
+        "DELETE FROM IngredientName WHERE ingredient_id = ?",
+        ingredient_id
+    )
+    .execute(&mut *transaction)
+    .await?;
+    sqlx::query!(
+        "DELETE FROM IngredientCategory WHERE ingredient_id = ?",
+        ingredient_id
+    )
+    .execute(&mut *transaction)
+    .await?;
+    sqlx::query!(
+        "DELETE FROM IngredientTag WHERE ingredient_id = ?",
+        ingredient_id
+    )
+    .execute(&mut *transaction)
+    .await?;
+    sqlx::query!(
+        "DELETE FROM IngredientTrackable WHERE ingredient_id = ?",
+        ingredient_id
+    )
+    .execute(&mut *transaction)
+    .await?;
+    sqlx::query!(
+        "UPDATE Ingredient SET generic_id = NULL WHERE generic_id = ?",
+        ingredient_id
+    )
+    .execute(&mut *transaction)
+    .await?;
+    sqlx::query!("DELETE FROM Ingredient WHERE id = ?", ingredient_id)
+        .execute(&mut *transaction)
+        .await?;
+
+    record_change(
+        &mut *transaction,
+        &user,
+        AuditEntity::Ingredient,
+        ingredient_id,
+        before,
+    )
+    .await?;
+    for (variant_id, before) in variant_ids.into_iter().zip(variants_before) {
+        record_change(
+            &mut *transaction,
+            &user,
+            AuditEntity::Ingredient,
+            variant_id,
+            before,
+        )
+        .await?;
+    }
+
+    transaction.commit().await?;
+
+    for meal_id in changed_meal_ids {
+        events.publish(&user, DomainEvent::MealUpdated { meal_id });
+    }
+
+    Ok(ServerResponse::success(true).json())
+}
@@ -0,0 +1,74 @@
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+use schemars::JsonSchema;
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::{
+    audit::{record_change, snapshot, AuditEntity},
+    etag::IfMatch,
+    server::{ServerResponse, ServerResponseResult},
+    session::AuthUser,
+    state::AppState,
+};
+
+#[derive(Deserialize, JsonSchema)]
+pub struct IngredientId {
+    ingredient_id: i64,
+}
+
+#[derive(Deserialize, JsonSchema)]
+pub struct PostFootprintBody {
+    /// Kilograms of CO2 equivalent emitted per kilogram of the ingredient. Not provided to use the
+    /// Eco-Score data of its properties again
+    co2e_per_kg: Option<f64>,
+}
+
+#[derive(Error, Debug)]
+enum PostFootprintError {
+    #[error("The footprint must not be negative, but was {0}")]
+    NegativeFootprint(f64),
+}
+
+/// Enters the carbon footprint of an ingredient by hand, for ingredients without Eco-Score data
+/// or whose data is off. It takes precedence over the one of the properties.
+pub async fn post_footprint(
+    State(AppState { connection, .. }): State<AppState>,
+    user: AuthUser,
+    if_match: IfMatch,
+    Path(IngredientId { ingredient_id }): Path<IngredientId>,
+    Json(PostFootprintBody { co2e_per_kg }): Json<PostFootprintBody>,
+) -> ServerResponseResult<Option<f64>> {
+    if let Some(co2e_per_kg) = co2e_per_kg.filter(|co2e_per_kg| *co2e_per_kg < 0.0) {
+        return Err(PostFootprintError::NegativeFootprint(co2e_per_kg))?;
+    }
+    user.check_ingredient(&connection, ingredient_id).await?;
+    let mut transaction = connection.begin().await?;
+    if_match
+        .check(&mut transaction, "Ingredient", ingredient_id)
+        .await?;
+    let before = snapshot(&mut *transaction, AuditEntity::Ingredient, ingredient_id).await?;
+
+    sqlx::query!(
+        "UPDATE Ingredient SET co2e_per_kg = ? WHERE id = ?",
+        co2e_per_kg,
+        ingredient_id
+    )
+    .execute(&mut *transaction)
+    .await?;
+
+    record_change(
+        &mut *transaction,
+        &user,
+        AuditEntity::Ingredient,
+        ingredient_id,
+        before,
+    )
+    .await?;
+
+    transaction.commit().await?;
+
+    Ok(ServerResponse::success(co2e_per_kg).json())
+}
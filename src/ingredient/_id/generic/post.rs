@@ -0,0 +1,99 @@
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+use schemars::JsonSchema;
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::{
+    audit::{record_change, snapshot, AuditEntity},
+    etag::IfMatch,
+    server::{ServerResponse, ServerResponseResult},
+    session::AuthUser,
+    state::AppState,
+};
+
+#[derive(Deserialize, JsonSchema)]
+pub struct IngredientId {
+    ingredient_id: i64,
+}
+
+#[derive(Deserialize, JsonSchema)]
+pub struct PostGenericBody {
+    /// Generic ingredient this one is a variant of. Not provided to make it stand on its own
+    generic_id: Option<i64>,
+}
+
+#[derive(Error, Debug)]
+enum PostGenericError {
+    #[error("An ingredient can't be a variant of itself")]
+    GenericIsItself,
+    #[error("Ingredient {0} is a variant of another ingredient, so it can't have variants")]
+    GenericIsVariant(i64),
+    #[error("Ingredient {0} has variants, so it can't be a variant of another ingredient")]
+    HasVariants(i64),
+}
+
+/// Makes the ingredient a variant of a generic one, such as a brand of milk of "Milk". Variants
+/// use the nutrients of their generic ingredient for the ones their own properties lack, and are
+/// counted as it in usage and category reports.
+pub async fn post_generic(
+    State(AppState { connection, .. }): State<AppState>,
+    user: AuthUser,
+    if_match: IfMatch,
+    Path(IngredientId { ingredient_id }): Path<IngredientId>,
+    Json(PostGenericBody { generic_id }): Json<PostGenericBody>,
+) -> ServerResponseResult<Option<i64>> {
+    user.check_ingredient(&connection, ingredient_id).await?;
+    if let Some(generic_id) = generic_id {
+        if generic_id == ingredient_id {
+            return Err(PostGenericError::GenericIsItself)?;
+        }
+        user.check_ingredient(&connection, generic_id).await?;
+        let generic_is_variant = sqlx::query_scalar!(
+            r#"SELECT generic_id IS NOT NULL AS "is_variant!: bool" FROM Ingredient WHERE id = ?"#,
+            generic_id
+        )
+        .fetch_one(&connection)
+        .await?;
+        if generic_is_variant {
+            return Err(PostGenericError::GenericIsVariant(generic_id))?;
+        }
+        let has_variants = sqlx::query_scalar!(
+            r#"SELECT EXISTS (SELECT 1 FROM Ingredient WHERE generic_id = ?) AS "has_variants!: bool""#,
+            ingredient_id
+        )
+        .fetch_one(&connection)
+        .await?;
+        if has_variants {
+            return Err(PostGenericError::HasVariants(ingredient_id))?;
+        }
+    }
+    let mut transaction = connection.begin().await?;
+    if_match
+        .check(&mut transaction, "Ingredient", ingredient_id)
+        .await?;
+    let before = snapshot(&mut *transaction, AuditEntity::Ingredient, ingredient_id).await?;
+
+    sqlx::query!(
+        "UPDATE Ingredient SET generic_id = ? WHERE id = ?",
+        generic_id,
+        ingredient_id
+    )
+    .execute(&mut *transaction)
+    .await?;
+
+    record_change(
+        &mut *transaction,
+        &user,
+        AuditEntity::Ingredient,
+        ingredient_id,
+        before,
+    )
+    .await?;
+
+    transaction.commit().await?;
+
+    Ok(ServerResponse::success(generic_id).json())
+}
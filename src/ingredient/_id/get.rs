@@ -11,7 +11,13 @@ use thiserror::Error;
 
 use crate::{
     app_error::InternalServerError,
-    server::{ServerResponse, ServerResponseResult},
+    carbon::fetch_ingredient_footprints,
+    diet::{fetch_ingredient_diets, parse_tags, DietInfo},
+    etag::{Versioned, VersionedResult},
+    language::AcceptLanguage,
+    models::{Category, Ingredient, IngredientAlias, IngredientName},
+    server::ServerResponse,
+    session::AuthUser,
     state::AppState,
 };
 
@@ -31,6 +37,14 @@ pub struct IngredientResult {
     id: i64,
     name: String,
     creation_date: i64,
+    version: i64,
+    /// Generic ingredient this one is a variant of
+    generic_id: Option<i64>,
+    /// Kilograms of CO2 equivalent emitted per kilogram of the ingredient, if entered by hand
+    co2e_per_kg: Option<f64>,
+    /// Name in the first language of the `Accept-Language` header the ingredient has a name in,
+    /// or `name` otherwise
+    display_name: String,
 }
 
 #[derive(JsonSchema, Serialize)]
@@ -41,17 +55,77 @@ pub struct IngredientPropertiesResult {
     carbohydrates_100g: Option<i64>,
     proteins_100g: Option<i64>,
     fat_100g: Option<i64>,
+    /// Open Food Facts tags of the allergens in the product, such as `en:gluten`
+    allergens_tags: Vec<String>,
+    /// Allergens the product may contain traces of
+    traces_tags: Vec<String>,
+    /// Such as `en:vegan` or `en:palm-oil-free`
+    ingredients_analysis_tags: Vec<String>,
+    /// From `a` (best) to `e` (worst)
+    nutriscore_grade: Option<String>,
+    /// From 1 (unprocessed) to 4 (ultra-processed)
+    nova_group: Option<i64>,
+    /// Open Food Facts tags of the additives in the product, such as `en:e330`
+    additives_tags: Vec<String>,
+    /// Kilograms of CO2 equivalent emitted per kilogram of the product, from its Eco-Score data
+    co2e_per_kg: Option<f64>,
+}
+
+struct DatabaseIngredientProperties {
+    kcal_100g: Option<i64>,
+    product_name: Option<String>,
+    product_code: String,
+    carbohydrates_100g: Option<i64>,
+    proteins_100g: Option<i64>,
+    fat_100g: Option<i64>,
+    allergens_tags: Option<String>,
+    traces_tags: Option<String>,
+    ingredients_analysis_tags: Option<String>,
+    nutriscore_grade: Option<String>,
+    nova_group: Option<i64>,
+    additives_tags: Option<String>,
+    co2e_per_kg: Option<f64>,
 }
 
 #[derive(JsonSchema, Serialize)]
 pub struct GetIngredientResult {
     ingredient: IngredientResult,
     ingredient_properties: Option<IngredientPropertiesResult>,
+    /// Properties of the generic ingredient, used for the nutrients the ingredient's own
+    /// properties lack
+    generic_properties: Option<IngredientPropertiesResult>,
+    /// Ingredients that are variants of this one
+    variants: Vec<Ingredient>,
+    aliases: Vec<IngredientAlias>,
+    /// The names of the ingredient in each language
+    names: Vec<IngredientName>,
+    /// Categories the ingredient was put in, without their ancestors
+    categories: Vec<Category>,
+    tags: Vec<String>,
+    /// Allergens and diets of the ingredient, from its properties or its generic ingredient's
+    diet: Option<DietInfo>,
+    /// Kilograms of CO2 equivalent per kilogram used in reports: the one entered by hand, the one
+    /// of the properties, or the one of the generic ingredient
+    co2e_per_kg: Option<f64>,
+    trackables: Vec<IngredientTrackableResult>,
+}
+
+#[derive(JsonSchema, Serialize)]
+pub struct IngredientTrackableResult {
+    trackable_id: i64,
+    name: String,
+    unit: String,
+    /// Amount of the trackable in 100g of the ingredient
+    amount_100g: f64,
+    /// Whether the amount comes from the generic ingredient
+    inherited: bool,
 }
 
 async fn fetch_ingredient(
     connection: &Pool<Sqlite>,
+    household_id: i64,
     ingredient_id: i64,
+    languages: &str,
 ) -> Result<IngredientResult, InternalServerError> {
     let ingredient = sqlx::query_as!(
         IngredientResult,
@@ -59,10 +133,23 @@ async fn fetch_ingredient(
         SELECT
             name,
             id,
-            creation_date
+            creation_date,
+            version,
+            generic_id,
+            co2e_per_kg,
+            COALESCE((
+                SELECT IngredientName.name
+                FROM IngredientName
+                WHERE IngredientName.ingredient_id = Ingredient.id
+                    AND instr(?3, ',' || language || ',') > 0
+                ORDER BY instr(?3, ',' || language || ',')
+                LIMIT 1
+            ), name) AS "display_name!: String"
         FROM Ingredient
-        WHERE id=?"#,
-        ingredient_id
+        WHERE id=?1 AND household_id=?2"#,
+        ingredient_id,
+        household_id,
+        languages
     )
     .fetch_optional(connection)
     .await?
@@ -76,7 +163,7 @@ async fn fetch_ingredient_properties(
     ingredient_id: i64,
 ) -> Result<Option<IngredientPropertiesResult>, InternalServerError> {
     let ingredient_properties = sqlx::query_as!(
-        IngredientPropertiesResult,
+        DatabaseIngredientProperties,
         r#"
         SELECT
             kcal_100g,
@@ -84,34 +171,194 @@ async fn fetch_ingredient_properties(
             carbohydrates_100g,
             proteins_100g,
             product_name,
-            product_code
+            product_code,
+            allergens_tags,
+            traces_tags,
+            ingredients_analysis_tags,
+            nutriscore_grade,
+            nova_group,
+            additives_tags,
+            co2e_per_kg
         FROM IngredientProperties
         WHERE ingredient_id=?"#,
         ingredient_id
     )
     .fetch_optional(connection)
-    .await?;
+    .await?
+    .map(|properties| IngredientPropertiesResult {
+        kcal_100g: properties.kcal_100g,
+        product_name: properties.product_name,
+        product_code: properties.product_code,
+        carbohydrates_100g: properties.carbohydrates_100g,
+        proteins_100g: properties.proteins_100g,
+        fat_100g: properties.fat_100g,
+        allergens_tags: parse_tags(properties.allergens_tags),
+        traces_tags: parse_tags(properties.traces_tags),
+        ingredients_analysis_tags: parse_tags(properties.ingredients_analysis_tags),
+        nutriscore_grade: properties.nutriscore_grade,
+        nova_group: properties.nova_group,
+        additives_tags: parse_tags(properties.additives_tags),
+        co2e_per_kg: properties.co2e_per_kg,
+    });
 
     Ok(ingredient_properties)
 }
 
+async fn fetch_ingredient_variants(
+    connection: &Pool<Sqlite>,
+    ingredient_id: i64,
+) -> Result<Vec<Ingredient>, InternalServerError> {
+    Ok(sqlx::query_as!(
+        Ingredient,
+        r#"
+        SELECT id, creation_date, name, version
+        FROM Ingredient
+        WHERE generic_id=?
+        ORDER BY name"#,
+        ingredient_id
+    )
+    .fetch_all(connection)
+    .await?)
+}
+
+async fn fetch_ingredient_aliases(
+    connection: &Pool<Sqlite>,
+    ingredient_id: i64,
+) -> Result<Vec<IngredientAlias>, InternalServerError> {
+    Ok(sqlx::query_as!(
+        IngredientAlias,
+        r#"
+        SELECT id, creation_date, ingredient_id, name
+        FROM IngredientAlias
+        WHERE ingredient_id=?
+        ORDER BY name"#,
+        ingredient_id
+    )
+    .fetch_all(connection)
+    .await?)
+}
+
+async fn fetch_ingredient_categories(
+    connection: &Pool<Sqlite>,
+    ingredient_id: i64,
+) -> Result<Vec<Category>, InternalServerError> {
+    Ok(sqlx::query_as!(
+        Category,
+        r#"
+        SELECT id, Category.creation_date, parent_id, name, open_food_facts_tag
+        FROM Category JOIN IngredientCategory ON IngredientCategory.category_id = Category.id
+        WHERE ingredient_id=?
+        ORDER BY name"#,
+        ingredient_id
+    )
+    .fetch_all(connection)
+    .await?)
+}
+
+async fn fetch_ingredient_tags(
+    connection: &Pool<Sqlite>,
+    ingredient_id: i64,
+) -> Result<Vec<String>, InternalServerError> {
+    Ok(sqlx::query_scalar!(
+        "SELECT name FROM IngredientTag WHERE ingredient_id=? ORDER BY name",
+        ingredient_id
+    )
+    .fetch_all(connection)
+    .await?)
+}
+
+async fn fetch_ingredient_trackables(
+    connection: &Pool<Sqlite>,
+    ingredient_id: i64,
+) -> Result<Vec<IngredientTrackableResult>, InternalServerError> {
+    Ok(sqlx::query_as!(
+        IngredientTrackableResult,
+        r#"
+        SELECT
+            Trackable.id AS "trackable_id!: i64",
+            Trackable.name AS "name!: String",
+            Trackable.unit AS "unit!: String",
+            CAST (IngredientTrackableAmount.amount_100g AS FLOAT) AS "amount_100g!: f64",
+            NOT EXISTS (
+                SELECT 1 FROM IngredientTrackable
+                WHERE ingredient_id = ?1 AND trackable_id = Trackable.id
+            ) AS "inherited!: bool"
+        FROM IngredientTrackableAmount
+            JOIN Trackable ON Trackable.id = IngredientTrackableAmount.trackable_id
+        WHERE IngredientTrackableAmount.ingredient_id = ?1
+        ORDER BY Trackable.name"#,
+        ingredient_id
+    )
+    .fetch_all(connection)
+    .await?)
+}
+
+async fn fetch_ingredient_names(
+    connection: &Pool<Sqlite>,
+    ingredient_id: i64,
+) -> Result<Vec<IngredientName>, InternalServerError> {
+    Ok(sqlx::query_as!(
+        IngredientName,
+        r#"
+        SELECT creation_date, ingredient_id, language, name, source
+        FROM IngredientName
+        WHERE ingredient_id=?
+        ORDER BY language"#,
+        ingredient_id
+    )
+    .fetch_all(connection)
+    .await?)
+}
+
 pub async fn get_ingredient(
-    State(AppState { connection }): State<AppState>,
+    State(AppState { connection, .. }): State<AppState>,
+    AuthUser { household_id, .. }: AuthUser,
+    accept_language: AcceptLanguage,
     Path(IngredientId { ingredient_id }): Path<IngredientId>,
-) -> ServerResponseResult<GetIngredientResult> {
-    let (ingredient, ingredient_properties) = futures::try_join!(
-        fetch_ingredient(&connection, ingredient_id),
-        fetch_ingredient_properties(&connection, ingredient_id)
-    )?;
-
-    Ok(ServerResponse::success_code(
-        GetIngredientResult {
-            ingredient,
-            ingredient_properties,
-        },
-        StatusCode::OK,
-    )
-    .json())
+) -> VersionedResult<GetIngredientResult> {
+    let languages = accept_language.preference();
+    let (ingredient, ingredient_properties, variants, aliases, names, categories, tags, trackables) =
+        futures::try_join!(
+            fetch_ingredient(&connection, household_id, ingredient_id, &languages),
+            fetch_ingredient_properties(&connection, ingredient_id),
+            fetch_ingredient_variants(&connection, ingredient_id),
+            fetch_ingredient_aliases(&connection, ingredient_id),
+            fetch_ingredient_names(&connection, ingredient_id),
+            fetch_ingredient_categories(&connection, ingredient_id),
+            fetch_ingredient_tags(&connection, ingredient_id),
+            fetch_ingredient_trackables(&connection, ingredient_id)
+        )?;
+    let generic_properties = match ingredient.generic_id {
+        Some(generic_id) => fetch_ingredient_properties(&connection, generic_id).await?,
+        None => None,
+    };
+    let diet = fetch_ingredient_diets(&connection, &[ingredient_id])
+        .await?
+        .remove(&ingredient_id);
+    let co2e_per_kg = fetch_ingredient_footprints(&connection, &[ingredient_id])
+        .await?
+        .remove(&ingredient_id);
+
+    Ok(Versioned(
+        ingredient.version,
+        ServerResponse::success_code(
+            GetIngredientResult {
+                ingredient,
+                ingredient_properties,
+                generic_properties,
+                variants,
+                aliases,
+                names,
+                categories,
+                tags,
+                diet,
+                co2e_per_kg,
+                trackables,
+            },
+            StatusCode::OK,
+        )
+        .json(),
+    ))
 }
 
 pub fn get_ingredient_docs(op: TransformOperation) -> TransformOperation {
@@ -0,0 +1,11 @@
+use aide::axum::{routing::post, ApiRouter};
+
+use crate::state::AppState;
+
+mod post;
+
+pub fn route(state: AppState) -> ApiRouter {
+    ApiRouter::new()
+        .api_route("/", post(post::post_merge))
+        .with_state(state)
+}
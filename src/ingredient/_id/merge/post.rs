@@ -0,0 +1,378 @@
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use sqlx::SqliteConnection;
+use thiserror::Error;
+
+use crate::{
+    audit::{record_change, snapshot, AuditEntity},
+    dependency::{resolve_dependents, DeletePolicy},
+    etag::IfMatch,
+    events::DomainEvent,
+    models::Ingredient,
+    server::{ServerResponse, ServerResponseResult},
+    session::AuthUser,
+    state::AppState,
+};
+
+#[derive(Deserialize, JsonSchema)]
+pub struct IngredientId {
+    ingredient_id: i64,
+}
+
+#[derive(Deserialize, JsonSchema)]
+pub struct PostMergeBody {
+    /// Ingredients that are the same as this one. They are deleted, and everything that used them
+    /// uses this one instead
+    duplicate_ids: Vec<i64>,
+}
+
+#[derive(Serialize, JsonSchema)]
+pub struct PostMergeResult {
+    ingredient: Ingredient,
+    /// Every other name of the ingredient, including the names of the duplicates
+    aliases: Vec<String>,
+    /// The ingredient the kept properties came from, if any had properties
+    properties_from_id: Option<i64>,
+}
+
+#[derive(Error, Debug)]
+enum PostMergeError {
+    #[error("No duplicates were given")]
+    NoDuplicates,
+    #[error("Can't merge ingredient \"{0}\" into itself")]
+    MergeIntoItself(i64),
+}
+
+/// Moves the most complete of the properties of the ingredients to `ingredient_id`, and deletes
+/// the others. The ingredient's own properties win ties. Returns where the kept properties came
+/// from.
+async fn keep_best_properties(
+    connection: &mut SqliteConnection,
+    ingredient_id: i64,
+    duplicate_ids: &[i64],
+) -> anyhow::Result<Option<i64>> {
+    let mut best: Option<(i64, i64)> = None;
+    for id in std::iter::once(ingredient_id).chain(duplicate_ids.iter().copied()) {
+        let completeness = sqlx::query_scalar!(
+            r#"
+            SELECT
+                (kcal_100g IS NOT NULL)
+                + (proteins_100g IS NOT NULL)
+                + (fat_100g IS NOT NULL)
+                + (carbohydrates_100g IS NOT NULL) AS "completeness!: i64"
+            FROM IngredientProperties
+            WHERE ingredient_id = ?"#,
+            id
+        )
+        .fetch_optional(&mut *connection)
+        .await?;
+        if let Some(completeness) = completeness {
+            if best.is_none_or(|(_, best)| completeness > best) {
+                best = Some((id, completeness));
+            }
+        }
+    }
+
+    let Some((best_id, _)) = best else {
+        return Ok(None);
+    };
+    if best_id != ingredient_id {
+        sqlx::query!(
+            "DELETE FROM IngredientProperties WHERE ingredient_id = ?",
+            ingredient_id
+        )
+        .execute(&mut *connection)
+        .await?;
+        sqlx::query!(
+            "UPDATE IngredientProperties SET ingredient_id = ? WHERE ingredient_id = ?",
+            ingredient_id,
+            best_id
+        )
+        .execute(&mut *connection)
+        .await?;
+    }
+    for id in duplicate_ids {
+        sqlx::query!(
+            "DELETE FROM IngredientProperties WHERE ingredient_id = ?",
+            id
+        )
+        .execute(&mut *connection)
+        .await?;
+    }
+
+    Ok(Some(best_id))
+}
+
+/// Moves the aliases, names, categories, tags and trackables of `duplicate_id` to
+/// `ingredient_id`, makes its variants variants of `ingredient_id`, and deletes it.
+async fn merge_duplicate(
+    connection: &mut SqliteConnection,
+    ingredient_id: i64,
+    duplicate_id: i64,
+) -> anyhow::Result<()> {
+    sqlx::query!(
+        r#"
+        INSERT OR IGNORE INTO IngredientAlias (ingredient_id, name)
+        SELECT ?1, name FROM Ingredient WHERE id = ?2
+        UNION ALL
+        SELECT ?1, name FROM IngredientAlias WHERE ingredient_id = ?2"#,
+        ingredient_id,
+        duplicate_id
+    )
+    .execute(&mut *connection)
+    .await?;
+    sqlx::query!(
+        "DELETE FROM IngredientAlias WHERE ingredient_id = ?",
+        duplicate_id
+    )
+    .execute(&mut *connection)
+    .await?;
+    sqlx::query!(
+        r#"
+        INSERT OR IGNORE INTO IngredientName (ingredient_id, language, name, source)
+        SELECT ?1, language, name, source FROM IngredientName WHERE ingredient_id = ?2"#,
+        ingredient_id,
+        duplicate_id
+    )
+    .execute(&mut *connection)
+    .await?;
+    sqlx::query!(
+        "DELETE FROM IngredientName WHERE ingredient_id = ?",
+        duplicate_id
+    )
+    .execute(&mut *connection)
+    .await?;
+    sqlx::query!(
+        r#"
+        INSERT OR IGNORE INTO IngredientCategory (ingredient_id, category_id, source)
+        SELECT ?1, category_id, source FROM IngredientCategory WHERE ingredient_id = ?2"#,
+        ingredient_id,
+        duplicate_id
+    )
+    .execute(&mut *connection)
+    .await?;
+    sqlx::query!(
+        "DELETE FROM IngredientCategory WHERE ingredient_id = ?",
+        duplicate_id
+    )
+    .execute(&mut *connection)
+    .await?;
+    sqlx::query!(
+        r#"
+        INSERT OR IGNORE INTO IngredientTag (ingredient_id, name)
+        SELECT ?1, name FROM IngredientTag WHERE ingredient_id = ?2"#,
+        ingredient_id,
+        duplicate_id
+    )
+    .execute(&mut *connection)
+    .await?;
+    sqlx::query!(
+        "DELETE FROM IngredientTag WHERE ingredient_id = ?",
+        duplicate_id
+    )
+    .execute(&mut *connection)
+    .await?;
+    sqlx::query!(
+        r#"
+        INSERT OR IGNORE INTO IngredientTrackable (ingredient_id, trackable_id, amount_100g)
+        SELECT ?1, trackable_id, amount_100g FROM IngredientTrackable WHERE ingredient_id = ?2"#,
+        ingredient_id,
+        duplicate_id
+    )
+    .execute(&mut *connection)
+    .await?;
+    sqlx::query!(
+        "DELETE FROM IngredientTrackable WHERE ingredient_id = ?",
+        duplicate_id
+    )
+    .execute(&mut *connection)
+    .await?;
+    sqlx::query!(
+        r#"
+        UPDATE Ingredient
+        SET co2e_per_kg = (SELECT co2e_per_kg FROM Ingredient WHERE id = ?2)
+        WHERE id = ?1 AND co2e_per_kg IS NULL"#,
+        ingredient_id,
+        duplicate_id
+    )
+    .execute(&mut *connection)
+    .await?;
+    sqlx::query!(
+        "UPDATE Ingredient SET generic_id = NULL WHERE id = ?1 AND generic_id = ?2",
+        ingredient_id,
+        duplicate_id
+    )
+    .execute(&mut *connection)
+    .await?;
+    sqlx::query!(
+        r#"
+        UPDATE Ingredient
+        SET generic_id = COALESCE((SELECT generic_id FROM Ingredient WHERE id = ?1), ?1)
+        WHERE generic_id = ?2"#,
+        ingredient_id,
+        duplicate_id
+    )
+    .execute(&mut *connection)
+    .await?;
+    sqlx::query!("DELETE FROM Ingredient WHERE id = ?", duplicate_id)
+        .execute(&mut *connection)
+        .await?;
+
+    Ok(())
+}
+
+/// Merges duplicates into the ingredient. Dishes, meals, plans, templates and purchases that used
+/// a duplicate use the ingredient instead, with the weights summed where both were used. The
+/// names of the duplicates are kept as aliases of the ingredient, and their names in languages the
+/// ingredient has no name in are moved to it, as are their categories, tags and variants, and their
+/// footprint and trackable amounts where the ingredient has none. Every change is recorded in the
+/// history.
+pub async fn post_merge(
+    State(AppState {
+        connection, events, ..
+    }): State<AppState>,
+    user: AuthUser,
+    if_match: IfMatch,
+    Path(IngredientId { ingredient_id }): Path<IngredientId>,
+    Json(PostMergeBody { mut duplicate_ids }): Json<PostMergeBody>,
+) -> ServerResponseResult<PostMergeResult> {
+    duplicate_ids.sort_unstable();
+    duplicate_ids.dedup();
+    if duplicate_ids.is_empty() {
+        return Err(PostMergeError::NoDuplicates)?;
+    }
+    if duplicate_ids.contains(&ingredient_id) {
+        return Err(PostMergeError::MergeIntoItself(ingredient_id))?;
+    }
+
+    user.check_ingredient(&connection, ingredient_id).await?;
+    for duplicate_id in &duplicate_ids {
+        user.check_ingredient(&connection, *duplicate_id).await?;
+    }
+    let mut transaction = connection.begin().await?;
+    if_match
+        .check(&mut transaction, "Ingredient", ingredient_id)
+        .await?;
+
+    let before = snapshot(&mut *transaction, AuditEntity::Ingredient, ingredient_id).await?;
+    let mut duplicates_before = Vec::with_capacity(duplicate_ids.len());
+    for duplicate_id in &duplicate_ids {
+        duplicates_before
+            .push(snapshot(&mut *transaction, AuditEntity::Ingredient, *duplicate_id).await?);
+    }
+    let mut variant_ids = vec![];
+    for duplicate_id in &duplicate_ids {
+        variant_ids.extend(
+            sqlx::query_scalar!(
+                "SELECT id FROM Ingredient WHERE generic_id = ? AND id != ?",
+                duplicate_id,
+                ingredient_id
+            )
+            .fetch_all(&mut *transaction)
+            .await?,
+        );
+    }
+    let mut variants_before = Vec::with_capacity(variant_ids.len());
+    for variant_id in &variant_ids {
+        variants_before
+            .push(snapshot(&mut *transaction, AuditEntity::Ingredient, *variant_id).await?);
+    }
+
+    let mut changed_meal_ids = vec![];
+    for duplicate_id in &duplicate_ids {
+        changed_meal_ids.extend(
+            resolve_dependents(
+                &mut transaction,
+                &user,
+                AuditEntity::Ingredient,
+                *duplicate_id,
+                &DeletePolicy::Reassign(ingredient_id),
+            )
+            .await?,
+        );
+    }
+
+    let properties_from_id =
+        keep_best_properties(&mut transaction, ingredient_id, &duplicate_ids).await?;
+
+    for duplicate_id in &duplicate_ids {
+        merge_duplicate(&mut transaction, ingredient_id, *duplicate_id).await?;
+    }
+    // A duplicate can have the same name as the ingredient, with a different case
+    sqlx::query!(
+        r#"
+        DELETE FROM IngredientAlias
+        WHERE ingredient_id = ?1 AND name = (SELECT name FROM Ingredient WHERE id = ?1)"#,
+        ingredient_id
+    )
+    .execute(&mut *transaction)
+    .await?;
+
+    for (duplicate_id, before) in duplicate_ids.iter().zip(duplicates_before) {
+        record_change(
+            &mut *transaction,
+            &user,
+            AuditEntity::Ingredient,
+            *duplicate_id,
+            before,
+        )
+        .await?;
+    }
+    for (variant_id, before) in variant_ids.into_iter().zip(variants_before) {
+        record_change(
+            &mut *transaction,
+            &user,
+            AuditEntity::Ingredient,
+            variant_id,
+            before,
+        )
+        .await?;
+    }
+    record_change(
+        &mut *transaction,
+        &user,
+        AuditEntity::Ingredient,
+        ingredient_id,
+        before,
+    )
+    .await?;
+
+    transaction.commit().await?;
+
+    changed_meal_ids.sort_unstable();
+    changed_meal_ids.dedup();
+    for meal_id in changed_meal_ids {
+        events.publish(&user, DomainEvent::MealUpdated { meal_id });
+    }
+    if properties_from_id.is_some_and(|id| id != ingredient_id) {
+        events.publish(
+            &user,
+            DomainEvent::IngredientPropertiesFetched { ingredient_id },
+        );
+    }
+
+    let ingredient = sqlx::query_as!(
+        Ingredient,
+        "SELECT id, creation_date, name, version FROM Ingredient WHERE id = ?",
+        ingredient_id
+    )
+    .fetch_one(&connection)
+    .await?;
+    let aliases = sqlx::query_scalar!(
+        "SELECT name FROM IngredientAlias WHERE ingredient_id = ? ORDER BY name",
+        ingredient_id
+    )
+    .fetch_all(&connection)
+    .await?;
+
+    Ok(ServerResponse::success(PostMergeResult {
+        ingredient,
+        aliases,
+        properties_from_id,
+    })
+    .json())
+}
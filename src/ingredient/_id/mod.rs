@@ -4,12 +4,39 @@ use crate::state::AppState;
 
 use self::get::get_ingredient_docs;
 
+mod alias;
+mod category;
+mod delete;
+mod footprint;
+mod generic;
 mod get;
+mod merge;
+mod name;
+mod post;
+mod price;
 mod properties;
+mod purchase;
+mod tag;
+mod trackable;
 
 pub fn route(state: AppState) -> ApiRouter {
     ApiRouter::new()
-        .api_route("/", get_with(get::get_ingredient, get_ingredient_docs))
+        .api_route(
+            "/",
+            get_with(get::get_ingredient, get_ingredient_docs)
+                .post(post::post_edit_ingredient)
+                .delete(delete::delete_ingredient),
+        )
+        .nest_api_service("/alias", alias::route(state.clone()))
+        .nest_api_service("/category", category::route(state.clone()))
+        .nest_api_service("/footprint", footprint::route(state.clone()))
+        .nest_api_service("/generic", generic::route(state.clone()))
+        .nest_api_service("/merge", merge::route(state.clone()))
+        .nest_api_service("/name", name::route(state.clone()))
         .nest_api_service("/properties", properties::route(state.clone()))
+        .nest_api_service("/purchase", purchase::route(state.clone()))
+        .nest_api_service("/price", price::route(state.clone()))
+        .nest_api_service("/tag", tag::route(state.clone()))
+        .nest_api_service("/trackable", trackable::route(state.clone()))
         .with_state(state)
 }
@@ -0,0 +1,16 @@
+use aide::axum::{
+    routing::{delete, post},
+    ApiRouter,
+};
+
+use crate::state::AppState;
+
+mod delete;
+mod post;
+
+pub fn route(state: AppState) -> ApiRouter {
+    ApiRouter::new()
+        .api_route("/", post(post::post_name))
+        .api_route("/:language", delete(delete::delete_name))
+        .with_state(state)
+}
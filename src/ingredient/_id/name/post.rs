@@ -0,0 +1,92 @@
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+use schemars::JsonSchema;
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::{
+    audit::{record_change, snapshot, AuditEntity},
+    etag::IfMatch,
+    models::IngredientName,
+    server::{ServerResponse, ServerResponseResult},
+    session::AuthUser,
+    state::AppState,
+};
+
+#[derive(Deserialize, JsonSchema)]
+pub struct IngredientId {
+    ingredient_id: i64,
+}
+
+#[derive(Deserialize, JsonSchema)]
+pub struct PostNameBody {
+    /// Language tag, such as `en` or `pt-BR`
+    language: String,
+    name: String,
+}
+
+#[derive(Error, Debug)]
+enum PostNameError {
+    #[error("Language \"{0}\" is invalid. It must be a tag such as \"en\" or \"pt-BR\"")]
+    InvalidLanguage(String),
+    #[error("The name should not be empty")]
+    EmptyName,
+}
+
+/// Sets the name of the ingredient in a language. It replaces the name fetched from Open Food
+/// Facts, if any, and is kept when the properties are fetched again.
+pub async fn post_name(
+    State(AppState { connection, .. }): State<AppState>,
+    user: AuthUser,
+    if_match: IfMatch,
+    Path(IngredientId { ingredient_id }): Path<IngredientId>,
+    Json(PostNameBody { language, name }): Json<PostNameBody>,
+) -> ServerResponseResult<IngredientName> {
+    let language = language.trim().to_lowercase();
+    if language.is_empty()
+        || !language
+            .split('-')
+            .all(|part| !part.is_empty() && part.chars().all(|c| c.is_ascii_alphanumeric()))
+    {
+        return Err(PostNameError::InvalidLanguage(language))?;
+    }
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(PostNameError::EmptyName)?;
+    }
+    user.check_ingredient(&connection, ingredient_id).await?;
+    let mut transaction = connection.begin().await?;
+    if_match
+        .check(&mut transaction, "Ingredient", ingredient_id)
+        .await?;
+    let before = snapshot(&mut *transaction, AuditEntity::Ingredient, ingredient_id).await?;
+
+    let ingredient_name = sqlx::query_as!(
+        IngredientName,
+        r#"
+        INSERT INTO IngredientName (ingredient_id, language, name, source)
+        VALUES (?, ?, ?, 'Manual')
+        ON CONFLICT DO UPDATE SET name = excluded.name, source = excluded.source
+        RETURNING creation_date, ingredient_id, language, name, source;"#,
+        ingredient_id,
+        language,
+        name
+    )
+    .fetch_one(&mut *transaction)
+    .await?;
+
+    record_change(
+        &mut *transaction,
+        &user,
+        AuditEntity::Ingredient,
+        ingredient_id,
+        before,
+    )
+    .await?;
+
+    transaction.commit().await?;
+
+    Ok(ServerResponse::success(ingredient_name).json())
+}
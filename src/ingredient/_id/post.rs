@@ -0,0 +1,88 @@
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+use schemars::JsonSchema;
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::{
+    audit::{record_change, snapshot, AuditEntity},
+    etag::{fetch_version, IfMatch, Versioned, VersionedResult},
+    models::Ingredient,
+    server::ServerResponse,
+    session::AuthUser,
+    state::AppState,
+};
+
+#[derive(Deserialize, JsonSchema)]
+pub struct IngredientId {
+    ingredient_id: i64,
+}
+
+#[derive(Deserialize, JsonSchema)]
+pub struct PostEditIngredientBody {
+    name: String,
+}
+
+#[derive(Error, Debug)]
+enum PostEditIngredientError {
+    #[error("The name of an ingredient should not be empty")]
+    EmptyName,
+}
+
+/// Renames the ingredient. An alias with the new name is removed, since it would be redundant.
+pub async fn post_edit_ingredient(
+    State(AppState { connection, .. }): State<AppState>,
+    user: AuthUser,
+    if_match: IfMatch,
+    Path(IngredientId { ingredient_id }): Path<IngredientId>,
+    Json(PostEditIngredientBody { name }): Json<PostEditIngredientBody>,
+) -> VersionedResult<Ingredient> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(PostEditIngredientError::EmptyName)?;
+    }
+    user.check_ingredient(&connection, ingredient_id).await?;
+    let mut transaction = connection.begin().await?;
+    if_match
+        .check(&mut transaction, "Ingredient", ingredient_id)
+        .await?;
+    let before = snapshot(&mut *transaction, AuditEntity::Ingredient, ingredient_id).await?;
+
+    sqlx::query!(
+        "DELETE FROM IngredientAlias WHERE ingredient_id = ? AND name = ?",
+        ingredient_id,
+        name
+    )
+    .execute(&mut *transaction)
+    .await?;
+    let mut ingredient = sqlx::query_as!(
+        Ingredient,
+        r#"
+        UPDATE Ingredient SET name = ? WHERE id = ?
+        RETURNING id as "id!", creation_date, name, version;"#,
+        name,
+        ingredient_id
+    )
+    .fetch_one(&mut *transaction)
+    .await?;
+
+    record_change(
+        &mut *transaction,
+        &user,
+        AuditEntity::Ingredient,
+        ingredient_id,
+        before,
+    )
+    .await?;
+
+    transaction.commit().await?;
+
+    ingredient.version = fetch_version(&connection, "Ingredient", ingredient_id).await?;
+
+    Ok(Versioned(
+        ingredient.version,
+        ServerResponse::success(ingredient).json(),
+    ))
+}
@@ -0,0 +1,52 @@
+use aide::transform::TransformOperation;
+use axum::{
+    extract::{Path, Query, State},
+    Json,
+};
+use schemars::JsonSchema;
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::{
+    app_error::InternalServerError,
+    price::{fetch_ingredient_prices, IngredientPrice, PriceMethod},
+    server::{ServerResponse, ServerResponseResult},
+    session::AuthUser,
+    state::AppState,
+};
+
+#[derive(JsonSchema, Deserialize)]
+pub struct IngredientId {
+    pub ingredient_id: i64,
+}
+
+#[derive(JsonSchema, Deserialize)]
+pub struct GetPriceQueryParams {
+    method: Option<PriceMethod>,
+}
+
+#[derive(Error, Debug)]
+enum GetPriceError {
+    #[error("Ingredient with id \"{0}\" has no purchases registered")]
+    NoPurchases(i64),
+}
+
+pub async fn get_price(
+    State(AppState { connection, .. }): State<AppState>,
+    user: AuthUser,
+    Path(IngredientId { ingredient_id }): Path<IngredientId>,
+    Query(GetPriceQueryParams { method }): Query<GetPriceQueryParams>,
+) -> ServerResponseResult<IngredientPrice> {
+    user.check_ingredient(&connection, ingredient_id).await?;
+    let price = fetch_ingredient_prices(&connection, method.unwrap_or_default(), &[ingredient_id])
+        .await?
+        .remove(&ingredient_id)
+        .ok_or(GetPriceError::NoPurchases(ingredient_id))?;
+
+    Ok(ServerResponse::success(price).json())
+}
+
+pub fn get_price_docs(op: TransformOperation) -> TransformOperation {
+    op.response::<200, Json<ServerResponse<IngredientPrice>>>()
+        .response::<500, Json<InternalServerError>>()
+}
@@ -0,0 +1,13 @@
+use aide::axum::{routing::get_with, ApiRouter};
+
+use crate::state::AppState;
+
+use self::get::{get_price, get_price_docs};
+
+mod get;
+
+pub fn route(state: AppState) -> ApiRouter {
+    ApiRouter::new()
+        .api_route("/", get_with(get_price, get_price_docs))
+        .with_state(state)
+}
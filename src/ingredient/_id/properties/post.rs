@@ -9,8 +9,12 @@ use serde::{Deserialize, Serialize};
 
 use crate::{
     app_error::InternalServerError,
+    audit::{record_change, snapshot, AuditEntity},
+    events::DomainEvent,
     server::{ServerResponse, ServerResponseResult},
+    session::AuthUser,
     state::AppState,
+    taxonomy::seed_categories,
 };
 
 #[derive(JsonSchema, Deserialize)]
@@ -89,12 +93,22 @@ pub struct PostIngredientPropertiesResult {
     kcal_100g: Option<i64>,
 }
 
+/// Fetches the product from Open Food Facts and stores it as the properties of the ingredient.
+/// The names the product has in each language and its category replace the ones fetched before,
+/// but never the ones entered by hand.
 pub async fn post_ingredient_properties(
-    State(AppState { connection }): State<AppState>,
+    State(AppState {
+        connection, events, ..
+    }): State<AppState>,
+    user: AuthUser,
     Path(IngredientId { ingredient_id }): Path<IngredientId>,
     Json(PostPropertiesBody { product_code }): Json<PostPropertiesBody>,
 ) -> ServerResponseResult<PostIngredientPropertiesResult> {
+    user.check_ingredient(&connection, ingredient_id).await?;
     let food_facts = fetch_from_open_food_facts(&product_code).await?;
+
+    let mut transaction = connection.begin().await?;
+    let before = snapshot(&mut *transaction, AuditEntity::Ingredient, ingredient_id).await?;
     let data = sqlx::query_as!(
         PostIngredientPropertiesResult,
         r#"
@@ -112,9 +126,46 @@ pub async fn post_ingredient_properties(
         product_code,
         food_facts
     )
-    .fetch_one(&connection)
+    .fetch_one(&mut *transaction)
+    .await?;
+
+    sqlx::query!(
+        "DELETE FROM IngredientName WHERE ingredient_id = ? AND source = 'OpenFoodFacts'",
+        ingredient_id
+    )
+    .execute(&mut *transaction)
+    .await?;
+    sqlx::query!(
+        r#"
+        INSERT INTO IngredientName (ingredient_id, language, name, source)
+        SELECT ?1, substr(key, 14), trim(value), 'OpenFoodFacts'
+        FROM json_each(?2, '$.product')
+        WHERE key GLOB 'product_name_[a-z][a-z]' AND type = 'text' AND trim(value) != ''
+        ON CONFLICT DO NOTHING"#,
+        ingredient_id,
+        food_facts
+    )
+    .execute(&mut *transaction)
+    .await?;
+
+    seed_categories(&mut transaction, ingredient_id).await?;
+
+    record_change(
+        &mut *transaction,
+        &user,
+        AuditEntity::Ingredient,
+        ingredient_id,
+        before,
+    )
     .await?;
 
+    transaction.commit().await?;
+
+    events.publish(
+        &user,
+        DomainEvent::IngredientPropertiesFetched { ingredient_id },
+    );
+
     Ok(ServerResponse::success_code(data, StatusCode::CREATED).json())
 }
 
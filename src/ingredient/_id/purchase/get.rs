@@ -0,0 +1,47 @@
+use aide::transform::TransformOperation;
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+use crate::{
+    app_error::InternalServerError,
+    models::IngredientPurchase,
+    server::{ServerResponse, ServerResponseResult},
+    session::AuthUser,
+    state::AppState,
+};
+
+#[derive(JsonSchema, Deserialize)]
+pub struct IngredientId {
+    pub ingredient_id: i64,
+}
+
+pub async fn list_purchases(
+    State(AppState { connection, .. }): State<AppState>,
+    user: AuthUser,
+    Path(IngredientId { ingredient_id }): Path<IngredientId>,
+) -> ServerResponseResult<Vec<IngredientPurchase>> {
+    user.check_ingredient(&connection, ingredient_id).await?;
+
+    let purchases = sqlx::query_as!(
+        IngredientPurchase,
+        r#"
+        SELECT *
+        FROM IngredientPurchase
+        WHERE ingredient_id = ?
+        ORDER BY purchase_date DESC;"#,
+        ingredient_id
+    )
+    .fetch_all(&connection)
+    .await?;
+
+    Ok(ServerResponse::success(purchases).json())
+}
+
+pub fn list_purchases_docs(op: TransformOperation) -> TransformOperation {
+    op.response::<200, Json<ServerResponse<Vec<IngredientPurchase>>>>()
+        .response::<500, Json<InternalServerError>>()
+}
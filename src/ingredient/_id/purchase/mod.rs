@@ -0,0 +1,21 @@
+use aide::axum::{routing::post_with, ApiRouter};
+
+use crate::state::AppState;
+
+use self::{
+    get::{list_purchases, list_purchases_docs},
+    post::{post_purchase, post_purchase_docs},
+};
+
+mod get;
+mod post;
+
+pub fn route(state: AppState) -> ApiRouter {
+    ApiRouter::new()
+        .api_route(
+            "/",
+            post_with(post_purchase, post_purchase_docs)
+                .get_with(list_purchases, list_purchases_docs),
+        )
+        .with_state(state)
+}
@@ -0,0 +1,110 @@
+use aide::transform::TransformOperation;
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use schemars::JsonSchema;
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::{
+    app_error::InternalServerError,
+    models::IngredientPurchase,
+    server::{ServerResponse, ServerResponseResult},
+    session::AuthUser,
+    state::AppState,
+};
+
+#[derive(JsonSchema, Deserialize)]
+pub struct IngredientId {
+    pub ingredient_id: i64,
+}
+
+#[derive(Deserialize, JsonSchema)]
+pub struct PostPurchaseBody {
+    /// When the purchase was made. Defaults to now
+    purchase_date: Option<i64>,
+    /// Price paid, in the smallest unit of the currency (cents, for example)
+    price: i64,
+    currency: String,
+    /// Weight of the package, in grams
+    package_weight: i64,
+    store: Option<String>,
+}
+
+#[derive(Error, Debug)]
+enum PostPurchaseError {
+    #[error("Could not find ingredient with id \"{0}\"")]
+    IngredientNotFound(i64),
+    #[error("Package weight {0} is invalid. It must be larger than 0")]
+    InvalidPackageWeight(i64),
+    #[error("Price {0} is invalid. It must not be negative")]
+    InvalidPrice(i64),
+    #[error("The currency should not be empty")]
+    CurrencyIsEmpty,
+}
+
+pub async fn post_purchase(
+    State(AppState { connection, .. }): State<AppState>,
+    AuthUser { household_id, .. }: AuthUser,
+    Path(IngredientId { ingredient_id }): Path<IngredientId>,
+    Json(PostPurchaseBody {
+        purchase_date,
+        price,
+        currency,
+        package_weight,
+        store,
+    }): Json<PostPurchaseBody>,
+) -> ServerResponseResult<IngredientPurchase> {
+    if package_weight <= 0 {
+        return Err(PostPurchaseError::InvalidPackageWeight(package_weight))?;
+    }
+    if price < 0 {
+        return Err(PostPurchaseError::InvalidPrice(price))?;
+    }
+    let currency = currency.trim().to_uppercase();
+    if currency.is_empty() {
+        return Err(PostPurchaseError::CurrencyIsEmpty)?;
+    }
+
+    sqlx::query_scalar!(
+        "SELECT id FROM Ingredient WHERE id = ? AND household_id = ?",
+        ingredient_id,
+        household_id
+    )
+    .fetch_optional(&connection)
+    .await?
+    .ok_or(PostPurchaseError::IngredientNotFound(ingredient_id))?;
+
+    let purchase_date = purchase_date.unwrap_or_else(|| chrono::Utc::now().timestamp_millis());
+
+    let data = sqlx::query_as!(
+        IngredientPurchase,
+        r#"
+        INSERT INTO IngredientPurchase (
+            purchase_date,
+            ingredient_id,
+            price,
+            currency,
+            package_weight,
+            store
+        ) VALUES (?, ?, ?, ?, ?, ?)
+        RETURNING *;"#,
+        purchase_date,
+        ingredient_id,
+        price,
+        currency,
+        package_weight,
+        store
+    )
+    .fetch_one(&connection)
+    .await?;
+
+    Ok(ServerResponse::success_code(data, StatusCode::CREATED).json())
+}
+
+pub fn post_purchase_docs(op: TransformOperation) -> TransformOperation {
+    op.response::<201, Json<ServerResponse<IngredientPurchase>>>()
+        .response::<500, Json<InternalServerError>>()
+}
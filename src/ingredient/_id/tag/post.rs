@@ -0,0 +1,72 @@
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+use schemars::JsonSchema;
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::{
+    audit::{record_change, snapshot, AuditEntity},
+    etag::IfMatch,
+    server::{ServerResponse, ServerResponseResult},
+    session::AuthUser,
+    state::AppState,
+};
+
+#[derive(Deserialize, JsonSchema)]
+pub struct IngredientId {
+    ingredient_id: i64,
+}
+
+#[derive(Deserialize, JsonSchema)]
+pub struct PostTagBody {
+    name: String,
+}
+
+#[derive(Error, Debug)]
+enum PostTagError {
+    #[error("The tag should not be empty")]
+    EmptyName,
+}
+
+/// Tags the ingredient. Tags ignore case, and tagging it twice with the same tag does nothing.
+pub async fn post_tag(
+    State(AppState { connection, .. }): State<AppState>,
+    user: AuthUser,
+    if_match: IfMatch,
+    Path(IngredientId { ingredient_id }): Path<IngredientId>,
+    Json(PostTagBody { name }): Json<PostTagBody>,
+) -> ServerResponseResult<bool> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(PostTagError::EmptyName)?;
+    }
+    user.check_ingredient(&connection, ingredient_id).await?;
+    let mut transaction = connection.begin().await?;
+    if_match
+        .check(&mut transaction, "Ingredient", ingredient_id)
+        .await?;
+    let before = snapshot(&mut *transaction, AuditEntity::Ingredient, ingredient_id).await?;
+
+    sqlx::query!(
+        "INSERT INTO IngredientTag (ingredient_id, name) VALUES (?, ?) ON CONFLICT DO NOTHING",
+        ingredient_id,
+        name
+    )
+    .execute(&mut *transaction)
+    .await?;
+
+    record_change(
+        &mut *transaction,
+        &user,
+        AuditEntity::Ingredient,
+        ingredient_id,
+        before,
+    )
+    .await?;
+
+    transaction.commit().await?;
+
+    Ok(ServerResponse::success(true).json())
+}
@@ -0,0 +1,16 @@
+use aide::axum::{
+    routing::{delete, post},
+    ApiRouter,
+};
+
+use crate::state::AppState;
+
+mod delete;
+mod post;
+
+pub fn route(state: AppState) -> ApiRouter {
+    ApiRouter::new()
+        .api_route("/", post(post::post_trackable_amount))
+        .api_route("/:trackable_id", delete(delete::delete_trackable_amount))
+        .with_state(state)
+}
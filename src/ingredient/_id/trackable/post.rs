@@ -0,0 +1,81 @@
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+use schemars::JsonSchema;
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::{
+    audit::{record_change, snapshot, AuditEntity},
+    etag::IfMatch,
+    server::{ServerResponse, ServerResponseResult},
+    session::AuthUser,
+    state::AppState,
+};
+
+#[derive(Deserialize, JsonSchema)]
+pub struct IngredientId {
+    ingredient_id: i64,
+}
+
+#[derive(Deserialize, JsonSchema)]
+pub struct PostTrackableAmountBody {
+    trackable_id: i64,
+    /// Amount of the trackable in 100g of the ingredient, in the trackable's unit
+    amount_100g: f64,
+}
+
+#[derive(Error, Debug)]
+enum PostTrackableAmountError {
+    #[error("The amount of a trackable should not be negative")]
+    NegativeAmount,
+}
+
+/// Sets how much of a trackable the ingredient has, replacing the previous amount. Variants of
+/// the ingredient without an amount of their own inherit this one.
+pub async fn post_trackable_amount(
+    State(AppState { connection, .. }): State<AppState>,
+    user: AuthUser,
+    if_match: IfMatch,
+    Path(IngredientId { ingredient_id }): Path<IngredientId>,
+    Json(PostTrackableAmountBody {
+        trackable_id,
+        amount_100g,
+    }): Json<PostTrackableAmountBody>,
+) -> ServerResponseResult<bool> {
+    if amount_100g < 0.0 {
+        return Err(PostTrackableAmountError::NegativeAmount)?;
+    }
+    user.check_ingredient(&connection, ingredient_id).await?;
+    user.check_trackable(&connection, trackable_id).await?;
+    let mut transaction = connection.begin().await?;
+    if_match
+        .check(&mut transaction, "Ingredient", ingredient_id)
+        .await?;
+    let before = snapshot(&mut *transaction, AuditEntity::Ingredient, ingredient_id).await?;
+
+    sqlx::query!(
+        r#"
+        INSERT INTO IngredientTrackable (ingredient_id, trackable_id, amount_100g) VALUES (?, ?, ?)
+        ON CONFLICT (ingredient_id, trackable_id) DO UPDATE SET amount_100g = excluded.amount_100g"#,
+        ingredient_id,
+        trackable_id,
+        amount_100g
+    )
+    .execute(&mut *transaction)
+    .await?;
+
+    record_change(
+        &mut *transaction,
+        &user,
+        AuditEntity::Ingredient,
+        ingredient_id,
+        before,
+    )
+    .await?;
+
+    transaction.commit().await?;
+
+    Ok(ServerResponse::success(true).json())
+}
@@ -0,0 +1,223 @@
+use std::collections::{HashMap, HashSet};
+
+use axum::extract::{Query, State};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    server::{ServerResponse, ServerResponseResult},
+    session::AuthUser,
+    state::AppState,
+};
+
+/// How similar two names must be to suggest the ingredients are the same, by default.
+const DEFAULT_MIN_SIMILARITY: f64 = 0.6;
+
+#[derive(Deserialize, JsonSchema)]
+pub struct ListDuplicatesQueryParams {
+    /// From 0 to 1. Defaults to 0.6
+    min_similarity: Option<f64>,
+}
+
+#[derive(Serialize, JsonSchema, Clone)]
+pub struct DuplicateCandidate {
+    id: i64,
+    name: String,
+    aliases: Vec<String>,
+    product_code: Option<String>,
+    /// How many dishes and meals use the ingredient. The most used one is usually the one to keep
+    usage_count: i64,
+}
+
+#[derive(Serialize, JsonSchema)]
+pub enum DuplicateReason {
+    /// Both have properties fetched for the same product
+    SameProductCode,
+    /// The names, or the aliases, of both look alike
+    SimilarName,
+}
+
+#[derive(Serialize, JsonSchema)]
+pub struct DuplicateSuggestion {
+    ingredients: [DuplicateCandidate; 2],
+    reason: DuplicateReason,
+    /// From 0 to 1. Always 1 for the same product code
+    similarity: f64,
+}
+
+/// Lowercases the name and keeps only its words, so punctuation and spacing don't matter.
+fn normalize(name: &str) -> String {
+    name.to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<&str>>()
+        .join(" ")
+}
+
+/// Pairs of consecutive characters of the name, padded with spaces so the first and last ones
+/// count too.
+fn bigrams(name: &str) -> Vec<(char, char)> {
+    let chars: Vec<char> = format!(" {name} ").chars().collect();
+    chars.windows(2).map(|pair| (pair[0], pair[1])).collect()
+}
+
+/// Dice coefficient of the bigrams of the names: 1 if they are the same, 0 if they have nothing in
+/// common.
+fn similarity(a: &str, b: &str) -> f64 {
+    let (a, b) = (normalize(a), normalize(b));
+    if a == b {
+        return 1.0;
+    }
+    let (a, mut b) = (bigrams(&a), bigrams(&b));
+    let total = a.len() + b.len();
+    let mut shared = 0;
+    for bigram in a {
+        if let Some(position) = b.iter().position(|other| *other == bigram) {
+            b.swap_remove(position);
+            shared += 1;
+        }
+    }
+    2.0 * shared as f64 / total as f64
+}
+
+/// Pairs of ingredients of the household that are likely the same, most likely first. Any of them
+/// can be merged into the other with `/ingredient/{ingredient_id}/merge`.
+pub async fn list_duplicates(
+    State(AppState { connection, .. }): State<AppState>,
+    AuthUser { household_id, .. }: AuthUser,
+    Query(ListDuplicatesQueryParams { min_similarity }): Query<ListDuplicatesQueryParams>,
+) -> ServerResponseResult<Vec<DuplicateSuggestion>> {
+    let min_similarity = min_similarity.unwrap_or(DEFAULT_MIN_SIMILARITY);
+
+    let rows = sqlx::query!(
+        r#"
+        SELECT
+            Ingredient.id,
+            Ingredient.name,
+            IngredientProperties.product_code AS "product_code?",
+            (SELECT COUNT(*) FROM DishIngredient WHERE ingredient_id = Ingredient.id)
+            + (SELECT COUNT(*) FROM MealIngredient WHERE ingredient_id = Ingredient.id)
+                AS "usage_count!: i64"
+        FROM Ingredient
+            LEFT JOIN IngredientProperties ON IngredientProperties.ingredient_id = Ingredient.id
+        WHERE Ingredient.household_id = ?
+        ORDER BY Ingredient.id;"#,
+        household_id
+    )
+    .fetch_all(&connection)
+    .await?;
+
+    let mut aliases = sqlx::query!(
+        r#"
+        SELECT IngredientAlias.ingredient_id, IngredientAlias.name
+        FROM IngredientAlias
+            JOIN Ingredient ON Ingredient.id = IngredientAlias.ingredient_id
+        WHERE Ingredient.household_id = ?
+        ORDER BY IngredientAlias.name;"#,
+        household_id
+    )
+    .fetch_all(&connection)
+    .await?
+    .into_iter()
+    .fold(HashMap::<i64, Vec<String>>::new(), |mut dict, alias| {
+        dict.entry(alias.ingredient_id)
+            .or_default()
+            .push(alias.name);
+        dict
+    });
+
+    let candidates: Vec<DuplicateCandidate> = rows
+        .into_iter()
+        .map(|row| DuplicateCandidate {
+            id: row.id,
+            aliases: aliases.remove(&row.id).unwrap_or_default(),
+            name: row.name,
+            product_code: row.product_code.filter(|code| !code.is_empty()),
+            usage_count: row.usage_count,
+        })
+        .collect();
+
+    let mut suggestions = vec![];
+    let mut suggested = HashSet::new();
+    for (index, a) in candidates.iter().enumerate() {
+        for b in &candidates[index + 1..] {
+            if a.product_code.is_some() && a.product_code == b.product_code {
+                suggested.insert((a.id, b.id));
+                suggestions.push(DuplicateSuggestion {
+                    ingredients: [a.clone(), b.clone()],
+                    reason: DuplicateReason::SameProductCode,
+                    similarity: 1.0,
+                });
+            }
+        }
+    }
+    for (index, a) in candidates.iter().enumerate() {
+        let a_names: Vec<&String> = std::iter::once(&a.name).chain(&a.aliases).collect();
+        for b in &candidates[index + 1..] {
+            if suggested.contains(&(a.id, b.id)) {
+                continue;
+            }
+            let best = std::iter::once(&b.name)
+                .chain(&b.aliases)
+                .flat_map(|b_name| a_names.iter().map(move |a_name| similarity(a_name, b_name)))
+                .fold(0.0, f64::max);
+            if best >= min_similarity {
+                suggestions.push(DuplicateSuggestion {
+                    ingredients: [a.clone(), b.clone()],
+                    reason: DuplicateReason::SimilarName,
+                    similarity: best,
+                });
+            }
+        }
+    }
+
+    suggestions.sort_by(|a, b| b.similarity.total_cmp(&a.similarity));
+
+    Ok(ServerResponse::success(suggestions).json())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalize_ignores_case_and_punctuation() {
+        assert_eq!(
+            normalize("  Peanut-Butter, CRUNCHY "),
+            "peanut butter crunchy"
+        );
+        assert_eq!(normalize("Crème fraîche"), "crème fraîche");
+    }
+
+    #[test]
+    fn similarity_is_one_for_the_same_name() {
+        assert_eq!(similarity("Olive oil", "olive  OIL"), 1.0);
+        assert_eq!(similarity("", ""), 1.0);
+    }
+
+    #[test]
+    fn similarity_is_zero_for_unrelated_names() {
+        assert_eq!(similarity("abc", "xyz"), 0.0);
+    }
+
+    #[test]
+    fn similarity_is_symmetric_and_between_zero_and_one() {
+        for (a, b) in [
+            ("Tomato", "Tomatoes"),
+            ("Rice", "Brown rice"),
+            ("Milk", "Oat milk drink"),
+        ] {
+            let similarity_ab = similarity(a, b);
+            assert_eq!(similarity_ab, similarity(b, a));
+            assert!(similarity_ab > 0.0 && similarity_ab < 1.0);
+        }
+        assert!(similarity("Tomato", "Tomatoes") > DEFAULT_MIN_SIMILARITY);
+        assert!(similarity("Tomato", "Potato") < similarity("Tomato", "Tomatoes"));
+    }
+
+    #[test]
+    fn similarity_counts_repeated_bigrams_once_each() {
+        // " a", "aa", "aa", "a " and " a", "aa", "a ": 3 shared out of 7
+        assert_eq!(similarity("aaa", "aa"), 6.0 / 7.0);
+    }
+}
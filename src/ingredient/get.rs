@@ -1,26 +1,120 @@
 use aide::transform::TransformOperation;
-use axum::{extract::State, http::StatusCode, Json};
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    Json,
+};
 use schemars::JsonSchema;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 use crate::{
     app_error::InternalServerError,
-    models::Ingredient,
+    language::AcceptLanguage,
     server::{ServerResponse, ServerResponseResult},
+    session::AuthUser,
     state::AppState,
 };
 
+#[derive(Deserialize, JsonSchema)]
+pub struct ListIngredientsQueryParams {
+    /// Only ingredients whose name, one of whose aliases, or one of whose names in other
+    /// languages contains this text, ignoring case
+    search: Option<String>,
+    /// Only ingredients in this category or in one of its descendants. Variants are in the
+    /// categories of their generic ingredient too
+    category_id: Option<i64>,
+    /// Only ingredients with this tag, ignoring case
+    tag: Option<String>,
+}
+
+#[derive(Serialize, JsonSchema)]
+pub struct ListedIngredient {
+    pub id: i64,
+    pub creation_date: i64,
+    pub name: String,
+    pub version: i64,
+    /// Generic ingredient this one is a variant of
+    pub generic_id: Option<i64>,
+    /// Name in the first language of the `Accept-Language` header the ingredient has a name in,
+    /// or `name` otherwise
+    pub display_name: String,
+}
+
 #[derive(Serialize, JsonSchema)]
 pub struct GetIngredientResponse {
-    pub ingredients: Vec<Ingredient>,
+    pub ingredients: Vec<ListedIngredient>,
 }
 
 pub async fn list_ingredients(
-    State(AppState { connection }): State<AppState>,
+    State(AppState { connection, .. }): State<AppState>,
+    AuthUser { household_id, .. }: AuthUser,
+    accept_language: AcceptLanguage,
+    Query(ListIngredientsQueryParams {
+        search,
+        category_id,
+        tag,
+    }): Query<ListIngredientsQueryParams>,
 ) -> ServerResponseResult<GetIngredientResponse> {
-    let ingredients = sqlx::query_as!(Ingredient, "select * from Ingredient;")
-        .fetch_all(&connection)
-        .await?;
+    let languages = accept_language.preference();
+    let search = search
+        .map(|search| search.trim().to_string())
+        .filter(|search| !search.is_empty());
+    let ingredients = sqlx::query_as!(
+        ListedIngredient,
+        r#"
+        WITH RECURSIVE Subcategory(id) AS (
+            SELECT ?4
+            UNION
+            SELECT Category.id FROM Category JOIN Subcategory ON Category.parent_id = Subcategory.id
+        )
+        SELECT
+            id,
+            creation_date,
+            name,
+            version,
+            generic_id,
+            COALESCE((
+                SELECT IngredientName.name
+                FROM IngredientName
+                WHERE IngredientName.ingredient_id = Ingredient.id
+                    AND instr(?2, ',' || language || ',') > 0
+                ORDER BY instr(?2, ',' || language || ',')
+                LIMIT 1
+            ), name) AS "display_name!: String"
+        FROM Ingredient
+        WHERE household_id = ?1 AND (
+            ?3 IS NULL
+            OR instr(lower(name), lower(?3)) > 0
+            OR EXISTS (
+                SELECT 1 FROM IngredientAlias
+                WHERE ingredient_id = Ingredient.id AND instr(lower(IngredientAlias.name), lower(?3)) > 0
+            )
+            OR EXISTS (
+                SELECT 1 FROM IngredientName
+                WHERE ingredient_id = Ingredient.id AND instr(lower(IngredientName.name), lower(?3)) > 0
+            )
+        ) AND (
+            ?4 IS NULL
+            OR EXISTS (
+                SELECT 1 FROM IngredientCategory
+                WHERE ingredient_id IN (Ingredient.id, Ingredient.generic_id)
+                    AND category_id IN (SELECT id FROM Subcategory)
+            )
+        ) AND (
+            ?5 IS NULL
+            OR EXISTS (
+                SELECT 1 FROM IngredientTag
+                WHERE ingredient_id = Ingredient.id AND IngredientTag.name = ?5
+            )
+        );"#,
+        household_id,
+        languages,
+        search,
+        category_id,
+        tag
+    )
+    .fetch_all(&connection)
+    .await?;
 
     Ok(
         ServerResponse::success_code(GetIngredientResponse { ingredients }, StatusCode::CREATED)
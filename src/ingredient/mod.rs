@@ -5,8 +5,10 @@ use crate::state::AppState;
 use self::{get::list_ingredients_docs, post::post_ingredient_docs};
 
 mod _id;
+mod duplicate;
 mod get;
 mod post;
+mod usage;
 
 pub fn route(state: AppState) -> ApiRouter {
     ApiRouter::new()
@@ -15,6 +17,8 @@ pub fn route(state: AppState) -> ApiRouter {
             post_with(post::post_ingredient, post_ingredient_docs)
                 .get_with(get::list_ingredients, list_ingredients_docs),
         )
+        .nest_api_service("/duplicate", duplicate::route(state.clone()))
+        .nest_api_service("/usage", usage::route(state.clone()))
         .nest_api_service("/:ingredient_id", _id::route(state.clone()))
         .with_state(state)
 }
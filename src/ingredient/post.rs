@@ -5,8 +5,10 @@ use serde::Deserialize;
 
 use crate::{
     app_error::InternalServerError,
+    audit::{record_change, AuditEntity},
     models::Ingredient,
     server::{ServerResponse, ServerResponseResult},
+    session::AuthUser,
     state::AppState,
 };
 
@@ -16,17 +18,33 @@ pub struct PostIngredientBody {
 }
 
 pub async fn post_ingredient(
-    State(AppState { connection }): State<AppState>,
+    State(AppState { connection, .. }): State<AppState>,
+    user @ AuthUser { household_id, .. }: AuthUser,
     Json(PostIngredientBody { name }): Json<PostIngredientBody>,
 ) -> ServerResponseResult<Ingredient> {
+    let mut transaction = connection.begin().await?;
     let data = sqlx::query_as!(
         Ingredient,
-        "INSERT INTO Ingredient (name) VALUES (?) RETURNING *;",
-        name
+        r#"
+        INSERT INTO Ingredient (name, household_id) VALUES (?, ?)
+        RETURNING id, creation_date, name, version;"#,
+        name,
+        household_id
     )
-    .fetch_one(&connection)
+    .fetch_one(&mut *transaction)
     .await?;
 
+    record_change(
+        &mut *transaction,
+        &user,
+        AuditEntity::Ingredient,
+        data.id,
+        None,
+    )
+    .await?;
+
+    transaction.commit().await?;
+
     Ok(ServerResponse::success_code(data, StatusCode::CREATED).json())
 }
 
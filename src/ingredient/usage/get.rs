@@ -0,0 +1,170 @@
+use std::collections::{HashMap, HashSet};
+
+use axum::extract::{Query, State};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use sqlx::prelude::FromRow;
+
+use crate::{
+    nutrition::fetch_dish_composition,
+    server::{ServerResponse, ServerResponseResult},
+    session::AuthUser,
+    state::AppState,
+};
+
+#[derive(Deserialize, JsonSchema)]
+pub struct ListUsageQueryParams {
+    from: i64,
+    to: i64,
+    /// Only the meals of this household member. Defaults to every member
+    member_id: Option<i64>,
+}
+
+#[derive(Serialize, JsonSchema)]
+pub struct VariantUsage {
+    ingredient_id: i64,
+    name: String,
+    /// Grams eaten, including the ingredient's share of the dishes eaten
+    weight: f64,
+    meal_count: i64,
+}
+
+#[derive(Serialize, JsonSchema)]
+pub struct IngredientUsage {
+    /// Generic ingredient, or an ingredient that is not a variant
+    ingredient_id: i64,
+    name: String,
+    /// Grams eaten of the ingredient and all of its variants, including their share of the
+    /// dishes eaten
+    weight: f64,
+    /// Meals in which the ingredient or any of its variants was eaten
+    meal_count: i64,
+    /// How the usage splits between the generic ingredient itself and each of its variants
+    variants: Vec<VariantUsage>,
+}
+
+#[derive(FromRow)]
+struct EatenComponent {
+    meal_id: i64,
+    dish_id: Option<i64>,
+    ingredient_id: Option<i64>,
+    weight: i64,
+}
+
+#[derive(Default)]
+struct Usage {
+    weight: f64,
+    meal_ids: HashSet<i64>,
+}
+
+/// How much of each ingredient the household ate in the period, most eaten first. Variants are
+/// rolled up to their generic ingredient.
+pub async fn list_usage(
+    State(AppState { connection, .. }): State<AppState>,
+    user @ AuthUser { household_id, .. }: AuthUser,
+    Query(ListUsageQueryParams {
+        from,
+        to,
+        member_id,
+    }): Query<ListUsageQueryParams>,
+) -> ServerResponseResult<Vec<IngredientUsage>> {
+    if let Some(member_id) = member_id {
+        user.check_member(&connection, member_id).await?;
+    }
+
+    let components = sqlx::query_as::<_, EatenComponent>(
+        r#"
+        SELECT meal_id, dish_id, NULL AS ingredient_id, weight
+        FROM Meal
+            JOIN User ON User.id = Meal.user_id
+            JOIN MealDish ON MealDish.meal_id = Meal.id
+        WHERE User.household_id = ?3 AND (?4 IS NULL OR Meal.user_id = ?4)
+            AND Meal.deletion_date IS NULL AND Meal.eat_date >= ?1 AND Meal.eat_date <= ?2
+        UNION ALL
+        SELECT meal_id, NULL AS dish_id, ingredient_id, weight
+        FROM Meal
+            JOIN User ON User.id = Meal.user_id
+            JOIN MealIngredient ON MealIngredient.meal_id = Meal.id
+        WHERE User.household_id = ?3 AND (?4 IS NULL OR Meal.user_id = ?4)
+            AND Meal.deletion_date IS NULL AND Meal.eat_date >= ?1 AND Meal.eat_date <= ?2;"#,
+    )
+    .bind(from)
+    .bind(to)
+    .bind(household_id)
+    .bind(member_id)
+    .fetch_all(&connection)
+    .await?;
+
+    let dishes = fetch_dish_composition(
+        &connection,
+        &components
+            .iter()
+            .filter_map(|c| c.dish_id)
+            .collect::<Vec<i64>>(),
+    )
+    .await?;
+
+    let mut usages = HashMap::<i64, Usage>::new();
+    for component in &components {
+        let eaten = match (component.dish_id, component.ingredient_id) {
+            (Some(dish_id), _) => dishes
+                .get(&dish_id)
+                .into_iter()
+                .flatten()
+                .map(|(ingredient_id, grams)| (*ingredient_id, grams * component.weight as f64))
+                .collect(),
+            (_, Some(ingredient_id)) => vec![(ingredient_id, component.weight as f64)],
+            (None, None) => vec![],
+        };
+        for (ingredient_id, weight) in eaten {
+            let usage = usages.entry(ingredient_id).or_default();
+            usage.weight += weight;
+            usage.meal_ids.insert(component.meal_id);
+        }
+    }
+
+    let ingredients = sqlx::query!(
+        "SELECT id, name, generic_id FROM Ingredient WHERE household_id = ?",
+        household_id
+    )
+    .fetch_all(&connection)
+    .await?
+    .into_iter()
+    .map(|ingredient| (ingredient.id, ingredient))
+    .collect::<HashMap<_, _>>();
+
+    let mut rolled_up = HashMap::<i64, (Usage, Vec<VariantUsage>)>::new();
+    for (ingredient_id, usage) in usages {
+        let Some(ingredient) = ingredients.get(&ingredient_id) else {
+            continue;
+        };
+        let (total, variants) = rolled_up
+            .entry(ingredient.generic_id.unwrap_or(ingredient_id))
+            .or_default();
+        total.weight += usage.weight;
+        total.meal_ids.extend(&usage.meal_ids);
+        variants.push(VariantUsage {
+            ingredient_id,
+            name: ingredient.name.clone(),
+            weight: usage.weight,
+            meal_count: usage.meal_ids.len() as i64,
+        });
+    }
+
+    let mut result = rolled_up
+        .into_iter()
+        .filter_map(|(ingredient_id, (total, mut variants))| {
+            variants.sort_by(|a, b| b.weight.total_cmp(&a.weight));
+            Some(IngredientUsage {
+                ingredient_id,
+                name: ingredients.get(&ingredient_id)?.name.clone(),
+                weight: total.weight,
+                meal_count: total.meal_ids.len() as i64,
+                variants,
+            })
+        })
+        .collect::<Vec<IngredientUsage>>();
+    result.sort_by(|a, b| b.weight.total_cmp(&a.weight));
+
+    Ok(ServerResponse::success(result).json())
+}
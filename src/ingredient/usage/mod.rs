@@ -0,0 +1,11 @@
+use aide::axum::{routing::get, ApiRouter};
+
+use crate::state::AppState;
+
+mod get;
+
+pub fn route(state: AppState) -> ApiRouter {
+    ApiRouter::new()
+        .api_route("/", get(get::list_usage))
+        .with_state(state)
+}
@@ -0,0 +1,67 @@
+use aide::OperationInput;
+use axum::{
+    async_trait,
+    extract::FromRequestParts,
+    http::{header, request::Parts},
+};
+
+/// The languages of the `Accept-Language` header, most preferred first. Ingredients are named in
+/// the first of them they have a name in, or by their own name otherwise.
+pub struct AcceptLanguage(Vec<String>);
+
+impl OperationInput for AcceptLanguage {}
+
+#[async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for AcceptLanguage {
+    type Rejection = std::convert::Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        Ok(AcceptLanguage(
+            parts
+                .headers
+                .get(header::ACCEPT_LANGUAGE)
+                .and_then(|value| value.to_str().ok())
+                .map(parse_languages)
+                .unwrap_or_default(),
+        ))
+    }
+}
+
+/// Parses a header such as `pt-BR, en;q=0.8`. Regional tags are followed by their language, since
+/// most names only have the language, so that one becomes `["pt-br", "pt", "en"]`.
+fn parse_languages(header: &str) -> Vec<String> {
+    let mut weighted: Vec<(String, f64)> = header
+        .split(',')
+        .filter_map(|item| {
+            let mut parts = item.split(';');
+            let tag = parts.next()?.trim().to_lowercase();
+            let quality = parts
+                .find_map(|param| param.trim().strip_prefix("q="))
+                .map_or(Some(1.0), |quality| quality.trim().parse::<f64>().ok())?;
+            (!tag.is_empty() && tag != "*" && quality > 0.0).then_some((tag, quality))
+        })
+        .collect();
+    weighted.sort_by(|(_, a), (_, b)| b.total_cmp(a));
+
+    let mut languages = vec![];
+    for (tag, _) in weighted {
+        let primary = tag.split('-').next().unwrap_or_default().to_string();
+        for language in [tag, primary] {
+            if !language.is_empty() && !languages.contains(&language) {
+                languages.push(language);
+            }
+        }
+    }
+    languages
+}
+
+impl AcceptLanguage {
+    /// The languages as `,pt-br,pt,en,`. Queries bind it to find and rank names with
+    /// `instr(?, ',' || language || ',')`.
+    pub fn preference(&self) -> String {
+        if self.0.is_empty() {
+            return String::new();
+        }
+        format!(",{},", self.0.join(","))
+    }
+}
@@ -1,11 +1,47 @@
 #![allow(non_snake_case)]
 
 mod app_error;
+mod audit;
+mod auth;
+mod carbon;
+mod category;
+mod comment;
+mod component;
+mod delete_preview;
+mod dependency;
+mod diet;
 mod dish;
+mod dose;
+mod etag;
+mod event;
+mod events;
+mod footprint;
+mod goal;
+mod history;
+mod household;
+mod idempotency;
 mod ingredient;
+mod language;
 mod meal;
 mod models;
+mod nutrition;
+mod period;
+mod plan;
+mod price;
+mod purchase;
+mod purge;
+mod quality;
+mod restriction;
 mod server;
+mod session;
+mod share;
+mod shared;
+mod supplement;
+mod sync;
+mod taxonomy;
+mod token;
+mod trackable;
+mod trash;
 
 use schemars::JsonSchema;
 use serde::Deserialize;
@@ -11,11 +11,15 @@ struct Args {
     /// Where to store the database file
     #[arg(short, long)]
     database_path: Option<PathBuf>,
+
+    /// How many days deleted dishes and meals stay in the trash before being purged
+    #[arg(long, default_value = "30")]
+    trash_retention_days: u32,
 }
 
 #[tokio::main]
 async fn main() {
     let args = Args::parse();
 
-    foodtracker_backend::server(args.port, args.database_path).await;
+    foodtracker_backend::server(args.port, args.database_path, args.trash_retention_days).await;
 }
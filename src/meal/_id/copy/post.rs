@@ -0,0 +1,257 @@
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{
+    audit::{record_change, AuditEntity},
+    component::{fetch_linked_components, insert_components, Component, LinkedComponent},
+    etag::fetch_version,
+    events::DomainEvent,
+    models::Meal,
+    server::{ServerResponse, ServerResponseResult},
+    session::AuthUser,
+    state::AppState,
+};
+
+#[derive(Deserialize, JsonSchema)]
+pub struct MealId {
+    meal_id: i64,
+}
+
+#[derive(Deserialize, JsonSchema)]
+pub struct PostCopyBody {
+    eat_date: i64,
+    /// Multiplies the weight of every component. Defaults to 1
+    scale: Option<f64>,
+    /// Defaults to the original meal's duration
+    duration: Option<i64>,
+    /// Defaults to the original meal's description
+    description: Option<String>,
+}
+
+#[derive(Serialize, JsonSchema)]
+pub enum CopyWarningKind {
+    /// The dish is in the trash, so it was left out of the copy
+    DishInTrash,
+    /// The dish was already marked as finished
+    DishFinished,
+    /// More of the dish was copied than what is left of it
+    NotEnoughLeft {
+        remaining_weight: i64,
+        copied_weight: i64,
+    },
+}
+
+#[derive(Serialize, JsonSchema)]
+pub struct CopyWarning {
+    dish_id: i64,
+    dish_name: Option<String>,
+    kind: CopyWarningKind,
+}
+
+#[derive(Serialize, JsonSchema)]
+pub struct PostCopyResult {
+    meal: Meal,
+    components: Vec<LinkedComponent>,
+    warnings: Vec<CopyWarning>,
+}
+
+#[derive(Error, Debug)]
+enum PostCopyError {
+    #[error("Could not find meal with id \"{0}\"")]
+    MealNotFound(i64),
+    #[error("Scale {0} is invalid. It must be larger than 0")]
+    InvalidScale(f64),
+}
+
+struct DishRemaining {
+    id: i64,
+    name: Option<String>,
+    is_finished: i64,
+    is_deleted: bool,
+    remaining_weight: i64,
+}
+
+pub async fn post_copy(
+    State(AppState {
+        connection, events, ..
+    }): State<AppState>,
+    user @ AuthUser { user_id, .. }: AuthUser,
+    Path(MealId { meal_id }): Path<MealId>,
+    Json(PostCopyBody {
+        eat_date,
+        scale,
+        duration,
+        description,
+    }): Json<PostCopyBody>,
+) -> ServerResponseResult<PostCopyResult> {
+    let scale = scale.unwrap_or(1.0);
+    if scale.is_nan() || scale <= 0.0 {
+        return Err(PostCopyError::InvalidScale(scale))?;
+    }
+
+    let original = sqlx::query_as!(
+        Meal,
+        r#"
+        SELECT
+            id,
+            creation_date,
+            duration,
+            description,
+            eat_date,
+            version
+        FROM Meal
+        WHERE id = ? AND user_id = ? AND deletion_date IS NULL;"#,
+        meal_id,
+        user_id
+    )
+    .fetch_optional(&connection)
+    .await?
+    .ok_or(PostCopyError::MealNotFound(meal_id))?;
+
+    let original_components = fetch_linked_components(&connection, "Meal", "meal_id", &[meal_id])
+        .await?
+        .remove(&meal_id)
+        .unwrap_or_default();
+
+    let scaled = |weight: i64| ((weight as f64 * scale).round() as i64).max(1);
+    let (mut dishes, ingredients) = original_components.iter().fold(
+        (vec![], vec![]),
+        |(mut dishes, mut ingredients), component| {
+            match (component.dish_id, component.ingredient_id) {
+                (Some(id), _) => dishes.push(Component {
+                    id,
+                    weight: scaled(component.weight),
+                }),
+                (_, Some(id)) => ingredients.push(Component {
+                    id,
+                    weight: scaled(component.weight),
+                }),
+                (None, None) => {}
+            }
+            (dishes, ingredients)
+        },
+    );
+
+    // Computed before the copy is inserted, so it reflects what was left for the new meal
+    let remaining = sqlx::query_as!(
+        DishRemaining,
+        r#"
+        SELECT
+            id,
+            name,
+            is_finished,
+            deletion_date IS NOT NULL AS "is_deleted!: bool",
+            CAST ((
+                CASE
+                    WHEN total_weight > 0 THEN total_weight
+                    ELSE (SELECT TOTAL(weight) FROM DishIngredient WHERE dish_id = Dish.id)
+                END - (
+                    SELECT TOTAL(MealDish.weight)
+                    FROM MealDish JOIN Meal ON Meal.id = MealDish.meal_id
+                    WHERE dish_id = Dish.id AND Meal.deletion_date IS NULL
+                )
+            ) AS INTEGER) AS "remaining_weight!: i64"
+        FROM Dish
+        WHERE id IN (SELECT dish_id FROM MealDish WHERE meal_id = ?);"#,
+        meal_id
+    )
+    .fetch_all(&connection)
+    .await?;
+
+    let warnings: Vec<CopyWarning> = remaining
+        .into_iter()
+        .filter_map(|dish| {
+            let copied_weight = dishes.iter().find(|d| d.id == dish.id)?.weight;
+            let kind = if dish.is_deleted {
+                CopyWarningKind::DishInTrash
+            } else if dish.is_finished != 0 {
+                CopyWarningKind::DishFinished
+            } else if copied_weight > dish.remaining_weight {
+                CopyWarningKind::NotEnoughLeft {
+                    remaining_weight: dish.remaining_weight,
+                    copied_weight,
+                }
+            } else {
+                return None;
+            };
+            Some(CopyWarning {
+                dish_id: dish.id,
+                dish_name: dish.name,
+                kind,
+            })
+        })
+        .collect();
+
+    dishes.retain(|dish| {
+        !warnings
+            .iter()
+            .any(|w| w.dish_id == dish.id && matches!(w.kind, CopyWarningKind::DishInTrash))
+    });
+
+    let duration = duration.or(original.duration);
+    let description = description.or(original.description);
+
+    let mut transaction = connection.begin().await?;
+
+    let mut meal = sqlx::query_as!(
+        Meal,
+        r#"INSERT INTO Meal (
+            eat_date,
+            duration,
+            description,
+            user_id
+        ) VALUES (?, ?, ?, ?)
+        RETURNING id, creation_date, eat_date, duration, description, version;"#,
+        eat_date,
+        duration,
+        description,
+        user_id
+    )
+    .fetch_one(&mut *transaction)
+    .await?;
+
+    insert_components(
+        &mut transaction,
+        "MealDish",
+        "meal_id",
+        meal.id,
+        "dish_id",
+        &dishes,
+    )
+    .await?;
+    insert_components(
+        &mut transaction,
+        "MealIngredient",
+        "meal_id",
+        meal.id,
+        "ingredient_id",
+        &ingredients,
+    )
+    .await?;
+
+    record_change(&mut *transaction, &user, AuditEntity::Meal, meal.id, None).await?;
+
+    transaction.commit().await?;
+
+    // Adding the components bumped the version
+    meal.version = fetch_version(&connection, "Meal", meal.id).await?;
+
+    events.publish(&user, DomainEvent::MealCreated { meal_id: meal.id });
+
+    let components = fetch_linked_components(&connection, "Meal", "meal_id", &[meal.id])
+        .await?
+        .remove(&meal.id)
+        .unwrap_or_default();
+
+    Ok(ServerResponse::success(PostCopyResult {
+        meal,
+        components,
+        warnings,
+    })
+    .json())
+}
@@ -0,0 +1,158 @@
+use axum::extract::{Path, Query, State};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{
+    price::{
+        add_to_totals, fetch_dish_costs, fetch_ingredient_prices, ComponentCost, CurrencyAmount,
+        PriceMethod,
+    },
+    server::{ServerResponse, ServerResponseResult},
+    session::AuthUser,
+    state::AppState,
+};
+
+#[derive(Serialize, JsonSchema)]
+pub struct GetMealCostResponse {
+    costs: Vec<CurrencyAmount>,
+    dishes: Vec<ComponentCost>,
+    ingredients: Vec<ComponentCost>,
+    /// Whether some of the components have no price information, and were left out of the cost
+    has_missing_prices: bool,
+}
+
+#[derive(Error, Debug)]
+enum GetMealCostError {
+    #[error("Could not find meal with id \"{0}\"")]
+    MealNotFound(i64),
+}
+
+#[derive(Deserialize, JsonSchema)]
+pub struct MealId {
+    meal_id: i64,
+}
+
+#[derive(Deserialize, JsonSchema)]
+pub struct GetCostQueryParams {
+    method: Option<PriceMethod>,
+}
+
+struct DatabaseComponent {
+    id: i64,
+    name: Option<String>,
+    weight: i64,
+}
+
+pub async fn get_cost(
+    State(AppState { connection, .. }): State<AppState>,
+    AuthUser { user_id, .. }: AuthUser,
+    Path(MealId { meal_id }): Path<MealId>,
+    Query(GetCostQueryParams { method }): Query<GetCostQueryParams>,
+) -> ServerResponseResult<GetMealCostResponse> {
+    let method = method.unwrap_or_default();
+
+    sqlx::query_scalar!(
+        "SELECT id FROM Meal WHERE id = ? AND user_id = ? AND deletion_date IS NULL",
+        meal_id,
+        user_id
+    )
+    .fetch_optional(&connection)
+    .await?
+    .ok_or(GetMealCostError::MealNotFound(meal_id))?;
+
+    let meal_ingredients = sqlx::query_as!(
+        DatabaseComponent,
+        r#"
+        SELECT
+            Ingredient.id,
+            Ingredient.name AS "name?",
+            MealIngredient.weight
+        FROM MealIngredient
+            JOIN Ingredient ON Ingredient.id = MealIngredient.ingredient_id
+        WHERE MealIngredient.meal_id = ?;"#,
+        meal_id
+    )
+    .fetch_all(&connection)
+    .await?;
+
+    let meal_dishes = sqlx::query_as!(
+        DatabaseComponent,
+        r#"
+        SELECT
+            Dish.id,
+            Dish.name,
+            MealDish.weight
+        FROM MealDish
+            JOIN Dish ON Dish.id = MealDish.dish_id
+        WHERE MealDish.meal_id = ?;"#,
+        meal_id
+    )
+    .fetch_all(&connection)
+    .await?;
+
+    let ingredient_ids = meal_ingredients.iter().map(|i| i.id).collect::<Vec<i64>>();
+    let dish_ids = meal_dishes.iter().map(|d| d.id).collect::<Vec<i64>>();
+    let (prices, dish_costs) = futures::try_join!(
+        fetch_ingredient_prices(&connection, method, &ingredient_ids),
+        fetch_dish_costs(&connection, method, &dish_ids),
+    )?;
+
+    let mut costs = vec![];
+    let mut has_missing_prices = false;
+
+    let ingredients = meal_ingredients
+        .into_iter()
+        .map(|DatabaseComponent { id, name, weight }| {
+            let component_costs = match prices.get(&id) {
+                Some(price) => vec![CurrencyAmount {
+                    currency: price.currency.clone(),
+                    amount: price.price_per_gram * weight as f64,
+                }],
+                None => {
+                    has_missing_prices = true;
+                    vec![]
+                }
+            };
+            component_costs
+                .iter()
+                .for_each(|c| add_to_totals(&mut costs, &c.currency, c.amount));
+            ComponentCost {
+                id,
+                name,
+                weight,
+                costs: component_costs,
+            }
+        })
+        .collect();
+
+    let dishes = meal_dishes
+        .into_iter()
+        .map(|DatabaseComponent { id, name, weight }| {
+            let component_costs = match dish_costs.get(&id) {
+                Some(dish_cost) => {
+                    has_missing_prices |= dish_cost.has_missing_prices;
+                    dish_cost.portion_costs(weight)
+                }
+                None => vec![],
+            };
+            component_costs
+                .iter()
+                .for_each(|c| add_to_totals(&mut costs, &c.currency, c.amount));
+            ComponentCost {
+                id,
+                name,
+                weight,
+                costs: component_costs,
+            }
+        })
+        .collect();
+
+    Ok(ServerResponse::success(GetMealCostResponse {
+        costs,
+        dishes,
+        ingredients,
+        has_missing_prices,
+    })
+    .json())
+}
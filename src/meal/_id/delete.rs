@@ -1,9 +1,13 @@
-use axum::extract::{Path, State};
+use axum::extract::{Path, Query, State};
 use schemars::JsonSchema;
 use serde::Deserialize;
 
 use crate::{
+    audit::{record_change, snapshot, AuditEntity},
+    dependency::{resolve_dependents, DeletePolicyKind, DeletePolicyParams},
+    etag::IfMatch,
     server::{ServerResponse, ServerResponseResult},
+    session::AuthUser,
     state::AppState,
 };
 
@@ -12,21 +16,38 @@ pub struct MealId {
     meal_id: i64,
 }
 
+/// Moves the meal to the trash, with its dishes and ingredients. The policy decides what happens
+/// to the plans, template usages and comments that point to it.
 pub async fn delete_meal(
-    State(AppState { connection }): State<AppState>,
+    State(AppState { connection, .. }): State<AppState>,
+    user: AuthUser,
+    if_match: IfMatch,
     Path(MealId { meal_id }): Path<MealId>,
+    Query(params): Query<DeletePolicyParams>,
 ) -> ServerResponseResult<bool> {
+    user.check_meal(&connection, meal_id).await?;
+    let mut transaction = connection.begin().await?;
+    if_match.check(&mut transaction, "Meal", meal_id).await?;
+    let policy = params.policy(DeletePolicyKind::Cascade)?;
+    policy
+        .check(&mut transaction, &user, AuditEntity::Meal, meal_id)
+        .await?;
+    let before = snapshot(&mut *transaction, AuditEntity::Meal, meal_id).await?;
+
+    resolve_dependents(&mut transaction, &user, AuditEntity::Meal, meal_id, &policy).await?;
+
+    let now = chrono::Utc::now().timestamp_millis();
     sqlx::query!(
-        r#"
-        DELETE FROM MealIngredient WHERE meal_id = ?;
-        DELETE FROM MealDish WHERE meal_id = ?;
-        DELETE FROM Meal WHERE id = ?"#,
-        meal_id,
-        meal_id,
+        "UPDATE Meal SET deletion_date = ? WHERE id = ?",
+        now,
         meal_id,
     )
-    .execute(&connection)
+    .execute(&mut *transaction)
     .await?;
 
+    record_change(&mut *transaction, &user, AuditEntity::Meal, meal_id, before).await?;
+
+    transaction.commit().await?;
+
     Ok(ServerResponse::success(true).json())
 }
@@ -3,7 +3,11 @@ use schemars::JsonSchema;
 use serde::Deserialize;
 
 use crate::{
+    audit::{record_change, snapshot, AuditEntity},
+    etag::IfMatch,
+    events::DomainEvent,
     server::{ServerResponse, ServerResponseResult},
+    session::AuthUser,
     state::AppState,
 };
 
@@ -18,18 +22,33 @@ pub struct MealId {
 }
 
 pub async fn delete_dish(
-    State(AppState { connection }): State<AppState>,
+    State(AppState {
+        connection, events, ..
+    }): State<AppState>,
+    user: AuthUser,
+    if_match: IfMatch,
     Path(DishId { dish_id }): Path<DishId>,
     Path(MealId { meal_id }): Path<MealId>,
 ) -> ServerResponseResult<bool> {
+    user.check_meal(&connection, meal_id).await?;
+    let mut transaction = connection.begin().await?;
+    if_match.check(&mut transaction, "Meal", meal_id).await?;
+    let before = snapshot(&mut *transaction, AuditEntity::Meal, meal_id).await?;
+
     sqlx::query!(
         r#"
         DELETE FROM MealDish WHERE dish_id = ? AND meal_id = ?"#,
         dish_id,
         meal_id,
     )
-    .execute(&connection)
+    .execute(&mut *transaction)
     .await?;
 
+    record_change(&mut *transaction, &user, AuditEntity::Meal, meal_id, before).await?;
+
+    transaction.commit().await?;
+
+    events.publish(&user, DomainEvent::MealUpdated { meal_id });
+
     Ok(ServerResponse::success(true).json())
 }
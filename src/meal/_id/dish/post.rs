@@ -6,7 +6,11 @@ use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 use crate::{
+    audit::{record_change, snapshot, AuditEntity},
+    etag::IfMatch,
+    events::DomainEvent,
     server::{ServerResponse, ServerResponseResult},
+    session::AuthUser,
     state::AppState,
 };
 
@@ -30,10 +34,22 @@ pub struct PostDishResult {
 }
 
 pub async fn post_dish(
-    State(AppState { connection }): State<AppState>,
+    State(AppState {
+        connection, events, ..
+    }): State<AppState>,
+    user: AuthUser,
+    if_match: IfMatch,
     Path(MealId { meal_id }): Path<MealId>,
     Json(PostDishBody { weight, dish_id }): Json<PostDishBody>,
 ) -> ServerResponseResult<PostDishResult> {
+    futures::try_join!(
+        user.check_meal(&connection, meal_id),
+        user.check_dish(&connection, dish_id),
+    )?;
+    let mut transaction = connection.begin().await?;
+    if_match.check(&mut transaction, "Meal", meal_id).await?;
+    let before = snapshot(&mut *transaction, AuditEntity::Meal, meal_id).await?;
+
     let data = sqlx::query_as!(
         PostDishResult,
         r#"
@@ -46,8 +62,14 @@ pub async fn post_dish(
         meal_id,
         weight
     )
-    .fetch_one(&connection)
+    .fetch_one(&mut *transaction)
     .await?;
 
+    record_change(&mut *transaction, &user, AuditEntity::Meal, meal_id, before).await?;
+
+    transaction.commit().await?;
+
+    events.publish(&user, DomainEvent::MealUpdated { meal_id });
+
     Ok(ServerResponse::success(data).json())
 }
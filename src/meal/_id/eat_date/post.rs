@@ -6,8 +6,12 @@ use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 use crate::{
+    audit::{record_change, snapshot, AuditEntity},
+    etag::IfMatch,
+    events::DomainEvent,
     models::Meal,
     server::{ServerResponse, ServerResponseResult},
+    session::AuthUser,
     state::AppState,
 };
 
@@ -27,21 +31,36 @@ pub struct PostEatDateResult {
 }
 
 pub async fn post_eat_date(
-    State(AppState { connection }): State<AppState>,
+    State(AppState {
+        connection, events, ..
+    }): State<AppState>,
+    user: AuthUser,
+    if_match: IfMatch,
     Path(MealId { meal_id: id }): Path<MealId>,
     Json(post_eat_date): Json<PostEatDateBody>,
 ) -> ServerResponseResult<PostEatDateResult> {
+    user.check_meal(&connection, id).await?;
+    let mut transaction = connection.begin().await?;
+    if_match.check(&mut transaction, "Meal", id).await?;
+    let before = snapshot(&mut *transaction, AuditEntity::Meal, id).await?;
+
     let meal = sqlx::query_as!(
         Meal,
         r#"UPDATE Meal SET
             eat_date = ?
         WHERE id = ?
-        RETURNING id as 'id!', creation_date, eat_date, duration, description;"#,
+        RETURNING id as 'id!', creation_date, eat_date, duration, description, version;"#,
         post_eat_date.eat_date,
         id
     )
-    .fetch_one(&connection)
+    .fetch_one(&mut *transaction)
     .await?;
 
+    record_change(&mut *transaction, &user, AuditEntity::Meal, id, before).await?;
+
+    transaction.commit().await?;
+
+    events.publish(&user, DomainEvent::MealUpdated { meal_id: id });
+
     Ok(ServerResponse::success(PostEatDateResult { meal }).json())
 }
@@ -4,8 +4,16 @@ use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
 use crate::{
-    models::Meal,
-    server::{ServerResponse, ServerResponseResult},
+    carbon::{Footprint, FootprintTable},
+    diet::{check_components, DietCheck, DietInfo, RestrictionWarning},
+    dose::DoseTable,
+    etag::{Versioned, VersionedResult},
+    models::{Meal, SupplementDose},
+    nutrition::{
+        Nutrients, TrackableAmount, TrackableAmounts, TrackableTable, Trackables, WeightedComponent,
+    },
+    server::ServerResponse,
+    session::AuthUser,
     state::AppState,
 };
 
@@ -25,6 +33,23 @@ pub struct GetMealResponse {
     meal: Meal,
     dishes: Vec<MealComponent>,
     ingredients: Vec<MealComponent>,
+    /// Allergens and diets of everything in the meal
+    diet: DietInfo,
+    /// Restrictions of the member who ate the meal it goes against
+    warnings: Vec<RestrictionWarning>,
+    footprint: Footprint,
+    /// Supplement doses taken with the meal
+    supplements: Vec<MealSupplementDose>,
+    /// Amounts of each of the household's trackables in the meal, including its supplements
+    trackables: Vec<TrackableAmount>,
+}
+
+#[derive(Serialize, JsonSchema)]
+pub struct MealSupplementDose {
+    dose: SupplementDose,
+    name: String,
+    dose_unit: String,
+    nutrients: Nutrients,
 }
 
 #[derive(Error, Debug)]
@@ -40,6 +65,7 @@ pub struct MealId {
 
 async fn get_meal_table(
     connection: &sqlx::Pool<sqlx::Sqlite>,
+    user_id: i64,
     meal_id: i64,
 ) -> anyhow::Result<Meal> {
     Ok(sqlx::query_as!(
@@ -50,10 +76,12 @@ async fn get_meal_table(
             creation_date,
             duration,
             description,
-            eat_date
+            eat_date,
+            version
         FROM Meal
-        WHERE id = ?;"#,
-        meal_id
+        WHERE id = ? AND user_id = ? AND deletion_date IS NULL;"#,
+        meal_id,
+        user_id
     )
     .fetch_optional(connection)
     .await?
@@ -78,7 +106,7 @@ async fn get_meal_ingredients_table(
         FROM Meal
             JOIN MealIngredient ON Meal.id = MealIngredient.meal_id
             JOIN Ingredient ON MealIngredient.ingredient_id = Ingredient.id
-            LEFT JOIN IngredientProperties ON IngredientProperties.ingredient_id = Ingredient.id
+            LEFT JOIN IngredientNutrition ON IngredientNutrition.ingredient_id = Ingredient.id
         WHERE Meal.id = ?;
         "#,
         meal_id
@@ -87,6 +115,50 @@ async fn get_meal_ingredients_table(
     .await?)
 }
 
+async fn get_meal_doses_table(
+    connection: &sqlx::Pool<sqlx::Sqlite>,
+    meal_id: i64,
+) -> anyhow::Result<Vec<(SupplementDose, String, String)>> {
+    Ok(sqlx::query!(
+        r#"
+        SELECT
+            SupplementDose.id,
+            SupplementDose.creation_date,
+            supplement_id,
+            take_date,
+            doses,
+            meal_id,
+            schedule_id,
+            scheduled_date,
+            Supplement.name,
+            Supplement.dose_unit
+        FROM SupplementDose JOIN Supplement ON Supplement.id = SupplementDose.supplement_id
+        WHERE meal_id = ?
+        ORDER BY take_date;"#,
+        meal_id
+    )
+    .fetch_all(connection)
+    .await?
+    .into_iter()
+    .map(|row| {
+        (
+            SupplementDose {
+                id: row.id,
+                creation_date: row.creation_date,
+                supplement_id: row.supplement_id,
+                take_date: row.take_date,
+                doses: row.doses,
+                meal_id: row.meal_id,
+                schedule_id: row.schedule_id,
+                scheduled_date: row.scheduled_date,
+            },
+            row.name,
+            row.dose_unit,
+        )
+    })
+    .collect())
+}
+
 mod meal_dishes {
     use std::collections::HashMap;
 
@@ -148,7 +220,7 @@ mod meal_dishes {
             FROM
                 DishIngredient
                 LEFT JOIN Ingredient ON Ingredient.id = DishIngredient.ingredient_id
-                LEFT JOIN IngredientProperties ON Ingredient.id = IngredientProperties.ingredient_id
+                LEFT JOIN IngredientNutrition ON Ingredient.id = IngredientNutrition.ingredient_id
             WHERE DishIngredient.dish_id IN 
             "#,
         )
@@ -215,19 +287,77 @@ mod meal_dishes {
 }
 
 pub async fn get_meal(
-    State(AppState { connection }): State<AppState>,
+    State(AppState { connection, .. }): State<AppState>,
+    AuthUser {
+        user_id,
+        household_id,
+    }: AuthUser,
     Path(MealId { meal_id }): Path<MealId>,
-) -> ServerResponseResult<GetMealResponse> {
-    let (meal, dishes, ingredients) = futures::try_join!(
-        get_meal_table(&connection, meal_id),
+) -> VersionedResult<GetMealResponse> {
+    let (meal, dishes, ingredients, doses) = futures::try_join!(
+        get_meal_table(&connection, user_id, meal_id),
         meal_dishes::get_meal_dishes_table(&connection, meal_id),
         get_meal_ingredients_table(&connection, meal_id),
+        get_meal_doses_table(&connection, meal_id),
     )?;
 
-    Ok(ServerResponse::success(GetMealResponse {
-        meal,
-        dishes,
-        ingredients,
-    })
-    .json())
+    let components = dishes
+        .iter()
+        .map(|dish| WeightedComponent {
+            dish_id: Some(dish.id),
+            ingredient_id: None,
+            weight: dish.weight,
+        })
+        .chain(ingredients.iter().map(|ingredient| WeightedComponent {
+            dish_id: None,
+            ingredient_id: Some(ingredient.id),
+            weight: ingredient.weight,
+        }))
+        .collect::<Vec<WeightedComponent>>();
+    let DietCheck { diet, warnings } =
+        check_components(&connection, household_id, Some(user_id), &components).await?;
+
+    let supplement_ids = doses
+        .iter()
+        .map(|(dose, _, _)| dose.supplement_id)
+        .collect::<Vec<i64>>();
+    let (footprint_table, trackable_table, dose_table, trackables) = futures::try_join!(
+        FootprintTable::fetch(&connection, &components),
+        TrackableTable::fetch(&connection, &components),
+        DoseTable::fetch(&connection, &supplement_ids),
+        Trackables::fetch(&connection, household_id),
+    )?;
+    let mut footprint = Footprint::default();
+    let mut amounts = TrackableAmounts::new();
+    for component in &components {
+        footprint += footprint_table.component_footprint(component);
+        trackable_table.add_component(&mut amounts, component);
+    }
+    let supplements = doses
+        .into_iter()
+        .map(|(dose, name, dose_unit)| {
+            dose_table.add_dose(&mut amounts, dose.supplement_id, dose.doses);
+            MealSupplementDose {
+                nutrients: dose_table.dose_nutrients(dose.supplement_id, dose.doses),
+                dose,
+                name,
+                dose_unit,
+            }
+        })
+        .collect();
+
+    Ok(Versioned(
+        meal.version,
+        ServerResponse::success(GetMealResponse {
+            meal,
+            dishes,
+            ingredients,
+            diet,
+            warnings,
+            footprint,
+            supplements,
+            trackables: trackables.amounts(&amounts),
+        })
+        .json(),
+    ))
 }
@@ -2,6 +2,8 @@ use aide::axum::{routing::get, ApiRouter};
 
 use crate::state::AppState;
 
+mod copy;
+mod cost;
 mod delete;
 mod dish;
 mod eat_date;
@@ -20,5 +22,7 @@ pub fn route(state: AppState) -> ApiRouter {
         .nest_api_service("/dish", dish::route(state.clone()))
         .nest_api_service("/ingredient", ingredient::route(state.clone()))
         .nest_api_service("/eat_date", eat_date::route(state.clone()))
+        .nest_api_service("/cost", cost::route(state.clone()))
+        .nest_api_service("/copy", copy::route(state.clone()))
         .with_state(state)
 }
@@ -5,20 +5,18 @@ use axum::{
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use sqlx::QueryBuilder;
-use thiserror::Error;
 
 use crate::{
-    get_missing_items,
-    models::{Meal, NewMeal},
-    server::{ServerResponse, ServerResponseResult},
+    audit::{record_change, snapshot, AuditEntity},
+    component::{check_missing_components, split_components, Component, PostComponent},
+    etag::{fetch_version, IfMatch, Versioned, VersionedResult},
+    events::DomainEvent,
+    models::Meal,
+    server::ServerResponse,
+    session::AuthUser,
     state::AppState,
 };
 
-struct Component {
-    id: i64,
-    weight: i64,
-}
-
 #[derive(Deserialize, JsonSchema)]
 pub struct MealId {
     meal_id: i64,
@@ -32,29 +30,12 @@ pub struct MealComponent {
     pub weight: i64,
 }
 
-#[derive(Deserialize, JsonSchema)]
-pub struct PostMealComponent {
-    weight: i64,
-    dish_id: Option<i64>,
-    ingredient_id: Option<i64>,
-}
-
-impl From<PostMealBody> for NewMeal {
-    fn from(val: PostMealBody) -> Self {
-        NewMeal {
-            eat_date: val.eat_date,
-            duration: val.duration,
-            description: val.description,
-        }
-    }
-}
-
 #[derive(Deserialize, JsonSchema)]
 pub struct PostMealBody {
     pub eat_date: Option<i64>,
     pub duration: Option<i64>,
     pub description: Option<String>,
-    pub components: Vec<PostMealComponent>,
+    pub components: Vec<PostComponent>,
 }
 
 #[derive(Serialize, JsonSchema)]
@@ -64,108 +45,38 @@ pub struct PostMealResult {
     meal_ingredients: Vec<MealComponent>,
 }
 
-#[derive(Error, Debug)]
-enum PostMealError {
-    #[error("The following dishes don't exits: {0:?}")]
-    UnknownDishId(Vec<i64>),
-    #[error("In one of the components provided, there was no dish_id and no ingredient_id")]
-    NoDishIdProvided,
-    #[error("In one of the components provided, both dish_id and ingredient_id were provided")]
-    DishIdAndIngredientIdProvided,
-}
-
-enum ComponentType {
-    Ingredient,
-    Dish,
-}
-
-impl ComponentType {
-    fn to_string(self) -> &'static str {
-        match self {
-            ComponentType::Ingredient => "Ingredient",
-            ComponentType::Dish => "Dish",
-        }
-    }
-}
-
-async fn check_missing_component(
-    connection: &sqlx::Pool<sqlx::Sqlite>,
-    component_type: ComponentType,
-    component_ids: &Vec<Component>,
-) -> anyhow::Result<()> {
-    if component_ids.is_empty() {
-        return Ok(());
-    }
-
-    let unknown_parts = {
-        let ids = component_ids
-            .iter()
-            .map(|item| item.id.to_string())
-            .collect::<Vec<String>>()
-            .join(", ");
-
-        let dishes_in_database = sqlx::QueryBuilder::new("SELECT id FROM ")
-            .push(component_type.to_string())
-            .push(" Dish WHERE id IN (")
-            .push(ids)
-            .push(")")
-            .build_query_scalar::<i64>()
-            .fetch_all(connection)
-            .await?;
-
-        get_missing_items(dishes_in_database, component_ids.iter().map(|i| i.id))
-    };
-
-    if !unknown_parts.is_empty() {
-        return Err(PostMealError::UnknownDishId(unknown_parts))?;
-    }
-    Ok(())
-}
-
 pub async fn post_meal(
-    State(AppState { connection }): State<AppState>,
+    State(AppState {
+        connection, events, ..
+    }): State<AppState>,
+    user: AuthUser,
+    if_match: IfMatch,
     Path(MealId { meal_id: id }): Path<MealId>,
     Json(post_meal): Json<PostMealBody>,
-) -> ServerResponseResult<PostMealResult> {
-    let (dishes, ingredients) = post_meal.components.into_iter().try_fold(
-        (vec![], vec![]),
-        |(mut dishAcc, mut ingredientAcc),
-         PostMealComponent {
-             weight,
-             dish_id,
-             ingredient_id,
-         }| {
-            match (dish_id, ingredient_id) {
-                (None, None) => return Err(PostMealError::NoDishIdProvided),
-                (None, Some(id)) => ingredientAcc.push(Component { id, weight }),
-                (Some(id), None) => dishAcc.push(Component { id, weight }),
-                (Some(_), Some(_)) => return Err(PostMealError::DishIdAndIngredientIdProvided),
-            }
-            Ok((dishAcc, ingredientAcc))
-        },
-    )?;
-
-    futures::try_join!(
-        check_missing_component(&connection, ComponentType::Dish, &dishes),
-        check_missing_component(&connection, ComponentType::Ingredient, &ingredients)
-    )?;
+) -> VersionedResult<PostMealResult> {
+    user.check_meal(&connection, id).await?;
+    let household_id = user.household_id;
+    let (dishes, ingredients) = split_components(post_meal.components)?;
+    check_missing_components(&connection, household_id, &dishes, &ingredients).await?;
 
-    let transaction = connection.begin().await?;
+    let mut transaction = connection.begin().await?;
+    if_match.check(&mut transaction, "Meal", id).await?;
+    let before = snapshot(&mut *transaction, AuditEntity::Meal, id).await?;
 
-    let meal = sqlx::query_as!(
+    let mut meal = sqlx::query_as!(
         Meal,
         r#"UPDATE Meal SET
             eat_date = ?,
             duration = ?,
             description = ?
         WHERE Meal.id = ?
-        RETURNING id as 'id!', creation_date, eat_date, duration, description;"#,
+        RETURNING id as 'id!', creation_date, eat_date, duration, description, version;"#,
         post_meal.eat_date,
         post_meal.duration,
         post_meal.description,
         id
     )
-    .fetch_one(&connection)
+    .fetch_one(&mut *transaction)
     .await?;
 
     sqlx::query!(
@@ -174,7 +85,7 @@ pub async fn post_meal(
         id,
         id
     )
-    .execute(&connection)
+    .execute(&mut *transaction)
     .await?;
 
     let meal_ingredients = if !ingredients.is_empty() {
@@ -187,7 +98,7 @@ pub async fn post_meal(
                 RETURNING meal_id, ingredient_id as id, weight, creation_date"#,
             )
             .build_query_as::<MealComponent>()
-            .fetch_all(&connection)
+            .fetch_all(&mut *transaction)
             .await?
     } else {
         vec![]
@@ -202,18 +113,27 @@ pub async fn post_meal(
                 RETURNING meal_id, dish_id as id, weight, creation_date"#,
             )
             .build_query_as::<MealComponent>()
-            .fetch_all(&connection)
+            .fetch_all(&mut *transaction)
             .await?
     } else {
         vec![]
     };
 
+    record_change(&mut *transaction, &user, AuditEntity::Meal, id, before).await?;
+
     transaction.commit().await?;
 
-    Ok(ServerResponse::success(PostMealResult {
-        meal,
-        meal_dishes,
-        meal_ingredients,
-    })
-    .json())
+    meal.version = fetch_version(&connection, "Meal", id).await?;
+
+    events.publish(&user, DomainEvent::MealUpdated { meal_id: id });
+
+    Ok(Versioned(
+        meal.version,
+        ServerResponse::success(PostMealResult {
+            meal,
+            meal_dishes,
+            meal_ingredients,
+        })
+        .json(),
+    ))
 }
@@ -1,17 +1,27 @@
-use axum::extract::State;
+use axum::extract::{Query, State};
 use schemars::JsonSchema;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 use crate::{
     server::{ServerResponse, ServerResponseResult},
+    session::AuthUser,
     state::AppState,
 };
 
+#[derive(Deserialize, JsonSchema)]
+pub struct GetComponentQueryParams {
+    /// Only ingredients in this category or in one of its descendants, and dishes made with at
+    /// least one of them
+    category_id: Option<i64>,
+}
+
 #[derive(Serialize, JsonSchema)]
 pub struct MealComponent {
     id: i64,
     name: Option<String>,
     component_type: String,
+    /// How many meals were created from templates that contain this component
+    template_usage: i64,
 }
 
 #[derive(Serialize, JsonSchema)]
@@ -20,15 +30,69 @@ pub struct GetComponentResult {
 }
 
 pub async fn get_component(
-    State(AppState { connection }): State<AppState>,
+    State(AppState { connection, .. }): State<AppState>,
+    AuthUser {
+        user_id,
+        household_id,
+    }: AuthUser,
+    Query(GetComponentQueryParams { category_id }): Query<GetComponentQueryParams>,
 ) -> ServerResponseResult<GetComponentResult> {
     let components = sqlx::query_as!(
         MealComponent,
         r#"
-        SELECT id, name, 'Ingredient' as component_type from Ingredient
+        WITH RECURSIVE Subcategory(id) AS (
+            SELECT ?3
+            UNION
+            SELECT Category.id FROM Category JOIN Subcategory ON Category.parent_id = Subcategory.id
+        ),
+        CategoryIngredient(id) AS (
+            SELECT Ingredient.id
+            FROM Ingredient
+                JOIN IngredientCategory
+                    ON IngredientCategory.ingredient_id IN (Ingredient.id, Ingredient.generic_id)
+            WHERE category_id IN (SELECT id FROM Subcategory)
+        )
+        SELECT
+            id,
+            name,
+            'Ingredient' as "component_type!: String",
+            (
+                SELECT COUNT(*)
+                FROM MealTemplateUsage
+                    JOIN MealTemplate ON MealTemplate.id = MealTemplateUsage.template_id
+                    JOIN MealTemplateIngredient
+                        ON MealTemplateIngredient.template_id = MealTemplateUsage.template_id
+                WHERE MealTemplateIngredient.ingredient_id = Ingredient.id
+                    AND MealTemplate.user_id = ?1
+            ) as "template_usage!: i64"
+        from Ingredient
+        WHERE household_id = ?2 AND (?3 IS NULL OR id IN CategoryIngredient)
         UNION
-        SELECT id, name, 'Dish' as component_type from Dish;
-        "#
+        SELECT
+            id,
+            name,
+            'Dish' as component_type,
+            (
+                SELECT COUNT(*)
+                FROM MealTemplateUsage
+                    JOIN MealTemplate ON MealTemplate.id = MealTemplateUsage.template_id
+                    JOIN MealTemplateDish ON MealTemplateDish.template_id = MealTemplateUsage.template_id
+                WHERE MealTemplateDish.dish_id = Dish.id
+                    AND MealTemplate.user_id = ?1
+            ) as template_usage
+        from Dish
+        WHERE household_id = ?2 AND deletion_date IS NULL AND (
+            ?3 IS NULL
+            OR EXISTS (
+                SELECT 1 FROM DishIngredient
+                WHERE dish_id = Dish.id AND ingredient_id IN CategoryIngredient
+            )
+        )
+        ORDER BY 4 DESC;
+        "#,
+        user_id,
+        household_id,
+        category_id
     )
     .fetch_all(&connection)
     .await?;
@@ -6,7 +6,11 @@ use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 use crate::{
+    audit::{record_change, snapshot, AuditEntity},
+    etag::IfMatch,
+    events::DomainEvent,
     server::{ServerResponse, ServerResponseResult},
+    session::AuthUser,
     state::AppState,
 };
 
@@ -37,7 +41,11 @@ pub struct PostComponentResult {
 }
 
 pub async fn post_component(
-    State(AppState { connection }): State<AppState>,
+    State(AppState {
+        connection, events, ..
+    }): State<AppState>,
+    user: AuthUser,
+    if_match: IfMatch,
     Path(MealId { meal_id }): Path<MealId>,
     Json(PostComponentBody {
         weight,
@@ -45,8 +53,14 @@ pub async fn post_component(
         component_type,
     }): Json<PostComponentBody>,
 ) -> ServerResponseResult<PostComponentResult> {
+    user.check_meal(&connection, meal_id).await?;
+    let mut transaction = connection.begin().await?;
+    if_match.check(&mut transaction, "Meal", meal_id).await?;
+    let before = snapshot(&mut *transaction, AuditEntity::Meal, meal_id).await?;
+
     let data = match component_type {
         ComponentType::Dish => {
+            user.check_dish(&connection, component_id).await?;
             sqlx::query_as!(
                 PostComponentResult,
                 r#"
@@ -59,10 +73,11 @@ pub async fn post_component(
                 meal_id,
                 weight
             )
-            .fetch_one(&connection)
+            .fetch_one(&mut *transaction)
             .await?
         }
         ComponentType::Ingredient => {
+            user.check_ingredient(&connection, component_id).await?;
             sqlx::query_as!(
                 PostComponentResult,
                 r#"
@@ -75,10 +90,16 @@ pub async fn post_component(
                 meal_id,
                 weight
             )
-            .fetch_one(&connection)
+            .fetch_one(&mut *transaction)
             .await?
         }
     };
 
+    record_change(&mut *transaction, &user, AuditEntity::Meal, meal_id, before).await?;
+
+    transaction.commit().await?;
+
+    events.publish(&user, DomainEvent::MealUpdated { meal_id });
+
     Ok(ServerResponse::success(data).json())
 }
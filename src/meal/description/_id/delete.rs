@@ -0,0 +1,31 @@
+use axum::extract::{Path, State};
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+use crate::{
+    server::{ServerResponse, ServerResponseResult},
+    session::AuthUser,
+    state::AppState,
+};
+
+#[derive(Deserialize, JsonSchema)]
+pub struct DescriptionId {
+    description_id: i64,
+}
+
+/// Deletes the usual description. Meals that used it keep their description.
+pub async fn delete_description(
+    State(AppState { connection, .. }): State<AppState>,
+    AuthUser { household_id, .. }: AuthUser,
+    Path(DescriptionId { description_id }): Path<DescriptionId>,
+) -> ServerResponseResult<bool> {
+    sqlx::query!(
+        "DELETE FROM UsualMealDescriptions WHERE id = ? AND household_id = ?",
+        description_id,
+        household_id
+    )
+    .execute(&connection)
+    .await?;
+
+    Ok(ServerResponse::success(true).json())
+}
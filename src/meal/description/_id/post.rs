@@ -0,0 +1,101 @@
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+use schemars::JsonSchema;
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::{
+    meal::description::post::check_description,
+    models::UsualMealDescription,
+    server::{ServerResponse, ServerResponseResult},
+    session::AuthUser,
+    state::AppState,
+};
+
+#[derive(Deserialize, JsonSchema)]
+pub struct DescriptionId {
+    description_id: i64,
+}
+
+#[derive(Deserialize, JsonSchema)]
+pub struct PostEditDescriptionBody {
+    description: String,
+    start_minute: Option<i64>,
+    end_minute: Option<i64>,
+    /// Whether meals, templates and plans that use the old description should be renamed too.
+    /// Defaults to true
+    rename_usages: Option<bool>,
+}
+
+#[derive(Error, Debug)]
+enum PostEditDescriptionError {
+    #[error("Could not find description with id \"{0}\"")]
+    DescriptionNotFound(i64),
+}
+
+pub async fn post_edit_description(
+    State(AppState { connection, .. }): State<AppState>,
+    AuthUser { household_id, .. }: AuthUser,
+    Path(DescriptionId { description_id }): Path<DescriptionId>,
+    Json(PostEditDescriptionBody {
+        description,
+        start_minute,
+        end_minute,
+        rename_usages,
+    }): Json<PostEditDescriptionBody>,
+) -> ServerResponseResult<UsualMealDescription> {
+    let description = check_description(&description, start_minute, end_minute)?;
+
+    let mut transaction = connection.begin().await?;
+
+    let old_description = sqlx::query_scalar!(
+        "SELECT description FROM UsualMealDescriptions WHERE id = ? AND household_id = ?",
+        description_id,
+        household_id
+    )
+    .fetch_optional(&mut *transaction)
+    .await?
+    .ok_or(PostEditDescriptionError::DescriptionNotFound(
+        description_id,
+    ))?;
+
+    let data = sqlx::query_as!(
+        UsualMealDescription,
+        r#"
+        UPDATE UsualMealDescriptions SET
+            description = ?,
+            start_minute = ?,
+            end_minute = ?
+        WHERE id = ?
+        RETURNING id as "id!", description, position, start_minute, end_minute;"#,
+        description,
+        start_minute,
+        end_minute,
+        description_id
+    )
+    .fetch_one(&mut *transaction)
+    .await?;
+
+    // Descriptions are shared by the household, so every member's usages are renamed
+    if rename_usages.unwrap_or(true) && old_description != description {
+        for table in ["Meal", "MealTemplate", "PlannedMeal", "RecurringMealPlan"] {
+            sqlx::query(&format!(
+                r#"
+                UPDATE {table} SET description = ?
+                WHERE description = ?
+                    AND user_id IN (SELECT id FROM User WHERE household_id = ?)"#
+            ))
+            .bind(&description)
+            .bind(&old_description)
+            .bind(household_id)
+            .execute(&mut *transaction)
+            .await?;
+        }
+    }
+
+    transaction.commit().await?;
+
+    Ok(ServerResponse::success(data).json())
+}
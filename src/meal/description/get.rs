@@ -2,15 +2,36 @@ use axum::extract::State;
 
 use crate::{
     server::{ServerResponse, ServerResponseResult},
+    session::AuthUser,
     state::AppState,
 };
 
 pub async fn get_descriptions(
-    State(AppState { connection }): State<AppState>,
+    State(AppState { connection, .. }): State<AppState>,
+    AuthUser {
+        user_id,
+        household_id,
+    }: AuthUser,
 ) -> ServerResponseResult<Vec<String>> {
-    let descriptions = sqlx::query_scalar!("SELECT description FROM UsualMealDescriptions;")
-        .fetch_all(&connection)
-        .await?;
+    // Descriptions are sorted by their position. The ones used by the most used templates come
+    // first among those with the same position
+    let descriptions = sqlx::query_scalar!(
+        r#"
+        SELECT description
+        FROM UsualMealDescriptions
+        WHERE household_id = ?
+        ORDER BY position, (
+            SELECT COUNT(*)
+            FROM MealTemplateUsage
+                JOIN MealTemplate ON MealTemplate.id = MealTemplateUsage.template_id
+            WHERE MealTemplate.description = UsualMealDescriptions.description
+                AND MealTemplate.user_id = ?
+        ) DESC, id;"#,
+        household_id,
+        user_id
+    )
+    .fetch_all(&connection)
+    .await?;
 
     Ok(ServerResponse::success(descriptions).json())
 }
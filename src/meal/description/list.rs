@@ -0,0 +1,43 @@
+use axum::extract::State;
+
+use crate::{
+    models::UsualMealDescription,
+    server::{ServerResponse, ServerResponseResult},
+    session::AuthUser,
+    state::AppState,
+};
+
+/// Same as `get_descriptions`, but with the id, position and time window of each description.
+pub async fn list_descriptions(
+    State(AppState { connection, .. }): State<AppState>,
+    AuthUser {
+        user_id,
+        household_id,
+    }: AuthUser,
+) -> ServerResponseResult<Vec<UsualMealDescription>> {
+    let descriptions = sqlx::query_as!(
+        UsualMealDescription,
+        r#"
+        SELECT
+            id,
+            description,
+            position,
+            start_minute,
+            end_minute
+        FROM UsualMealDescriptions
+        WHERE household_id = ?
+        ORDER BY position, (
+            SELECT COUNT(*)
+            FROM MealTemplateUsage
+                JOIN MealTemplate ON MealTemplate.id = MealTemplateUsage.template_id
+            WHERE MealTemplate.description = UsualMealDescriptions.description
+                AND MealTemplate.user_id = ?
+        ) DESC, id;"#,
+        household_id,
+        user_id
+    )
+    .fetch_all(&connection)
+    .await?;
+
+    Ok(ServerResponse::success(descriptions).json())
+}
@@ -1,11 +1,23 @@
-use aide::axum::{routing::get, ApiRouter};
+use aide::axum::{
+    routing::{get, post},
+    ApiRouter,
+};
 
 use crate::state::AppState;
 
+mod _id;
 mod get;
+mod list;
+mod order;
+mod post;
+mod suggestion;
 
 pub fn route(state: AppState) -> ApiRouter {
     ApiRouter::new()
-        .api_route("/", get(get::get_descriptions))
+        .api_route("/", get(get::get_descriptions).post(post::post_description))
+        .api_route("/detailed", get(list::list_descriptions))
+        .api_route("/order", post(order::post_order))
+        .api_route("/suggestion", get(suggestion::get_suggestion))
+        .nest_api_service("/:description_id", _id::route(state.clone()))
         .with_state(state)
 }
@@ -0,0 +1,66 @@
+use axum::{extract::State, Json};
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+use crate::{
+    models::UsualMealDescription,
+    server::{ServerResponse, ServerResponseResult},
+    session::AuthUser,
+    state::AppState,
+};
+
+#[derive(Deserialize, JsonSchema)]
+pub struct PostOrderBody {
+    /// The ids of the descriptions, in the new order. Descriptions left out are moved to the end
+    description_ids: Vec<i64>,
+}
+
+pub async fn post_order(
+    State(AppState { connection, .. }): State<AppState>,
+    AuthUser { household_id, .. }: AuthUser,
+    Json(PostOrderBody { description_ids }): Json<PostOrderBody>,
+) -> ServerResponseResult<Vec<UsualMealDescription>> {
+    let mut transaction = connection.begin().await?;
+
+    let last_position = description_ids.len() as i64 + 1;
+    sqlx::query!(
+        "UPDATE UsualMealDescriptions SET position = ? WHERE household_id = ?",
+        last_position,
+        household_id
+    )
+    .execute(&mut *transaction)
+    .await?;
+
+    for (position, id) in description_ids.iter().enumerate() {
+        let position = position as i64 + 1;
+        sqlx::query!(
+            "UPDATE UsualMealDescriptions SET position = ? WHERE id = ? AND household_id = ?",
+            position,
+            id,
+            household_id
+        )
+        .execute(&mut *transaction)
+        .await?;
+    }
+
+    let descriptions = sqlx::query_as!(
+        UsualMealDescription,
+        r#"
+        SELECT
+            id,
+            description,
+            position,
+            start_minute,
+            end_minute
+        FROM UsualMealDescriptions
+        WHERE household_id = ?
+        ORDER BY position, id;"#,
+        household_id
+    )
+    .fetch_all(&mut *transaction)
+    .await?;
+
+    transaction.commit().await?;
+
+    Ok(ServerResponse::success(descriptions).json())
+}
@@ -0,0 +1,97 @@
+use axum::{extract::State, Json};
+use schemars::JsonSchema;
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::{
+    models::UsualMealDescription,
+    server::{ServerResponse, ServerResponseResult},
+    session::AuthUser,
+    state::AppState,
+};
+
+const MINUTES_IN_A_DAY: i64 = 24 * 60;
+
+#[derive(Deserialize, JsonSchema)]
+pub struct PostDescriptionBody {
+    description: String,
+    /// Start of the typical time window of the meal, in minutes since midnight
+    start_minute: Option<i64>,
+    /// End of the typical time window of the meal, in minutes since midnight
+    end_minute: Option<i64>,
+}
+
+#[derive(Error, Debug)]
+pub enum DescriptionError {
+    #[error("The description should not be empty")]
+    DescriptionIsEmpty,
+    #[error("Minute {0} is invalid. It must be between 0 and 1439")]
+    InvalidMinute(i64),
+    #[error("Both start_minute and end_minute must be provided, or neither")]
+    IncompleteWindow,
+}
+
+/// Validates the description and its time window, returning the trimmed description.
+pub fn check_description(
+    description: &str,
+    start_minute: Option<i64>,
+    end_minute: Option<i64>,
+) -> Result<String, DescriptionError> {
+    let description = description.trim();
+    if description.is_empty() {
+        return Err(DescriptionError::DescriptionIsEmpty);
+    }
+    match (start_minute, end_minute) {
+        (Some(start), Some(end)) => {
+            if let Some(minute) = [start, end]
+                .into_iter()
+                .find(|minute| !(0..MINUTES_IN_A_DAY).contains(minute))
+            {
+                return Err(DescriptionError::InvalidMinute(minute));
+            }
+        }
+        (None, None) => {}
+        _ => return Err(DescriptionError::IncompleteWindow),
+    }
+    Ok(description.to_string())
+}
+
+pub async fn post_description(
+    State(AppState { connection, .. }): State<AppState>,
+    AuthUser { household_id, .. }: AuthUser,
+    Json(PostDescriptionBody {
+        description,
+        start_minute,
+        end_minute,
+    }): Json<PostDescriptionBody>,
+) -> ServerResponseResult<UsualMealDescription> {
+    let description = check_description(&description, start_minute, end_minute)?;
+
+    let data = sqlx::query_as!(
+        UsualMealDescription,
+        r#"
+        INSERT INTO UsualMealDescriptions (
+            household_id,
+            description,
+            position,
+            start_minute,
+            end_minute
+        ) VALUES (
+            ?,
+            ?,
+            (SELECT IFNULL(MAX(position), 0) + 1 FROM UsualMealDescriptions WHERE household_id = ?),
+            ?,
+            ?
+        )
+        RETURNING id, description, position, start_minute, end_minute;"#,
+        household_id,
+        description,
+        household_id,
+        start_minute,
+        end_minute
+    )
+    .fetch_one(&connection)
+    .await?;
+
+    Ok(ServerResponse::success(data).json())
+}
@@ -0,0 +1,243 @@
+use axum::extract::{Query, State};
+use chrono::{FixedOffset, TimeZone, Timelike};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{
+    models::UsualMealDescription,
+    period::utc_offset,
+    server::{ServerResponse, ServerResponseResult},
+    session::AuthUser,
+    state::AppState,
+};
+
+const MINUTES_IN_A_DAY: i64 = 24 * 60;
+/// Past meals eaten further than this from the requested time of day are not considered
+const HISTORY_WINDOW_MINUTES: i64 = 90;
+/// Added to the score of descriptions whose time window contains the requested time of day
+const TIME_WINDOW_SCORE: f64 = 0.5;
+/// How many of the most recent meals are considered
+const HISTORY_SIZE: i64 = 1000;
+
+#[derive(Deserialize, JsonSchema)]
+pub struct GetSuggestionQueryParams {
+    /// When the meal is being eaten. Defaults to now
+    timestamp: Option<i64>,
+    /// Offset of the user's timezone, used to compute the time of day
+    utc_offset_minutes: Option<i32>,
+}
+
+#[derive(Serialize, JsonSchema)]
+pub struct DescriptionCandidate {
+    description: UsualMealDescription,
+    score: f64,
+    /// How many past meals with this description were eaten around the same time of day
+    similar_meals: i64,
+    is_in_time_window: bool,
+}
+
+#[derive(Serialize, JsonSchema)]
+pub struct GetSuggestionResult {
+    /// The most likely description, if any of them is likely at all
+    suggestion: Option<UsualMealDescription>,
+    /// Every description, from the most to the least likely
+    candidates: Vec<DescriptionCandidate>,
+}
+
+#[derive(Error, Debug)]
+enum GetSuggestionError {
+    #[error("Invalid timestamp: {0}")]
+    InvalidTimestamp(i64),
+}
+
+struct PastMeal {
+    description: String,
+    eat_date: i64,
+}
+
+fn minute_of_day(offset: &FixedOffset, timestamp: i64) -> Option<i64> {
+    let date = offset.timestamp_millis_opt(timestamp).single()?;
+    Some((date.hour() * 60 + date.minute()) as i64)
+}
+
+/// Distance between two times of day, considering that 23:50 and 00:10 are 20 minutes apart
+fn minute_distance(a: i64, b: i64) -> i64 {
+    let distance = (a - b).abs();
+    distance.min(MINUTES_IN_A_DAY - distance)
+}
+
+fn is_in_time_window(description: &UsualMealDescription, minute: i64) -> bool {
+    match (description.start_minute, description.end_minute) {
+        (Some(start), Some(end)) if start <= end => (start..=end).contains(&minute),
+        (Some(start), Some(end)) => minute >= start || minute <= end,
+        _ => false,
+    }
+}
+
+pub async fn get_suggestion(
+    State(AppState { connection, .. }): State<AppState>,
+    AuthUser {
+        user_id,
+        household_id,
+    }: AuthUser,
+    Query(GetSuggestionQueryParams {
+        timestamp,
+        utc_offset_minutes,
+    }): Query<GetSuggestionQueryParams>,
+) -> ServerResponseResult<GetSuggestionResult> {
+    let offset = utc_offset(utc_offset_minutes)?;
+    let timestamp = timestamp.unwrap_or_else(|| chrono::Utc::now().timestamp_millis());
+    let target_minute =
+        minute_of_day(&offset, timestamp).ok_or(GetSuggestionError::InvalidTimestamp(timestamp))?;
+
+    let descriptions = sqlx::query_as!(
+        UsualMealDescription,
+        r#"
+        SELECT
+            id,
+            description,
+            position,
+            start_minute,
+            end_minute
+        FROM UsualMealDescriptions
+        WHERE household_id = ?
+        ORDER BY position, id;"#,
+        household_id
+    )
+    .fetch_all(&connection)
+    .await?;
+
+    let past_meals = sqlx::query_as!(
+        PastMeal,
+        r#"
+        SELECT
+            description AS "description!",
+            eat_date AS "eat_date!"
+        FROM Meal
+        WHERE eat_date IS NOT NULL AND description IS NOT NULL AND user_id = ? AND deletion_date IS NULL
+        ORDER BY eat_date DESC
+        LIMIT ?;"#,
+        user_id,
+        HISTORY_SIZE
+    )
+    .fetch_all(&connection)
+    .await?;
+
+    // Each past meal contributes more the closer it was eaten to the requested time of day
+    let history_scores = descriptions
+        .iter()
+        .map(|description| {
+            past_meals
+                .iter()
+                .filter(|meal| meal.description == description.description)
+                .filter_map(|meal| minute_of_day(&offset, meal.eat_date))
+                .map(|minute| minute_distance(minute, target_minute))
+                .filter(|distance| *distance <= HISTORY_WINDOW_MINUTES)
+                .fold((0.0, 0), |(score, count), distance| {
+                    (
+                        score + 1.0 - distance as f64 / HISTORY_WINDOW_MINUTES as f64,
+                        count + 1,
+                    )
+                })
+        })
+        .collect::<Vec<(f64, i64)>>();
+    let total_history_score: f64 = history_scores.iter().map(|(score, _)| score).sum();
+
+    let mut candidates = descriptions
+        .into_iter()
+        .zip(history_scores)
+        .map(|(description, (history_score, similar_meals))| {
+            let is_in_time_window = is_in_time_window(&description, target_minute);
+            let history_share = if total_history_score > 0.0 {
+                history_score / total_history_score
+            } else {
+                0.0
+            };
+            let window_score = if is_in_time_window {
+                TIME_WINDOW_SCORE
+            } else {
+                0.0
+            };
+            DescriptionCandidate {
+                description,
+                score: history_share + window_score,
+                similar_meals,
+                is_in_time_window,
+            }
+        })
+        .collect::<Vec<DescriptionCandidate>>();
+
+    // Stable sort, so descriptions with the same score keep their position order
+    candidates.sort_by(|a, b| b.score.total_cmp(&a.score));
+
+    let suggestion = candidates
+        .first()
+        .filter(|candidate| candidate.score > 0.0)
+        .map(|candidate| candidate.description.clone());
+
+    Ok(ServerResponse::success(GetSuggestionResult {
+        suggestion,
+        candidates,
+    })
+    .json())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn description(start_minute: Option<i64>, end_minute: Option<i64>) -> UsualMealDescription {
+        UsualMealDescription {
+            id: 1,
+            description: "Breakfast".to_string(),
+            position: 0,
+            start_minute,
+            end_minute,
+        }
+    }
+
+    #[test]
+    fn minute_distance_wraps_around_midnight() {
+        assert_eq!(minute_distance(600, 630), 30);
+        assert_eq!(minute_distance(630, 600), 30);
+        assert_eq!(minute_distance(23 * 60 + 50, 10), 20);
+        assert_eq!(minute_distance(10, 23 * 60 + 50), 20);
+        assert_eq!(minute_distance(0, 12 * 60), 12 * 60);
+        assert_eq!(minute_distance(300, 300), 0);
+    }
+
+    #[test]
+    fn time_window_includes_its_bounds() {
+        let breakfast = description(Some(7 * 60), Some(9 * 60));
+        assert!(is_in_time_window(&breakfast, 7 * 60));
+        assert!(is_in_time_window(&breakfast, 8 * 60));
+        assert!(is_in_time_window(&breakfast, 9 * 60));
+        assert!(!is_in_time_window(&breakfast, 9 * 60 + 1));
+        assert!(!is_in_time_window(&breakfast, 0));
+    }
+
+    #[test]
+    fn time_window_wraps_around_midnight() {
+        let late_snack = description(Some(23 * 60), Some(60));
+        assert!(is_in_time_window(&late_snack, 23 * 60 + 30));
+        assert!(is_in_time_window(&late_snack, 0));
+        assert!(is_in_time_window(&late_snack, 60));
+        assert!(!is_in_time_window(&late_snack, 12 * 60));
+    }
+
+    #[test]
+    fn missing_time_window_contains_nothing() {
+        assert!(!is_in_time_window(&description(None, None), 0));
+        assert!(!is_in_time_window(&description(Some(0), None), 0));
+        assert!(!is_in_time_window(&description(None, Some(60)), 0));
+    }
+
+    #[test]
+    fn minute_of_day_uses_the_offset() {
+        let offset = FixedOffset::east_opt(2 * 60 * 60).unwrap();
+        // 1970-01-01 23:30 UTC
+        let timestamp = (23 * 60 + 30) * 60 * 1000;
+        assert_eq!(minute_of_day(&offset, timestamp), Some(60 + 30));
+    }
+}
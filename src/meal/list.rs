@@ -3,11 +3,13 @@ use axum::extract::State;
 use crate::{
     models::Meal,
     server::{ServerResponse, ServerResponseResult},
+    session::AuthUser,
     state::AppState,
 };
 
 pub async fn list_meal(
-    State(AppState { connection }): State<AppState>,
+    State(AppState { connection, .. }): State<AppState>,
+    AuthUser { user_id, .. }: AuthUser,
 ) -> ServerResponseResult<Vec<Meal>> {
     let meals = sqlx::query_as!(
         Meal,
@@ -17,9 +19,12 @@ pub async fn list_meal(
             creation_date,
             duration,
             description,
-            eat_date
+            eat_date,
+            version
         FROM Meal
-        ORDER BY eat_date DESC NULLS FIRST;"#
+        WHERE user_id = ? AND deletion_date IS NULL
+        ORDER BY eat_date DESC NULLS FIRST;"#,
+        user_id
     )
     .fetch_all(&connection)
     .await?;
@@ -8,6 +8,7 @@ mod description;
 mod list;
 mod post;
 mod summary;
+mod template;
 
 pub fn route(state: AppState) -> ApiRouter {
     ApiRouter::new()
@@ -15,6 +16,7 @@ pub fn route(state: AppState) -> ApiRouter {
         .nest_api_service("/summary", summary::route(state.clone()))
         .nest_api_service("/description", description::route(state.clone()))
         .nest_api_service("/component", component::route(state.clone()))
+        .nest_api_service("/template", template::route(state.clone()))
         .nest_api_service("/:meal_id", _id::route(state.clone()))
         .with_state(state)
 }
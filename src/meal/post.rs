@@ -2,20 +2,20 @@ use axum::{extract::State, Json};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use sqlx::QueryBuilder;
-use thiserror::Error;
 
 use crate::{
-    get_missing_items,
-    models::{Meal, NewMeal},
+    audit::{record_change, AuditEntity},
+    component::{check_missing_components, split_components, Component, PostComponent},
+    diet::{check_components, RestrictionWarning},
+    etag::fetch_version,
+    events::DomainEvent,
+    models::Meal,
+    nutrition::WeightedComponent,
     server::{ServerResponse, ServerResponseResult},
+    session::AuthUser,
     state::AppState,
 };
 
-struct Component {
-    id: i64,
-    weight: i64,
-}
-
 #[derive(Serialize, sqlx::FromRow, JsonSchema)]
 pub struct MealComponent {
     pub creation_date: i64,
@@ -24,29 +24,12 @@ pub struct MealComponent {
     pub weight: i64,
 }
 
-#[derive(Deserialize, JsonSchema)]
-pub struct PostMealComponent {
-    weight: i64,
-    dish_id: Option<i64>,
-    ingredient_id: Option<i64>,
-}
-
-impl From<PostMealBody> for NewMeal {
-    fn from(val: PostMealBody) -> Self {
-        NewMeal {
-            eat_date: val.eat_date,
-            duration: val.duration,
-            description: val.description,
-        }
-    }
-}
-
 #[derive(Deserialize, JsonSchema)]
 pub struct PostMealBody {
     pub eat_date: Option<i64>,
     pub duration: Option<i64>,
     pub description: Option<String>,
-    pub components: Vec<PostMealComponent>,
+    pub components: Vec<PostComponent>,
 }
 
 #[derive(Serialize, JsonSchema)]
@@ -54,107 +37,40 @@ pub struct PostMealResult {
     meal: Meal,
     meal_dishes: Vec<MealComponent>,
     meal_ingredients: Vec<MealComponent>,
-}
-
-#[derive(Error, Debug)]
-enum PostMealError {
-    #[error("The following dishes don't exits: {0:?}")]
-    UnknownDishId(Vec<i64>),
-    #[error("In one of the components provided, there was no dish_id and no ingredient_id")]
-    NoDishIdProvided,
-    #[error("In one of the components provided, both dish_id and ingredient_id were provided")]
-    DishIdAndIngredientIdProvided,
-}
-
-enum ComponentType {
-    Ingredient,
-    Dish,
-}
-
-impl ComponentType {
-    fn to_string(self) -> &'static str {
-        match self {
-            ComponentType::Ingredient => "Ingredient",
-            ComponentType::Dish => "Dish",
-        }
-    }
-}
-
-async fn check_missing_component(
-    connection: &sqlx::Pool<sqlx::Sqlite>,
-    component_type: ComponentType,
-    component_ids: &Vec<Component>,
-) -> anyhow::Result<()> {
-    if component_ids.is_empty() {
-        return Ok(());
-    }
-
-    let unknown_parts = {
-        let ids = component_ids
-            .iter()
-            .map(|item| item.id.to_string())
-            .collect::<Vec<String>>()
-            .join(", ");
-
-        let dishes_in_database = sqlx::QueryBuilder::new("SELECT id FROM ")
-            .push(component_type.to_string())
-            .push(" Dish WHERE id IN (")
-            .push(ids)
-            .push(")")
-            .build_query_scalar::<i64>()
-            .fetch_all(connection)
-            .await?;
-
-        get_missing_items(dishes_in_database, component_ids.iter().map(|i| i.id))
-    };
-
-    if !unknown_parts.is_empty() {
-        return Err(PostMealError::UnknownDishId(unknown_parts))?;
-    }
-    Ok(())
+    /// Restrictions of the user the meal goes against
+    warnings: Vec<RestrictionWarning>,
 }
 
 pub async fn post_meal(
-    State(AppState { connection }): State<AppState>,
+    State(AppState {
+        connection, events, ..
+    }): State<AppState>,
+    user @ AuthUser {
+        user_id,
+        household_id,
+    }: AuthUser,
     Json(post_meal): Json<PostMealBody>,
 ) -> ServerResponseResult<PostMealResult> {
-    let (dishes, ingredients) = post_meal.components.into_iter().try_fold(
-        (vec![], vec![]),
-        |(mut dishAcc, mut ingredientAcc),
-         PostMealComponent {
-             weight,
-             dish_id,
-             ingredient_id,
-         }| {
-            match (dish_id, ingredient_id) {
-                (None, None) => return Err(PostMealError::NoDishIdProvided),
-                (None, Some(id)) => ingredientAcc.push(Component { id, weight }),
-                (Some(id), None) => dishAcc.push(Component { id, weight }),
-                (Some(_), Some(_)) => return Err(PostMealError::DishIdAndIngredientIdProvided),
-            }
-            Ok((dishAcc, ingredientAcc))
-        },
-    )?;
-
-    futures::try_join!(
-        check_missing_component(&connection, ComponentType::Dish, &dishes),
-        check_missing_component(&connection, ComponentType::Ingredient, &ingredients)
-    )?;
+    let (dishes, ingredients) = split_components(post_meal.components)?;
+    check_missing_components(&connection, household_id, &dishes, &ingredients).await?;
 
-    let transaction = connection.begin().await?;
+    let mut transaction = connection.begin().await?;
 
-    let meal = sqlx::query_as!(
+    let mut meal = sqlx::query_as!(
         Meal,
         r#"INSERT INTO Meal (
             eat_date,
             duration,
-            description
-        ) VALUES (?, ?, ?) RETURNING *;"#,
+            description,
+            user_id
+        ) VALUES (?, ?, ?, ?)
+        RETURNING id, creation_date, eat_date, duration, description, version;"#,
         post_meal.eat_date,
         post_meal.duration,
-        post_meal.description
+        post_meal.description,
+        user_id
     )
-    .fetch_one(&connection)
+    .fetch_one(&mut *transaction)
     .await?;
 
     let meal_ingredients = if !ingredients.is_empty() {
@@ -164,7 +80,7 @@ pub async fn post_meal(
             })
             .push("RETURNING meal_id, ingredient_id as id, weight, creation_date")
             .build_query_as::<MealComponent>()
-            .fetch_all(&connection)
+            .fetch_all(&mut *transaction)
             .await?
     } else {
         vec![]
@@ -176,18 +92,43 @@ pub async fn post_meal(
             })
             .push("RETURNING meal_id, dish_id as id, weight, creation_date")
             .build_query_as::<MealComponent>()
-            .fetch_all(&connection)
+            .fetch_all(&mut *transaction)
             .await?
     } else {
         vec![]
     };
 
+    record_change(&mut *transaction, &user, AuditEntity::Meal, meal.id, None).await?;
+
     transaction.commit().await?;
 
+    // Adding the components bumped the version
+    meal.version = fetch_version(&connection, "Meal", meal.id).await?;
+
+    let components = meal_dishes
+        .iter()
+        .map(|dish| WeightedComponent {
+            dish_id: Some(dish.id),
+            ingredient_id: None,
+            weight: dish.weight,
+        })
+        .chain(meal_ingredients.iter().map(|ingredient| WeightedComponent {
+            dish_id: None,
+            ingredient_id: Some(ingredient.id),
+            weight: ingredient.weight,
+        }))
+        .collect::<Vec<WeightedComponent>>();
+    let warnings = check_components(&connection, household_id, Some(user_id), &components)
+        .await?
+        .warnings;
+
+    events.publish(&user, DomainEvent::MealCreated { meal_id: meal.id });
+
     Ok(ServerResponse::success(PostMealResult {
         meal,
         meal_dishes,
         meal_ingredients,
+        warnings,
     })
     .json())
 }
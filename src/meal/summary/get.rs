@@ -1,240 +1,190 @@
+use std::collections::HashMap;
+
 use axum::extract::{Query, State};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use sqlx::{prelude::FromRow, Sqlite};
-use thiserror::Error;
+use sqlx::prelude::FromRow;
 
 use crate::{
+    dose::{fetch_taken_doses, DoseTable},
     models::Meal,
+    nutrition::{
+        NutrientTable, Nutrients, TrackableAmount, TrackableAmounts, TrackableTable, Trackables,
+        WeightedComponent,
+    },
     server::{ServerResponse, ServerResponseResult},
+    session::AuthUser,
     state::AppState,
 };
 
-#[derive(Serialize, JsonSchema, FromRow, Clone)]
-pub struct MealComponent {
+#[derive(Deserialize, JsonSchema)]
+pub struct GetSummaryQueryParams {
+    eaten_since: i64,
+    /// Household member whose meals should be summarized. Defaults to the current user
+    member_id: Option<i64>,
+}
+
+#[derive(Serialize, JsonSchema)]
+pub struct MealSummary {
+    meal: Meal,
+    /// Total weight eaten in the meal, in grams
     weight: i64,
-    name: Option<String>,
-    id: i64,
-    kcal: Option<i64>,
-    proteins: Option<i64>,
-    fat: Option<i64>,
-    carbohydrates: Option<i64>,
+    /// Nutrients of the meal, including the supplement doses taken with it
+    nutrients: Nutrients,
+    /// Amounts of each of the household's trackables
+    trackables: Vec<TrackableAmount>,
 }
 
-#[derive(Serialize, JsonSchema, Clone)]
-pub struct GetMealResponse {
-    dishes: Vec<MealComponent>,
-    ingredients: Vec<MealComponent>,
+#[derive(Serialize, JsonSchema)]
+pub struct GetSummaryResponse {
+    meals: Vec<MealSummary>,
+    /// Nutrients of the supplement doses taken since `eaten_since` that were not taken with any of
+    /// the meals
+    supplements: Nutrients,
+    /// Sum of the nutrients of every meal and supplement dose
+    total: Nutrients,
+    /// Sum of the trackables of every meal and supplement dose
+    total_trackables: Vec<TrackableAmount>,
 }
 
-#[derive(Error, Debug)]
-enum GetMeal {
-    #[error("Could not find meal with id \"{0}\"")]
-    MealNotFound(i64),
+#[derive(FromRow)]
+struct MealComponent {
+    meal_id: i64,
+    dish_id: Option<i64>,
+    ingredient_id: Option<i64>,
+    weight: i64,
 }
 
-#[derive(Deserialize, JsonSchema)]
-pub struct EatenSince {
-    eaten_since: i64,
+impl From<&MealComponent> for WeightedComponent {
+    fn from(value: &MealComponent) -> Self {
+        WeightedComponent {
+            dish_id: value.dish_id,
+            ingredient_id: value.ingredient_id,
+            weight: value.weight,
+        }
+    }
 }
 
-async fn get_meal_table(
-    connection: &sqlx::Pool<sqlx::Sqlite>,
-    eaten_since: i64,
-) -> anyhow::Result<Vec<Meal>> {
-    Ok(sqlx::query_as!(
+pub async fn get_summary(
+    State(AppState { connection, .. }): State<AppState>,
+    user @ AuthUser { household_id, .. }: AuthUser,
+    Query(GetSummaryQueryParams {
+        eaten_since,
+        member_id,
+    }): Query<GetSummaryQueryParams>,
+) -> ServerResponseResult<GetSummaryResponse> {
+    let member_id = user.resolve_member(&connection, member_id).await?;
+
+    let meals = sqlx::query_as!(
         Meal,
         r#"
         SELECT
             id,
             creation_date,
+            eat_date,
             duration,
             description,
-            eat_date
+            version
         FROM Meal
-        WHERE eat_date > ?;"#,
+        WHERE user_id = ? AND deletion_date IS NULL AND eat_date > ?
+        ORDER BY eat_date;"#,
+        member_id,
         eaten_since
     )
-    .fetch_all(connection)
-    .await?)
-}
-
-mod meal_dishes {
-    use std::collections::HashMap;
-
-    use schemars::JsonSchema;
-    use serde::Serialize;
-    use sqlx::{prelude::FromRow, Sqlite};
+    .fetch_all(&connection)
+    .await?;
 
-    use crate::models::Meal;
-
-    use super::MealComponent;
-
-    #[derive(Serialize, JsonSchema, FromRow)]
-    pub struct DatabaseDish {
-        weight: f64,
-        dish_total_weight: Option<f64>,
-        name: Option<String>,
-        meal_id: i64,
-        dish_id: i64,
-    }
-
-    #[derive(Serialize, JsonSchema, sqlx::FromRow, Clone)]
-    pub struct DatabaseDishIngredient {
-        dish_id: i64,
-        meal_id: i64,
-        ingredient_weight: f64,
-        kcal_100g: Option<f64>,
-        proteins_100g: Option<f64>,
-        fat_100g: Option<f64>,
-        carbohydrates_100g: Option<f64>,
+    let components = sqlx::query_as::<_, MealComponent>(
+        r#"
+        SELECT meal_id, dish_id, NULL AS ingredient_id, weight
+        FROM Meal JOIN MealDish ON MealDish.meal_id = Meal.id
+        WHERE Meal.user_id = ?1 AND Meal.deletion_date IS NULL AND Meal.eat_date > ?2
+        UNION ALL
+        SELECT meal_id, NULL AS dish_id, ingredient_id, weight
+        FROM Meal JOIN MealIngredient ON MealIngredient.meal_id = Meal.id
+        WHERE Meal.user_id = ?1 AND Meal.deletion_date IS NULL AND Meal.eat_date > ?2;"#,
+    )
+    .bind(member_id)
+    .bind(eaten_since)
+    .fetch_all(&connection)
+    .await?;
+
+    let doses = fetch_taken_doses(
+        &connection,
+        household_id,
+        Some(member_id),
+        eaten_since.saturating_add(1),
+        i64::MAX,
+    )
+    .await?;
+
+    let weighted_components = components
+        .iter()
+        .map(WeightedComponent::from)
+        .collect::<Vec<WeightedComponent>>();
+    let supplement_ids = doses
+        .iter()
+        .map(|dose| dose.supplement_id)
+        .collect::<Vec<i64>>();
+    let (table, trackable_table, dose_table, trackables) = futures::try_join!(
+        NutrientTable::fetch(&connection, &weighted_components),
+        TrackableTable::fetch(&connection, &weighted_components),
+        DoseTable::fetch(&connection, &supplement_ids),
+        Trackables::fetch(&connection, household_id),
+    )?;
+
+    let mut meal_totals = HashMap::<i64, (i64, Nutrients, TrackableAmounts)>::new();
+    for component in &components {
+        let (weight, nutrients, amounts) = meal_totals.entry(component.meal_id).or_default();
+        let component = WeightedComponent::from(component);
+        *weight += component.weight;
+        *nutrients += table.component_nutrients(&component);
+        trackable_table.add_component(amounts, &component);
     }
 
-    pub async fn get_meal_dishes_table(
-        connection: &sqlx::Pool<sqlx::Sqlite>,
-        meals: &[Meal],
-    ) -> anyhow::Result<Vec<MealComponent>> {
-        let dishes = sqlx::QueryBuilder::<Sqlite>::new(
-            r#"
-        SELECT 
-            (CAST (MealDish.weight AS FLOAT)) AS weight,
-            (CAST (Dish.total_weight AS FLOAT)) AS dish_total_weight,
-            Dish.name,
-            Dish.id as dish_id,
-            Meal.id as meal_id
-        FROM Meal
-            JOIN MealDish ON Meal.id = MealDish.meal_id
-            JOIN Dish ON MealDish.dish_id = Dish.id
-        WHERE Meal.id IN ?;
-        "#,
-        )
-        .push_tuples(meals.iter(), |mut p, meal| {
-            p.push_bind(meal.id);
-        })
-        .build_query_as::<DatabaseDish>()
-        .fetch_all(connection)
-        .await?;
-
-        let (mut meal_ingredient_dict, dish_ingredients_dict)= sqlx::QueryBuilder::<Sqlite>::new(
-            r#"
-            SELECT
-                iif(kcal_100g IS NOT NULL, CAST (kcal_100g AS FLOAT), NULL) AS kcal_100g,
-                iif(proteins_100g IS NOT NULL, CAST (proteins_100g AS FLOAT), NULL) AS proteins_100g,
-                iif(fat_100g IS NOT NULL, CAST (fat_100g AS FLOAT), NULL) AS fat_100g,
-                iif(carbohydrates_100g IS NOT NULL, CAST (carbohydrates_100g AS FLOAT), NULL) AS carbohydrates_100g,
-                DishIngredient.dish_id,
-                MealDish.meal_id,
-                CAST (DishIngredient.weight AS FLOAT) AS ingredient_weight
-            FROM
-                DishIngredient
-                LEFT JOIN Dish ON Dish.id = DishIngredient.dish_id
-                LEFT JOIN MealDish ON MealDish.dish_id = Dish.id
-                LEFT JOIN Ingredient ON Ingredient.id = DishIngredient.ingredient_id
-                LEFT JOIN IngredientProperties ON Ingredient.id = IngredientProperties.ingredient_id
-            WHERE MealDish.id IN
-            "#
-        )
-        .push_tuples(dishes.iter(), |mut p, dish| {
-             p.push_bind(dish.dish_id);
-        })
-        .build_query_as::<DatabaseDishIngredient>()
-        .fetch_all(connection)
-        .await?
-        .into_iter()
-        .fold(
-            (HashMap::<i64, Vec<DatabaseDishIngredient>>::new(),HashMap::<i64, Vec<DatabaseDishIngredient>>::new()),
-            |(mut mealDict, mut dishDict), ingredient| {
-                    let meal_id = ingredient.meal_id;
-                    let dish_id = ingredient.dish_id;
-                mealDict.entry(meal_id).or_default().push(ingredient.clone());
-                dishDict.entry(dish_id).or_default().push(ingredient);
-                (mealDict,dishDict)
-            },
-        );
-
-        let dishes_dict = dishes.iter().fold(
-            HashMap::<i64, Vec<&DatabaseDish>>::new(),
-            |mut dict, dish| {
-                dict.entry(dish.meal_id).or_default().push(dish);
-                dict
-            },
-        );
-
-        Ok(meals
-            .iter()
-            .map(|meal| {
-                let ingredients = meal_ingredient_dict.remove(&meal.id).unwrap_or_default();
-                let meal_total_weight: f64 = dishes_dict
-                    .get(&meal.id)
-                    .unwrap()
-                    .iter()
-                    .map(|d| {
-                        d.dish_total_weight.unwrap_or_else(|| {
-                            dish_ingredients_dict
-                                .get(&d.dish_id)
-                                .unwrap()
-                                .iter()
-                                .map(|i| i.ingredient_weight)
-                                .sum()
-                        })
-                    })
-                    .sum();
-
-                let nutrients = ingredients
-                    .into_iter()
-                    .map(|i| {
-                        (
-                            i.kcal_100g.unwrap_or_default() * i.ingredient_weight
-                                / meal_total_weight,
-                            i.proteins_100g.unwrap_or_default() * i.ingredient_weight
-                                / meal_total_weight,
-                            i.fat_100g.unwrap_or_default() * i.ingredient_weight
-                                / meal_total_weight,
-                            i.carbohydrates_100g.unwrap_or_default() * i.ingredient_weight
-                                / meal_total_weight,
-                        )
-                    })
-                    .reduce(|(a1, b1, c1, d1), (a2, b2, c2, d2)| {
-                        ((a1 + a2), (b1 + b2), (c1 + c2), (d1 + d2))
-                    });
-
-                MealComponent {
-                    meal_id: meal.id,
-                    weight: meal_total_weight as i64,
-                    name: meal.description.clone(),
-                    id: meal.id,
-                    kcal: nutrients.map(|n| n.0 as i64),
-                    proteins: nutrients.map(|n| n.1 as i64),
-                    fat: nutrients.map(|n| n.2 as i64),
-                    carbohydrates: nutrients.map(|n| n.3 as i64),
-                }
-            })
-            .collect())
+    let mut supplements = Nutrients::default();
+    let mut total_amounts = TrackableAmounts::new();
+    for dose in &doses {
+        let nutrients = dose_table.dose_nutrients(dose.supplement_id, dose.doses);
+        let meal_id = dose
+            .meal_id
+            .filter(|meal_id| meals.iter().any(|meal| meal.id == *meal_id));
+        match meal_id {
+            Some(meal_id) => {
+                let (_, meal_nutrients, amounts) = meal_totals.entry(meal_id).or_default();
+                *meal_nutrients += nutrients;
+                dose_table.add_dose(amounts, dose.supplement_id, dose.doses);
+            }
+            None => {
+                supplements += nutrients;
+                dose_table.add_dose(&mut total_amounts, dose.supplement_id, dose.doses);
+            }
+        }
     }
-}
-
-pub async fn get_summary(
-    State(AppState { connection }): State<AppState>,
-    Query(EatenSince { eaten_since }): Query<EatenSince>,
-) -> ServerResponseResult<Vec<GetMealResponse>> {
-    let meals = get_meal_table(&connection, eaten_since).await?;
-    let dishes = meal_dishes::get_meal_dishes_table(&connection, &meals).await?;
 
+    let mut total = supplements;
     let meals = meals
         .into_iter()
-        .map(|meal| MealComponent {
-            dishes: dishes
-                .iter()
-                .filter(|d| d.meal_id == meal.id)
-                .cloned()
-                .collect::<Vec<MealComponent>>(),
-            ingredients: ingredients
-                .iter()
-                .filter(|i| i.meal_id == meal.id)
-                .cloned()
-                .collect::<Vec<MealComponent>>(),
+        .map(|meal| {
+            let (weight, nutrients, amounts) = meal_totals.remove(&meal.id).unwrap_or_default();
+            total += nutrients;
+            for (trackable_id, amount) in &amounts {
+                *total_amounts.entry(*trackable_id).or_default() += amount;
+            }
+            MealSummary {
+                meal,
+                weight,
+                nutrients,
+                trackables: trackables.amounts(&amounts),
+            }
         })
         .collect();
 
-    Ok(ServerResponse::success(GetMealResponse {}).json())
+    Ok(ServerResponse::success(GetSummaryResponse {
+        meals,
+        supplements,
+        total,
+        total_trackables: trackables.amounts(&total_amounts),
+    })
+    .json())
 }
@@ -0,0 +1,53 @@
+use axum::extract::{Path, State};
+use schemars::JsonSchema;
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::{
+    server::{ServerResponse, ServerResponseResult},
+    session::AuthUser,
+    state::AppState,
+};
+
+#[derive(Deserialize, JsonSchema)]
+pub struct TemplateId {
+    template_id: i64,
+}
+
+#[derive(Error, Debug)]
+enum DeleteTemplateError {
+    #[error("Could not find template with id \"{0}\"")]
+    TemplateNotFound(i64),
+}
+
+/// Deletes the template. Meals created from it are kept.
+pub async fn delete_template(
+    State(AppState { connection, .. }): State<AppState>,
+    AuthUser { user_id, .. }: AuthUser,
+    Path(TemplateId { template_id }): Path<TemplateId>,
+) -> ServerResponseResult<bool> {
+    sqlx::query_scalar!(
+        "SELECT id FROM MealTemplate WHERE id = ? AND user_id = ?",
+        template_id,
+        user_id
+    )
+    .fetch_optional(&connection)
+    .await?
+    .ok_or(DeleteTemplateError::TemplateNotFound(template_id))?;
+
+    sqlx::query!(
+        r#"
+        DELETE FROM MealTemplateUsage WHERE template_id = ?;
+        DELETE FROM MealTemplateDish WHERE template_id = ?;
+        DELETE FROM MealTemplateIngredient WHERE template_id = ?;
+        DELETE FROM MealTemplate WHERE id = ?"#,
+        template_id,
+        template_id,
+        template_id,
+        template_id,
+    )
+    .execute(&connection)
+    .await?;
+
+    Ok(ServerResponse::success(true).json())
+}
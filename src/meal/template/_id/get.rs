@@ -0,0 +1,35 @@
+use axum::extract::{Path, State};
+use schemars::JsonSchema;
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::{
+    meal::template::fetch::{fetch_templates, TemplateResponse},
+    server::{ServerResponse, ServerResponseResult},
+    session::AuthUser,
+    state::AppState,
+};
+
+#[derive(Error, Debug)]
+enum GetTemplateError {
+    #[error("Could not find template with id \"{0}\"")]
+    TemplateNotFound(i64),
+}
+
+#[derive(Deserialize, JsonSchema)]
+pub struct TemplateId {
+    template_id: i64,
+}
+
+pub async fn get_template(
+    State(AppState { connection, .. }): State<AppState>,
+    AuthUser { user_id, .. }: AuthUser,
+    Path(TemplateId { template_id }): Path<TemplateId>,
+) -> ServerResponseResult<TemplateResponse> {
+    let template = fetch_templates(&connection, user_id, Some(template_id))
+        .await?
+        .pop()
+        .ok_or(GetTemplateError::TemplateNotFound(template_id))?;
+
+    Ok(ServerResponse::success(template).json())
+}
@@ -0,0 +1,156 @@
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{
+    audit::{record_change, AuditEntity},
+    component::{
+        check_missing_components, fetch_linked_components, insert_components, split_components,
+        LinkedComponent, PostComponent,
+    },
+    etag::fetch_version,
+    events::DomainEvent,
+    meal::template::fetch::fetch_templates,
+    models::Meal,
+    server::{ServerResponse, ServerResponseResult},
+    session::AuthUser,
+    state::AppState,
+};
+
+#[derive(Deserialize, JsonSchema)]
+pub struct TemplateId {
+    template_id: i64,
+}
+
+#[derive(Deserialize, JsonSchema)]
+pub struct PostTemplateMealBody {
+    pub eat_date: Option<i64>,
+    pub duration: Option<i64>,
+    /// Defaults to the template's description
+    pub description: Option<String>,
+    /// Replaces the weight of the template's components with the same dish_id or ingredient_id.
+    /// Components that are not in the template are added to the meal, and a weight of 0 removes
+    /// the component from the meal.
+    pub overrides: Option<Vec<PostComponent>>,
+}
+
+#[derive(Serialize, JsonSchema)]
+pub struct PostTemplateMealResult {
+    meal: Meal,
+    components: Vec<LinkedComponent>,
+}
+
+#[derive(Error, Debug)]
+enum PostTemplateMealError {
+    #[error("Could not find template with id \"{0}\"")]
+    TemplateNotFound(i64),
+}
+
+pub async fn post_meal(
+    State(AppState {
+        connection, events, ..
+    }): State<AppState>,
+    user @ AuthUser {
+        user_id,
+        household_id,
+    }: AuthUser,
+    Path(TemplateId { template_id }): Path<TemplateId>,
+    Json(PostTemplateMealBody {
+        eat_date,
+        duration,
+        description,
+        overrides,
+    }): Json<PostTemplateMealBody>,
+) -> ServerResponseResult<PostTemplateMealResult> {
+    let template = fetch_templates(&connection, user_id, Some(template_id))
+        .await?
+        .pop()
+        .ok_or(PostTemplateMealError::TemplateNotFound(template_id))?;
+
+    let mut components = template
+        .components
+        .into_iter()
+        .map(PostComponent::from)
+        .collect::<Vec<PostComponent>>();
+    for component_override in overrides.unwrap_or_default() {
+        match components.iter_mut().find(|c| {
+            c.dish_id == component_override.dish_id
+                && c.ingredient_id == component_override.ingredient_id
+        }) {
+            Some(component) => component.weight = component_override.weight,
+            None => components.push(component_override),
+        }
+    }
+    components.retain(|c| c.weight != 0);
+
+    let (dishes, ingredients) = split_components(components)?;
+    check_missing_components(&connection, household_id, &dishes, &ingredients).await?;
+
+    let description = description.or(template.template.description);
+
+    let mut transaction = connection.begin().await?;
+
+    let mut meal = sqlx::query_as!(
+        Meal,
+        r#"INSERT INTO Meal (
+            eat_date,
+            duration,
+            description,
+            user_id
+        ) VALUES (?, ?, ?, ?)
+        RETURNING id, creation_date, eat_date, duration, description, version;"#,
+        eat_date,
+        duration,
+        description,
+        user_id
+    )
+    .fetch_one(&mut *transaction)
+    .await?;
+
+    insert_components(
+        &mut transaction,
+        "MealDish",
+        "meal_id",
+        meal.id,
+        "dish_id",
+        &dishes,
+    )
+    .await?;
+    insert_components(
+        &mut transaction,
+        "MealIngredient",
+        "meal_id",
+        meal.id,
+        "ingredient_id",
+        &ingredients,
+    )
+    .await?;
+
+    sqlx::query!(
+        "INSERT INTO MealTemplateUsage (template_id, meal_id) VALUES (?, ?)",
+        template_id,
+        meal.id
+    )
+    .execute(&mut *transaction)
+    .await?;
+
+    record_change(&mut *transaction, &user, AuditEntity::Meal, meal.id, None).await?;
+
+    transaction.commit().await?;
+
+    // Adding the components bumped the version
+    meal.version = fetch_version(&connection, "Meal", meal.id).await?;
+
+    events.publish(&user, DomainEvent::MealCreated { meal_id: meal.id });
+
+    let components = fetch_linked_components(&connection, "Meal", "meal_id", &[meal.id])
+        .await?
+        .remove(&meal.id)
+        .unwrap_or_default();
+
+    Ok(ServerResponse::success(PostTemplateMealResult { meal, components }).json())
+}
@@ -0,0 +1,20 @@
+use aide::axum::{routing::get, ApiRouter};
+
+use crate::state::AppState;
+
+mod delete;
+mod get;
+mod meal;
+mod post;
+
+pub fn route(state: AppState) -> ApiRouter {
+    ApiRouter::new()
+        .api_route(
+            "/",
+            get(get::get_template)
+                .delete(delete::delete_template)
+                .post(post::post_edit_template),
+        )
+        .nest_api_service("/meal", meal::route(state.clone()))
+        .with_state(state)
+}
@@ -0,0 +1,111 @@
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+use schemars::JsonSchema;
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::{
+    component::{check_missing_components, insert_components, split_components, PostComponent},
+    meal::template::fetch::{fetch_templates, TemplateResponse},
+    server::{ServerResponse, ServerResponseResult},
+    session::AuthUser,
+    state::AppState,
+};
+
+#[derive(Deserialize, JsonSchema)]
+pub struct TemplateId {
+    template_id: i64,
+}
+
+#[derive(Deserialize, JsonSchema)]
+pub struct PostEditTemplateBody {
+    name: String,
+    description: Option<String>,
+    /// Replaces all of the template's components
+    components: Vec<PostComponent>,
+}
+
+#[derive(Error, Debug)]
+enum PostEditTemplateError {
+    #[error("Could not find template with id \"{0}\"")]
+    TemplateNotFound(i64),
+    #[error("The template name should not be empty")]
+    NameIsEmpty,
+}
+
+pub async fn post_edit_template(
+    State(AppState { connection, .. }): State<AppState>,
+    AuthUser {
+        user_id,
+        household_id,
+    }: AuthUser,
+    Path(TemplateId { template_id }): Path<TemplateId>,
+    Json(PostEditTemplateBody {
+        name,
+        description,
+        components,
+    }): Json<PostEditTemplateBody>,
+) -> ServerResponseResult<TemplateResponse> {
+    if name.trim().is_empty() {
+        return Err(PostEditTemplateError::NameIsEmpty)?;
+    }
+    let (dishes, ingredients) = split_components(components)?;
+    check_missing_components(&connection, household_id, &dishes, &ingredients).await?;
+
+    let mut transaction = connection.begin().await?;
+
+    sqlx::query_scalar!(
+        r#"
+        UPDATE MealTemplate SET
+            name = ?,
+            description = ?
+        WHERE id = ? AND user_id = ?
+        RETURNING id;"#,
+        name,
+        description,
+        template_id,
+        user_id
+    )
+    .fetch_optional(&mut *transaction)
+    .await?
+    .ok_or(PostEditTemplateError::TemplateNotFound(template_id))?;
+
+    sqlx::query!(
+        r#"
+        DELETE FROM MealTemplateDish WHERE template_id = ?;
+        DELETE FROM MealTemplateIngredient WHERE template_id = ?;"#,
+        template_id,
+        template_id
+    )
+    .execute(&mut *transaction)
+    .await?;
+
+    insert_components(
+        &mut transaction,
+        "MealTemplateDish",
+        "template_id",
+        template_id,
+        "dish_id",
+        &dishes,
+    )
+    .await?;
+    insert_components(
+        &mut transaction,
+        "MealTemplateIngredient",
+        "template_id",
+        template_id,
+        "ingredient_id",
+        &ingredients,
+    )
+    .await?;
+
+    transaction.commit().await?;
+
+    let template = fetch_templates(&connection, user_id, Some(template_id))
+        .await?
+        .remove(0);
+
+    Ok(ServerResponse::success(template).json())
+}
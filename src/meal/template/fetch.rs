@@ -0,0 +1,78 @@
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use crate::{
+    component::{fetch_linked_components, LinkedComponent},
+    models::MealTemplate,
+};
+
+#[derive(Serialize, JsonSchema)]
+pub struct TemplateResponse {
+    pub template: MealTemplate,
+    pub components: Vec<LinkedComponent>,
+    /// How many meals were created from this template
+    pub usage_count: i64,
+    pub last_used_date: Option<i64>,
+}
+
+struct DatabaseTemplate {
+    id: i64,
+    creation_date: i64,
+    name: String,
+    description: Option<String>,
+    usage_count: i64,
+    last_used_date: Option<i64>,
+}
+
+/// Fetches every template of the user, or only the one with `template_id`, ordered by how often
+/// they were used.
+pub async fn fetch_templates(
+    connection: &sqlx::Pool<sqlx::Sqlite>,
+    user_id: i64,
+    template_id: Option<i64>,
+) -> anyhow::Result<Vec<TemplateResponse>> {
+    let templates = sqlx::query_as!(
+        DatabaseTemplate,
+        r#"
+        SELECT
+            MealTemplate.id AS "id!: i64",
+            MealTemplate.creation_date AS "creation_date!: i64",
+            MealTemplate.name AS "name!: String",
+            MealTemplate.description,
+            COUNT(MealTemplateUsage.meal_id) AS "usage_count!: i64",
+            MAX(MealTemplateUsage.creation_date) AS "last_used_date?: i64"
+        FROM MealTemplate
+            LEFT JOIN MealTemplateUsage ON MealTemplateUsage.template_id = MealTemplate.id
+        WHERE MealTemplate.user_id = ? AND (? IS NULL OR MealTemplate.id = ?)
+        GROUP BY MealTemplate.id
+        ORDER BY 5 DESC, MealTemplate.name;"#,
+        user_id,
+        template_id,
+        template_id
+    )
+    .fetch_all(connection)
+    .await?;
+
+    let mut components = fetch_linked_components(
+        connection,
+        "MealTemplate",
+        "template_id",
+        &templates.iter().map(|t| t.id).collect::<Vec<i64>>(),
+    )
+    .await?;
+
+    Ok(templates
+        .into_iter()
+        .map(|template| TemplateResponse {
+            components: components.remove(&template.id).unwrap_or_default(),
+            usage_count: template.usage_count,
+            last_used_date: template.last_used_date,
+            template: MealTemplate {
+                id: template.id,
+                creation_date: template.creation_date,
+                name: template.name,
+                description: template.description,
+            },
+        })
+        .collect())
+}
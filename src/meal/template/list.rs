@@ -0,0 +1,18 @@
+use axum::extract::State;
+
+use crate::{
+    server::{ServerResponse, ServerResponseResult},
+    session::AuthUser,
+    state::AppState,
+};
+
+use super::fetch::{fetch_templates, TemplateResponse};
+
+pub async fn list_template(
+    State(AppState { connection, .. }): State<AppState>,
+    AuthUser { user_id, .. }: AuthUser,
+) -> ServerResponseResult<Vec<TemplateResponse>> {
+    let templates = fetch_templates(&connection, user_id, None).await?;
+
+    Ok(ServerResponse::success(templates).json())
+}
@@ -0,0 +1,15 @@
+use aide::axum::{routing::post, ApiRouter};
+
+use crate::state::AppState;
+
+mod _id;
+mod fetch;
+mod list;
+mod post;
+
+pub fn route(state: AppState) -> ApiRouter {
+    ApiRouter::new()
+        .api_route("/", post(post::post_template).get(list::list_template))
+        .nest_api_service("/:template_id", _id::route(state.clone()))
+        .with_state(state)
+}
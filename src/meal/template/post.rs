@@ -0,0 +1,91 @@
+use axum::{extract::State, Json};
+use schemars::JsonSchema;
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::{
+    component::{check_missing_components, insert_components, split_components, PostComponent},
+    models::MealTemplate,
+    server::{ServerResponse, ServerResponseResult},
+    session::AuthUser,
+    state::AppState,
+};
+
+use super::fetch::{fetch_templates, TemplateResponse};
+
+#[derive(Deserialize, JsonSchema)]
+pub struct PostTemplateBody {
+    /// Such as "Usual breakfast"
+    name: String,
+    /// Description given to the meals created from this template, such as "Breakfast"
+    description: Option<String>,
+    /// The components, with their default weights
+    components: Vec<PostComponent>,
+}
+
+#[derive(Error, Debug)]
+enum PostTemplateError {
+    #[error("The template name should not be empty")]
+    NameIsEmpty,
+}
+
+pub async fn post_template(
+    State(AppState { connection, .. }): State<AppState>,
+    AuthUser {
+        user_id,
+        household_id,
+    }: AuthUser,
+    Json(PostTemplateBody {
+        name,
+        description,
+        components,
+    }): Json<PostTemplateBody>,
+) -> ServerResponseResult<TemplateResponse> {
+    if name.trim().is_empty() {
+        return Err(PostTemplateError::NameIsEmpty)?;
+    }
+    let (dishes, ingredients) = split_components(components)?;
+    check_missing_components(&connection, household_id, &dishes, &ingredients).await?;
+
+    let mut transaction = connection.begin().await?;
+
+    let template = sqlx::query_as!(
+        MealTemplate,
+        r#"
+        INSERT INTO MealTemplate (name, description, user_id)
+        VALUES (?, ?, ?)
+        RETURNING id, creation_date, name, description;"#,
+        name,
+        description,
+        user_id
+    )
+    .fetch_one(&mut *transaction)
+    .await?;
+
+    insert_components(
+        &mut transaction,
+        "MealTemplateDish",
+        "template_id",
+        template.id,
+        "dish_id",
+        &dishes,
+    )
+    .await?;
+    insert_components(
+        &mut transaction,
+        "MealTemplateIngredient",
+        "template_id",
+        template.id,
+        "ingredient_id",
+        &ingredients,
+    )
+    .await?;
+
+    transaction.commit().await?;
+
+    let template = fetch_templates(&connection, user_id, Some(template.id))
+        .await?
+        .remove(0);
+
+    Ok(ServerResponse::success(template).json())
+}
@@ -1,11 +1,98 @@
 use schemars::JsonSchema;
-use serde::{Deserialize, Serialize};
+use serde::Serialize;
 
 #[derive(Serialize, PartialEq, PartialOrd, JsonSchema)]
 pub struct Ingredient {
     pub id: i64,
     pub creation_date: i64,
     pub name: String,
+    /// Bumped on every change. Sent back in `If-Match` to avoid overwriting someone else's changes
+    pub version: i64,
+}
+
+#[derive(Serialize, sqlx::FromRow, JsonSchema)]
+pub struct IngredientAlias {
+    pub id: i64,
+    pub creation_date: i64,
+    pub ingredient_id: i64,
+    pub name: String,
+}
+
+#[derive(Serialize, sqlx::FromRow, JsonSchema)]
+pub struct IngredientName {
+    pub creation_date: i64,
+    pub ingredient_id: i64,
+    /// Lowercase language tag, such as `en` or `pt-br`
+    pub language: String,
+    pub name: String,
+    /// Either `Manual` or `OpenFoodFacts`
+    pub source: String,
+}
+
+#[derive(Serialize, sqlx::FromRow, JsonSchema, Clone)]
+pub struct Category {
+    pub id: i64,
+    pub creation_date: i64,
+    /// `None` for top level categories
+    pub parent_id: Option<i64>,
+    pub name: String,
+    /// Tag of the category in Open Food Facts, such as `en:brown-rices`, if it was created from there
+    pub open_food_facts_tag: Option<String>,
+}
+
+/// Something tracked besides the built-in nutrients, such as caffeine
+#[derive(Serialize, sqlx::FromRow, JsonSchema, Clone)]
+pub struct Trackable {
+    pub id: i64,
+    pub creation_date: i64,
+    pub name: String,
+    /// Unit the amounts are in, such as `mg`
+    pub unit: String,
+    /// Most that should be had in a day
+    pub daily_limit: Option<f64>,
+}
+
+/// Something taken by the dose instead of being weighed, such as a vitamin or a protein scoop
+#[derive(Serialize, sqlx::FromRow, JsonSchema, Clone)]
+pub struct Supplement {
+    pub id: i64,
+    pub creation_date: i64,
+    pub name: String,
+    /// What a single dose is, such as `capsule`
+    pub dose_unit: String,
+    /// Nutrients in a single dose
+    pub kcal: Option<f64>,
+    pub proteins: Option<f64>,
+    pub fat: Option<f64>,
+    pub carbohydrates: Option<f64>,
+}
+
+#[derive(Serialize, sqlx::FromRow, JsonSchema, Clone)]
+pub struct SupplementDose {
+    pub id: i64,
+    pub creation_date: i64,
+    pub supplement_id: i64,
+    pub take_date: i64,
+    /// How many doses were taken
+    pub doses: f64,
+    /// The meal the dose was taken with
+    pub meal_id: Option<i64>,
+    /// The schedule the dose was taken for, along with the date of the occurrence
+    pub schedule_id: Option<i64>,
+    pub scheduled_date: Option<i64>,
+}
+
+#[derive(Serialize, sqlx::FromRow, JsonSchema, Clone)]
+pub struct SupplementSchedule {
+    pub id: i64,
+    pub creation_date: i64,
+    pub supplement_id: i64,
+    /// How many doses should be taken in each occurrence
+    pub doses: f64,
+    pub start_date: i64,
+    pub end_date: Option<i64>,
+    pub weekdays: i64,
+    pub utc_offset_minutes: i64,
 }
 
 #[derive(Serialize, JsonSchema)]
@@ -16,6 +103,8 @@ pub struct Dish {
     pub name: Option<String>,
     pub total_weight: i64,
     pub is_finished: i64,
+    /// Bumped on every change, including to the dish's ingredients
+    pub version: i64,
 }
 
 #[derive(sqlx::FromRow, Serialize, JsonSchema)]
@@ -26,46 +115,135 @@ pub struct DishIngredient {
     pub weight: i64,
 }
 
-#[derive(JsonSchema)]
-pub struct NewDish {
-    pub name: Option<String>,
-    pub prep_date: Option<i64>,
+#[derive(Serialize, Default, JsonSchema, Clone)]
+pub struct Meal {
+    pub id: i64,
+    pub creation_date: i64,
+    pub eat_date: Option<i64>,
+    pub duration: Option<i64>,
+    pub description: Option<String>,
+    /// Bumped on every change, including to the meal's dishes and ingredients
+    pub version: i64,
 }
 
-#[derive(JsonSchema)]
-pub struct NewDishIngredient {
-    pub dish_id: i64,
+#[derive(Serialize, sqlx::FromRow, JsonSchema)]
+pub struct IngredientPurchase {
+    pub id: i64,
+    pub creation_date: i64,
+    pub purchase_date: i64,
     pub ingredient_id: i64,
-    pub weight: i64,
+    pub price: i64,
+    pub currency: String,
+    pub package_weight: i64,
+    pub store: Option<String>,
 }
 
-#[derive(Serialize, Default, JsonSchema, Clone)]
-pub struct Meal {
+#[derive(Serialize, sqlx::FromRow, JsonSchema, Clone)]
+pub struct PlannedMeal {
     pub id: i64,
     pub creation_date: i64,
-    pub eat_date: Option<i64>,
-    pub duration: Option<i64>,
+    pub plan_date: i64,
     pub description: Option<String>,
+    pub recurring_plan_id: Option<i64>,
+    pub meal_id: Option<i64>,
 }
 
-#[derive(Serialize, sqlx::FromRow, Default, JsonSchema)]
-pub struct MealDish {
+#[derive(Serialize, sqlx::FromRow, JsonSchema, Clone)]
+pub struct RecurringMealPlan {
+    pub id: i64,
     pub creation_date: i64,
-    pub dish_id: i64,
-    pub meal_id: i64,
-    pub weight: i64,
+    pub description: Option<String>,
+    pub start_date: i64,
+    pub end_date: Option<i64>,
+    pub weekdays: i64,
+    pub utc_offset_minutes: i64,
+    pub scheduled_until: i64,
 }
 
-#[derive(Deserialize, Default, JsonSchema)]
-pub struct NewMeal {
-    pub eat_date: Option<i64>,
-    pub duration: Option<i64>,
+#[derive(Serialize, JsonSchema)]
+pub struct NutritionGoal {
+    pub nutrient: String,
+    pub creation_date: i64,
+    pub daily_min: Option<f64>,
+    pub daily_max: Option<f64>,
+}
+
+#[derive(Serialize, sqlx::FromRow, JsonSchema, Clone)]
+pub struct MealTemplate {
+    pub id: i64,
+    pub creation_date: i64,
+    pub name: String,
     pub description: Option<String>,
 }
 
-#[derive(Default, JsonSchema)]
-pub struct NewMealDish {
-    pub dish_id: i64,
-    pub meal_id: i64,
-    pub weight: i64,
+#[derive(Serialize, sqlx::FromRow, JsonSchema, Clone)]
+pub struct UsualMealDescription {
+    pub id: i64,
+    pub description: String,
+    pub position: i64,
+    /// Start of the typical time window of the meal, in minutes since midnight
+    pub start_minute: Option<i64>,
+    /// End of the typical time window of the meal, in minutes since midnight. The window wraps
+    /// around midnight if it's smaller than `start_minute`
+    pub end_minute: Option<i64>,
+}
+
+#[derive(Serialize, sqlx::FromRow, JsonSchema, Clone)]
+pub struct Household {
+    pub id: i64,
+    pub creation_date: i64,
+    pub name: String,
+    /// Code other people can register with to join this household. Left out for API tokens that
+    /// are not admin tokens
+    pub invite_code: Option<String>,
+}
+
+#[derive(Serialize, sqlx::FromRow, JsonSchema, Clone)]
+pub struct User {
+    pub id: i64,
+    pub creation_date: i64,
+    pub username: String,
+    /// Name shown to the other members of the household
+    pub display_name: Option<String>,
+    pub household_id: i64,
+}
+
+#[derive(Serialize, sqlx::FromRow, JsonSchema, Clone)]
+pub struct ApiToken {
+    pub id: i64,
+    pub creation_date: i64,
+    pub name: String,
+    /// One of `ReadOnly`, `LogMeals` or `Admin`
+    pub scope: String,
+    pub last_used_date: Option<i64>,
+}
+
+#[derive(Serialize, sqlx::FromRow, JsonSchema, Clone)]
+pub struct Share {
+    pub id: i64,
+    pub creation_date: i64,
+    /// Who the share is for, such as "Dietitian"
+    pub name: String,
+    /// Only meals eaten between `start_date` and `end_date` are shared
+    pub start_date: i64,
+    pub end_date: i64,
+    /// Whether the free text descriptions of the meals are hidden from the guest
+    pub redact_descriptions: i64,
+    pub expiration_date: Option<i64>,
+    pub last_used_date: Option<i64>,
+}
+
+#[derive(Serialize, sqlx::FromRow, JsonSchema, Clone)]
+pub struct ShareComment {
+    pub id: i64,
+    pub creation_date: i64,
+    pub share_id: i64,
+    /// The meal the comment is about. Either this or `day` is set
+    pub meal_id: Option<i64>,
+    /// The day the comment is about, in the `YYYY-MM-DD` format
+    pub day: Option<String>,
+    pub reply_to_id: Option<i64>,
+    /// Either `Guest` or `Owner`
+    pub author: String,
+    pub body: String,
 }
@@ -0,0 +1,471 @@
+use std::{collections::HashMap, ops::AddAssign};
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use sqlx::{prelude::FromRow, Sqlite};
+
+use crate::models::{NutritionGoal, Trackable};
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Nutrient {
+    Kcal,
+    Proteins,
+    Fat,
+    Carbohydrates,
+}
+
+impl Nutrient {
+    pub const ALL: [Nutrient; 4] = [
+        Nutrient::Kcal,
+        Nutrient::Proteins,
+        Nutrient::Fat,
+        Nutrient::Carbohydrates,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Nutrient::Kcal => "Kcal",
+            Nutrient::Proteins => "Proteins",
+            Nutrient::Fat => "Fat",
+            Nutrient::Carbohydrates => "Carbohydrates",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Nutrient> {
+        Nutrient::ALL.into_iter().find(|n| n.name() == name)
+    }
+}
+
+#[derive(Serialize, JsonSchema, Default, Clone, Copy, Debug)]
+pub struct Nutrients {
+    pub kcal: f64,
+    pub proteins: f64,
+    pub fat: f64,
+    pub carbohydrates: f64,
+}
+
+impl Nutrients {
+    pub fn scale(self, factor: f64) -> Self {
+        Nutrients {
+            kcal: self.kcal * factor,
+            proteins: self.proteins * factor,
+            fat: self.fat * factor,
+            carbohydrates: self.carbohydrates * factor,
+        }
+    }
+
+    pub fn get(&self, nutrient: Nutrient) -> f64 {
+        match nutrient {
+            Nutrient::Kcal => self.kcal,
+            Nutrient::Proteins => self.proteins,
+            Nutrient::Fat => self.fat,
+            Nutrient::Carbohydrates => self.carbohydrates,
+        }
+    }
+}
+
+impl AddAssign for Nutrients {
+    fn add_assign(&mut self, rhs: Self) {
+        self.kcal += rhs.kcal;
+        self.proteins += rhs.proteins;
+        self.fat += rhs.fat;
+        self.carbohydrates += rhs.carbohydrates;
+    }
+}
+
+#[derive(Serialize, JsonSchema)]
+pub enum GoalStatus {
+    Below,
+    Within,
+    Above,
+}
+
+impl GoalStatus {
+    /// Where `amount` of the goal's nutrient stands relative to its daily limits.
+    pub fn new(goal: &NutritionGoal, amount: f64) -> Self {
+        GoalStatus::from_limits(goal.daily_min, goal.daily_max, amount)
+    }
+
+    /// Where `amount` stands relative to a daily minimum and maximum, either of which may be unset.
+    pub fn from_limits(daily_min: Option<f64>, daily_max: Option<f64>, amount: f64) -> Self {
+        match (daily_min, daily_max) {
+            (Some(min), _) if amount < min => GoalStatus::Below,
+            (_, Some(max)) if amount > max => GoalStatus::Above,
+            _ => GoalStatus::Within,
+        }
+    }
+}
+
+#[derive(FromRow)]
+struct DatabaseIngredientNutrients {
+    id: i64,
+    kcal_100g: Option<f64>,
+    proteins_100g: Option<f64>,
+    fat_100g: Option<f64>,
+    carbohydrates_100g: Option<f64>,
+}
+
+impl From<&DatabaseIngredientNutrients> for Nutrients {
+    /// Converts the per 100g values into per gram values. Unknown values are considered zero.
+    fn from(value: &DatabaseIngredientNutrients) -> Self {
+        Nutrients {
+            kcal: value.kcal_100g.unwrap_or_default(),
+            proteins: value.proteins_100g.unwrap_or_default(),
+            fat: value.fat_100g.unwrap_or_default(),
+            carbohydrates: value.carbohydrates_100g.unwrap_or_default(),
+        }
+        .scale(1.0 / 100.0)
+    }
+}
+
+/// Fetches the nutrients in a single gram of every ingredient in `ingredient_ids`. Variants fall
+/// back to their generic ingredient for the nutrients they lack. Ingredients without properties,
+/// or whose generic ingredient has none either, are left out of the result.
+pub async fn fetch_ingredient_nutrients(
+    connection: &sqlx::Pool<sqlx::Sqlite>,
+    ingredient_ids: &[i64],
+) -> anyhow::Result<HashMap<i64, Nutrients>> {
+    if ingredient_ids.is_empty() {
+        return Ok(HashMap::new());
+    }
+
+    Ok(sqlx::QueryBuilder::<Sqlite>::new(
+        r#"
+        SELECT
+            ingredient_id AS id,
+            iif(kcal_100g IS NOT NULL, CAST (kcal_100g AS FLOAT), NULL) AS kcal_100g,
+            iif(proteins_100g IS NOT NULL, CAST (proteins_100g AS FLOAT), NULL) AS proteins_100g,
+            iif(fat_100g IS NOT NULL, CAST (fat_100g AS FLOAT), NULL) AS fat_100g,
+            iif(carbohydrates_100g IS NOT NULL, CAST (carbohydrates_100g AS FLOAT), NULL) AS carbohydrates_100g
+        FROM IngredientNutrition
+        WHERE ingredient_id IN "#,
+    )
+    .push_tuples(ingredient_ids.iter(), |mut p, id| {
+        p.push_bind(*id);
+    })
+    .build_query_as::<DatabaseIngredientNutrients>()
+    .fetch_all(connection)
+    .await?
+    .iter()
+    .map(|ingredient| (ingredient.id, Nutrients::from(ingredient)))
+    .collect())
+}
+
+#[derive(FromRow)]
+struct DatabaseDishIngredient {
+    dish_id: i64,
+    dish_total_weight: i64,
+    ingredient_id: i64,
+    weight: i64,
+}
+
+/// Fetches the grams of each ingredient in a single gram of every dish in `dish_ids`. If the dish
+/// has no total weight, the sum of its ingredients' weights is used. Dishes without any weight are
+/// left out of the result.
+pub async fn fetch_dish_composition(
+    connection: &sqlx::Pool<sqlx::Sqlite>,
+    dish_ids: &[i64],
+) -> anyhow::Result<HashMap<i64, Vec<(i64, f64)>>> {
+    if dish_ids.is_empty() {
+        return Ok(HashMap::new());
+    }
+
+    let dish_ingredients = sqlx::QueryBuilder::<Sqlite>::new(
+        r#"
+        SELECT
+            Dish.id AS dish_id,
+            Dish.total_weight AS dish_total_weight,
+            DishIngredient.ingredient_id,
+            DishIngredient.weight
+        FROM Dish
+            JOIN DishIngredient ON DishIngredient.dish_id = Dish.id
+        WHERE Dish.id IN "#,
+    )
+    .push_tuples(dish_ids.iter(), |mut p, id| {
+        p.push_bind(*id);
+    })
+    .build_query_as::<DatabaseDishIngredient>()
+    .fetch_all(connection)
+    .await?;
+
+    let dishes = dish_ingredients.into_iter().fold(
+        HashMap::<i64, (i64, i64, Vec<(i64, i64)>)>::new(),
+        |mut dict, ingredient| {
+            let (_, ingredients_weight, ingredients) =
+                dict.entry(ingredient.dish_id)
+                    .or_insert((ingredient.dish_total_weight, 0, vec![]));
+            *ingredients_weight += ingredient.weight;
+            ingredients.push((ingredient.ingredient_id, ingredient.weight));
+            dict
+        },
+    );
+
+    Ok(dishes
+        .into_iter()
+        .filter_map(
+            |(dish_id, (total_weight, ingredients_weight, ingredients))| {
+                let weight = if total_weight > 0 {
+                    total_weight
+                } else {
+                    ingredients_weight
+                };
+                if weight <= 0 {
+                    return None;
+                }
+                Some((
+                    dish_id,
+                    ingredients
+                        .into_iter()
+                        .map(|(id, grams)| (id, grams as f64 / weight as f64))
+                        .collect(),
+                ))
+            },
+        )
+        .collect())
+}
+
+/// Fetches the nutrients in a single gram of every dish in `dish_ids`, based on the dish's
+/// ingredients, as weighed by `fetch_dish_composition`.
+pub async fn fetch_dish_nutrients(
+    connection: &sqlx::Pool<sqlx::Sqlite>,
+    dish_ids: &[i64],
+) -> anyhow::Result<HashMap<i64, Nutrients>> {
+    let dishes = fetch_dish_composition(connection, dish_ids).await?;
+
+    let ingredient_nutrients = fetch_ingredient_nutrients(
+        connection,
+        &dishes
+            .values()
+            .flatten()
+            .map(|(ingredient_id, _)| *ingredient_id)
+            .collect::<Vec<i64>>(),
+    )
+    .await?;
+
+    Ok(dishes
+        .into_iter()
+        .map(|(dish_id, ingredients)| {
+            let nutrients = ingredients.into_iter().fold(
+                Nutrients::default(),
+                |mut nutrients, (ingredient_id, grams)| {
+                    if let Some(per_gram) = ingredient_nutrients.get(&ingredient_id) {
+                        nutrients += per_gram.scale(grams);
+                    }
+                    nutrients
+                },
+            );
+            (dish_id, nutrients)
+        })
+        .collect())
+}
+
+/// A dish or ingredient eaten (or planned to be eaten), with its weight in grams.
+pub struct WeightedComponent {
+    pub dish_id: Option<i64>,
+    pub ingredient_id: Option<i64>,
+    pub weight: i64,
+}
+
+/// Per gram nutrients of a set of dishes and ingredients, fetched all at once.
+pub struct NutrientTable {
+    dishes: HashMap<i64, Nutrients>,
+    ingredients: HashMap<i64, Nutrients>,
+}
+
+impl NutrientTable {
+    pub async fn fetch(
+        connection: &sqlx::Pool<sqlx::Sqlite>,
+        components: &[WeightedComponent],
+    ) -> anyhow::Result<Self> {
+        let dish_ids = components
+            .iter()
+            .filter_map(|c| c.dish_id)
+            .collect::<Vec<i64>>();
+        let ingredient_ids = components
+            .iter()
+            .filter_map(|c| c.ingredient_id)
+            .collect::<Vec<i64>>();
+
+        let (dishes, ingredients) = futures::try_join!(
+            fetch_dish_nutrients(connection, &dish_ids),
+            fetch_ingredient_nutrients(connection, &ingredient_ids),
+        )?;
+
+        Ok(NutrientTable {
+            dishes,
+            ingredients,
+        })
+    }
+
+    /// The nutrients in `component`. Components without nutritional information count as zero.
+    pub fn component_nutrients(&self, component: &WeightedComponent) -> Nutrients {
+        let per_gram = match (component.dish_id, component.ingredient_id) {
+            (Some(id), _) => self.dishes.get(&id),
+            (_, Some(id)) => self.ingredients.get(&id),
+            (None, None) => None,
+        };
+        per_gram
+            .map(|n| n.scale(component.weight as f64))
+            .unwrap_or_default()
+    }
+}
+
+/// Amounts of trackables, by trackable id, in the trackable's unit.
+pub type TrackableAmounts = HashMap<i64, f64>;
+
+/// Adds `factor` times each amount in `other` to `amounts`.
+pub fn add_amounts(amounts: &mut TrackableAmounts, other: &TrackableAmounts, factor: f64) {
+    for (trackable_id, amount) in other {
+        *amounts.entry(*trackable_id).or_default() += amount * factor;
+    }
+}
+
+#[derive(FromRow)]
+struct DatabaseIngredientTrackable {
+    ingredient_id: i64,
+    trackable_id: i64,
+    amount_100g: f64,
+}
+
+/// Fetches the amounts of trackables in a single gram of every ingredient in `ingredient_ids`.
+/// Variants fall back to their generic ingredient for the trackables they have no amount of.
+/// Ingredients without any amounts are left out of the result.
+pub async fn fetch_ingredient_trackables(
+    connection: &sqlx::Pool<sqlx::Sqlite>,
+    ingredient_ids: &[i64],
+) -> anyhow::Result<HashMap<i64, TrackableAmounts>> {
+    if ingredient_ids.is_empty() {
+        return Ok(HashMap::new());
+    }
+
+    Ok(sqlx::QueryBuilder::<Sqlite>::new(
+        r#"
+        SELECT ingredient_id, trackable_id, CAST (amount_100g AS FLOAT) AS amount_100g
+        FROM IngredientTrackableAmount
+        WHERE ingredient_id IN "#,
+    )
+    .push_tuples(ingredient_ids.iter(), |mut p, id| {
+        p.push_bind(*id);
+    })
+    .build_query_as::<DatabaseIngredientTrackable>()
+    .fetch_all(connection)
+    .await?
+    .into_iter()
+    .fold(HashMap::new(), |mut dict, row| {
+        dict.entry(row.ingredient_id)
+            .or_insert_with(TrackableAmounts::new)
+            .insert(row.trackable_id, row.amount_100g / 100.0);
+        dict
+    }))
+}
+
+/// Per gram amounts of trackables of a set of dishes and ingredients, fetched all at once, the
+/// same way as `NutrientTable`.
+pub struct TrackableTable {
+    dishes: HashMap<i64, TrackableAmounts>,
+    ingredients: HashMap<i64, TrackableAmounts>,
+}
+
+impl TrackableTable {
+    pub async fn fetch(
+        connection: &sqlx::Pool<sqlx::Sqlite>,
+        components: &[WeightedComponent],
+    ) -> anyhow::Result<Self> {
+        let compositions = fetch_dish_composition(
+            connection,
+            &components
+                .iter()
+                .filter_map(|c| c.dish_id)
+                .collect::<Vec<i64>>(),
+        )
+        .await?;
+
+        let ingredients = fetch_ingredient_trackables(
+            connection,
+            &components
+                .iter()
+                .filter_map(|c| c.ingredient_id)
+                .chain(compositions.values().flatten().map(|(id, _)| *id))
+                .collect::<Vec<i64>>(),
+        )
+        .await?;
+
+        let dishes = compositions
+            .into_iter()
+            .map(|(dish_id, composition)| {
+                let mut amounts = TrackableAmounts::new();
+                for (ingredient_id, grams) in composition {
+                    if let Some(per_gram) = ingredients.get(&ingredient_id) {
+                        add_amounts(&mut amounts, per_gram, grams);
+                    }
+                }
+                (dish_id, amounts)
+            })
+            .collect();
+
+        Ok(TrackableTable {
+            dishes,
+            ingredients,
+        })
+    }
+
+    /// Adds the amounts of trackables in `component` to `amounts`. Components without amounts
+    /// add nothing.
+    pub fn add_component(&self, amounts: &mut TrackableAmounts, component: &WeightedComponent) {
+        let per_gram = match (component.dish_id, component.ingredient_id) {
+            (Some(id), _) => self.dishes.get(&id),
+            (_, Some(id)) => self.ingredients.get(&id),
+            (None, None) => None,
+        };
+        if let Some(per_gram) = per_gram {
+            add_amounts(amounts, per_gram, component.weight as f64);
+        }
+    }
+}
+
+#[derive(Serialize, JsonSchema, Clone)]
+pub struct TrackableAmount {
+    pub trackable_id: i64,
+    pub name: String,
+    pub unit: String,
+    pub amount: f64,
+    pub daily_limit: Option<f64>,
+}
+
+/// The trackables of a household, to show amounts of them.
+pub struct Trackables(Vec<Trackable>);
+
+impl Trackables {
+    pub async fn fetch(
+        connection: &sqlx::Pool<sqlx::Sqlite>,
+        household_id: i64,
+    ) -> anyhow::Result<Self> {
+        Ok(Trackables(
+            sqlx::query_as!(
+                Trackable,
+                r#"
+                SELECT id, creation_date, name, unit, daily_limit
+                FROM Trackable
+                WHERE household_id = ?
+                ORDER BY name"#,
+                household_id
+            )
+            .fetch_all(connection)
+            .await?,
+        ))
+    }
+
+    /// Every trackable of the household with its amount in `amounts`, zero if there's none.
+    pub fn amounts(&self, amounts: &TrackableAmounts) -> Vec<TrackableAmount> {
+        self.0
+            .iter()
+            .map(|trackable| TrackableAmount {
+                trackable_id: trackable.id,
+                name: trackable.name.clone(),
+                unit: trackable.unit.clone(),
+                amount: amounts.get(&trackable.id).copied().unwrap_or_default(),
+                daily_limit: trackable.daily_limit,
+            })
+            .collect()
+    }
+}
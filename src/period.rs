@@ -0,0 +1,274 @@
+use chrono::{Datelike, Duration, FixedOffset, NaiveDate, TimeZone};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// How reports that go day by day or week by week group what was eaten.
+#[derive(Deserialize, JsonSchema, Clone, Copy, Default)]
+pub enum SummaryPeriod {
+    #[default]
+    Day,
+    /// Weeks start on Monday
+    Week,
+}
+
+/// First day of the period `date` is in.
+pub fn period_start(date: NaiveDate, period: SummaryPeriod) -> NaiveDate {
+    match period {
+        SummaryPeriod::Day => date,
+        SummaryPeriod::Week => date - Duration::days(date.weekday().num_days_from_monday().into()),
+    }
+}
+
+/// Mistakes in the timezone or weekdays sent by a client, answered with a bad request.
+#[derive(Error, Debug)]
+pub enum PeriodError {
+    #[error("Invalid utc offset: {0} minutes")]
+    InvalidUtcOffset(i64),
+    #[error("At least one weekday must be provided")]
+    NoWeekdays,
+}
+
+fn fixed_offset(utc_offset_minutes: i64) -> Option<FixedOffset> {
+    i32::try_from(utc_offset_minutes)
+        .ok()
+        .and_then(|minutes| minutes.checked_mul(60))
+        .and_then(FixedOffset::east_opt)
+}
+
+/// Timezone of the `utc_offset_minutes` query parameter, which must be less than a day.
+pub fn utc_offset(utc_offset_minutes: Option<i32>) -> Result<FixedOffset, PeriodError> {
+    let utc_offset_minutes = utc_offset_minutes.unwrap_or(0).into();
+    fixed_offset(utc_offset_minutes).ok_or(PeriodError::InvalidUtcOffset(utc_offset_minutes))
+}
+
+/// Checks the weekdays and timezone of something repeated every week, returning the weekday mask
+/// and the offset to store for `weekday_occurrences`.
+pub fn weekly_schedule(
+    weekdays: &[Weekday],
+    utc_offset_minutes: Option<i64>,
+) -> Result<(i64, i64), PeriodError> {
+    if weekdays.is_empty() {
+        return Err(PeriodError::NoWeekdays);
+    }
+    let utc_offset_minutes = utc_offset_minutes.unwrap_or(0);
+    if fixed_offset(utc_offset_minutes).is_none() {
+        return Err(PeriodError::InvalidUtcOffset(utc_offset_minutes));
+    }
+    Ok((Weekday::to_mask(weekdays), utc_offset_minutes))
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Copy, PartialEq, Eq)]
+pub enum Weekday {
+    Monday,
+    Tuesday,
+    Wednesday,
+    Thursday,
+    Friday,
+    Saturday,
+    Sunday,
+}
+
+impl Weekday {
+    const ALL: [Weekday; 7] = [
+        Weekday::Monday,
+        Weekday::Tuesday,
+        Weekday::Wednesday,
+        Weekday::Thursday,
+        Weekday::Friday,
+        Weekday::Saturday,
+        Weekday::Sunday,
+    ];
+
+    fn bit(self) -> i64 {
+        1 << Weekday::ALL.iter().position(|w| *w == self).unwrap()
+    }
+
+    pub fn to_mask(weekdays: &[Weekday]) -> i64 {
+        weekdays
+            .iter()
+            .fold(0, |mask, weekday| mask | weekday.bit())
+    }
+
+    pub fn from_mask(mask: i64) -> Vec<Weekday> {
+        Weekday::ALL
+            .into_iter()
+            .filter(|weekday| mask & weekday.bit() != 0)
+            .collect()
+    }
+}
+
+/// Lists the dates after `after` and up to `until` of something that first occurs at `start_date`
+/// and then again at the same time of day in each of `weekdays`, a mask from `Weekday::to_mask`.
+/// Weekdays are decided in the timezone of `utc_offset_minutes`.
+pub fn weekday_occurrences(
+    start_date: i64,
+    end_date: Option<i64>,
+    weekdays: i64,
+    utc_offset_minutes: i64,
+    after: i64,
+    until: i64,
+) -> Vec<i64> {
+    let Some(offset) = fixed_offset(utc_offset_minutes) else {
+        return vec![];
+    };
+    let Some(start) = offset.timestamp_millis_opt(start_date).single() else {
+        return vec![];
+    };
+    let Some(resume) = offset.timestamp_millis_opt(after.max(start_date)).single() else {
+        return vec![];
+    };
+    let until = end_date.map_or(until, |end_date| end_date.min(until));
+
+    let time = start.time();
+    let mut date = resume.date_naive();
+    let mut dates = vec![];
+    while let Some(occurrence) = date.and_time(time).and_local_timezone(offset).single() {
+        let occurrence = occurrence.timestamp_millis();
+        if occurrence > until {
+            break;
+        }
+        let weekday_bit = 1 << date.weekday().num_days_from_monday();
+        if occurrence > after && weekdays & weekday_bit != 0 {
+            dates.push(occurrence);
+        }
+        let Some(next) = date.succ_opt() else {
+            break;
+        };
+        date = next;
+    }
+    dates
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn weekday_mask_round_trips() {
+        let weekdays = [Weekday::Monday, Weekday::Wednesday, Weekday::Sunday];
+        let mask = Weekday::to_mask(&weekdays);
+        assert_eq!(mask, 0b1000101);
+        assert!(Weekday::from_mask(mask) == weekdays);
+        assert!(Weekday::from_mask(Weekday::to_mask(&Weekday::ALL)) == Weekday::ALL);
+        assert!(Weekday::from_mask(0).is_empty());
+    }
+
+    #[test]
+    fn weekday_mask_ignores_duplicates_and_order() {
+        assert_eq!(
+            Weekday::to_mask(&[Weekday::Friday, Weekday::Monday, Weekday::Friday]),
+            Weekday::to_mask(&[Weekday::Monday, Weekday::Friday])
+        );
+    }
+
+    #[test]
+    fn utc_offset_must_be_less_than_a_day() {
+        assert_eq!(utc_offset(None).unwrap().local_minus_utc(), 0);
+        assert_eq!(utc_offset(Some(120)).unwrap().local_minus_utc(), 120 * 60);
+        assert_eq!(utc_offset(Some(-570)).unwrap().local_minus_utc(), -570 * 60);
+        assert!(matches!(
+            utc_offset(Some(24 * 60)),
+            Err(PeriodError::InvalidUtcOffset(1440))
+        ));
+        assert!(matches!(
+            utc_offset(Some(i32::MAX)),
+            Err(PeriodError::InvalidUtcOffset(_))
+        ));
+        assert!(matches!(
+            utc_offset(Some(i32::MIN)),
+            Err(PeriodError::InvalidUtcOffset(_))
+        ));
+    }
+
+    #[test]
+    fn weekly_schedule_checks_weekdays_and_offset() {
+        assert_eq!(
+            weekly_schedule(&[Weekday::Tuesday], Some(60)).unwrap(),
+            (0b10, 60)
+        );
+        assert_eq!(
+            weekly_schedule(&[Weekday::Tuesday], None).unwrap(),
+            (0b10, 0)
+        );
+        assert!(matches!(
+            weekly_schedule(&[], Some(60)),
+            Err(PeriodError::NoWeekdays)
+        ));
+        assert!(matches!(
+            weekly_schedule(&[Weekday::Tuesday], Some(i64::from(i32::MAX) + 1)),
+            Err(PeriodError::InvalidUtcOffset(_))
+        ));
+        assert!(matches!(
+            weekly_schedule(&[Weekday::Tuesday], Some(-24 * 60)),
+            Err(PeriodError::InvalidUtcOffset(-1440))
+        ));
+    }
+
+    fn timestamp(offset: FixedOffset, day: u32, hour: u32) -> i64 {
+        offset
+            .with_ymd_and_hms(2024, 1, day, hour, 0, 0)
+            .unwrap()
+            .timestamp_millis()
+    }
+
+    #[test]
+    fn weekday_occurrences_repeat_at_the_start_time() {
+        let utc = FixedOffset::east_opt(0).unwrap();
+        // 2024-01-01 is a Monday
+        let start = timestamp(utc, 1, 8);
+        let mask = Weekday::to_mask(&[Weekday::Monday, Weekday::Thursday]);
+        assert_eq!(
+            weekday_occurrences(start, None, mask, 0, start - 1, timestamp(utc, 15, 0)),
+            vec![
+                start,
+                timestamp(utc, 4, 8),
+                timestamp(utc, 8, 8),
+                timestamp(utc, 11, 8)
+            ]
+        );
+    }
+
+    #[test]
+    fn weekday_occurrences_are_limited_by_after_and_end_date() {
+        let utc = FixedOffset::east_opt(0).unwrap();
+        let start = timestamp(utc, 1, 8);
+        let mask = Weekday::to_mask(&Weekday::ALL);
+        assert_eq!(
+            weekday_occurrences(
+                start,
+                Some(timestamp(utc, 5, 8)),
+                mask,
+                0,
+                timestamp(utc, 3, 8),
+                timestamp(utc, 31, 0)
+            ),
+            vec![timestamp(utc, 4, 8), timestamp(utc, 5, 8)]
+        );
+        assert!(weekday_occurrences(start, None, mask, 0, start, start).is_empty());
+    }
+
+    #[test]
+    fn weekday_occurrences_use_the_offset_for_weekdays() {
+        let offset = FixedOffset::east_opt(-5 * 60 * 60).unwrap();
+        // Tuesday 2024-01-02 at 02:00 UTC is still Monday evening at -05:00
+        let start = timestamp(offset, 1, 21);
+        let mask = Weekday::to_mask(&[Weekday::Monday]);
+        assert_eq!(
+            weekday_occurrences(
+                start,
+                None,
+                mask,
+                -5 * 60,
+                start - 1,
+                timestamp(offset, 9, 0)
+            ),
+            vec![start, timestamp(offset, 8, 21)]
+        );
+    }
+
+    #[test]
+    fn weekday_occurrences_ignore_invalid_offsets() {
+        assert!(weekday_occurrences(0, None, 0b1111111, 24 * 60, -1, 1_000_000_000).is_empty());
+    }
+}
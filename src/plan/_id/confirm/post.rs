@@ -0,0 +1,182 @@
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{
+    audit::{record_change, AuditEntity},
+    component::{check_missing_components, insert_components, split_components, PostComponent},
+    etag::fetch_version,
+    events::DomainEvent,
+    models::{Meal, PlannedMeal},
+    plan::components::fetch_planned_meal_components,
+    server::{ServerResponse, ServerResponseResult},
+    session::AuthUser,
+    state::AppState,
+};
+
+#[derive(Deserialize, JsonSchema)]
+pub struct PlannedMealId {
+    planned_meal_id: i64,
+}
+
+#[derive(Deserialize, JsonSchema, Default)]
+pub struct PostConfirmBody {
+    /// When the meal was eaten. Defaults to the planned date
+    eat_date: Option<i64>,
+    duration: Option<i64>,
+    /// What was actually eaten, if it differs from the plan. Defaults to the planned components
+    components: Option<Vec<PostComponent>>,
+}
+
+#[derive(Serialize, JsonSchema)]
+pub struct PostConfirmResult {
+    planned_meal: PlannedMeal,
+    meal: Meal,
+}
+
+#[derive(Error, Debug)]
+enum PostConfirmError {
+    #[error("Could not find planned meal with id \"{0}\"")]
+    PlannedMealNotFound(i64),
+    #[error("Planned meal \"{0}\" was already confirmed as meal \"{1}\"")]
+    AlreadyConfirmed(i64, i64),
+}
+
+pub async fn post_confirm(
+    State(AppState {
+        connection, events, ..
+    }): State<AppState>,
+    user @ AuthUser {
+        user_id,
+        household_id,
+    }: AuthUser,
+    Path(PlannedMealId { planned_meal_id }): Path<PlannedMealId>,
+    Json(PostConfirmBody {
+        eat_date,
+        duration,
+        components,
+    }): Json<PostConfirmBody>,
+) -> ServerResponseResult<PostConfirmResult> {
+    let planned_meal = sqlx::query_as!(
+        PlannedMeal,
+        r#"
+        SELECT
+            id,
+            creation_date,
+            plan_date,
+            description,
+            recurring_plan_id,
+            meal_id
+        FROM PlannedMeal
+        WHERE id = ? AND user_id = ?;"#,
+        planned_meal_id,
+        user_id
+    )
+    .fetch_optional(&connection)
+    .await?
+    .ok_or(PostConfirmError::PlannedMealNotFound(planned_meal_id))?;
+
+    if let Some(meal_id) = planned_meal.meal_id {
+        return Err(PostConfirmError::AlreadyConfirmed(planned_meal_id, meal_id))?;
+    }
+
+    let components = match components {
+        Some(components) => components,
+        None => fetch_planned_meal_components(&connection, &[planned_meal_id])
+            .await?
+            .remove(&planned_meal_id)
+            .unwrap_or_default()
+            .into_iter()
+            .map(PostComponent::from)
+            .collect(),
+    };
+    let (dishes, ingredients) = split_components(components)?;
+    check_missing_components(&connection, household_id, &dishes, &ingredients).await?;
+
+    let eat_date = eat_date.unwrap_or(planned_meal.plan_date);
+
+    let mut transaction = connection.begin().await?;
+
+    let mut meal = sqlx::query_as!(
+        Meal,
+        r#"INSERT INTO Meal (
+            eat_date,
+            duration,
+            description,
+            user_id
+        ) VALUES (?, ?, ?, ?)
+        RETURNING id, creation_date, eat_date, duration, description, version;"#,
+        eat_date,
+        duration,
+        planned_meal.description,
+        user_id
+    )
+    .fetch_one(&mut *transaction)
+    .await?;
+
+    insert_components(
+        &mut transaction,
+        "MealDish",
+        "meal_id",
+        meal.id,
+        "dish_id",
+        &dishes,
+    )
+    .await?;
+    insert_components(
+        &mut transaction,
+        "MealIngredient",
+        "meal_id",
+        meal.id,
+        "ingredient_id",
+        &ingredients,
+    )
+    .await?;
+
+    // Claims the planned meal, so that a concurrent confirmation cannot create a second meal
+    let planned_meal = sqlx::query_as!(
+        PlannedMeal,
+        r#"
+        UPDATE PlannedMeal SET meal_id = ?
+        WHERE id = ? AND meal_id IS NULL
+        RETURNING
+            id as "id!",
+            creation_date,
+            plan_date,
+            description,
+            recurring_plan_id,
+            meal_id;"#,
+        meal.id,
+        planned_meal_id
+    )
+    .fetch_optional(&mut *transaction)
+    .await?;
+    let Some(planned_meal) = planned_meal else {
+        let confirmed_meal_id = sqlx::query_scalar!(
+            "SELECT meal_id FROM PlannedMeal WHERE id = ?;",
+            planned_meal_id
+        )
+        .fetch_one(&mut *transaction)
+        .await?
+        .unwrap_or_default();
+        return Err(PostConfirmError::AlreadyConfirmed(
+            planned_meal_id,
+            confirmed_meal_id,
+        ))?;
+    };
+
+    record_change(&mut *transaction, &user, AuditEntity::Meal, meal.id, None).await?;
+
+    transaction.commit().await?;
+
+    // Adding the components bumped the version
+    meal.version = fetch_version(&connection, "Meal", meal.id).await?;
+
+    events.publish(&user, DomainEvent::MealCreated { meal_id: meal.id });
+
+    Ok(ServerResponse::success(PostConfirmResult { planned_meal, meal }).json())
+}
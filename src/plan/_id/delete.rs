@@ -0,0 +1,50 @@
+use axum::extract::{Path, State};
+use schemars::JsonSchema;
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::{
+    server::{ServerResponse, ServerResponseResult},
+    session::AuthUser,
+    state::AppState,
+};
+
+#[derive(Deserialize, JsonSchema)]
+pub struct PlannedMealId {
+    planned_meal_id: i64,
+}
+
+#[derive(Error, Debug)]
+enum DeletePlanError {
+    #[error("Could not find planned meal with id \"{0}\"")]
+    PlannedMealNotFound(i64),
+}
+
+pub async fn delete_plan(
+    State(AppState { connection, .. }): State<AppState>,
+    AuthUser { user_id, .. }: AuthUser,
+    Path(PlannedMealId { planned_meal_id }): Path<PlannedMealId>,
+) -> ServerResponseResult<bool> {
+    sqlx::query_scalar!(
+        "SELECT id FROM PlannedMeal WHERE id = ? AND user_id = ?",
+        planned_meal_id,
+        user_id
+    )
+    .fetch_optional(&connection)
+    .await?
+    .ok_or(DeletePlanError::PlannedMealNotFound(planned_meal_id))?;
+
+    sqlx::query!(
+        r#"
+        DELETE FROM PlannedMealDish WHERE planned_meal_id = ?;
+        DELETE FROM PlannedMealIngredient WHERE planned_meal_id = ?;
+        DELETE FROM PlannedMeal WHERE id = ?"#,
+        planned_meal_id,
+        planned_meal_id,
+        planned_meal_id,
+    )
+    .execute(&connection)
+    .await?;
+
+    Ok(ServerResponse::success(true).json())
+}
@@ -0,0 +1,54 @@
+use axum::extract::{Path, State};
+use schemars::JsonSchema;
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::{
+    models::PlannedMeal,
+    plan::components::{with_components, PlannedMealResponse},
+    server::{ServerResponse, ServerResponseResult},
+    session::AuthUser,
+    state::AppState,
+};
+
+#[derive(Error, Debug)]
+enum GetPlanError {
+    #[error("Could not find planned meal with id \"{0}\"")]
+    PlannedMealNotFound(i64),
+}
+
+#[derive(Deserialize, JsonSchema)]
+pub struct PlannedMealId {
+    planned_meal_id: i64,
+}
+
+pub async fn get_plan(
+    State(AppState { connection, .. }): State<AppState>,
+    AuthUser { user_id, .. }: AuthUser,
+    Path(PlannedMealId { planned_meal_id }): Path<PlannedMealId>,
+) -> ServerResponseResult<PlannedMealResponse> {
+    let planned_meal = sqlx::query_as!(
+        PlannedMeal,
+        r#"
+        SELECT
+            id,
+            creation_date,
+            plan_date,
+            description,
+            recurring_plan_id,
+            meal_id
+        FROM PlannedMeal
+        WHERE id = ? AND user_id = ?;"#,
+        planned_meal_id,
+        user_id
+    )
+    .fetch_optional(&connection)
+    .await?
+    .ok_or(GetPlanError::PlannedMealNotFound(planned_meal_id))?;
+
+    let planned_meal = with_components(&connection, vec![planned_meal])
+        .await?
+        .remove(0);
+
+    Ok(ServerResponse::success(planned_meal).json())
+}
@@ -0,0 +1,14 @@
+use aide::axum::{routing::get, ApiRouter};
+
+use crate::state::AppState;
+
+mod confirm;
+mod delete;
+mod get;
+
+pub fn route(state: AppState) -> ApiRouter {
+    ApiRouter::new()
+        .api_route("/", get(get::get_plan).delete(delete::delete_plan))
+        .nest_api_service("/confirm", confirm::route(state.clone()))
+        .with_state(state)
+}
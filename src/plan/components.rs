@@ -0,0 +1,61 @@
+use std::collections::HashMap;
+
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use crate::{
+    component::{fetch_linked_components, LinkedComponent},
+    models::PlannedMeal,
+};
+
+#[derive(Serialize, JsonSchema)]
+pub struct PlannedMealResponse {
+    pub planned_meal: PlannedMeal,
+    pub components: Vec<LinkedComponent>,
+}
+
+pub async fn fetch_planned_meal_components(
+    connection: &sqlx::Pool<sqlx::Sqlite>,
+    planned_meal_ids: &[i64],
+) -> anyhow::Result<HashMap<i64, Vec<LinkedComponent>>> {
+    fetch_linked_components(
+        connection,
+        "PlannedMeal",
+        "planned_meal_id",
+        planned_meal_ids,
+    )
+    .await
+}
+
+pub async fn fetch_recurring_plan_components(
+    connection: &sqlx::Pool<sqlx::Sqlite>,
+    recurring_plan_ids: &[i64],
+) -> anyhow::Result<HashMap<i64, Vec<LinkedComponent>>> {
+    fetch_linked_components(
+        connection,
+        "RecurringMealPlan",
+        "recurring_plan_id",
+        recurring_plan_ids,
+    )
+    .await
+}
+
+/// Attaches the components to each planned meal in `planned_meals`.
+pub async fn with_components(
+    connection: &sqlx::Pool<sqlx::Sqlite>,
+    planned_meals: Vec<PlannedMeal>,
+) -> anyhow::Result<Vec<PlannedMealResponse>> {
+    let mut components = fetch_planned_meal_components(
+        connection,
+        &planned_meals.iter().map(|p| p.id).collect::<Vec<i64>>(),
+    )
+    .await?;
+
+    Ok(planned_meals
+        .into_iter()
+        .map(|planned_meal| PlannedMealResponse {
+            components: components.remove(&planned_meal.id).unwrap_or_default(),
+            planned_meal,
+        })
+        .collect())
+}
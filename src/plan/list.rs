@@ -0,0 +1,60 @@
+use axum::extract::{Query, State};
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+use crate::{
+    models::PlannedMeal,
+    server::{ServerResponse, ServerResponseResult},
+    session::AuthUser,
+    state::AppState,
+};
+
+use super::components::{with_components, PlannedMealResponse};
+
+#[derive(Deserialize, JsonSchema)]
+pub struct ListPlanQueryParams {
+    /// Defaults to now
+    from: Option<i64>,
+    to: Option<i64>,
+    /// Whether plans that were already confirmed as eaten should be listed. Defaults to false
+    include_confirmed: Option<bool>,
+}
+
+pub async fn list_plan(
+    State(AppState { connection, .. }): State<AppState>,
+    AuthUser { user_id, .. }: AuthUser,
+    Query(ListPlanQueryParams {
+        from,
+        to,
+        include_confirmed,
+    }): Query<ListPlanQueryParams>,
+) -> ServerResponseResult<Vec<PlannedMealResponse>> {
+    let from = from.unwrap_or_else(|| chrono::Utc::now().timestamp_millis());
+    let to = to.unwrap_or(i64::MAX);
+    let include_confirmed = include_confirmed.unwrap_or(false);
+
+    let planned_meals = sqlx::query_as!(
+        PlannedMeal,
+        r#"
+        SELECT
+            id,
+            creation_date,
+            plan_date,
+            description,
+            recurring_plan_id,
+            meal_id
+        FROM PlannedMeal
+        WHERE user_id = ? AND plan_date >= ? AND plan_date <= ? AND (? OR meal_id IS NULL)
+        ORDER BY plan_date ASC;"#,
+        user_id,
+        from,
+        to,
+        include_confirmed
+    )
+    .fetch_all(&connection)
+    .await?;
+
+    let planned_meals = with_components(&connection, planned_meals).await?;
+
+    Ok(ServerResponse::success(planned_meals).json())
+}
@@ -0,0 +1,22 @@
+use aide::axum::{
+    routing::{get, post},
+    ApiRouter,
+};
+
+use crate::state::AppState;
+
+mod _id;
+mod components;
+mod list;
+mod post;
+mod projection;
+mod recurring;
+
+pub fn route(state: AppState) -> ApiRouter {
+    ApiRouter::new()
+        .api_route("/", post(post::post_plan).get(list::list_plan))
+        .api_route("/projection", get(projection::get_projection))
+        .nest_api_service("/recurring", recurring::route(state.clone()))
+        .nest_api_service("/:planned_meal_id", _id::route(state.clone()))
+        .with_state(state)
+}
@@ -0,0 +1,86 @@
+use axum::{extract::State, Json};
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+use crate::{
+    component::{check_missing_components, insert_components, split_components, PostComponent},
+    models::PlannedMeal,
+    server::{ServerResponse, ServerResponseResult},
+    session::AuthUser,
+    state::AppState,
+};
+
+use super::components::{with_components, PlannedMealResponse};
+
+#[derive(Deserialize, JsonSchema)]
+pub struct PostPlanBody {
+    /// When the meal is planned to be eaten
+    plan_date: i64,
+    /// Usually one of the usual meal descriptions, such as "Breakfast"
+    description: Option<String>,
+    components: Vec<PostComponent>,
+}
+
+pub async fn post_plan(
+    State(AppState { connection, .. }): State<AppState>,
+    AuthUser {
+        user_id,
+        household_id,
+    }: AuthUser,
+    Json(PostPlanBody {
+        plan_date,
+        description,
+        components,
+    }): Json<PostPlanBody>,
+) -> ServerResponseResult<PlannedMealResponse> {
+    let (dishes, ingredients) = split_components(components)?;
+    check_missing_components(&connection, household_id, &dishes, &ingredients).await?;
+
+    let mut transaction = connection.begin().await?;
+
+    let planned_meal = sqlx::query_as!(
+        PlannedMeal,
+        r#"
+        INSERT INTO PlannedMeal (plan_date, description, user_id)
+        VALUES (?, ?, ?)
+        RETURNING
+            id,
+            creation_date,
+            plan_date,
+            description,
+            recurring_plan_id,
+            meal_id;"#,
+        plan_date,
+        description,
+        user_id
+    )
+    .fetch_one(&mut *transaction)
+    .await?;
+
+    insert_components(
+        &mut transaction,
+        "PlannedMealDish",
+        "planned_meal_id",
+        planned_meal.id,
+        "dish_id",
+        &dishes,
+    )
+    .await?;
+    insert_components(
+        &mut transaction,
+        "PlannedMealIngredient",
+        "planned_meal_id",
+        planned_meal.id,
+        "ingredient_id",
+        &ingredients,
+    )
+    .await?;
+
+    transaction.commit().await?;
+
+    let planned_meal = with_components(&connection, vec![planned_meal])
+        .await?
+        .remove(0);
+
+    Ok(ServerResponse::success(planned_meal).json())
+}
@@ -0,0 +1,196 @@
+use std::collections::BTreeMap;
+
+use axum::extract::{Query, State};
+use chrono::TimeZone;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use sqlx::prelude::FromRow;
+
+use crate::{
+    dose::{fetch_scheduled_doses, fetch_taken_doses, DoseTable},
+    models::NutritionGoal,
+    nutrition::{GoalStatus, Nutrient, NutrientTable, Nutrients, WeightedComponent},
+    period::utc_offset,
+    server::{ServerResponse, ServerResponseResult},
+    session::AuthUser,
+    state::AppState,
+};
+
+#[derive(Deserialize, JsonSchema)]
+pub struct GetProjectionQueryParams {
+    from: i64,
+    to: i64,
+    /// Offset of the user's timezone, used to decide which day each meal belongs to
+    utc_offset_minutes: Option<i32>,
+}
+
+#[derive(Serialize, JsonSchema)]
+pub struct GoalProgress {
+    nutrient: Nutrient,
+    daily_min: Option<f64>,
+    daily_max: Option<f64>,
+    projected: f64,
+    status: GoalStatus,
+}
+
+#[derive(Serialize, JsonSchema)]
+pub struct DayProjection {
+    /// Day in the `YYYY-MM-DD` format
+    date: String,
+    /// Nutrients of the meals already eaten and the supplement doses already taken
+    eaten: Nutrients,
+    /// Nutrients of the planned meals that were not confirmed yet, and of the scheduled
+    /// supplement doses that were not taken yet
+    planned: Nutrients,
+    /// Sum of eaten and planned
+    projected: Nutrients,
+    goals: Vec<GoalProgress>,
+}
+
+#[derive(FromRow)]
+struct DatedComponent {
+    date: i64,
+    is_planned: bool,
+    dish_id: Option<i64>,
+    ingredient_id: Option<i64>,
+    weight: i64,
+}
+
+impl From<&DatedComponent> for WeightedComponent {
+    fn from(value: &DatedComponent) -> Self {
+        WeightedComponent {
+            dish_id: value.dish_id,
+            ingredient_id: value.ingredient_id,
+            weight: value.weight,
+        }
+    }
+}
+
+fn goal_progress(goal: &NutritionGoal, projected: &Nutrients) -> Option<GoalProgress> {
+    let nutrient = Nutrient::from_name(&goal.nutrient)?;
+    let projected = projected.get(nutrient);
+    Some(GoalProgress {
+        nutrient,
+        daily_min: goal.daily_min,
+        daily_max: goal.daily_max,
+        projected,
+        status: GoalStatus::new(goal, projected),
+    })
+}
+
+pub async fn get_projection(
+    State(AppState { connection, .. }): State<AppState>,
+    AuthUser {
+        user_id,
+        household_id,
+    }: AuthUser,
+    Query(GetProjectionQueryParams {
+        from,
+        to,
+        utc_offset_minutes,
+    }): Query<GetProjectionQueryParams>,
+) -> ServerResponseResult<Vec<DayProjection>> {
+    let offset = utc_offset(utc_offset_minutes)?;
+
+    let components = sqlx::query_as::<_, DatedComponent>(
+        r#"
+        SELECT Meal.eat_date AS date, FALSE AS is_planned, dish_id, NULL AS ingredient_id, weight
+        FROM Meal JOIN MealDish ON MealDish.meal_id = Meal.id
+        WHERE Meal.user_id = ?3 AND Meal.deletion_date IS NULL AND Meal.eat_date >= ?1 AND Meal.eat_date <= ?2
+        UNION ALL
+        SELECT Meal.eat_date AS date, FALSE AS is_planned, NULL AS dish_id, ingredient_id, weight
+        FROM Meal JOIN MealIngredient ON MealIngredient.meal_id = Meal.id
+        WHERE Meal.user_id = ?3 AND Meal.deletion_date IS NULL AND Meal.eat_date >= ?1 AND Meal.eat_date <= ?2
+        UNION ALL
+        SELECT plan_date AS date, TRUE AS is_planned, dish_id, NULL AS ingredient_id, weight
+        FROM PlannedMeal JOIN PlannedMealDish ON PlannedMealDish.planned_meal_id = PlannedMeal.id
+        WHERE PlannedMeal.user_id = ?3 AND plan_date >= ?1 AND plan_date <= ?2 AND meal_id IS NULL
+        UNION ALL
+        SELECT plan_date AS date, TRUE AS is_planned, NULL AS dish_id, ingredient_id, weight
+        FROM PlannedMeal JOIN PlannedMealIngredient ON PlannedMealIngredient.planned_meal_id = PlannedMeal.id
+        WHERE PlannedMeal.user_id = ?3 AND plan_date >= ?1 AND plan_date <= ?2 AND meal_id IS NULL;"#,
+    )
+    .bind(from)
+    .bind(to)
+    .bind(user_id)
+    .fetch_all(&connection)
+    .await?;
+
+    let goals = sqlx::query_as!(
+        NutritionGoal,
+        r#"
+        SELECT nutrient, creation_date, daily_min, daily_max
+        FROM NutritionGoal
+        WHERE user_id = ?;"#,
+        user_id
+    )
+    .fetch_all(&connection)
+    .await?;
+
+    let (taken_doses, scheduled_doses) = futures::try_join!(
+        fetch_taken_doses(&connection, household_id, Some(user_id), from, to),
+        fetch_scheduled_doses(&connection, user_id, from, to),
+    )?;
+    let supplement_ids = taken_doses
+        .iter()
+        .map(|dose| dose.supplement_id)
+        .chain(scheduled_doses.iter().map(|dose| dose.supplement_id))
+        .collect::<Vec<i64>>();
+
+    let weighted_components = components
+        .iter()
+        .map(WeightedComponent::from)
+        .collect::<Vec<WeightedComponent>>();
+    let (table, dose_table) = futures::try_join!(
+        NutrientTable::fetch(&connection, &weighted_components),
+        DoseTable::fetch(&connection, &supplement_ids),
+    )?;
+
+    let mut days = BTreeMap::<String, (Nutrients, Nutrients)>::new();
+    for component in &components {
+        let Some(date) = offset.timestamp_millis_opt(component.date).single() else {
+            continue;
+        };
+        let (eaten, planned) = days.entry(date.format("%Y-%m-%d").to_string()).or_default();
+        let nutrients = table.component_nutrients(&WeightedComponent::from(component));
+        if component.is_planned {
+            *planned += nutrients;
+        } else {
+            *eaten += nutrients;
+        }
+    }
+    for dose in &taken_doses {
+        let Some(date) = offset.timestamp_millis_opt(dose.date).single() else {
+            continue;
+        };
+        let (eaten, _) = days.entry(date.format("%Y-%m-%d").to_string()).or_default();
+        *eaten += dose_table.dose_nutrients(dose.supplement_id, dose.doses);
+    }
+    for dose in scheduled_doses.iter().filter(|dose| dose.dose_id.is_none()) {
+        let Some(date) = offset.timestamp_millis_opt(dose.scheduled_date).single() else {
+            continue;
+        };
+        let (_, planned) = days.entry(date.format("%Y-%m-%d").to_string()).or_default();
+        *planned += dose_table.dose_nutrients(dose.supplement_id, dose.doses);
+    }
+
+    let projection = days
+        .into_iter()
+        .map(|(date, (eaten, planned))| {
+            let mut projected = eaten;
+            projected += planned;
+            DayProjection {
+                date,
+                eaten,
+                planned,
+                projected,
+                goals: goals
+                    .iter()
+                    .filter_map(|goal| goal_progress(goal, &projected))
+                    .collect(),
+            }
+        })
+        .collect();
+
+    Ok(ServerResponse::success(projection).json())
+}
@@ -0,0 +1,66 @@
+use axum::extract::{Path, State};
+use schemars::JsonSchema;
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::{
+    server::{ServerResponse, ServerResponseResult},
+    session::AuthUser,
+    state::AppState,
+};
+
+#[derive(Deserialize, JsonSchema)]
+pub struct RecurringPlanId {
+    recurring_plan_id: i64,
+}
+
+#[derive(Error, Debug)]
+enum DeleteRecurringPlanError {
+    #[error("Could not find recurring plan with id \"{0}\"")]
+    RecurringPlanNotFound(i64),
+}
+
+/// Deletes the recurring plan, and every one of its occurrences that was not confirmed yet.
+/// Confirmed occurrences are kept, but no longer reference the plan.
+pub async fn delete_recurring_plan(
+    State(AppState { connection, .. }): State<AppState>,
+    AuthUser { user_id, .. }: AuthUser,
+    Path(RecurringPlanId { recurring_plan_id }): Path<RecurringPlanId>,
+) -> ServerResponseResult<bool> {
+    sqlx::query_scalar!(
+        "SELECT id FROM RecurringMealPlan WHERE id = ? AND user_id = ?",
+        recurring_plan_id,
+        user_id
+    )
+    .fetch_optional(&connection)
+    .await?
+    .ok_or(DeleteRecurringPlanError::RecurringPlanNotFound(
+        recurring_plan_id,
+    ))?;
+
+    sqlx::query!(
+        r#"
+        DELETE FROM PlannedMealDish WHERE planned_meal_id IN (
+            SELECT id FROM PlannedMeal WHERE recurring_plan_id = ? AND meal_id IS NULL
+        );
+        DELETE FROM PlannedMealIngredient WHERE planned_meal_id IN (
+            SELECT id FROM PlannedMeal WHERE recurring_plan_id = ? AND meal_id IS NULL
+        );
+        DELETE FROM PlannedMeal WHERE recurring_plan_id = ? AND meal_id IS NULL;
+        UPDATE PlannedMeal SET recurring_plan_id = NULL WHERE recurring_plan_id = ?;
+        DELETE FROM RecurringMealPlanDish WHERE recurring_plan_id = ?;
+        DELETE FROM RecurringMealPlanIngredient WHERE recurring_plan_id = ?;
+        DELETE FROM RecurringMealPlan WHERE id = ?"#,
+        recurring_plan_id,
+        recurring_plan_id,
+        recurring_plan_id,
+        recurring_plan_id,
+        recurring_plan_id,
+        recurring_plan_id,
+        recurring_plan_id,
+    )
+    .execute(&connection)
+    .await?;
+
+    Ok(ServerResponse::success(true).json())
+}
@@ -0,0 +1,13 @@
+use aide::axum::{routing::delete, ApiRouter};
+
+use crate::state::AppState;
+
+mod delete;
+mod schedule;
+
+pub fn route(state: AppState) -> ApiRouter {
+    ApiRouter::new()
+        .api_route("/", delete(delete::delete_recurring_plan))
+        .nest_api_service("/schedule", schedule::route(state.clone()))
+        .with_state(state)
+}
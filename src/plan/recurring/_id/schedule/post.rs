@@ -0,0 +1,69 @@
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+use schemars::JsonSchema;
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::{
+    models::{PlannedMeal, RecurringMealPlan},
+    plan::recurring::occurrences::schedule_occurrences,
+    server::{ServerResponse, ServerResponseResult},
+    session::AuthUser,
+    state::AppState,
+};
+
+#[derive(Deserialize, JsonSchema)]
+pub struct RecurringPlanId {
+    recurring_plan_id: i64,
+}
+
+#[derive(Deserialize, JsonSchema)]
+pub struct PostScheduleBody {
+    /// Occurrences are created up to this date
+    until: i64,
+}
+
+#[derive(Error, Debug)]
+enum PostScheduleError {
+    #[error("Could not find recurring plan with id \"{0}\"")]
+    RecurringPlanNotFound(i64),
+}
+
+/// Creates the occurrences of the recurring plan that were not scheduled yet, up to `until`.
+pub async fn post_schedule(
+    State(AppState { connection, .. }): State<AppState>,
+    AuthUser { user_id, .. }: AuthUser,
+    Path(RecurringPlanId { recurring_plan_id }): Path<RecurringPlanId>,
+    Json(PostScheduleBody { until }): Json<PostScheduleBody>,
+) -> ServerResponseResult<Vec<PlannedMeal>> {
+    let mut transaction = connection.begin().await?;
+
+    let recurring_plan = sqlx::query_as!(
+        RecurringMealPlan,
+        r#"
+        SELECT
+            id,
+            creation_date,
+            description,
+            start_date,
+            end_date,
+            weekdays,
+            utc_offset_minutes,
+            scheduled_until
+        FROM RecurringMealPlan
+        WHERE id = ? AND user_id = ?;"#,
+        recurring_plan_id,
+        user_id
+    )
+    .fetch_optional(&mut *transaction)
+    .await?
+    .ok_or(PostScheduleError::RecurringPlanNotFound(recurring_plan_id))?;
+
+    let scheduled = schedule_occurrences(&mut transaction, &recurring_plan, until).await?;
+
+    transaction.commit().await?;
+
+    Ok(ServerResponse::success(scheduled).json())
+}
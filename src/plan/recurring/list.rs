@@ -0,0 +1,54 @@
+use axum::extract::State;
+
+use crate::{
+    models::RecurringMealPlan,
+    period::Weekday,
+    plan::components::fetch_recurring_plan_components,
+    server::{ServerResponse, ServerResponseResult},
+    session::AuthUser,
+    state::AppState,
+};
+
+use super::occurrences::RecurringPlanResponse;
+
+pub async fn list_recurring_plan(
+    State(AppState { connection, .. }): State<AppState>,
+    AuthUser { user_id, .. }: AuthUser,
+) -> ServerResponseResult<Vec<RecurringPlanResponse>> {
+    let recurring_plans = sqlx::query_as!(
+        RecurringMealPlan,
+        r#"
+        SELECT
+            id,
+            creation_date,
+            description,
+            start_date,
+            end_date,
+            weekdays,
+            utc_offset_minutes,
+            scheduled_until
+        FROM RecurringMealPlan
+        WHERE user_id = ?
+        ORDER BY start_date DESC;"#,
+        user_id
+    )
+    .fetch_all(&connection)
+    .await?;
+
+    let mut components = fetch_recurring_plan_components(
+        &connection,
+        &recurring_plans.iter().map(|p| p.id).collect::<Vec<i64>>(),
+    )
+    .await?;
+
+    let recurring_plans = recurring_plans
+        .into_iter()
+        .map(|recurring_plan| RecurringPlanResponse {
+            weekdays: Weekday::from_mask(recurring_plan.weekdays),
+            components: components.remove(&recurring_plan.id).unwrap_or_default(),
+            recurring_plan,
+        })
+        .collect();
+
+    Ok(ServerResponse::success(recurring_plans).json())
+}
@@ -0,0 +1,18 @@
+use aide::axum::{routing::post, ApiRouter};
+
+use crate::state::AppState;
+
+mod _id;
+mod list;
+mod occurrences;
+mod post;
+
+pub fn route(state: AppState) -> ApiRouter {
+    ApiRouter::new()
+        .api_route(
+            "/",
+            post(post::post_recurring_plan).get(list::list_recurring_plan),
+        )
+        .nest_api_service("/:recurring_plan_id", _id::route(state.clone()))
+        .with_state(state)
+}
@@ -0,0 +1,106 @@
+use schemars::JsonSchema;
+use serde::Serialize;
+use thiserror::Error;
+
+use crate::{
+    component::LinkedComponent,
+    models::{PlannedMeal, RecurringMealPlan},
+    period::{weekday_occurrences, Weekday},
+};
+
+/// Occurrences can't be scheduled more than this many days into the future
+const MAX_SCHEDULE_DAYS: i64 = 366;
+
+#[derive(Serialize, JsonSchema)]
+pub struct RecurringPlanResponse {
+    pub recurring_plan: RecurringMealPlan,
+    pub weekdays: Vec<Weekday>,
+    pub components: Vec<LinkedComponent>,
+}
+
+#[derive(Error, Debug)]
+pub enum ScheduleError {
+    #[error("Occurrences can't be scheduled more than {MAX_SCHEDULE_DAYS} days into the future")]
+    TooFarInTheFuture,
+}
+
+/// Lists the dates of every occurrence of `plan` after its `scheduled_until` and up to `until`.
+/// Occurrences more than [`MAX_SCHEDULE_DAYS`] days before `until` are skipped.
+fn occurrence_dates(plan: &RecurringMealPlan, until: i64) -> Vec<i64> {
+    let after = plan
+        .scheduled_until
+        .max(until - MAX_SCHEDULE_DAYS * 24 * 60 * 60 * 1000);
+    weekday_occurrences(
+        plan.start_date,
+        plan.end_date,
+        plan.weekdays,
+        plan.utc_offset_minutes,
+        after,
+        until,
+    )
+}
+
+/// Creates a planned meal for every occurrence of `plan` up to `until`, copying the plan's
+/// components into each one of them.
+pub async fn schedule_occurrences(
+    connection: &mut sqlx::SqliteConnection,
+    plan: &RecurringMealPlan,
+    until: i64,
+) -> anyhow::Result<Vec<PlannedMeal>> {
+    let limit = chrono::Utc::now().timestamp_millis() + MAX_SCHEDULE_DAYS * 24 * 60 * 60 * 1000;
+    if until > limit {
+        return Err(ScheduleError::TooFarInTheFuture)?;
+    }
+    if until <= plan.scheduled_until {
+        return Ok(vec![]);
+    }
+
+    let mut planned_meals = vec![];
+    for date in occurrence_dates(plan, until) {
+        let planned_meal = sqlx::query_as!(
+            PlannedMeal,
+            r#"
+            INSERT INTO PlannedMeal (plan_date, description, recurring_plan_id, user_id)
+            VALUES (?, ?, ?, (SELECT user_id FROM RecurringMealPlan WHERE id = ?))
+            RETURNING
+                id,
+                creation_date,
+                plan_date,
+                description,
+                recurring_plan_id,
+                meal_id;"#,
+            date,
+            plan.description,
+            plan.id,
+            plan.id
+        )
+        .fetch_one(&mut *connection)
+        .await?;
+
+        sqlx::query!(
+            r#"
+            INSERT INTO PlannedMealDish (planned_meal_id, dish_id, weight)
+            SELECT ?, dish_id, weight FROM RecurringMealPlanDish WHERE recurring_plan_id = ?;
+            INSERT INTO PlannedMealIngredient (planned_meal_id, ingredient_id, weight)
+            SELECT ?, ingredient_id, weight FROM RecurringMealPlanIngredient WHERE recurring_plan_id = ?;"#,
+            planned_meal.id,
+            plan.id,
+            planned_meal.id,
+            plan.id
+        )
+        .execute(&mut *connection)
+        .await?;
+
+        planned_meals.push(planned_meal);
+    }
+
+    sqlx::query!(
+        "UPDATE RecurringMealPlan SET scheduled_until = ? WHERE id = ?",
+        until,
+        plan.id
+    )
+    .execute(&mut *connection)
+    .await?;
+
+    Ok(planned_meals)
+}
@@ -0,0 +1,156 @@
+use axum::{extract::State, Json};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    component::{check_missing_components, insert_components, split_components, PostComponent},
+    models::{PlannedMeal, RecurringMealPlan},
+    period::{weekly_schedule, Weekday},
+    plan::components::fetch_recurring_plan_components,
+    server::{ServerResponse, ServerResponseResult},
+    session::AuthUser,
+    state::AppState,
+};
+
+use super::occurrences::{schedule_occurrences, RecurringPlanResponse};
+
+/// How far ahead occurrences are scheduled if `schedule_until` is not provided
+const DEFAULT_SCHEDULE_DAYS: i64 = 14;
+
+#[derive(Deserialize, JsonSchema)]
+pub struct PostRecurringPlanBody {
+    /// Usually one of the usual meal descriptions, such as "Breakfast"
+    description: Option<String>,
+    /// Date of the first occurrence. Its time of day is used for every occurrence
+    start_date: i64,
+    end_date: Option<i64>,
+    weekdays: Vec<Weekday>,
+    /// Offset of the user's timezone, used to decide which weekday an occurrence falls in
+    utc_offset_minutes: Option<i64>,
+    components: Vec<PostComponent>,
+    /// Occurrences are created up to this date. Defaults to two weeks after the start date
+    schedule_until: Option<i64>,
+}
+
+#[derive(Serialize, JsonSchema)]
+pub struct PostRecurringPlanResult {
+    recurring_plan: RecurringPlanResponse,
+    scheduled: Vec<PlannedMeal>,
+}
+
+pub async fn post_recurring_plan(
+    State(AppState { connection, .. }): State<AppState>,
+    AuthUser {
+        user_id,
+        household_id,
+    }: AuthUser,
+    Json(PostRecurringPlanBody {
+        description,
+        start_date,
+        end_date,
+        weekdays,
+        utc_offset_minutes,
+        components,
+        schedule_until,
+    }): Json<PostRecurringPlanBody>,
+) -> ServerResponseResult<PostRecurringPlanResult> {
+    let (weekdays, utc_offset_minutes) = weekly_schedule(&weekdays, utc_offset_minutes)?;
+    let (dishes, ingredients) = split_components(components)?;
+    check_missing_components(&connection, household_id, &dishes, &ingredients).await?;
+
+    // Nothing was scheduled yet, so it starts right before the first occurrence
+    let scheduled_until = start_date - 1;
+    let schedule_until =
+        schedule_until.unwrap_or(start_date + DEFAULT_SCHEDULE_DAYS * 24 * 60 * 60 * 1000);
+
+    let mut transaction = connection.begin().await?;
+
+    let recurring_plan = sqlx::query_as!(
+        RecurringMealPlan,
+        r#"
+        INSERT INTO RecurringMealPlan (
+            description,
+            start_date,
+            end_date,
+            weekdays,
+            utc_offset_minutes,
+            scheduled_until,
+            user_id
+        ) VALUES (?, ?, ?, ?, ?, ?, ?)
+        RETURNING
+            id,
+            creation_date,
+            description,
+            start_date,
+            end_date,
+            weekdays,
+            utc_offset_minutes,
+            scheduled_until;"#,
+        description,
+        start_date,
+        end_date,
+        weekdays,
+        utc_offset_minutes,
+        scheduled_until,
+        user_id
+    )
+    .fetch_one(&mut *transaction)
+    .await?;
+
+    insert_components(
+        &mut transaction,
+        "RecurringMealPlanDish",
+        "recurring_plan_id",
+        recurring_plan.id,
+        "dish_id",
+        &dishes,
+    )
+    .await?;
+    insert_components(
+        &mut transaction,
+        "RecurringMealPlanIngredient",
+        "recurring_plan_id",
+        recurring_plan.id,
+        "ingredient_id",
+        &ingredients,
+    )
+    .await?;
+
+    let scheduled = schedule_occurrences(&mut transaction, &recurring_plan, schedule_until).await?;
+
+    let recurring_plan = sqlx::query_as!(
+        RecurringMealPlan,
+        r#"
+        SELECT
+            id,
+            creation_date,
+            description,
+            start_date,
+            end_date,
+            weekdays,
+            utc_offset_minutes,
+            scheduled_until
+        FROM RecurringMealPlan
+        WHERE id = ?;"#,
+        recurring_plan.id
+    )
+    .fetch_one(&mut *transaction)
+    .await?;
+
+    transaction.commit().await?;
+
+    let components = fetch_recurring_plan_components(&connection, &[recurring_plan.id])
+        .await?
+        .remove(&recurring_plan.id)
+        .unwrap_or_default();
+
+    Ok(ServerResponse::success(PostRecurringPlanResult {
+        recurring_plan: RecurringPlanResponse {
+            weekdays: Weekday::from_mask(recurring_plan.weekdays),
+            recurring_plan,
+            components,
+        },
+        scheduled,
+    })
+    .json())
+}
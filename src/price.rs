@@ -0,0 +1,371 @@
+use std::collections::HashMap;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use sqlx::{prelude::FromRow, Sqlite};
+
+#[derive(Deserialize, JsonSchema, Clone, Copy, Default)]
+pub enum PriceMethod {
+    /// Uses the price of the most recent purchase
+    #[default]
+    Latest,
+    /// Averages every purchase, weighted by how long each price was in effect
+    TimeWeighted,
+}
+
+#[derive(FromRow)]
+struct DatabasePurchase {
+    ingredient_id: i64,
+    purchase_date: i64,
+    price: i64,
+    currency: String,
+    package_weight: i64,
+}
+
+#[derive(Serialize, JsonSchema, Clone)]
+pub struct IngredientPrice {
+    pub ingredient_id: i64,
+    pub currency: String,
+    /// Price of a single gram, in the smallest unit of the currency
+    pub price_per_gram: f64,
+}
+
+#[derive(Serialize, JsonSchema, Clone)]
+pub struct CurrencyAmount {
+    pub currency: String,
+    pub amount: f64,
+}
+
+/// Adds `amount` to the entry of `currency` in `totals`, creating it if necessary.
+pub fn add_to_totals(totals: &mut Vec<CurrencyAmount>, currency: &str, amount: f64) {
+    match totals.iter_mut().find(|t| t.currency == currency) {
+        Some(total) => total.amount += amount,
+        None => totals.push(CurrencyAmount {
+            currency: currency.to_string(),
+            amount,
+        }),
+    }
+}
+
+fn compute_price(method: PriceMethod, purchases: &[DatabasePurchase]) -> Option<IngredientPrice> {
+    let latest = purchases.last()?;
+    // Purchases made with a different currency can't be compared, so only the ones that share the
+    // latest purchase's currency are considered.
+    let purchases = purchases
+        .iter()
+        .filter(|p| p.currency == latest.currency && p.package_weight > 0)
+        .collect::<Vec<_>>();
+
+    let price_per_gram = match method {
+        PriceMethod::Latest => {
+            let latest = purchases.last()?;
+            latest.price as f64 / latest.package_weight as f64
+        }
+        PriceMethod::TimeWeighted => {
+            let now = chrono::Utc::now().timestamp_millis();
+            let (weighted_sum, total_duration) = purchases
+                .iter()
+                .enumerate()
+                .map(|(index, purchase)| {
+                    let end = purchases
+                        .get(index + 1)
+                        .map(|next| next.purchase_date)
+                        .unwrap_or(now);
+                    let duration = (end - purchase.purchase_date).max(1) as f64;
+                    let price_per_gram = purchase.price as f64 / purchase.package_weight as f64;
+                    (price_per_gram * duration, duration)
+                })
+                .fold((0.0, 0.0), |(a1, b1), (a2, b2)| (a1 + a2, b1 + b2));
+            if total_duration == 0.0 {
+                return None;
+            }
+            weighted_sum / total_duration
+        }
+    };
+
+    Some(IngredientPrice {
+        ingredient_id: latest.ingredient_id,
+        currency: latest.currency.clone(),
+        price_per_gram,
+    })
+}
+
+/// Computes the per-gram price of every ingredient in `ingredient_ids` that has at least one
+/// purchase registered. Ingredients without purchases are left out of the result.
+pub async fn fetch_ingredient_prices(
+    connection: &sqlx::Pool<sqlx::Sqlite>,
+    method: PriceMethod,
+    ingredient_ids: &[i64],
+) -> anyhow::Result<HashMap<i64, IngredientPrice>> {
+    if ingredient_ids.is_empty() {
+        return Ok(HashMap::new());
+    }
+
+    let purchases = sqlx::QueryBuilder::<Sqlite>::new(
+        r#"
+        SELECT
+            ingredient_id,
+            purchase_date,
+            price,
+            currency,
+            package_weight
+        FROM IngredientPurchase
+        WHERE ingredient_id IN "#,
+    )
+    .push_tuples(ingredient_ids.iter(), |mut p, id| {
+        p.push_bind(*id);
+    })
+    .push(" ORDER BY purchase_date ASC, id ASC")
+    .build_query_as::<DatabasePurchase>()
+    .fetch_all(connection)
+    .await?
+    .into_iter()
+    .fold(
+        HashMap::<i64, Vec<DatabasePurchase>>::new(),
+        |mut dict, purchase| {
+            dict.entry(purchase.ingredient_id)
+                .or_default()
+                .push(purchase);
+            dict
+        },
+    );
+
+    Ok(purchases
+        .into_iter()
+        .filter_map(|(id, purchases)| Some((id, compute_price(method, &purchases)?)))
+        .collect())
+}
+
+#[derive(Serialize, JsonSchema, Clone)]
+pub struct ComponentCost {
+    pub id: i64,
+    pub name: Option<String>,
+    pub weight: i64,
+    /// Empty if the component has no price information
+    pub costs: Vec<CurrencyAmount>,
+}
+
+#[derive(Serialize, JsonSchema, Clone)]
+pub struct DishCost {
+    pub dish_id: i64,
+    /// The dish's total weight, or the sum of its ingredients' weights if it was not set
+    pub weight: f64,
+    pub costs: Vec<CurrencyAmount>,
+    pub ingredients: Vec<ComponentCost>,
+    /// Whether some of the ingredients have no purchases registered, and were left out of the
+    /// cost
+    pub has_missing_prices: bool,
+}
+
+impl DishCost {
+    /// The cost of `weight` grams of this dish
+    pub fn portion_costs(&self, weight: i64) -> Vec<CurrencyAmount> {
+        if self.weight <= 0.0 {
+            return vec![];
+        }
+        self.costs
+            .iter()
+            .map(|cost| CurrencyAmount {
+                currency: cost.currency.clone(),
+                amount: cost.amount * weight as f64 / self.weight,
+            })
+            .collect()
+    }
+}
+
+#[derive(FromRow)]
+struct DatabaseDishIngredient {
+    dish_id: i64,
+    dish_total_weight: i64,
+    ingredient_id: Option<i64>,
+    ingredient_name: Option<String>,
+    weight: Option<i64>,
+}
+
+/// Computes the cost of every dish in `dish_ids`, based on the price of its ingredients.
+pub async fn fetch_dish_costs(
+    connection: &sqlx::Pool<sqlx::Sqlite>,
+    method: PriceMethod,
+    dish_ids: &[i64],
+) -> anyhow::Result<HashMap<i64, DishCost>> {
+    if dish_ids.is_empty() {
+        return Ok(HashMap::new());
+    }
+
+    let rows = sqlx::QueryBuilder::<Sqlite>::new(
+        r#"
+        SELECT
+            Dish.id AS dish_id,
+            Dish.total_weight AS dish_total_weight,
+            DishIngredient.ingredient_id,
+            Ingredient.name AS ingredient_name,
+            DishIngredient.weight
+        FROM Dish
+            LEFT JOIN DishIngredient ON DishIngredient.dish_id = Dish.id
+            LEFT JOIN Ingredient ON Ingredient.id = DishIngredient.ingredient_id
+        WHERE Dish.id IN "#,
+    )
+    .push_tuples(dish_ids.iter(), |mut p, id| {
+        p.push_bind(*id);
+    })
+    .build_query_as::<DatabaseDishIngredient>()
+    .fetch_all(connection)
+    .await?;
+
+    let ingredient_ids = rows
+        .iter()
+        .filter_map(|row| row.ingredient_id)
+        .collect::<Vec<i64>>();
+    let prices = fetch_ingredient_prices(connection, method, &ingredient_ids).await?;
+
+    let mut dishes = HashMap::<i64, DishCost>::new();
+    for row in rows {
+        let dish = dishes.entry(row.dish_id).or_insert_with(|| DishCost {
+            dish_id: row.dish_id,
+            weight: row.dish_total_weight as f64,
+            costs: vec![],
+            ingredients: vec![],
+            has_missing_prices: false,
+        });
+        let (Some(ingredient_id), Some(weight)) = (row.ingredient_id, row.weight) else {
+            continue;
+        };
+
+        let costs = match prices.get(&ingredient_id) {
+            Some(price) => {
+                let amount = price.price_per_gram * weight as f64;
+                add_to_totals(&mut dish.costs, &price.currency, amount);
+                vec![CurrencyAmount {
+                    currency: price.currency.clone(),
+                    amount,
+                }]
+            }
+            None => {
+                dish.has_missing_prices = true;
+                vec![]
+            }
+        };
+        dish.ingredients.push(ComponentCost {
+            id: ingredient_id,
+            name: row.ingredient_name,
+            weight,
+            costs,
+        });
+    }
+
+    for dish in dishes.values_mut() {
+        if dish.weight <= 0.0 {
+            dish.weight = dish.ingredients.iter().map(|i| i.weight as f64).sum();
+        }
+    }
+
+    Ok(dishes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Far enough in the future that the last purchase is only in effect for a millisecond, which
+    /// keeps the time weighted prices independent of the current time.
+    const FUTURE: i64 = 100 * 365 * 24 * 60 * 60 * 1000;
+
+    fn purchase(purchase_date: i64, price: i64, currency: &str, weight: i64) -> DatabasePurchase {
+        DatabasePurchase {
+            ingredient_id: 1,
+            purchase_date,
+            price,
+            currency: currency.to_string(),
+            package_weight: weight,
+        }
+    }
+
+    fn assert_close(a: f64, b: f64) {
+        assert!((a - b).abs() < 1e-9, "{a} != {b}");
+    }
+
+    #[test]
+    fn no_purchases_have_no_price() {
+        assert!(compute_price(PriceMethod::Latest, &[]).is_none());
+        assert!(compute_price(PriceMethod::TimeWeighted, &[]).is_none());
+    }
+
+    #[test]
+    fn latest_uses_the_last_purchase() {
+        let purchases = [purchase(0, 100, "EUR", 100), purchase(10, 300, "EUR", 200)];
+        let price = compute_price(PriceMethod::Latest, &purchases).unwrap();
+        assert_eq!(price.ingredient_id, 1);
+        assert_eq!(price.currency, "EUR");
+        assert_close(price.price_per_gram, 1.5);
+    }
+
+    #[test]
+    fn time_weighted_weights_by_how_long_each_price_was_in_effect() {
+        let purchases = [
+            purchase(FUTURE, 100, "EUR", 100),
+            purchase(FUTURE + 1000, 200, "EUR", 100),
+            purchase(FUTURE + 3000, 500, "EUR", 100),
+        ];
+        let price = compute_price(PriceMethod::TimeWeighted, &purchases).unwrap();
+        assert_close(
+            price.price_per_gram,
+            (1.0 * 1000.0 + 2.0 * 2000.0 + 5.0) / 3001.0,
+        );
+    }
+
+    #[test]
+    fn only_purchases_in_the_latest_currency_are_used() {
+        let purchases = [
+            purchase(FUTURE, 100, "EUR", 100),
+            purchase(FUTURE + 1000, 900, "USD", 100),
+            purchase(FUTURE + 2000, 300, "EUR", 100),
+        ];
+        let price = compute_price(PriceMethod::TimeWeighted, &purchases).unwrap();
+        assert_eq!(price.currency, "EUR");
+        assert_close(price.price_per_gram, (1.0 * 2000.0 + 3.0) / 2001.0);
+    }
+
+    #[test]
+    fn purchases_without_weight_are_ignored() {
+        let purchases = [purchase(0, 100, "EUR", 100), purchase(10, 300, "EUR", 0)];
+        let price = compute_price(PriceMethod::Latest, &purchases).unwrap();
+        assert_close(price.price_per_gram, 1.0);
+        assert!(compute_price(PriceMethod::Latest, &[purchase(0, 100, "EUR", 0)]).is_none());
+    }
+
+    #[test]
+    fn totals_are_kept_per_currency() {
+        let mut totals = vec![];
+        add_to_totals(&mut totals, "EUR", 1.5);
+        add_to_totals(&mut totals, "USD", 2.0);
+        add_to_totals(&mut totals, "EUR", 0.5);
+        assert_eq!(totals.len(), 2);
+        assert_eq!(totals[0].currency, "EUR");
+        assert_close(totals[0].amount, 2.0);
+        assert_eq!(totals[1].currency, "USD");
+        assert_close(totals[1].amount, 2.0);
+    }
+
+    #[test]
+    fn portion_costs_are_proportional_to_the_weight() {
+        let dish = DishCost {
+            dish_id: 1,
+            weight: 400.0,
+            costs: vec![CurrencyAmount {
+                currency: "EUR".to_string(),
+                amount: 8.0,
+            }],
+            ingredients: vec![],
+            has_missing_prices: false,
+        };
+        let costs = dish.portion_costs(100);
+        assert_eq!(costs.len(), 1);
+        assert_close(costs[0].amount, 2.0);
+        assert!(DishCost {
+            weight: 0.0,
+            ..dish
+        }
+        .portion_costs(100)
+        .is_empty());
+    }
+}
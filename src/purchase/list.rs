@@ -0,0 +1,38 @@
+use axum::extract::{Query, State};
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+use crate::{
+    models::IngredientPurchase,
+    server::{ServerResponse, ServerResponseResult},
+    session::AuthUser,
+    state::AppState,
+};
+
+#[derive(JsonSchema, Deserialize)]
+pub struct ListPurchasesQueryParams {
+    purchased_since: Option<i64>,
+}
+
+pub async fn list_purchases(
+    State(AppState { connection, .. }): State<AppState>,
+    AuthUser { household_id, .. }: AuthUser,
+    Query(ListPurchasesQueryParams { purchased_since }): Query<ListPurchasesQueryParams>,
+) -> ServerResponseResult<Vec<IngredientPurchase>> {
+    let purchased_since = purchased_since.unwrap_or(0);
+    let purchases = sqlx::query_as!(
+        IngredientPurchase,
+        r#"
+        SELECT IngredientPurchase.*
+        FROM IngredientPurchase
+            JOIN Ingredient ON Ingredient.id = IngredientPurchase.ingredient_id
+        WHERE Ingredient.household_id = ? AND purchase_date >= ?
+        ORDER BY purchase_date DESC;"#,
+        household_id,
+        purchased_since
+    )
+    .fetch_all(&connection)
+    .await?;
+
+    Ok(ServerResponse::success(purchases).json())
+}
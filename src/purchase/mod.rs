@@ -0,0 +1,18 @@
+use aide::axum::{routing::get, ApiRouter};
+
+use crate::state::AppState;
+
+mod list;
+mod monthly;
+mod nutrient_ranking;
+
+pub fn route(state: AppState) -> ApiRouter {
+    ApiRouter::new()
+        .api_route("/", get(list::list_purchases))
+        .api_route("/monthly", get(monthly::get_monthly_spend))
+        .api_route(
+            "/nutrient-ranking",
+            get(nutrient_ranking::get_nutrient_ranking),
+        )
+        .with_state(state)
+}
@@ -0,0 +1,44 @@
+use axum::extract::State;
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use crate::{
+    server::{ServerResponse, ServerResponseResult},
+    session::AuthUser,
+    state::AppState,
+};
+
+#[derive(Serialize, JsonSchema)]
+pub struct MonthlySpend {
+    /// Month in the `YYYY-MM` format
+    month: String,
+    currency: String,
+    /// Total spent in the month, in the smallest unit of the currency
+    total: i64,
+    purchase_count: i64,
+}
+
+pub async fn get_monthly_spend(
+    State(AppState { connection, .. }): State<AppState>,
+    AuthUser { household_id, .. }: AuthUser,
+) -> ServerResponseResult<Vec<MonthlySpend>> {
+    let spend = sqlx::query_as!(
+        MonthlySpend,
+        r#"
+        SELECT
+            strftime('%Y-%m', purchase_date / 1000, 'unixepoch') AS "month!: String",
+            currency,
+            SUM(price) AS "total!: i64",
+            COUNT(*) AS "purchase_count!: i64"
+        FROM IngredientPurchase
+            JOIN Ingredient ON Ingredient.id = IngredientPurchase.ingredient_id
+        WHERE Ingredient.household_id = ?
+        GROUP BY 1, currency
+        ORDER BY 1 DESC, currency;"#,
+        household_id
+    )
+    .fetch_all(&connection)
+    .await?;
+
+    Ok(ServerResponse::success(spend).json())
+}
@@ -0,0 +1,95 @@
+use axum::extract::{Query, State};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    nutrition::Nutrient,
+    price::{fetch_ingredient_prices, PriceMethod},
+    server::{ServerResponse, ServerResponseResult},
+    session::AuthUser,
+    state::AppState,
+};
+
+#[derive(Deserialize, JsonSchema)]
+pub struct GetNutrientRankingQueryParams {
+    nutrient: Nutrient,
+    method: Option<PriceMethod>,
+}
+
+#[derive(Serialize, JsonSchema)]
+pub struct NutrientRankingEntry {
+    ingredient_id: i64,
+    ingredient_name: String,
+    currency: String,
+    /// Grams (or kcal) of the nutrient bought with 100 units of the price. That is, one
+    /// euro/dollar when prices are registered in cents.
+    nutrient_per_price: f64,
+}
+
+struct DatabaseIngredient {
+    id: i64,
+    name: String,
+    kcal_100g: Option<f64>,
+    proteins_100g: Option<f64>,
+    fat_100g: Option<f64>,
+    carbohydrates_100g: Option<f64>,
+}
+
+pub async fn get_nutrient_ranking(
+    State(AppState { connection, .. }): State<AppState>,
+    AuthUser { household_id, .. }: AuthUser,
+    Query(GetNutrientRankingQueryParams { nutrient, method }): Query<GetNutrientRankingQueryParams>,
+) -> ServerResponseResult<Vec<NutrientRankingEntry>> {
+    let ingredients = sqlx::query_as!(
+        DatabaseIngredient,
+        r#"
+        SELECT
+            Ingredient.id,
+            Ingredient.name,
+            CAST (kcal_100g AS FLOAT) AS "kcal_100g?: f64",
+            CAST (proteins_100g AS FLOAT) AS "proteins_100g?: f64",
+            CAST (fat_100g AS FLOAT) AS "fat_100g?: f64",
+            CAST (carbohydrates_100g AS FLOAT) AS "carbohydrates_100g?: f64"
+        FROM Ingredient
+            JOIN IngredientNutrition ON IngredientNutrition.ingredient_id = Ingredient.id
+        WHERE Ingredient.household_id = ? AND EXISTS (
+            SELECT 1 FROM IngredientPurchase WHERE IngredientPurchase.ingredient_id = Ingredient.id
+        );"#,
+        household_id
+    )
+    .fetch_all(&connection)
+    .await?;
+
+    let prices = fetch_ingredient_prices(
+        &connection,
+        method.unwrap_or_default(),
+        &ingredients.iter().map(|i| i.id).collect::<Vec<i64>>(),
+    )
+    .await?;
+
+    let mut ranking = ingredients
+        .into_iter()
+        .filter_map(|ingredient| {
+            let price = prices.get(&ingredient.id)?;
+            let nutrient_100g = match nutrient {
+                Nutrient::Kcal => ingredient.kcal_100g,
+                Nutrient::Proteins => ingredient.proteins_100g,
+                Nutrient::Fat => ingredient.fat_100g,
+                Nutrient::Carbohydrates => ingredient.carbohydrates_100g,
+            }?;
+            if price.price_per_gram <= 0.0 {
+                return None;
+            }
+            Some(NutrientRankingEntry {
+                ingredient_id: ingredient.id,
+                ingredient_name: ingredient.name,
+                currency: price.currency.clone(),
+                nutrient_per_price: nutrient_100g / price.price_per_gram,
+            })
+        })
+        .collect::<Vec<NutrientRankingEntry>>();
+
+    ranking.sort_by(|a, b| b.nutrient_per_price.total_cmp(&a.nutrient_per_price));
+
+    Ok(ServerResponse::success(ranking).json())
+}
@@ -0,0 +1,95 @@
+use std::time::Duration;
+
+use sqlx::{Pool, Sqlite, SqliteConnection};
+
+/// How often the trash is checked for dishes and meals that were kept there long enough.
+const PURGE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// Deletes a meal for good, along with its components and everything that points to it.
+pub async fn purge_meal(connection: &mut SqliteConnection, meal_id: i64) -> anyhow::Result<()> {
+    sqlx::query!(
+        r#"
+        DELETE FROM MealIngredient WHERE meal_id = ?;
+        DELETE FROM MealDish WHERE meal_id = ?;
+        DELETE FROM MealTemplateUsage WHERE meal_id = ?;
+        DELETE FROM ShareComment WHERE meal_id = ?;
+        UPDATE SupplementDose SET meal_id = NULL WHERE meal_id = ?;
+        UPDATE PlannedMeal SET meal_id = NULL WHERE meal_id = ?;
+        DELETE FROM Meal WHERE id = ?"#,
+        meal_id,
+        meal_id,
+        meal_id,
+        meal_id,
+        meal_id,
+        meal_id,
+        meal_id,
+    )
+    .execute(connection)
+    .await?;
+    Ok(())
+}
+
+/// Deletes a dish for good, along with its ingredients. Meals, plans and templates that still
+/// use it lose it.
+pub async fn purge_dish(connection: &mut SqliteConnection, dish_id: i64) -> anyhow::Result<()> {
+    sqlx::query!(
+        r#"
+        DELETE FROM DishIngredient WHERE dish_id = ?;
+        DELETE FROM MealDish WHERE dish_id = ?;
+        DELETE FROM PlannedMealDish WHERE dish_id = ?;
+        DELETE FROM MealTemplateDish WHERE dish_id = ?;
+        DELETE FROM RecurringMealPlanDish WHERE dish_id = ?;
+        DELETE FROM Dish WHERE id = ?"#,
+        dish_id,
+        dish_id,
+        dish_id,
+        dish_id,
+        dish_id,
+        dish_id,
+    )
+    .execute(connection)
+    .await?;
+    Ok(())
+}
+
+/// Purges the dishes and meals that have been in the trash for longer than `retention`, in
+/// milliseconds.
+pub async fn purge_expired(connection: &Pool<Sqlite>, retention: i64) -> anyhow::Result<()> {
+    let expired_before = chrono::Utc::now().timestamp_millis() - retention;
+
+    let mut transaction = connection.begin().await?;
+
+    let meal_ids = sqlx::query_scalar!(
+        "SELECT id FROM Meal WHERE deletion_date < ?",
+        expired_before
+    )
+    .fetch_all(&mut *transaction)
+    .await?;
+    for meal_id in meal_ids {
+        purge_meal(&mut transaction, meal_id).await?;
+    }
+
+    let dish_ids = sqlx::query_scalar!(
+        "SELECT id FROM Dish WHERE deletion_date < ?",
+        expired_before
+    )
+    .fetch_all(&mut *transaction)
+    .await?;
+    for dish_id in dish_ids {
+        purge_dish(&mut transaction, dish_id).await?;
+    }
+
+    transaction.commit().await?;
+    Ok(())
+}
+
+/// Runs `purge_expired` every `PURGE_INTERVAL`, for as long as the server is up.
+pub async fn purge_expired_periodically(connection: Pool<Sqlite>, retention: i64) {
+    let mut interval = tokio::time::interval(PURGE_INTERVAL);
+    loop {
+        interval.tick().await;
+        if let Err(error) = purge_expired(&connection, retention).await {
+            println!("Could not purge the trash: {error}");
+        }
+    }
+}
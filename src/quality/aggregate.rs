@@ -0,0 +1,250 @@
+use std::collections::{BTreeSet, HashMap};
+
+use schemars::JsonSchema;
+use serde::Serialize;
+use sqlx::{prelude::FromRow, Sqlite};
+
+use crate::{
+    diet::parse_tags,
+    nutrition::{fetch_dish_composition, fetch_ingredient_nutrients, Nutrients},
+};
+
+/// Kcal eaten from food of each NOVA group.
+#[derive(Serialize, JsonSchema, Default, Clone, Copy)]
+pub struct NovaBreakdown {
+    pub group_1: f64,
+    pub group_2: f64,
+    pub group_3: f64,
+    pub group_4: f64,
+    /// Food without a NOVA group
+    pub unknown: f64,
+}
+
+/// Grams eaten of food of each Nutri-Score grade.
+#[derive(Serialize, JsonSchema, Default, Clone, Copy)]
+pub struct NutriScoreBreakdown {
+    pub a: f64,
+    pub b: f64,
+    pub c: f64,
+    pub d: f64,
+    pub e: f64,
+    /// Food without a Nutri-Score grade
+    pub unknown: f64,
+}
+
+#[derive(Serialize, JsonSchema, Default, Clone)]
+pub struct QualitySummary {
+    /// Grams eaten
+    pub weight: f64,
+    pub kcal: f64,
+    pub nova_kcal: NovaBreakdown,
+    /// Share of the kcal that came from ultra-processed food (NOVA group 4), from 0 to 1
+    pub ultra_processed_share: Option<f64>,
+    pub nutriscore_weight: NutriScoreBreakdown,
+    /// Average Nutri-Score grade of the graded food, weighted by grams, from 1 (A) to 5 (E)
+    pub nutriscore_average: Option<f64>,
+    /// Open Food Facts tags of the distinct additives eaten, such as `en:e330`
+    pub additives: BTreeSet<String>,
+    pub additive_count: usize,
+}
+
+impl QualitySummary {
+    fn add(&mut self, quality: Option<&IngredientQuality>, grams: f64, kcal: f64) {
+        self.weight += grams;
+        self.kcal += kcal;
+
+        let nova_group = quality.and_then(|q| q.nova_group);
+        *match nova_group {
+            Some(1) => &mut self.nova_kcal.group_1,
+            Some(2) => &mut self.nova_kcal.group_2,
+            Some(3) => &mut self.nova_kcal.group_3,
+            Some(4) => &mut self.nova_kcal.group_4,
+            _ => &mut self.nova_kcal.unknown,
+        } += kcal;
+
+        let grade = quality.and_then(|q| q.nutriscore_grade.as_deref());
+        *match grade {
+            Some("a") => &mut self.nutriscore_weight.a,
+            Some("b") => &mut self.nutriscore_weight.b,
+            Some("c") => &mut self.nutriscore_weight.c,
+            Some("d") => &mut self.nutriscore_weight.d,
+            Some("e") => &mut self.nutriscore_weight.e,
+            _ => &mut self.nutriscore_weight.unknown,
+        } += grams;
+
+        if let Some(quality) = quality {
+            self.additives.extend(quality.additives.iter().cloned());
+        }
+    }
+
+    /// Fills in the values derived from the totals, once everything was added.
+    fn finish(mut self) -> Self {
+        self.ultra_processed_share =
+            (self.kcal > 0.0).then_some(self.nova_kcal.group_4 / self.kcal);
+
+        let grades = self.nutriscore_weight;
+        let graded = grades.a + grades.b + grades.c + grades.d + grades.e;
+        let score = grades.a + grades.b * 2.0 + grades.c * 3.0 + grades.d * 4.0 + grades.e * 5.0;
+        self.nutriscore_average = (graded > 0.0).then_some(score / graded);
+
+        self.additive_count = self.additives.len();
+        self
+    }
+}
+
+struct IngredientQuality {
+    nutriscore_grade: Option<String>,
+    nova_group: Option<i64>,
+    additives: Vec<String>,
+}
+
+#[derive(FromRow)]
+struct DatabaseIngredientQuality {
+    ingredient_id: i64,
+    nutriscore_grade: Option<String>,
+    nova_group: Option<i64>,
+    additives_tags: Option<String>,
+}
+
+async fn fetch_ingredient_quality(
+    connection: &sqlx::Pool<Sqlite>,
+    ingredient_ids: &[i64],
+) -> anyhow::Result<HashMap<i64, IngredientQuality>> {
+    if ingredient_ids.is_empty() {
+        return Ok(HashMap::new());
+    }
+
+    Ok(sqlx::QueryBuilder::<Sqlite>::new(
+        r#"
+        SELECT ingredient_id, nutriscore_grade, nova_group, additives_tags
+        FROM IngredientQuality
+        WHERE ingredient_id IN "#,
+    )
+    .push_tuples(ingredient_ids.iter(), |mut p, id| {
+        p.push_bind(*id);
+    })
+    .build_query_as::<DatabaseIngredientQuality>()
+    .fetch_all(connection)
+    .await?
+    .into_iter()
+    .map(|row| {
+        (
+            row.ingredient_id,
+            IngredientQuality {
+                nutriscore_grade: row.nutriscore_grade,
+                nova_group: row.nova_group,
+                additives: parse_tags(row.additives_tags),
+            },
+        )
+    })
+    .collect())
+}
+
+/// A dish or ingredient eaten by a household member.
+#[derive(FromRow)]
+pub struct EatenComponent {
+    pub meal_id: i64,
+    pub date: i64,
+    pub dish_id: Option<i64>,
+    pub ingredient_id: Option<i64>,
+    pub weight: i64,
+}
+
+/// Fetches what a household member ate between `from` and `to`.
+pub async fn fetch_eaten_components(
+    connection: &sqlx::Pool<Sqlite>,
+    member_id: i64,
+    from: i64,
+    to: i64,
+) -> anyhow::Result<Vec<EatenComponent>> {
+    Ok(sqlx::query_as::<_, EatenComponent>(
+        r#"
+        SELECT Meal.id AS meal_id, Meal.eat_date AS date, dish_id, NULL AS ingredient_id, weight
+        FROM Meal JOIN MealDish ON MealDish.meal_id = Meal.id
+        WHERE Meal.user_id = ?3 AND Meal.deletion_date IS NULL AND Meal.eat_date >= ?1 AND Meal.eat_date <= ?2
+        UNION ALL
+        SELECT Meal.id AS meal_id, Meal.eat_date AS date, NULL AS dish_id, ingredient_id, weight
+        FROM Meal JOIN MealIngredient ON MealIngredient.meal_id = Meal.id
+        WHERE Meal.user_id = ?3 AND Meal.deletion_date IS NULL AND Meal.eat_date >= ?1 AND Meal.eat_date <= ?2;"#,
+    )
+    .bind(from)
+    .bind(to)
+    .bind(member_id)
+    .fetch_all(connection)
+    .await?)
+}
+
+/// Everything needed to summarize the quality of a set of eaten components, fetched all at once.
+pub struct QualityTable {
+    dishes: HashMap<i64, Vec<(i64, f64)>>,
+    quality: HashMap<i64, IngredientQuality>,
+    nutrients: HashMap<i64, Nutrients>,
+}
+
+impl QualityTable {
+    pub async fn fetch(
+        connection: &sqlx::Pool<Sqlite>,
+        components: &[EatenComponent],
+    ) -> anyhow::Result<Self> {
+        let dishes = fetch_dish_composition(
+            connection,
+            &components
+                .iter()
+                .filter_map(|c| c.dish_id)
+                .collect::<Vec<i64>>(),
+        )
+        .await?;
+
+        let ingredient_ids = components
+            .iter()
+            .filter_map(|c| c.ingredient_id)
+            .chain(dishes.values().flatten().map(|(id, _)| *id))
+            .collect::<Vec<i64>>();
+        let (quality, nutrients) = futures::try_join!(
+            fetch_ingredient_quality(connection, &ingredient_ids),
+            fetch_ingredient_nutrients(connection, &ingredient_ids),
+        )?;
+
+        Ok(QualityTable {
+            dishes,
+            quality,
+            nutrients,
+        })
+    }
+
+    /// Adds the ingredients of `component` to `summary`. Dishes count as the ingredients they are
+    /// made of.
+    fn add(&self, summary: &mut QualitySummary, component: &EatenComponent) {
+        let ingredients = match (component.dish_id, component.ingredient_id) {
+            (Some(dish_id), _) => self
+                .dishes
+                .get(&dish_id)
+                .into_iter()
+                .flatten()
+                .map(|(id, grams)| (*id, grams * component.weight as f64))
+                .collect(),
+            (_, Some(ingredient_id)) => vec![(ingredient_id, component.weight as f64)],
+            (None, None) => vec![],
+        };
+
+        for (ingredient_id, grams) in ingredients {
+            let kcal = self
+                .nutrients
+                .get(&ingredient_id)
+                .map_or(0.0, |n| n.kcal * grams);
+            summary.add(self.quality.get(&ingredient_id), grams, kcal);
+        }
+    }
+
+    /// Summary of `components`.
+    pub fn summarize<'a>(
+        &self,
+        components: impl IntoIterator<Item = &'a EatenComponent>,
+    ) -> QualitySummary {
+        let mut summary = QualitySummary::default();
+        for component in components {
+            self.add(&mut summary, component);
+        }
+        summary.finish()
+    }
+}
@@ -0,0 +1,65 @@
+use std::collections::BTreeMap;
+
+use axum::extract::{Query, State};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    server::{ServerResponse, ServerResponseResult},
+    session::AuthUser,
+    state::AppState,
+};
+
+use super::aggregate::{fetch_eaten_components, EatenComponent, QualitySummary, QualityTable};
+
+#[derive(Deserialize, JsonSchema)]
+pub struct GetMealQualityQueryParams {
+    from: i64,
+    to: i64,
+    /// Household member whose meals should be summarized. Defaults to the current user
+    member_id: Option<i64>,
+}
+
+#[derive(Serialize, JsonSchema)]
+pub struct MealQuality {
+    meal_id: i64,
+    eat_date: i64,
+    quality: QualitySummary,
+}
+
+/// Nutri-Score, NOVA groups and additives of each meal a household member ate, oldest first.
+pub async fn get_meal_quality(
+    State(AppState { connection, .. }): State<AppState>,
+    user: AuthUser,
+    Query(GetMealQualityQueryParams {
+        from,
+        to,
+        member_id,
+    }): Query<GetMealQualityQueryParams>,
+) -> ServerResponseResult<Vec<MealQuality>> {
+    let member_id = user.resolve_member(&connection, member_id).await?;
+
+    let components = fetch_eaten_components(&connection, member_id, from, to).await?;
+    let table = QualityTable::fetch(&connection, &components).await?;
+
+    let meals = components.iter().fold(
+        BTreeMap::<(i64, i64), Vec<&EatenComponent>>::new(),
+        |mut dict, component| {
+            dict.entry((component.date, component.meal_id))
+                .or_default()
+                .push(component);
+            dict
+        },
+    );
+
+    let meals = meals
+        .into_iter()
+        .map(|((eat_date, meal_id), components)| MealQuality {
+            meal_id,
+            eat_date,
+            quality: table.summarize(components),
+        })
+        .collect();
+
+    Ok(ServerResponse::success(meals).json())
+}
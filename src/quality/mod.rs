@@ -0,0 +1,16 @@
+use aide::axum::{routing::get, ApiRouter};
+
+use crate::state::AppState;
+
+mod aggregate;
+mod meal;
+mod period;
+mod trend;
+
+pub fn route(state: AppState) -> ApiRouter {
+    ApiRouter::new()
+        .api_route("/meal", get(meal::get_meal_quality))
+        .api_route("/period", get(period::get_period_quality))
+        .api_route("/trend", get(trend::get_quality_trend))
+        .with_state(state)
+}
@@ -0,0 +1,76 @@
+use std::collections::BTreeMap;
+
+use axum::extract::{Query, State};
+use chrono::{NaiveDate, TimeZone};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    period::utc_offset,
+    period::{period_start, SummaryPeriod},
+    server::{ServerResponse, ServerResponseResult},
+    session::AuthUser,
+    state::AppState,
+};
+
+use super::aggregate::{fetch_eaten_components, EatenComponent, QualitySummary, QualityTable};
+
+#[derive(Deserialize, JsonSchema)]
+pub struct GetPeriodQualityQueryParams {
+    from: i64,
+    to: i64,
+    /// Offset of the household's timezone, used to decide which day each meal belongs to
+    utc_offset_minutes: Option<i32>,
+    period: Option<SummaryPeriod>,
+    /// Household member whose meals should be summarized. Defaults to the current user
+    member_id: Option<i64>,
+}
+
+#[derive(Serialize, JsonSchema)]
+pub struct PeriodQuality {
+    /// First day of the period, in the `YYYY-MM-DD` format
+    start: String,
+    quality: QualitySummary,
+}
+
+/// Nutri-Score, NOVA groups and additives of what a household member ate, day by day or week by
+/// week. Weekly additive counts are of the distinct additives eaten in the whole week.
+pub async fn get_period_quality(
+    State(AppState { connection, .. }): State<AppState>,
+    user: AuthUser,
+    Query(GetPeriodQualityQueryParams {
+        from,
+        to,
+        utc_offset_minutes,
+        period,
+        member_id,
+    }): Query<GetPeriodQualityQueryParams>,
+) -> ServerResponseResult<Vec<PeriodQuality>> {
+    let offset = utc_offset(utc_offset_minutes)?;
+    let period = period.unwrap_or_default();
+    let member_id = user.resolve_member(&connection, member_id).await?;
+
+    let components = fetch_eaten_components(&connection, member_id, from, to).await?;
+    let table = QualityTable::fetch(&connection, &components).await?;
+
+    let mut periods = BTreeMap::<NaiveDate, Vec<&EatenComponent>>::new();
+    for component in &components {
+        let Some(date) = offset.timestamp_millis_opt(component.date).single() else {
+            continue;
+        };
+        periods
+            .entry(period_start(date.date_naive(), period))
+            .or_default()
+            .push(component);
+    }
+
+    let summaries = periods
+        .into_iter()
+        .map(|(start, components)| PeriodQuality {
+            start: start.format("%Y-%m-%d").to_string(),
+            quality: table.summarize(components),
+        })
+        .collect();
+
+    Ok(ServerResponse::success(summaries).json())
+}
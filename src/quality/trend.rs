@@ -0,0 +1,117 @@
+use std::collections::BTreeMap;
+
+use axum::extract::{Query, State};
+use chrono::{Datelike, NaiveDate, TimeZone};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    period::utc_offset,
+    period::{period_start, SummaryPeriod},
+    server::{ServerResponse, ServerResponseResult},
+    session::AuthUser,
+    state::AppState,
+};
+
+use super::aggregate::{fetch_eaten_components, EatenComponent, QualitySummary, QualityTable};
+
+#[derive(Deserialize, JsonSchema)]
+pub struct GetQualityTrendQueryParams {
+    from: i64,
+    to: i64,
+    /// Offset of the household's timezone, used to decide which month each meal belongs to
+    utc_offset_minutes: Option<i32>,
+    /// Household member whose meals should be summarized. Defaults to the current user
+    member_id: Option<i64>,
+}
+
+/// How much a month's values went up (or down, if negative) since the month before.
+#[derive(Serialize, JsonSchema)]
+pub struct QualityChange {
+    ultra_processed_share: Option<f64>,
+    nutriscore_average: Option<f64>,
+    weekly_additive_count: f64,
+}
+
+#[derive(Serialize, JsonSchema)]
+pub struct MonthQuality {
+    /// Month in the `YYYY-MM` format
+    month: String,
+    quality: QualitySummary,
+    /// Average number of distinct additives eaten per week. Weeks that span two months are split
+    /// between them
+    weekly_additive_count: f64,
+    /// Change since the previous month with meals, if there's one
+    change: Option<QualityChange>,
+}
+
+fn difference(current: Option<f64>, previous: Option<f64>) -> Option<f64> {
+    Some(current? - previous?)
+}
+
+/// Nutri-Score, NOVA groups and additives of what a household member ate, month by month, with
+/// how they changed since the month before.
+pub async fn get_quality_trend(
+    State(AppState { connection, .. }): State<AppState>,
+    user: AuthUser,
+    Query(GetQualityTrendQueryParams {
+        from,
+        to,
+        utc_offset_minutes,
+        member_id,
+    }): Query<GetQualityTrendQueryParams>,
+) -> ServerResponseResult<Vec<MonthQuality>> {
+    let offset = utc_offset(utc_offset_minutes)?;
+    let member_id = user.resolve_member(&connection, member_id).await?;
+
+    let components = fetch_eaten_components(&connection, member_id, from, to).await?;
+    let table = QualityTable::fetch(&connection, &components).await?;
+
+    // Components eaten in each week of each month
+    let mut months = BTreeMap::<NaiveDate, BTreeMap<NaiveDate, Vec<&EatenComponent>>>::new();
+    for component in &components {
+        let Some(date) = offset.timestamp_millis_opt(component.date).single() else {
+            continue;
+        };
+        let date = date.date_naive();
+        let Some(month) = date.with_day(1) else {
+            continue;
+        };
+        months
+            .entry(month)
+            .or_default()
+            .entry(period_start(date, SummaryPeriod::Week))
+            .or_default()
+            .push(component);
+    }
+
+    let mut trend = Vec::<MonthQuality>::new();
+    for (month, weeks) in months {
+        let quality = table.summarize(weeks.values().flatten().copied());
+        let weekly_additive_count = weeks
+            .values()
+            .map(|components| table.summarize(components.iter().copied()).additive_count as f64)
+            .sum::<f64>()
+            / weeks.len() as f64;
+        let change = trend.last().map(|previous| QualityChange {
+            ultra_processed_share: difference(
+                quality.ultra_processed_share,
+                previous.quality.ultra_processed_share,
+            ),
+            nutriscore_average: difference(
+                quality.nutriscore_average,
+                previous.quality.nutriscore_average,
+            ),
+            weekly_additive_count: weekly_additive_count - previous.weekly_additive_count,
+        });
+
+        trend.push(MonthQuality {
+            month: month.format("%Y-%m").to_string(),
+            quality,
+            weekly_additive_count,
+            change,
+        });
+    }
+
+    Ok(ServerResponse::success(trend).json())
+}
@@ -0,0 +1,41 @@
+use axum::extract::{Path, State};
+use schemars::JsonSchema;
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::{
+    server::{ServerResponse, ServerResponseResult},
+    session::AuthUser,
+    state::AppState,
+};
+
+#[derive(Deserialize, JsonSchema)]
+pub struct RestrictionId {
+    restriction_id: i64,
+}
+
+#[derive(Error, Debug)]
+enum DeleteRestrictionError {
+    #[error("Could not find restriction with id \"{0}\"")]
+    RestrictionNotFound(i64),
+}
+
+pub async fn delete_restriction(
+    State(AppState { connection, .. }): State<AppState>,
+    AuthUser { user_id, .. }: AuthUser,
+    Path(RestrictionId { restriction_id }): Path<RestrictionId>,
+) -> ServerResponseResult<bool> {
+    let result = sqlx::query!(
+        "DELETE FROM DietaryRestriction WHERE id = ? AND user_id = ?",
+        restriction_id,
+        user_id
+    )
+    .execute(&connection)
+    .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(DeleteRestrictionError::RestrictionNotFound(restriction_id))?;
+    }
+
+    Ok(ServerResponse::success(true).json())
+}
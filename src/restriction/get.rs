@@ -0,0 +1,28 @@
+use axum::extract::{Query, State};
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+use crate::{
+    diet::{fetch_restrictions, DietaryRestriction},
+    server::{ServerResponse, ServerResponseResult},
+    session::AuthUser,
+    state::AppState,
+};
+
+#[derive(Deserialize, JsonSchema)]
+pub struct ListRestrictionsQueryParams {
+    /// Household member whose restrictions should be listed. Defaults to the current user
+    member_id: Option<i64>,
+}
+
+pub async fn list_restrictions(
+    State(AppState { connection, .. }): State<AppState>,
+    user @ AuthUser { household_id, .. }: AuthUser,
+    Query(ListRestrictionsQueryParams { member_id }): Query<ListRestrictionsQueryParams>,
+) -> ServerResponseResult<Vec<DietaryRestriction>> {
+    let member_id = user.resolve_member(&connection, member_id).await?;
+
+    let restrictions = fetch_restrictions(&connection, household_id, Some(member_id)).await?;
+
+    Ok(ServerResponse::success(restrictions).json())
+}
@@ -0,0 +1,20 @@
+use aide::axum::{
+    routing::{delete, post},
+    ApiRouter,
+};
+
+use crate::state::AppState;
+
+mod delete;
+mod get;
+mod post;
+
+pub fn route(state: AppState) -> ApiRouter {
+    ApiRouter::new()
+        .api_route(
+            "/",
+            post(post::post_restriction).get(get::list_restrictions),
+        )
+        .api_route("/:restriction_id", delete(delete::delete_restriction))
+        .with_state(state)
+}
@@ -0,0 +1,77 @@
+use axum::{extract::State, Json};
+use schemars::JsonSchema;
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::{
+    diet::{DietaryRestriction, Restriction},
+    server::{ServerResponse, ServerResponseResult},
+    session::AuthUser,
+    state::AppState,
+};
+
+#[derive(Deserialize, JsonSchema)]
+pub struct PostRestrictionBody {
+    /// Adding a restriction the user already has replaces it
+    restriction: Restriction,
+}
+
+#[derive(Error, Debug)]
+enum PostRestrictionError {
+    #[error("The allergen tag must not be empty")]
+    EmptyAllergen,
+}
+
+/// Turns an allergen such as `Peanuts` into an Open Food Facts tag such as `en:peanuts`.
+fn allergen_tag(allergen: &str) -> String {
+    let tag = allergen.trim().to_lowercase().replace(' ', "-");
+    if tag.contains(':') {
+        tag
+    } else {
+        format!("en:{tag}")
+    }
+}
+
+pub async fn post_restriction(
+    State(AppState { connection, .. }): State<AppState>,
+    AuthUser { user_id, .. }: AuthUser,
+    Json(PostRestrictionBody { restriction }): Json<PostRestrictionBody>,
+) -> ServerResponseResult<DietaryRestriction> {
+    let (restriction, avoid_traces) = match restriction {
+        Restriction::Allergen { tag, avoid_traces } => {
+            if tag.trim().is_empty() {
+                return Err(PostRestrictionError::EmptyAllergen)?;
+            }
+            let tag = allergen_tag(&tag);
+            (Restriction::Allergen { tag, avoid_traces }, avoid_traces)
+        }
+        restriction => (restriction, false),
+    };
+    let (kind, allergen) = restriction.kind();
+
+    let row = sqlx::query!(
+        r#"
+        INSERT INTO DietaryRestriction (user_id, kind, allergen, avoid_traces)
+        VALUES (?, ?, ?, ?)
+        ON CONFLICT (user_id, kind, coalesce(allergen, '')) DO UPDATE SET avoid_traces = excluded.avoid_traces
+        RETURNING
+            id,
+            creation_date,
+            (SELECT COALESCE(display_name, username) FROM User WHERE User.id = user_id) AS "member_name!: String";"#,
+        user_id,
+        kind,
+        allergen,
+        avoid_traces
+    )
+    .fetch_one(&connection)
+    .await?;
+
+    Ok(ServerResponse::success(DietaryRestriction {
+        id: row.id,
+        creation_date: row.creation_date,
+        member_id: user_id,
+        member_name: row.member_name,
+        restriction,
+    })
+    .json())
+}
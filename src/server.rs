@@ -7,6 +7,7 @@ use aide::{
     OperationOutput,
 };
 use axum::{
+    extract::State,
     http::{Request, StatusCode},
     middleware::{self, Next},
     response::{IntoResponse, Response},
@@ -58,6 +59,14 @@ impl ServerResponse<()> {
             status_code: StatusCode::INTERNAL_SERVER_ERROR,
         }
     }
+
+    pub fn error_code(message: impl ToString, status_code: StatusCode) -> Self {
+        ServerResponse {
+            data: (),
+            message: message.to_string(),
+            status_code,
+        }
+    }
 }
 
 impl<T: Serialize> IntoResponse for ServerResponse<T> {
@@ -69,8 +78,35 @@ impl<T: Serialize> IntoResponse for ServerResponse<T> {
 pub type ServerResponseResult<T> = Result<Json<ServerResponse<T>>, InternalServerError>;
 
 use crate::{
-    app_error::InternalServerError, dish::route as route_dish,
-    ingredient::route as route_ingredient, meal::route as route_meal, state::AppState,
+    app_error::InternalServerError,
+    auth::route as route_auth,
+    category::route as route_category,
+    delete_preview::route as route_delete_preview,
+    dish::route as route_dish,
+    etag::etag_middleware,
+    event::route as route_event,
+    events::EventBus,
+    footprint::route as route_footprint,
+    goal::route as route_goal,
+    history::route as route_history,
+    household::route as route_household,
+    idempotency::idempotency_middleware,
+    ingredient::route as route_ingredient,
+    meal::route as route_meal,
+    plan::route as route_plan,
+    purchase::route as route_purchase,
+    purge::purge_expired_periodically,
+    quality::route as route_quality,
+    restriction::route as route_restriction,
+    session::{authenticate, Authenticated},
+    share::route as route_share,
+    shared::route as route_shared,
+    state::AppState,
+    supplement::route as route_supplement,
+    sync::route as route_sync,
+    token::route as route_token,
+    trackable::route as route_trackable,
+    trash::route as route_trash,
 };
 
 async fn logging_middleware<B>(request: Request<B>, next: Next<B>) -> Response {
@@ -84,11 +120,38 @@ async fn logging_middleware<B>(request: Request<B>, next: Next<B>) -> Response {
     response
 }
 
+async fn auth_middleware<B>(
+    State(AppState { connection, .. }): State<AppState>,
+    mut request: Request<B>,
+    next: Next<B>,
+) -> Response {
+    match authenticate(
+        &connection,
+        request.headers(),
+        request.method(),
+        request.uri().path(),
+    )
+    .await
+    {
+        Ok(Some(Authenticated::User(user, scope))) => {
+            request.extensions_mut().insert(user);
+            request.extensions_mut().insert(scope);
+        }
+        Ok(Some(Authenticated::Guest(share))) => {
+            request.extensions_mut().insert(share);
+        }
+        Ok(None) => {}
+        Err(error) => return error.into_response(),
+    }
+
+    next.run(request).await
+}
+
 async fn serve_api(Extension(api): Extension<OpenApi>) -> impl IntoApiResponse {
     Json(api)
 }
 
-pub async fn server(port: u16, database_path: Option<PathBuf>) {
+pub async fn server(port: u16, database_path: Option<PathBuf>, trash_retention_days: u32) {
     let mut api = OpenApi {
         info: Info {
             description: Some("API for the Food Tracker app".to_string()),
@@ -120,6 +183,8 @@ pub async fn server(port: u16, database_path: Option<PathBuf>) {
         connection: sqlx::sqlite::SqlitePool::connect(&database_url)
             .await
             .unwrap(),
+        events: EventBus::new(),
+        trash_retention: i64::from(trash_retention_days) * 24 * 60 * 60 * 1000,
     };
 
     sqlx::migrate!("./migrations")
@@ -127,12 +192,45 @@ pub async fn server(port: u16, database_path: Option<PathBuf>) {
         .await
         .unwrap();
 
+    tokio::spawn(purge_expired_periodically(
+        state.connection.clone(),
+        state.trash_retention,
+    ));
+
     let app = ApiRouter::new()
+        .nest_api_service("/auth", route_auth(state.clone()))
+        .nest_api_service("/household", route_household(state.clone()))
+        .nest_api_service("/token", route_token(state.clone()))
+        .nest_api_service("/share", route_share(state.clone()))
+        .nest_api_service("/shared", route_shared(state.clone()))
+        .nest_api_service("/sync", route_sync(state.clone()))
+        .nest_api_service("/event", route_event(state.clone()))
+        .nest_api_service("/history", route_history(state.clone()))
+        .nest_api_service("/trash", route_trash(state.clone()))
+        .nest_api_service("/delete-preview", route_delete_preview(state.clone()))
         .nest_api_service("/ingredient", route_ingredient(state.clone()))
+        .nest_api_service("/category", route_category(state.clone()))
         .nest_api_service("/dish", route_dish(state.clone()))
         .nest_api_service("/meal", route_meal(state.clone()))
+        .nest_api_service("/purchase", route_purchase(state.clone()))
+        .nest_api_service("/plan", route_plan(state.clone()))
+        .nest_api_service("/goal", route_goal(state.clone()))
+        .nest_api_service("/restriction", route_restriction(state.clone()))
+        .nest_api_service("/quality", route_quality(state.clone()))
+        .nest_api_service("/footprint", route_footprint(state.clone()))
+        .nest_api_service("/trackable", route_trackable(state.clone()))
+        .nest_api_service("/supplement", route_supplement(state.clone()))
         .route("/openapi.json", get(serve_api))
         .route("/docs", Redoc::new("/openapi.json").axum_route())
+        .layer(middleware::from_fn(etag_middleware))
+        .layer(middleware::from_fn_with_state(
+            state.clone(),
+            idempotency_middleware,
+        ))
+        .layer(middleware::from_fn_with_state(
+            state.clone(),
+            auth_middleware,
+        ))
         .layer(middleware::from_fn(logging_middleware))
         .layer(tower_http::cors::CorsLayer::permissive())
         .with_state(state);
@@ -0,0 +1,643 @@
+use aide::OperationInput;
+use argon2::{
+    password_hash::{rand_core::OsRng, PasswordHash, PasswordHasher, PasswordVerifier, SaltString},
+    Argon2,
+};
+use axum::{
+    async_trait,
+    extract::FromRequestParts,
+    http::{header, request::Parts, HeaderMap, Method, StatusCode},
+    response::{IntoResponse, Response},
+};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use sqlx::{Pool, Sqlite};
+use thiserror::Error;
+
+use crate::server::ServerResponse;
+
+/// How long a session stays valid after it was last used, in milliseconds
+pub const SESSION_DURATION: i64 = 30 * 24 * 60 * 60 * 1000;
+
+/// How long after a token was last used its use is written down again, in milliseconds. Writing it
+/// on every request would make every read wait for the database's single writer.
+const LAST_USED_REFRESH_INTERVAL: i64 = 60 * 1000;
+
+/// Prefix that tells API tokens apart from session tokens
+pub const API_TOKEN_PREFIX: &str = "ft_";
+
+/// Prefix that tells share tokens apart from session tokens
+pub const SHARE_TOKEN_PREFIX: &str = "fts_";
+
+pub fn hash_password(password: &str) -> anyhow::Result<String> {
+    let salt = SaltString::generate(&mut OsRng);
+    Ok(Argon2::default()
+        .hash_password(password.as_bytes(), &salt)?
+        .to_string())
+}
+
+pub fn verify_password(password: &str, password_hash: &str) -> bool {
+    PasswordHash::new(password_hash)
+        .map(|hash| {
+            Argon2::default()
+                .verify_password(password.as_bytes(), &hash)
+                .is_ok()
+        })
+        .unwrap_or(false)
+}
+
+/// Generates a random token, encoded as hex. Only its hash should be stored.
+pub fn generate_token() -> String {
+    let mut bytes = [0u8; 32];
+    argon2::password_hash::rand_core::RngCore::fill_bytes(&mut OsRng, &mut bytes);
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+/// The token in the `Authorization: Bearer <token>` header, if any.
+pub fn bearer_token(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(str::trim)
+}
+
+pub fn hash_token(token: &str) -> String {
+    format!("{:x}", Sha256::digest(token.as_bytes()))
+}
+
+/// What an API token is allowed to do. Sessions are not limited by scopes.
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TokenScope {
+    /// Only `GET` requests
+    ReadOnly,
+    /// Read everything, and create or change meals. Meal descriptions and templates can't be
+    /// changed, but planned meals can be confirmed
+    LogMeals,
+    /// Everything a session can do
+    Admin,
+}
+
+impl TokenScope {
+    pub fn name(self) -> &'static str {
+        match self {
+            TokenScope::ReadOnly => "ReadOnly",
+            TokenScope::LogMeals => "LogMeals",
+            TokenScope::Admin => "Admin",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<TokenScope> {
+        [
+            TokenScope::ReadOnly,
+            TokenScope::LogMeals,
+            TokenScope::Admin,
+        ]
+        .into_iter()
+        .find(|s| s.name() == name)
+    }
+
+    /// Whether a request with `method` to `path` can be made with this scope.
+    pub fn allows(self, method: &Method, path: &str) -> bool {
+        let is_read = matches!(*method, Method::GET | Method::HEAD | Method::OPTIONS);
+        match self {
+            TokenScope::Admin => true,
+            TokenScope::ReadOnly => is_read,
+            TokenScope::LogMeals => {
+                let is_meal = (path == "/meal" || path.starts_with("/meal/"))
+                    && !path.starts_with("/meal/description")
+                    && !path.starts_with("/meal/template");
+                let is_confirmation = path.starts_with("/plan/") && path.ends_with("/confirm");
+                is_read || is_meal || is_confirmation
+            }
+        }
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum AuthError {
+    #[error("Missing \"Authorization: Bearer <token>\" header")]
+    MissingToken,
+    #[error("The token is invalid, revoked or has expired")]
+    InvalidToken,
+    #[error("The token's scope ({}) does not allow this request", .0.name())]
+    ScopeNotAllowed(TokenScope),
+    #[error("Share tokens can only be used to read what was shared and to comment on it")]
+    ShareNotAllowed,
+    #[error(transparent)]
+    Database(#[from] sqlx::Error),
+}
+
+impl IntoResponse for AuthError {
+    fn into_response(self) -> Response {
+        let status_code = match self {
+            AuthError::Database(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            AuthError::MissingToken | AuthError::InvalidToken => StatusCode::UNAUTHORIZED,
+            AuthError::ScopeNotAllowed(_) | AuthError::ShareNotAllowed => StatusCode::FORBIDDEN,
+        };
+        ServerResponse::error_code(self, status_code).into_response()
+    }
+}
+
+#[derive(Error, Debug)]
+#[allow(clippy::enum_variant_names)]
+pub enum OwnershipError {
+    #[error("Could not find meal with id \"{0}\"")]
+    MealNotFound(i64),
+    #[error("Could not find dish with id \"{0}\"")]
+    DishNotFound(i64),
+    #[error("Could not find ingredient with id \"{0}\"")]
+    IngredientNotFound(i64),
+    #[error("Could not find household member with id \"{0}\"")]
+    MemberNotFound(i64),
+    #[error("Could not find category with id \"{0}\"")]
+    CategoryNotFound(i64),
+    #[error("Could not find trackable with id \"{0}\"")]
+    TrackableNotFound(i64),
+    #[error("Could not find supplement with id \"{0}\"")]
+    SupplementNotFound(i64),
+}
+
+/// The user that made the request, resolved from the `Authorization: Bearer <token>` header.
+/// Meals, plans, templates and goals belong to the user, while ingredients and dishes belong to
+/// the user's household.
+#[derive(Clone, Copy, Debug)]
+pub struct AuthUser {
+    pub user_id: i64,
+    pub household_id: i64,
+}
+
+impl OperationInput for AuthUser {}
+
+#[async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for AuthUser {
+    type Rejection = AuthError;
+
+    /// The user is resolved by `authenticate`, which runs as a middleware before every handler.
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        parts
+            .extensions
+            .get::<AuthUser>()
+            .copied()
+            .ok_or(AuthError::MissingToken)
+    }
+}
+
+/// The scope of the API token a user authenticated with, or nothing for a session.
+#[derive(Clone, Copy, Debug)]
+pub struct AuthScope(pub Option<TokenScope>);
+
+impl AuthScope {
+    /// Whether the household's invite code can be seen, since anyone with it can join the
+    /// household. Only sessions and admin tokens are trusted with it.
+    pub fn can_invite(self) -> bool {
+        matches!(self.0, None | Some(TokenScope::Admin))
+    }
+}
+
+impl OperationInput for AuthScope {}
+
+#[async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for AuthScope {
+    type Rejection = AuthError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        parts
+            .extensions
+            .get::<AuthScope>()
+            .copied()
+            .ok_or(AuthError::MissingToken)
+    }
+}
+
+/// A guest reading what a user shared with them, resolved from a share token.
+#[derive(Clone, Copy, Debug)]
+pub struct GuestShare {
+    pub share_id: i64,
+    /// The user that shared their meals
+    pub user_id: i64,
+    pub start_date: i64,
+    pub end_date: i64,
+    pub redact_descriptions: bool,
+}
+
+impl OperationInput for GuestShare {}
+
+#[async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for GuestShare {
+    type Rejection = AuthError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        parts
+            .extensions
+            .get::<GuestShare>()
+            .copied()
+            .ok_or(AuthError::MissingToken)
+    }
+}
+
+/// Who made a request.
+pub enum Authenticated {
+    User(AuthUser, AuthScope),
+    Guest(GuestShare),
+}
+
+fn is_last_use_stale(last_used_date: Option<i64>, now: i64) -> bool {
+    last_used_date.is_none_or(|date| now - date >= LAST_USED_REFRESH_INTERVAL)
+}
+
+async fn authenticate_session(
+    connection: &Pool<Sqlite>,
+    token_hash: &str,
+    now: i64,
+) -> Result<AuthUser, AuthError> {
+    let session = sqlx::query!(
+        r#"
+        SELECT
+            User.id AS user_id,
+            User.household_id,
+            Session.last_used_date
+        FROM Session
+            JOIN User ON User.id = Session.user_id
+        WHERE Session.token_hash = ? AND Session.expiration_date > ?"#,
+        token_hash,
+        now
+    )
+    .fetch_optional(connection)
+    .await?
+    .ok_or(AuthError::InvalidToken)?;
+
+    if is_last_use_stale(session.last_used_date, now) {
+        let expiration_date = now + SESSION_DURATION;
+        sqlx::query!(
+            "UPDATE Session SET last_used_date = ?, expiration_date = ? WHERE token_hash = ?",
+            now,
+            expiration_date,
+            token_hash
+        )
+        .execute(connection)
+        .await?;
+    }
+
+    Ok(AuthUser {
+        user_id: session.user_id,
+        household_id: session.household_id,
+    })
+}
+
+async fn authenticate_api_token(
+    connection: &Pool<Sqlite>,
+    token_hash: &str,
+    now: i64,
+    method: &Method,
+    path: &str,
+) -> Result<(AuthUser, TokenScope), AuthError> {
+    let token = sqlx::query!(
+        r#"
+        SELECT
+            User.id AS user_id,
+            User.household_id,
+            ApiToken.scope,
+            ApiToken.last_used_date
+        FROM ApiToken
+            JOIN User ON User.id = ApiToken.user_id
+        WHERE ApiToken.token_hash = ?"#,
+        token_hash
+    )
+    .fetch_optional(connection)
+    .await?
+    .ok_or(AuthError::InvalidToken)?;
+
+    let scope = TokenScope::from_name(&token.scope).ok_or(AuthError::InvalidToken)?;
+    if !scope.allows(method, path) {
+        return Err(AuthError::ScopeNotAllowed(scope));
+    }
+
+    if is_last_use_stale(token.last_used_date, now) {
+        sqlx::query!(
+            "UPDATE ApiToken SET last_used_date = ? WHERE token_hash = ?",
+            now,
+            token_hash
+        )
+        .execute(connection)
+        .await?;
+    }
+
+    let user = AuthUser {
+        user_id: token.user_id,
+        household_id: token.household_id,
+    };
+    Ok((user, scope))
+}
+
+/// Guests can read everything under `/shared`, and comment on it.
+fn share_allows(method: &Method, path: &str) -> bool {
+    let is_read = matches!(*method, Method::GET | Method::HEAD | Method::OPTIONS);
+    let is_shared = path == "/shared" || path.starts_with("/shared/");
+    is_shared && (is_read || (*method == Method::POST && path == "/shared/comment"))
+}
+
+async fn authenticate_share(
+    connection: &Pool<Sqlite>,
+    token_hash: &str,
+    now: i64,
+    method: &Method,
+    path: &str,
+) -> Result<GuestShare, AuthError> {
+    let share = sqlx::query!(
+        r#"
+        SELECT
+            id,
+            user_id,
+            start_date,
+            end_date,
+            redact_descriptions,
+            last_used_date
+        FROM Share
+        WHERE token_hash = ? AND (expiration_date IS NULL OR expiration_date > ?)"#,
+        token_hash,
+        now
+    )
+    .fetch_optional(connection)
+    .await?
+    .ok_or(AuthError::InvalidToken)?;
+
+    if !share_allows(method, path) {
+        return Err(AuthError::ShareNotAllowed);
+    }
+
+    if is_last_use_stale(share.last_used_date, now) {
+        sqlx::query!(
+            "UPDATE Share SET last_used_date = ? WHERE id = ?",
+            now,
+            share.id
+        )
+        .execute(connection)
+        .await?;
+    }
+
+    Ok(GuestShare {
+        share_id: share.id,
+        user_id: share.user_id,
+        start_date: share.start_date,
+        end_date: share.end_date,
+        redact_descriptions: share.redact_descriptions != 0,
+    })
+}
+
+/// Resolves who made a request from its session, API or share token, making sure API and share
+/// tokens are only used for what they allow. Requests without a token resolve to nobody, and are
+/// rejected later by the handlers that require someone.
+pub async fn authenticate(
+    connection: &Pool<Sqlite>,
+    headers: &HeaderMap,
+    method: &Method,
+    path: &str,
+) -> Result<Option<Authenticated>, AuthError> {
+    let Some(token) = bearer_token(headers) else {
+        return Ok(None);
+    };
+    let token_hash = hash_token(token);
+    let now = chrono::Utc::now().timestamp_millis();
+
+    let authenticated = if token.starts_with(SHARE_TOKEN_PREFIX) {
+        Authenticated::Guest(authenticate_share(connection, &token_hash, now, method, path).await?)
+    } else if token.starts_with(API_TOKEN_PREFIX) {
+        let (user, scope) =
+            authenticate_api_token(connection, &token_hash, now, method, path).await?;
+        Authenticated::User(user, AuthScope(Some(scope)))
+    } else {
+        let user = authenticate_session(connection, &token_hash, now).await?;
+        Authenticated::User(user, AuthScope(None))
+    };
+
+    Ok(Some(authenticated))
+}
+
+impl AuthUser {
+    /// Makes sure the meal exists, isn't in the trash and belongs to this user.
+    pub async fn check_meal<'c>(
+        &self,
+        connection: impl sqlx::Executor<'c, Database = Sqlite>,
+        meal_id: i64,
+    ) -> anyhow::Result<()> {
+        sqlx::query_scalar!(
+            "SELECT id FROM Meal WHERE id = ? AND user_id = ? AND deletion_date IS NULL",
+            meal_id,
+            self.user_id
+        )
+        .fetch_optional(connection)
+        .await?
+        .ok_or(OwnershipError::MealNotFound(meal_id))?;
+        Ok(())
+    }
+
+    /// Makes sure the dish exists, isn't in the trash and belongs to this user's household.
+    pub async fn check_dish<'c>(
+        &self,
+        connection: impl sqlx::Executor<'c, Database = Sqlite>,
+        dish_id: i64,
+    ) -> anyhow::Result<()> {
+        sqlx::query_scalar!(
+            "SELECT id FROM Dish WHERE id = ? AND household_id = ? AND deletion_date IS NULL",
+            dish_id,
+            self.household_id
+        )
+        .fetch_optional(connection)
+        .await?
+        .ok_or(OwnershipError::DishNotFound(dish_id))?;
+        Ok(())
+    }
+
+    /// Makes sure the ingredient exists and belongs to this user's household.
+    pub async fn check_ingredient<'c>(
+        &self,
+        connection: impl sqlx::Executor<'c, Database = Sqlite>,
+        ingredient_id: i64,
+    ) -> anyhow::Result<()> {
+        sqlx::query_scalar!(
+            "SELECT id FROM Ingredient WHERE id = ? AND household_id = ?",
+            ingredient_id,
+            self.household_id
+        )
+        .fetch_optional(connection)
+        .await?
+        .ok_or(OwnershipError::IngredientNotFound(ingredient_id))?;
+        Ok(())
+    }
+
+    /// Makes sure the category exists and belongs to this user's household.
+    pub async fn check_category(
+        &self,
+        connection: &Pool<Sqlite>,
+        category_id: i64,
+    ) -> anyhow::Result<()> {
+        sqlx::query_scalar!(
+            "SELECT id FROM Category WHERE id = ? AND household_id = ?",
+            category_id,
+            self.household_id
+        )
+        .fetch_optional(connection)
+        .await?
+        .ok_or(OwnershipError::CategoryNotFound(category_id))?;
+        Ok(())
+    }
+
+    /// Makes sure the trackable exists and belongs to this user's household.
+    pub async fn check_trackable(
+        &self,
+        connection: &Pool<Sqlite>,
+        trackable_id: i64,
+    ) -> anyhow::Result<()> {
+        sqlx::query_scalar!(
+            "SELECT id FROM Trackable WHERE id = ? AND household_id = ?",
+            trackable_id,
+            self.household_id
+        )
+        .fetch_optional(connection)
+        .await?
+        .ok_or(OwnershipError::TrackableNotFound(trackable_id))?;
+        Ok(())
+    }
+
+    /// Makes sure the supplement exists and belongs to this user's household.
+    pub async fn check_supplement(
+        &self,
+        connection: &Pool<Sqlite>,
+        supplement_id: i64,
+    ) -> anyhow::Result<()> {
+        sqlx::query_scalar!(
+            "SELECT id FROM Supplement WHERE id = ? AND household_id = ?",
+            supplement_id,
+            self.household_id
+        )
+        .fetch_optional(connection)
+        .await?
+        .ok_or(OwnershipError::SupplementNotFound(supplement_id))?;
+        Ok(())
+    }
+
+    /// Makes sure the user exists and is a member of this user's household.
+    pub async fn check_member(
+        &self,
+        connection: &Pool<Sqlite>,
+        member_id: i64,
+    ) -> anyhow::Result<()> {
+        sqlx::query_scalar!(
+            "SELECT id FROM User WHERE id = ? AND household_id = ?",
+            member_id,
+            self.household_id
+        )
+        .fetch_optional(connection)
+        .await?
+        .ok_or(OwnershipError::MemberNotFound(member_id))?;
+        Ok(())
+    }
+
+    /// The household member whose data should be read: `member_id` if given, this user
+    /// otherwise.
+    pub async fn resolve_member(
+        &self,
+        connection: &Pool<Sqlite>,
+        member_id: Option<i64>,
+    ) -> anyhow::Result<i64> {
+        match member_id {
+            Some(member_id) if member_id != self.user_id => {
+                self.check_member(connection, member_id).await?;
+                Ok(member_id)
+            }
+            _ => Ok(self.user_id),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn admin_scope_allows_everything() {
+        assert!(TokenScope::Admin.allows(&Method::GET, "/household"));
+        assert!(TokenScope::Admin.allows(&Method::POST, "/household/invite"));
+        assert!(TokenScope::Admin.allows(&Method::DELETE, "/token/1"));
+    }
+
+    #[test]
+    fn read_only_scope_only_allows_reads() {
+        assert!(TokenScope::ReadOnly.allows(&Method::GET, "/meal"));
+        assert!(TokenScope::ReadOnly.allows(&Method::HEAD, "/meal/1"));
+        assert!(TokenScope::ReadOnly.allows(&Method::OPTIONS, "/dish"));
+        assert!(!TokenScope::ReadOnly.allows(&Method::POST, "/meal"));
+        assert!(!TokenScope::ReadOnly.allows(&Method::PUT, "/meal/1"));
+        assert!(!TokenScope::ReadOnly.allows(&Method::DELETE, "/meal/1"));
+    }
+
+    #[test]
+    fn log_meals_scope_allows_meal_changes() {
+        assert!(TokenScope::LogMeals.allows(&Method::GET, "/dish/1"));
+        assert!(TokenScope::LogMeals.allows(&Method::POST, "/meal"));
+        assert!(TokenScope::LogMeals.allows(&Method::POST, "/meal/1"));
+        assert!(TokenScope::LogMeals.allows(&Method::DELETE, "/meal/1"));
+        assert!(TokenScope::LogMeals.allows(&Method::POST, "/plan/1/confirm"));
+    }
+
+    #[test]
+    fn log_meals_scope_rejects_everything_else() {
+        assert!(!TokenScope::LogMeals.allows(&Method::POST, "/meal/description"));
+        assert!(!TokenScope::LogMeals.allows(&Method::DELETE, "/meal/description/1"));
+        assert!(!TokenScope::LogMeals.allows(&Method::POST, "/meal/template"));
+        assert!(!TokenScope::LogMeals.allows(&Method::POST, "/mealtime"));
+        assert!(!TokenScope::LogMeals.allows(&Method::POST, "/dish"));
+        assert!(!TokenScope::LogMeals.allows(&Method::POST, "/plan/1"));
+        assert!(!TokenScope::LogMeals.allows(&Method::POST, "/token"));
+        assert!(!TokenScope::LogMeals.allows(&Method::POST, "/household/invite"));
+    }
+
+    #[test]
+    fn scope_names_round_trip() {
+        for scope in [
+            TokenScope::ReadOnly,
+            TokenScope::LogMeals,
+            TokenScope::Admin,
+        ] {
+            assert_eq!(TokenScope::from_name(scope.name()), Some(scope));
+        }
+        assert_eq!(TokenScope::from_name("admin"), None);
+    }
+
+    #[test]
+    fn share_allows_reading_what_was_shared() {
+        assert!(share_allows(&Method::GET, "/shared"));
+        assert!(share_allows(&Method::GET, "/shared/meal"));
+        assert!(share_allows(&Method::HEAD, "/shared/meal/1"));
+        assert!(share_allows(&Method::POST, "/shared/comment"));
+    }
+
+    #[test]
+    fn share_rejects_everything_else() {
+        assert!(!share_allows(&Method::GET, "/meal"));
+        assert!(!share_allows(&Method::GET, "/household"));
+        assert!(!share_allows(&Method::GET, "/sharedsecret"));
+        assert!(!share_allows(&Method::GET, "/share"));
+        assert!(!share_allows(&Method::POST, "/shared/meal"));
+        assert!(!share_allows(&Method::DELETE, "/shared/comment"));
+        assert!(!share_allows(&Method::POST, "/shared/comment/1"));
+        assert!(!share_allows(&Method::POST, "/meal"));
+    }
+
+    #[test]
+    fn last_use_is_only_refreshed_after_the_interval() {
+        let now = 10 * LAST_USED_REFRESH_INTERVAL;
+        assert!(is_last_use_stale(None, now));
+        assert!(!is_last_use_stale(Some(now), now));
+        assert!(!is_last_use_stale(
+            Some(now - LAST_USED_REFRESH_INTERVAL + 1),
+            now
+        ));
+        assert!(is_last_use_stale(
+            Some(now - LAST_USED_REFRESH_INTERVAL),
+            now
+        ));
+    }
+}
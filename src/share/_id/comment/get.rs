@@ -0,0 +1,42 @@
+use axum::extract::{Path, State};
+use schemars::JsonSchema;
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::{
+    comment::fetch_comments,
+    models::ShareComment,
+    server::{ServerResponse, ServerResponseResult},
+    session::AuthUser,
+    state::AppState,
+};
+
+#[derive(Deserialize, JsonSchema)]
+pub struct ShareId {
+    share_id: i64,
+}
+
+#[derive(Error, Debug)]
+enum ListCommentsError {
+    #[error("Could not find share with id \"{0}\"")]
+    ShareNotFound(i64),
+}
+
+pub async fn list_comments(
+    State(AppState { connection, .. }): State<AppState>,
+    AuthUser { user_id, .. }: AuthUser,
+    Path(ShareId { share_id }): Path<ShareId>,
+) -> ServerResponseResult<Vec<ShareComment>> {
+    sqlx::query_scalar!(
+        "SELECT id FROM Share WHERE id = ? AND user_id = ?",
+        share_id,
+        user_id
+    )
+    .fetch_optional(&connection)
+    .await?
+    .ok_or(ListCommentsError::ShareNotFound(share_id))?;
+
+    let comments = fetch_comments(&connection, share_id).await?;
+
+    Ok(ServerResponse::success(comments).json())
+}
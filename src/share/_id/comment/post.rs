@@ -0,0 +1,58 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use schemars::JsonSchema;
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::{
+    comment::{insert_comment, CommentAuthor, PostCommentBody},
+    models::ShareComment,
+    server::{ServerResponse, ServerResponseResult},
+    session::{AuthUser, GuestShare},
+    state::AppState,
+};
+
+#[derive(Deserialize, JsonSchema)]
+pub struct ShareId {
+    share_id: i64,
+}
+
+#[derive(Error, Debug)]
+enum PostCommentError {
+    #[error("Could not find share with id \"{0}\"")]
+    ShareNotFound(i64),
+}
+
+/// Comments on, or replies to the guest's comments about, something shared.
+pub async fn post_comment(
+    State(AppState { connection, .. }): State<AppState>,
+    AuthUser { user_id, .. }: AuthUser,
+    Path(ShareId { share_id }): Path<ShareId>,
+    Json(body): Json<PostCommentBody>,
+) -> ServerResponseResult<ShareComment> {
+    let share = sqlx::query!(
+        r#"
+        SELECT start_date, end_date, redact_descriptions
+        FROM Share
+        WHERE id = ? AND user_id = ?"#,
+        share_id,
+        user_id
+    )
+    .fetch_optional(&connection)
+    .await?
+    .ok_or(PostCommentError::ShareNotFound(share_id))?;
+
+    let share = GuestShare {
+        share_id,
+        user_id,
+        start_date: share.start_date,
+        end_date: share.end_date,
+        redact_descriptions: share.redact_descriptions != 0,
+    };
+    let comment = insert_comment(&connection, &share, CommentAuthor::Owner, body).await?;
+
+    Ok(ServerResponse::success_code(comment, StatusCode::CREATED).json())
+}
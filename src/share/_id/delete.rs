@@ -0,0 +1,49 @@
+use axum::extract::{Path, State};
+use schemars::JsonSchema;
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::{
+    server::{ServerResponse, ServerResponseResult},
+    session::AuthUser,
+    state::AppState,
+};
+
+#[derive(Deserialize, JsonSchema)]
+pub struct ShareId {
+    share_id: i64,
+}
+
+#[derive(Error, Debug)]
+enum DeleteShareError {
+    #[error("Could not find share with id \"{0}\"")]
+    ShareNotFound(i64),
+}
+
+/// Revokes the share, deleting the comments left on it.
+pub async fn delete_share(
+    State(AppState { connection, .. }): State<AppState>,
+    AuthUser { user_id, .. }: AuthUser,
+    Path(ShareId { share_id }): Path<ShareId>,
+) -> ServerResponseResult<bool> {
+    sqlx::query_scalar!(
+        "SELECT id FROM Share WHERE id = ? AND user_id = ?",
+        share_id,
+        user_id
+    )
+    .fetch_optional(&connection)
+    .await?
+    .ok_or(DeleteShareError::ShareNotFound(share_id))?;
+
+    sqlx::query!(
+        r#"
+        DELETE FROM ShareComment WHERE share_id = ?;
+        DELETE FROM Share WHERE id = ?"#,
+        share_id,
+        share_id,
+    )
+    .execute(&connection)
+    .await?;
+
+    Ok(ServerResponse::success(true).json())
+}
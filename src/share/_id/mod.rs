@@ -0,0 +1,13 @@
+use aide::axum::{routing::delete, ApiRouter};
+
+use crate::state::AppState;
+
+mod comment;
+mod delete;
+
+pub fn route(state: AppState) -> ApiRouter {
+    ApiRouter::new()
+        .api_route("/", delete(delete::delete_share))
+        .nest_api_service("/comment", comment::route(state.clone()))
+        .with_state(state)
+}
@@ -0,0 +1,35 @@
+use axum::extract::State;
+
+use crate::{
+    models::Share,
+    server::{ServerResponse, ServerResponseResult},
+    session::AuthUser,
+    state::AppState,
+};
+
+pub async fn list_shares(
+    State(AppState { connection, .. }): State<AppState>,
+    AuthUser { user_id, .. }: AuthUser,
+) -> ServerResponseResult<Vec<Share>> {
+    let shares = sqlx::query_as!(
+        Share,
+        r#"
+        SELECT
+            id,
+            creation_date,
+            name,
+            start_date,
+            end_date,
+            redact_descriptions,
+            expiration_date,
+            last_used_date
+        FROM Share
+        WHERE user_id = ?
+        ORDER BY creation_date DESC;"#,
+        user_id
+    )
+    .fetch_all(&connection)
+    .await?;
+
+    Ok(ServerResponse::success(shares).json())
+}
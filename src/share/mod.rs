@@ -0,0 +1,14 @@
+use aide::axum::{routing::post, ApiRouter};
+
+use crate::state::AppState;
+
+mod _id;
+mod list;
+mod post;
+
+pub fn route(state: AppState) -> ApiRouter {
+    ApiRouter::new()
+        .api_route("/", post(post::post_share).get(list::list_shares))
+        .nest_api_service("/:share_id", _id::route(state.clone()))
+        .with_state(state)
+}
@@ -0,0 +1,102 @@
+use axum::{extract::State, http::StatusCode, Json};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{
+    models::Share,
+    server::{ServerResponse, ServerResponseResult},
+    session::{generate_token, hash_token, AuthUser, SHARE_TOKEN_PREFIX},
+    state::AppState,
+};
+
+#[derive(Deserialize, JsonSchema)]
+pub struct PostShareBody {
+    /// Who the share is for, such as "Dietitian"
+    name: String,
+    /// Only meals eaten between `start_date` and `end_date` are shared
+    start_date: i64,
+    end_date: i64,
+    /// Hides the free text descriptions of the meals from the guest
+    redact_descriptions: Option<bool>,
+    /// When the share stops working. It works until revoked if not provided
+    expiration_date: Option<i64>,
+}
+
+#[derive(Serialize, JsonSchema)]
+pub struct PostShareResponse {
+    /// Must be sent by the guest in the `Authorization: Bearer <token>` header. It is only shown
+    /// once
+    token: String,
+    share: Share,
+}
+
+#[derive(Error, Debug)]
+enum PostShareError {
+    #[error("The share name should not be empty")]
+    NameIsEmpty,
+    #[error("start_date ({0}) must not be after end_date ({1})")]
+    StartAfterEnd(i64, i64),
+}
+
+pub async fn post_share(
+    State(AppState { connection, .. }): State<AppState>,
+    AuthUser { user_id, .. }: AuthUser,
+    Json(PostShareBody {
+        name,
+        start_date,
+        end_date,
+        redact_descriptions,
+        expiration_date,
+    }): Json<PostShareBody>,
+) -> ServerResponseResult<PostShareResponse> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(PostShareError::NameIsEmpty)?;
+    }
+    if start_date > end_date {
+        return Err(PostShareError::StartAfterEnd(start_date, end_date))?;
+    }
+
+    let token = format!("{SHARE_TOKEN_PREFIX}{}", generate_token());
+    let token_hash = hash_token(&token);
+    let redact_descriptions = redact_descriptions.unwrap_or(false);
+
+    let share = sqlx::query_as!(
+        Share,
+        r#"
+        INSERT INTO Share (
+            user_id,
+            name,
+            token_hash,
+            start_date,
+            end_date,
+            redact_descriptions,
+            expiration_date
+        )
+        VALUES (?, ?, ?, ?, ?, ?, ?)
+        RETURNING
+            id,
+            creation_date,
+            name,
+            start_date,
+            end_date,
+            redact_descriptions,
+            expiration_date,
+            last_used_date;"#,
+        user_id,
+        name,
+        token_hash,
+        start_date,
+        end_date,
+        redact_descriptions,
+        expiration_date
+    )
+    .fetch_one(&connection)
+    .await?;
+
+    Ok(
+        ServerResponse::success_code(PostShareResponse { token, share }, StatusCode::CREATED)
+            .json(),
+    )
+}
@@ -0,0 +1,18 @@
+use axum::extract::State;
+
+use crate::{
+    comment::fetch_comments,
+    models::ShareComment,
+    server::{ServerResponse, ServerResponseResult},
+    session::GuestShare,
+    state::AppState,
+};
+
+pub async fn list_comments(
+    State(AppState { connection, .. }): State<AppState>,
+    share: GuestShare,
+) -> ServerResponseResult<Vec<ShareComment>> {
+    let comments = fetch_comments(&connection, share.share_id).await?;
+
+    Ok(ServerResponse::success(comments).json())
+}
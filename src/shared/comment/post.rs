@@ -0,0 +1,19 @@
+use axum::{extract::State, http::StatusCode, Json};
+
+use crate::{
+    comment::{insert_comment, CommentAuthor, PostCommentBody},
+    models::ShareComment,
+    server::{ServerResponse, ServerResponseResult},
+    session::GuestShare,
+    state::AppState,
+};
+
+pub async fn post_comment(
+    State(AppState { connection, .. }): State<AppState>,
+    share: GuestShare,
+    Json(body): Json<PostCommentBody>,
+) -> ServerResponseResult<ShareComment> {
+    let comment = insert_comment(&connection, &share, CommentAuthor::Guest, body).await?;
+
+    Ok(ServerResponse::success_code(comment, StatusCode::CREATED).json())
+}
@@ -0,0 +1,39 @@
+use axum::extract::State;
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use crate::{
+    server::{ServerResponse, ServerResponseResult},
+    session::GuestShare,
+    state::AppState,
+};
+
+#[derive(Serialize, JsonSchema)]
+pub struct GetSharedResponse {
+    /// Name of the user that shared their meals
+    owner_name: String,
+    start_date: i64,
+    end_date: i64,
+    /// Whether the descriptions of the meals are hidden
+    redact_descriptions: bool,
+}
+
+pub async fn get_shared(
+    State(AppState { connection, .. }): State<AppState>,
+    share: GuestShare,
+) -> ServerResponseResult<GetSharedResponse> {
+    let owner_name = sqlx::query_scalar!(
+        r#"SELECT COALESCE(display_name, username) AS "name!: String" FROM User WHERE id = ?"#,
+        share.user_id
+    )
+    .fetch_one(&connection)
+    .await?;
+
+    Ok(ServerResponse::success(GetSharedResponse {
+        owner_name,
+        start_date: share.start_date,
+        end_date: share.end_date,
+        redact_descriptions: share.redact_descriptions,
+    })
+    .json())
+}
@@ -0,0 +1,84 @@
+use axum::extract::State;
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use crate::{
+    component::{fetch_linked_components, LinkedComponent},
+    models::Meal,
+    nutrition::{NutrientTable, Nutrients, WeightedComponent},
+    server::{ServerResponse, ServerResponseResult},
+    session::GuestShare,
+    state::AppState,
+};
+
+#[derive(Serialize, JsonSchema)]
+pub struct SharedMeal {
+    meal: Meal,
+    components: Vec<LinkedComponent>,
+    nutrients: Nutrients,
+}
+
+/// Every meal in the shared date range, with what was eaten in it.
+pub async fn list_shared_meals(
+    State(AppState { connection, .. }): State<AppState>,
+    share: GuestShare,
+) -> ServerResponseResult<Vec<SharedMeal>> {
+    let meals = sqlx::query_as!(
+        Meal,
+        r#"
+        SELECT
+            id,
+            creation_date,
+            eat_date,
+            duration,
+            description,
+            version
+        FROM Meal
+        WHERE user_id = ? AND deletion_date IS NULL AND eat_date BETWEEN ? AND ?
+        ORDER BY eat_date;"#,
+        share.user_id,
+        share.start_date,
+        share.end_date
+    )
+    .fetch_all(&connection)
+    .await?;
+
+    let mut components = fetch_linked_components(
+        &connection,
+        "Meal",
+        "meal_id",
+        &meals.iter().map(|m| m.id).collect::<Vec<i64>>(),
+    )
+    .await?;
+
+    let table = NutrientTable::fetch(
+        &connection,
+        &components
+            .values()
+            .flatten()
+            .map(WeightedComponent::from)
+            .collect::<Vec<WeightedComponent>>(),
+    )
+    .await?;
+
+    let meals = meals
+        .into_iter()
+        .map(|mut meal| {
+            if share.redact_descriptions {
+                meal.description = None;
+            }
+            let components = components.remove(&meal.id).unwrap_or_default();
+            let mut nutrients = Nutrients::default();
+            for component in &components {
+                nutrients += table.component_nutrients(&WeightedComponent::from(component));
+            }
+            SharedMeal {
+                meal,
+                components,
+                nutrients,
+            }
+        })
+        .collect();
+
+    Ok(ServerResponse::success(meals).json())
+}
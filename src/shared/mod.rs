@@ -0,0 +1,18 @@
+use aide::axum::{routing::get, ApiRouter};
+
+use crate::state::AppState;
+
+mod comment;
+mod get;
+mod meal;
+mod summary;
+
+/// Routes used by guests through a share token. Everything here is read only, except comments.
+pub fn route(state: AppState) -> ApiRouter {
+    ApiRouter::new()
+        .api_route("/", get(get::get_shared))
+        .api_route("/meal", get(meal::list_shared_meals))
+        .api_route("/summary", get(summary::get_shared_summary))
+        .nest_api_service("/comment", comment::route(state.clone()))
+        .with_state(state)
+}
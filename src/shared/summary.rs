@@ -0,0 +1,118 @@
+use std::collections::BTreeMap;
+
+use axum::extract::{Query, State};
+use chrono::TimeZone;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use sqlx::prelude::FromRow;
+
+use crate::{
+    models::NutritionGoal,
+    nutrition::{NutrientTable, Nutrients, WeightedComponent},
+    period::utc_offset,
+    server::{ServerResponse, ServerResponseResult},
+    session::GuestShare,
+    state::AppState,
+};
+
+#[derive(Deserialize, JsonSchema)]
+pub struct GetSharedSummaryQueryParams {
+    /// Offset of the owner's timezone, used to decide which day each meal belongs to
+    utc_offset_minutes: Option<i32>,
+}
+
+#[derive(Serialize, JsonSchema)]
+pub struct SharedDay {
+    /// Day in the `YYYY-MM-DD` format
+    date: String,
+    eaten: Nutrients,
+}
+
+#[derive(Serialize, JsonSchema)]
+pub struct GetSharedSummaryResponse {
+    days: Vec<SharedDay>,
+    /// The owner's current daily goals
+    goals: Vec<NutritionGoal>,
+}
+
+#[derive(FromRow)]
+struct DatedComponent {
+    date: i64,
+    dish_id: Option<i64>,
+    ingredient_id: Option<i64>,
+    weight: i64,
+}
+
+impl From<&DatedComponent> for WeightedComponent {
+    fn from(value: &DatedComponent) -> Self {
+        WeightedComponent {
+            dish_id: value.dish_id,
+            ingredient_id: value.ingredient_id,
+            weight: value.weight,
+        }
+    }
+}
+
+/// Nutrients eaten on each day of the shared date range.
+pub async fn get_shared_summary(
+    State(AppState { connection, .. }): State<AppState>,
+    share: GuestShare,
+    Query(GetSharedSummaryQueryParams { utc_offset_minutes }): Query<GetSharedSummaryQueryParams>,
+) -> ServerResponseResult<GetSharedSummaryResponse> {
+    let offset = utc_offset(utc_offset_minutes)?;
+
+    let components = sqlx::query_as::<_, DatedComponent>(
+        r#"
+        SELECT Meal.eat_date AS date, dish_id, NULL AS ingredient_id, weight
+        FROM Meal JOIN MealDish ON MealDish.meal_id = Meal.id
+        WHERE Meal.user_id = ?3 AND Meal.deletion_date IS NULL AND Meal.eat_date >= ?1 AND Meal.eat_date <= ?2
+        UNION ALL
+        SELECT Meal.eat_date AS date, NULL AS dish_id, ingredient_id, weight
+        FROM Meal JOIN MealIngredient ON MealIngredient.meal_id = Meal.id
+        WHERE Meal.user_id = ?3 AND Meal.deletion_date IS NULL AND Meal.eat_date >= ?1 AND Meal.eat_date <= ?2;"#,
+    )
+    .bind(share.start_date)
+    .bind(share.end_date)
+    .bind(share.user_id)
+    .fetch_all(&connection)
+    .await?;
+
+    let goals = sqlx::query_as!(
+        NutritionGoal,
+        r#"
+        SELECT nutrient, creation_date, daily_min, daily_max
+        FROM NutritionGoal
+        WHERE user_id = ?
+        ORDER BY nutrient;"#,
+        share.user_id
+    )
+    .fetch_all(&connection)
+    .await?;
+
+    let table = NutrientTable::fetch(
+        &connection,
+        &components
+            .iter()
+            .map(WeightedComponent::from)
+            .collect::<Vec<WeightedComponent>>(),
+    )
+    .await?;
+
+    let mut days = BTreeMap::<String, Nutrients>::new();
+    for component in &components {
+        let Some(date) = offset.timestamp_millis_opt(component.date).single() else {
+            continue;
+        };
+        *days.entry(date.format("%Y-%m-%d").to_string()).or_default() +=
+            table.component_nutrients(&WeightedComponent::from(component));
+    }
+
+    Ok(ServerResponse::success(GetSharedSummaryResponse {
+        days: days
+            .into_iter()
+            .map(|(date, eaten)| SharedDay { date, eaten })
+            .collect(),
+        goals,
+    })
+    .json())
+}
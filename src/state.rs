@@ -1,6 +1,11 @@
 use sqlx::{Pool, Sqlite};
 
+use crate::events::EventBus;
+
 #[derive(Debug, Clone)]
 pub struct AppState {
     pub connection: Pool<Sqlite>,
+    pub events: EventBus,
+    /// How long deleted dishes and meals stay in the trash before being purged, in milliseconds
+    pub trash_retention: i64,
 }
@@ -0,0 +1,61 @@
+use axum::extract::{Path, State};
+use schemars::JsonSchema;
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::{
+    server::{ServerResponse, ServerResponseResult},
+    session::AuthUser,
+    state::AppState,
+};
+
+#[derive(Deserialize, JsonSchema)]
+pub struct SupplementId {
+    supplement_id: i64,
+}
+
+#[derive(Error, Debug)]
+enum DeleteSupplementError {
+    #[error("Supplement {0} can't be deleted while doses of it are logged")]
+    SupplementInUse(i64),
+}
+
+/// Deletes the supplement and its schedules. It's rejected while doses of it are logged, since
+/// they count toward what was eaten.
+pub async fn delete_supplement(
+    State(AppState { connection, .. }): State<AppState>,
+    user: AuthUser,
+    Path(SupplementId { supplement_id }): Path<SupplementId>,
+) -> ServerResponseResult<bool> {
+    user.check_supplement(&connection, supplement_id).await?;
+
+    let dose = sqlx::query_scalar!(
+        "SELECT id FROM SupplementDose WHERE supplement_id = ? LIMIT 1",
+        supplement_id
+    )
+    .fetch_optional(&connection)
+    .await?;
+    if dose.is_some() {
+        return Err(DeleteSupplementError::SupplementInUse(supplement_id))?;
+    }
+
+    let mut transaction = connection.begin().await?;
+    sqlx::query!(
+        "DELETE FROM SupplementSchedule WHERE supplement_id = ?",
+        supplement_id
+    )
+    .execute(&mut *transaction)
+    .await?;
+    sqlx::query!(
+        "DELETE FROM SupplementTrackable WHERE supplement_id = ?",
+        supplement_id
+    )
+    .execute(&mut *transaction)
+    .await?;
+    sqlx::query!("DELETE FROM Supplement WHERE id = ?", supplement_id)
+        .execute(&mut *transaction)
+        .await?;
+    transaction.commit().await?;
+
+    Ok(ServerResponse::success(true).json())
+}
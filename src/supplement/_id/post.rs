@@ -0,0 +1,83 @@
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+use schemars::JsonSchema;
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::{
+    models::Supplement,
+    server::{ServerResponse, ServerResponseResult},
+    session::AuthUser,
+    state::AppState,
+    supplement::contents::{fetch_supplement_trackables, SupplementBody, SupplementResponse},
+};
+
+#[derive(Deserialize, JsonSchema)]
+pub struct SupplementId {
+    supplement_id: i64,
+}
+
+#[derive(Error, Debug)]
+enum PostEditSupplementError {
+    #[error("There is already a supplement named \"{0}\"")]
+    SupplementExists(String),
+}
+
+/// Replaces what the supplement is. Doses already taken count with the new contents from then on.
+pub async fn post_edit_supplement(
+    State(AppState { connection, .. }): State<AppState>,
+    user @ AuthUser { household_id, .. }: AuthUser,
+    Path(SupplementId { supplement_id }): Path<SupplementId>,
+    Json(body): Json<SupplementBody>,
+) -> ServerResponseResult<SupplementResponse> {
+    user.check_supplement(&connection, supplement_id).await?;
+    body.check(&connection, &user).await?;
+    let name = body.name.trim();
+    let existing = sqlx::query_scalar!(
+        "SELECT id FROM Supplement WHERE household_id = ? AND name = ? AND id != ?",
+        household_id,
+        name,
+        supplement_id
+    )
+    .fetch_optional(&connection)
+    .await?;
+    if existing.is_some() {
+        return Err(PostEditSupplementError::SupplementExists(name.to_string()))?;
+    }
+
+    let dose_unit = body.dose_unit.trim();
+    let mut transaction = connection.begin().await?;
+    let supplement = sqlx::query_as!(
+        Supplement,
+        r#"
+        UPDATE Supplement
+        SET name = ?, dose_unit = ?, kcal = ?, proteins = ?, fat = ?, carbohydrates = ?
+        WHERE id = ?
+        RETURNING id as "id!", creation_date, name, dose_unit, kcal, proteins, fat, carbohydrates;"#,
+        name,
+        dose_unit,
+        body.kcal,
+        body.proteins,
+        body.fat,
+        body.carbohydrates,
+        supplement_id
+    )
+    .fetch_one(&mut *transaction)
+    .await?;
+    body.replace_trackables(&mut transaction, supplement_id)
+        .await?;
+    transaction.commit().await?;
+
+    let trackables = fetch_supplement_trackables(&connection, &[supplement_id])
+        .await?
+        .remove(&supplement_id)
+        .unwrap_or_default();
+
+    Ok(ServerResponse::success(SupplementResponse {
+        supplement,
+        trackables,
+    })
+    .json())
+}
@@ -0,0 +1,152 @@
+use std::collections::HashMap;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use sqlx::{prelude::FromRow, Sqlite};
+use thiserror::Error;
+
+use crate::{models::Supplement, session::AuthUser};
+
+#[derive(Deserialize, JsonSchema)]
+pub struct PostSupplementTrackable {
+    trackable_id: i64,
+    /// Amount in a single dose, in the trackable's unit
+    amount: f64,
+}
+
+/// What a supplement is, shared by its creation and its edition.
+#[derive(Deserialize, JsonSchema)]
+pub struct SupplementBody {
+    pub name: String,
+    /// What a single dose is, such as `capsule` or `scoop`
+    pub dose_unit: String,
+    /// Nutrients in a single dose. Unknown if not provided
+    pub kcal: Option<f64>,
+    pub proteins: Option<f64>,
+    pub fat: Option<f64>,
+    pub carbohydrates: Option<f64>,
+    /// Amounts of trackables in a single dose, such as vitamins and minerals
+    #[serde(default)]
+    pub trackables: Vec<PostSupplementTrackable>,
+}
+
+#[derive(Error, Debug)]
+enum SupplementBodyError {
+    #[error("The name of a supplement should not be empty")]
+    EmptyName,
+    #[error("The nutrients of a supplement should not be negative")]
+    NegativeNutrient,
+    #[error("The amount of trackable {0} should not be negative")]
+    NegativeAmount(i64),
+    #[error("Trackable {0} was provided more than once")]
+    DuplicateTrackable(i64),
+}
+
+impl SupplementBody {
+    /// Makes sure every value makes sense and every trackable belongs to the user's household.
+    pub async fn check(
+        &self,
+        connection: &sqlx::Pool<Sqlite>,
+        user: &AuthUser,
+    ) -> anyhow::Result<()> {
+        if self.name.trim().is_empty() {
+            return Err(SupplementBodyError::EmptyName)?;
+        }
+        let nutrients = [self.kcal, self.proteins, self.fat, self.carbohydrates];
+        if nutrients.into_iter().flatten().any(|value| value < 0.0) {
+            return Err(SupplementBodyError::NegativeNutrient)?;
+        }
+        let mut seen = vec![];
+        for trackable in &self.trackables {
+            if trackable.amount < 0.0 {
+                return Err(SupplementBodyError::NegativeAmount(trackable.trackable_id))?;
+            }
+            if seen.contains(&trackable.trackable_id) {
+                return Err(SupplementBodyError::DuplicateTrackable(
+                    trackable.trackable_id,
+                ))?;
+            }
+            seen.push(trackable.trackable_id);
+            user.check_trackable(connection, trackable.trackable_id)
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Replaces the trackables of the supplement with the ones in the body.
+    pub async fn replace_trackables(
+        &self,
+        connection: &mut sqlx::SqliteConnection,
+        supplement_id: i64,
+    ) -> anyhow::Result<()> {
+        sqlx::query!(
+            "DELETE FROM SupplementTrackable WHERE supplement_id = ?",
+            supplement_id
+        )
+        .execute(&mut *connection)
+        .await?;
+        if self.trackables.is_empty() {
+            return Ok(());
+        }
+        sqlx::QueryBuilder::<Sqlite>::new(
+            "INSERT INTO SupplementTrackable (supplement_id, trackable_id, amount)",
+        )
+        .push_values(&self.trackables, |mut b, trackable| {
+            b.push_bind(supplement_id)
+                .push_bind(trackable.trackable_id)
+                .push_bind(trackable.amount);
+        })
+        .build()
+        .execute(&mut *connection)
+        .await?;
+        Ok(())
+    }
+}
+
+#[derive(Serialize, JsonSchema, FromRow)]
+pub struct SupplementTrackableAmount {
+    #[serde(skip)]
+    supplement_id: i64,
+    trackable_id: i64,
+    name: String,
+    unit: String,
+    /// Amount in a single dose
+    amount: f64,
+}
+
+#[derive(Serialize, JsonSchema)]
+pub struct SupplementResponse {
+    pub supplement: Supplement,
+    pub trackables: Vec<SupplementTrackableAmount>,
+}
+
+/// Fetches the per dose amounts of trackables of every supplement in `supplement_ids`.
+pub async fn fetch_supplement_trackables(
+    connection: &sqlx::Pool<Sqlite>,
+    supplement_ids: &[i64],
+) -> anyhow::Result<HashMap<i64, Vec<SupplementTrackableAmount>>> {
+    if supplement_ids.is_empty() {
+        return Ok(HashMap::new());
+    }
+
+    Ok(sqlx::QueryBuilder::<Sqlite>::new(
+        r#"
+        SELECT supplement_id, trackable_id, name, unit, amount
+        FROM SupplementTrackable JOIN Trackable ON Trackable.id = SupplementTrackable.trackable_id
+        WHERE supplement_id IN "#,
+    )
+    .push_tuples(supplement_ids.iter(), |mut p, id| {
+        p.push_bind(*id);
+    })
+    .push(" ORDER BY name")
+    .build_query_as::<SupplementTrackableAmount>()
+    .fetch_all(connection)
+    .await?
+    .into_iter()
+    .fold(HashMap::new(), |mut dict, row| {
+        dict.entry(row.supplement_id)
+            .or_insert_with(Vec::new)
+            .push(row);
+        dict
+    }))
+}
@@ -0,0 +1,41 @@
+use axum::extract::{Path, State};
+use schemars::JsonSchema;
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::{
+    server::{ServerResponse, ServerResponseResult},
+    session::AuthUser,
+    state::AppState,
+};
+
+#[derive(Deserialize, JsonSchema)]
+pub struct DoseId {
+    dose_id: i64,
+}
+
+#[derive(Error, Debug)]
+enum DeleteDoseError {
+    #[error("Could not find dose with id \"{0}\"")]
+    DoseNotFound(i64),
+}
+
+pub async fn delete_dose(
+    State(AppState { connection, .. }): State<AppState>,
+    AuthUser { user_id, .. }: AuthUser,
+    Path(DoseId { dose_id }): Path<DoseId>,
+) -> ServerResponseResult<bool> {
+    let result = sqlx::query!(
+        "DELETE FROM SupplementDose WHERE id = ? AND user_id = ?",
+        dose_id,
+        user_id
+    )
+    .execute(&connection)
+    .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(DeleteDoseError::DoseNotFound(dose_id))?;
+    }
+
+    Ok(ServerResponse::success(true).json())
+}
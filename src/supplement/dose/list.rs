@@ -0,0 +1,55 @@
+use axum::extract::{Query, State};
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+use crate::{
+    models::SupplementDose,
+    server::{ServerResponse, ServerResponseResult},
+    session::AuthUser,
+    state::AppState,
+};
+
+#[derive(Deserialize, JsonSchema)]
+pub struct ListDosesQueryParams {
+    from: i64,
+    to: i64,
+    /// Household member whose doses should be listed. Defaults to the current user
+    member_id: Option<i64>,
+}
+
+/// Doses taken between `from` and `to`, oldest first.
+pub async fn list_doses(
+    State(AppState { connection, .. }): State<AppState>,
+    user: AuthUser,
+    Query(ListDosesQueryParams {
+        from,
+        to,
+        member_id,
+    }): Query<ListDosesQueryParams>,
+) -> ServerResponseResult<Vec<SupplementDose>> {
+    let member_id = user.resolve_member(&connection, member_id).await?;
+
+    let doses = sqlx::query_as!(
+        SupplementDose,
+        r#"
+        SELECT
+            id,
+            creation_date,
+            supplement_id,
+            take_date,
+            doses,
+            meal_id,
+            schedule_id,
+            scheduled_date
+        FROM SupplementDose
+        WHERE user_id = ? AND take_date >= ? AND take_date <= ?
+        ORDER BY take_date;"#,
+        member_id,
+        from,
+        to
+    )
+    .fetch_all(&connection)
+    .await?;
+
+    Ok(ServerResponse::success(doses).json())
+}
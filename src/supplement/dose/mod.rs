@@ -0,0 +1,17 @@
+use aide::axum::{
+    routing::{delete, post},
+    ApiRouter,
+};
+
+use crate::state::AppState;
+
+mod delete;
+mod list;
+mod post;
+
+pub fn route(state: AppState) -> ApiRouter {
+    ApiRouter::new()
+        .api_route("/", post(post::post_dose).get(list::list_doses))
+        .api_route("/:dose_id", delete(delete::delete_dose))
+        .with_state(state)
+}
@@ -0,0 +1,155 @@
+use axum::{extract::State, Json};
+use schemars::JsonSchema;
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::{
+    models::{SupplementDose, SupplementSchedule},
+    period::weekday_occurrences,
+    server::{ServerResponse, ServerResponseResult},
+    session::AuthUser,
+    state::AppState,
+};
+
+#[derive(Deserialize, JsonSchema)]
+pub struct ScheduledOccurrence {
+    schedule_id: i64,
+    /// Date the occurrence was scheduled for, as listed by `/supplement/schedule/due`
+    scheduled_date: i64,
+}
+
+#[derive(Deserialize, JsonSchema)]
+pub struct PostDoseBody {
+    supplement_id: i64,
+    take_date: i64,
+    /// How many doses were taken. Defaults to the doses of the schedule, or to a single one
+    doses: Option<f64>,
+    /// The meal the dose was taken with
+    meal_id: Option<i64>,
+    /// The occurrence of a schedule the dose was taken for
+    occurrence: Option<ScheduledOccurrence>,
+}
+
+#[derive(Error, Debug)]
+enum PostDoseError {
+    #[error("The number of doses should be greater than zero")]
+    NonPositiveDoses,
+    #[error("Could not find schedule with id \"{0}\"")]
+    ScheduleNotFound(i64),
+    #[error("Schedule {0} is for another supplement")]
+    WrongSupplement(i64),
+    #[error("Schedule {0} has no occurrence at {1}")]
+    NotAnOccurrence(i64, i64),
+    #[error("The occurrence of schedule {0} at {1} was already taken")]
+    AlreadyTaken(i64, i64),
+}
+
+/// Logs a dose of a supplement. It counts toward the nutrients and trackables of the day it was
+/// taken in, and of the meal it was taken with.
+pub async fn post_dose(
+    State(AppState { connection, .. }): State<AppState>,
+    user @ AuthUser { user_id, .. }: AuthUser,
+    Json(PostDoseBody {
+        supplement_id,
+        take_date,
+        doses,
+        meal_id,
+        occurrence,
+    }): Json<PostDoseBody>,
+) -> ServerResponseResult<SupplementDose> {
+    user.check_supplement(&connection, supplement_id).await?;
+    if let Some(meal_id) = meal_id {
+        user.check_meal(&connection, meal_id).await?;
+    }
+
+    let occurrence = occurrence.map(|o| (o.schedule_id, o.scheduled_date));
+    let mut scheduled_doses = None;
+    if let Some((schedule_id, scheduled_date)) = occurrence {
+        let schedule = sqlx::query_as!(
+            SupplementSchedule,
+            r#"
+            SELECT
+                id,
+                creation_date,
+                supplement_id,
+                doses,
+                start_date,
+                end_date,
+                weekdays,
+                utc_offset_minutes
+            FROM SupplementSchedule
+            WHERE id = ? AND user_id = ?;"#,
+            schedule_id,
+            user_id
+        )
+        .fetch_optional(&connection)
+        .await?
+        .ok_or(PostDoseError::ScheduleNotFound(schedule_id))?;
+        if schedule.supplement_id != supplement_id {
+            return Err(PostDoseError::WrongSupplement(schedule_id))?;
+        }
+        let occurrences = weekday_occurrences(
+            schedule.start_date,
+            schedule.end_date,
+            schedule.weekdays,
+            schedule.utc_offset_minutes,
+            scheduled_date - 1,
+            scheduled_date,
+        );
+        if !occurrences.contains(&scheduled_date) {
+            return Err(PostDoseError::NotAnOccurrence(schedule_id, scheduled_date))?;
+        }
+        let taken = sqlx::query_scalar!(
+            "SELECT id FROM SupplementDose WHERE schedule_id = ? AND scheduled_date = ?",
+            schedule_id,
+            scheduled_date
+        )
+        .fetch_optional(&connection)
+        .await?;
+        if taken.is_some() {
+            return Err(PostDoseError::AlreadyTaken(schedule_id, scheduled_date))?;
+        }
+        scheduled_doses = Some(schedule.doses);
+    }
+
+    let doses = doses.or(scheduled_doses).unwrap_or(1.0);
+    if doses <= 0.0 {
+        return Err(PostDoseError::NonPositiveDoses)?;
+    }
+    let schedule_id = occurrence.map(|(schedule_id, _)| schedule_id);
+    let scheduled_date = occurrence.map(|(_, scheduled_date)| scheduled_date);
+
+    let dose = sqlx::query_as!(
+        SupplementDose,
+        r#"
+        INSERT INTO SupplementDose (
+            user_id,
+            supplement_id,
+            take_date,
+            doses,
+            meal_id,
+            schedule_id,
+            scheduled_date
+        ) VALUES (?, ?, ?, ?, ?, ?, ?)
+        RETURNING
+            id,
+            creation_date,
+            supplement_id,
+            take_date,
+            doses,
+            meal_id,
+            schedule_id,
+            scheduled_date;"#,
+        user_id,
+        supplement_id,
+        take_date,
+        doses,
+        meal_id,
+        schedule_id,
+        scheduled_date
+    )
+    .fetch_one(&connection)
+    .await?;
+
+    Ok(ServerResponse::success(dose).json())
+}
@@ -0,0 +1,44 @@
+use axum::extract::State;
+
+use crate::{
+    models::Supplement,
+    server::{ServerResponse, ServerResponseResult},
+    session::AuthUser,
+    state::AppState,
+};
+
+use super::contents::{fetch_supplement_trackables, SupplementResponse};
+
+/// Every supplement of the household, with what a dose of each has.
+pub async fn list_supplements(
+    State(AppState { connection, .. }): State<AppState>,
+    AuthUser { household_id, .. }: AuthUser,
+) -> ServerResponseResult<Vec<SupplementResponse>> {
+    let supplements = sqlx::query_as!(
+        Supplement,
+        r#"
+        SELECT id, creation_date, name, dose_unit, kcal, proteins, fat, carbohydrates
+        FROM Supplement
+        WHERE household_id = ?
+        ORDER BY name;"#,
+        household_id
+    )
+    .fetch_all(&connection)
+    .await?;
+
+    let mut trackables = fetch_supplement_trackables(
+        &connection,
+        &supplements.iter().map(|s| s.id).collect::<Vec<i64>>(),
+    )
+    .await?;
+
+    let supplements = supplements
+        .into_iter()
+        .map(|supplement| SupplementResponse {
+            trackables: trackables.remove(&supplement.id).unwrap_or_default(),
+            supplement,
+        })
+        .collect();
+
+    Ok(ServerResponse::success(supplements).json())
+}
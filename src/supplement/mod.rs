@@ -0,0 +1,19 @@
+use aide::axum::{routing::post, ApiRouter};
+
+use crate::state::AppState;
+
+mod _id;
+mod contents;
+mod dose;
+mod list;
+mod post;
+mod schedule;
+
+pub fn route(state: AppState) -> ApiRouter {
+    ApiRouter::new()
+        .api_route("/", post(post::post_supplement).get(list::list_supplements))
+        .nest_api_service("/dose", dose::route(state.clone()))
+        .nest_api_service("/schedule", schedule::route(state.clone()))
+        .nest_api_service("/:supplement_id", _id::route(state.clone()))
+        .with_state(state)
+}
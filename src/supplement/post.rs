@@ -0,0 +1,70 @@
+use axum::{extract::State, Json};
+use thiserror::Error;
+
+use crate::{
+    models::Supplement,
+    server::{ServerResponse, ServerResponseResult},
+    session::AuthUser,
+    state::AppState,
+};
+
+use super::contents::{fetch_supplement_trackables, SupplementBody, SupplementResponse};
+
+#[derive(Error, Debug)]
+enum PostSupplementError {
+    #[error("There is already a supplement named \"{0}\"")]
+    SupplementExists(String),
+}
+
+/// Creates a supplement, along with the nutrients and trackables in a single dose of it.
+pub async fn post_supplement(
+    State(AppState { connection, .. }): State<AppState>,
+    user @ AuthUser { household_id, .. }: AuthUser,
+    Json(body): Json<SupplementBody>,
+) -> ServerResponseResult<SupplementResponse> {
+    body.check(&connection, &user).await?;
+    let name = body.name.trim();
+    let existing = sqlx::query_scalar!(
+        "SELECT id FROM Supplement WHERE household_id = ? AND name = ?",
+        household_id,
+        name
+    )
+    .fetch_optional(&connection)
+    .await?;
+    if existing.is_some() {
+        return Err(PostSupplementError::SupplementExists(name.to_string()))?;
+    }
+
+    let dose_unit = body.dose_unit.trim();
+    let mut transaction = connection.begin().await?;
+    let supplement = sqlx::query_as!(
+        Supplement,
+        r#"
+        INSERT INTO Supplement (household_id, name, dose_unit, kcal, proteins, fat, carbohydrates)
+        VALUES (?, ?, ?, ?, ?, ?, ?)
+        RETURNING id, creation_date, name, dose_unit, kcal, proteins, fat, carbohydrates;"#,
+        household_id,
+        name,
+        dose_unit,
+        body.kcal,
+        body.proteins,
+        body.fat,
+        body.carbohydrates
+    )
+    .fetch_one(&mut *transaction)
+    .await?;
+    body.replace_trackables(&mut transaction, supplement.id)
+        .await?;
+    transaction.commit().await?;
+
+    let trackables = fetch_supplement_trackables(&connection, &[supplement.id])
+        .await?
+        .remove(&supplement.id)
+        .unwrap_or_default();
+
+    Ok(ServerResponse::success(SupplementResponse {
+        supplement,
+        trackables,
+    })
+    .json())
+}
@@ -0,0 +1,51 @@
+use axum::extract::{Path, State};
+use schemars::JsonSchema;
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::{
+    server::{ServerResponse, ServerResponseResult},
+    session::AuthUser,
+    state::AppState,
+};
+
+#[derive(Deserialize, JsonSchema)]
+pub struct ScheduleId {
+    schedule_id: i64,
+}
+
+#[derive(Error, Debug)]
+enum DeleteScheduleError {
+    #[error("Could not find schedule with id \"{0}\"")]
+    ScheduleNotFound(i64),
+}
+
+/// Deletes the schedule. Doses already taken for it are kept, but no longer reference it.
+pub async fn delete_schedule(
+    State(AppState { connection, .. }): State<AppState>,
+    AuthUser { user_id, .. }: AuthUser,
+    Path(ScheduleId { schedule_id }): Path<ScheduleId>,
+) -> ServerResponseResult<bool> {
+    sqlx::query_scalar!(
+        "SELECT id FROM SupplementSchedule WHERE id = ? AND user_id = ?",
+        schedule_id,
+        user_id
+    )
+    .fetch_optional(&connection)
+    .await?
+    .ok_or(DeleteScheduleError::ScheduleNotFound(schedule_id))?;
+
+    let mut transaction = connection.begin().await?;
+    sqlx::query!(
+        "UPDATE SupplementDose SET schedule_id = NULL, scheduled_date = NULL WHERE schedule_id = ?",
+        schedule_id
+    )
+    .execute(&mut *transaction)
+    .await?;
+    sqlx::query!("DELETE FROM SupplementSchedule WHERE id = ?", schedule_id)
+        .execute(&mut *transaction)
+        .await?;
+    transaction.commit().await?;
+
+    Ok(ServerResponse::success(true).json())
+}
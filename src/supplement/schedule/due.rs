@@ -0,0 +1,28 @@
+use axum::extract::{Query, State};
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+use crate::{
+    dose::{fetch_scheduled_doses, ScheduledDose},
+    server::{ServerResponse, ServerResponseResult},
+    session::AuthUser,
+    state::AppState,
+};
+
+#[derive(Deserialize, JsonSchema)]
+pub struct GetDueDosesQueryParams {
+    from: i64,
+    to: i64,
+}
+
+/// Every occurrence of the user's schedules between `from` and `to`, with the dose taken for it
+/// if it was.
+pub async fn get_due_doses(
+    State(AppState { connection, .. }): State<AppState>,
+    AuthUser { user_id, .. }: AuthUser,
+    Query(GetDueDosesQueryParams { from, to }): Query<GetDueDosesQueryParams>,
+) -> ServerResponseResult<Vec<ScheduledDose>> {
+    let doses = fetch_scheduled_doses(&connection, user_id, from, to).await?;
+
+    Ok(ServerResponse::success(doses).json())
+}
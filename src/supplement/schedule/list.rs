@@ -0,0 +1,50 @@
+use axum::extract::State;
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use crate::{
+    models::SupplementSchedule,
+    period::Weekday,
+    server::{ServerResponse, ServerResponseResult},
+    session::AuthUser,
+    state::AppState,
+};
+
+#[derive(Serialize, JsonSchema)]
+pub struct ScheduleResponse {
+    pub schedule: SupplementSchedule,
+    pub weekdays: Vec<Weekday>,
+}
+
+pub async fn list_schedules(
+    State(AppState { connection, .. }): State<AppState>,
+    AuthUser { user_id, .. }: AuthUser,
+) -> ServerResponseResult<Vec<ScheduleResponse>> {
+    let schedules = sqlx::query_as!(
+        SupplementSchedule,
+        r#"
+        SELECT
+            id,
+            creation_date,
+            supplement_id,
+            doses,
+            start_date,
+            end_date,
+            weekdays,
+            utc_offset_minutes
+        FROM SupplementSchedule
+        WHERE user_id = ?
+        ORDER BY start_date DESC;"#,
+        user_id
+    )
+    .fetch_all(&connection)
+    .await?
+    .into_iter()
+    .map(|schedule| ScheduleResponse {
+        weekdays: Weekday::from_mask(schedule.weekdays),
+        schedule,
+    })
+    .collect();
+
+    Ok(ServerResponse::success(schedules).json())
+}
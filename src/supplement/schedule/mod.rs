@@ -0,0 +1,19 @@
+use aide::axum::{
+    routing::{delete, get, post},
+    ApiRouter,
+};
+
+use crate::state::AppState;
+
+mod delete;
+mod due;
+mod list;
+mod post;
+
+pub fn route(state: AppState) -> ApiRouter {
+    ApiRouter::new()
+        .api_route("/", post(post::post_schedule).get(list::list_schedules))
+        .api_route("/due", get(due::get_due_doses))
+        .api_route("/:schedule_id", delete(delete::delete_schedule))
+        .with_state(state)
+}
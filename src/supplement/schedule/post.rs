@@ -0,0 +1,93 @@
+use axum::{extract::State, Json};
+use schemars::JsonSchema;
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::{
+    models::SupplementSchedule,
+    period::{weekly_schedule, Weekday},
+    server::{ServerResponse, ServerResponseResult},
+    session::AuthUser,
+    state::AppState,
+};
+
+use super::list::ScheduleResponse;
+
+#[derive(Deserialize, JsonSchema)]
+pub struct PostScheduleBody {
+    supplement_id: i64,
+    /// How many doses should be taken in each occurrence. Defaults to a single one
+    doses: Option<f64>,
+    /// Date of the first occurrence. Its time of day is used for every occurrence
+    start_date: i64,
+    end_date: Option<i64>,
+    weekdays: Vec<Weekday>,
+    /// Offset of the user's timezone, used to decide which weekday an occurrence falls in
+    utc_offset_minutes: Option<i64>,
+}
+
+#[derive(Error, Debug)]
+enum PostScheduleError {
+    #[error("The number of doses should be greater than zero")]
+    NonPositiveDoses,
+}
+
+/// Schedules doses of a supplement to be taken at the same time of day in some weekdays. Doses
+/// not taken yet count as planned in the projection.
+pub async fn post_schedule(
+    State(AppState { connection, .. }): State<AppState>,
+    user @ AuthUser { user_id, .. }: AuthUser,
+    Json(PostScheduleBody {
+        supplement_id,
+        doses,
+        start_date,
+        end_date,
+        weekdays,
+        utc_offset_minutes,
+    }): Json<PostScheduleBody>,
+) -> ServerResponseResult<ScheduleResponse> {
+    let (weekdays, utc_offset_minutes) = weekly_schedule(&weekdays, utc_offset_minutes)?;
+    let doses = doses.unwrap_or(1.0);
+    if doses <= 0.0 {
+        return Err(PostScheduleError::NonPositiveDoses)?;
+    }
+    user.check_supplement(&connection, supplement_id).await?;
+
+    let schedule = sqlx::query_as!(
+        SupplementSchedule,
+        r#"
+        INSERT INTO SupplementSchedule (
+            user_id,
+            supplement_id,
+            doses,
+            start_date,
+            end_date,
+            weekdays,
+            utc_offset_minutes
+        ) VALUES (?, ?, ?, ?, ?, ?, ?)
+        RETURNING
+            id,
+            creation_date,
+            supplement_id,
+            doses,
+            start_date,
+            end_date,
+            weekdays,
+            utc_offset_minutes;"#,
+        user_id,
+        supplement_id,
+        doses,
+        start_date,
+        end_date,
+        weekdays,
+        utc_offset_minutes
+    )
+    .fetch_one(&connection)
+    .await?;
+
+    Ok(ServerResponse::success(ScheduleResponse {
+        weekdays: Weekday::from_mask(schedule.weekdays),
+        schedule,
+    })
+    .json())
+}
@@ -0,0 +1,119 @@
+use sqlx::SqliteConnection;
+
+use crate::{
+    audit::{record_change, snapshot, AuditEntity},
+    session::AuthUser,
+};
+
+use super::record::{fetch_record, SyncEntity, SyncRecord};
+
+/// The ingredient, dish or meal a link belongs to, and the column that references it.
+fn link_owner(entity: SyncEntity) -> Option<(SyncEntity, &'static str)> {
+    match entity {
+        SyncEntity::DishIngredient => Some((SyncEntity::Dish, "dish_id")),
+        SyncEntity::MealDish | SyncEntity::MealIngredient => Some((SyncEntity::Meal, "meal_id")),
+        SyncEntity::Ingredient | SyncEntity::Dish | SyncEntity::Meal => None,
+    }
+}
+
+fn audit_entity(entity: SyncEntity) -> Option<AuditEntity> {
+    match entity {
+        SyncEntity::Ingredient => Some(AuditEntity::Ingredient),
+        SyncEntity::Dish => Some(AuditEntity::Dish),
+        SyncEntity::Meal => Some(AuditEntity::Meal),
+        _ => None,
+    }
+}
+
+/// The ingredients, dishes and meals that a change to the row with `uuid` touches, as they are
+/// in the database. Changes to links touch the dish or meal they belong to.
+async fn touched(
+    connection: &mut SqliteConnection,
+    user: &AuthUser,
+    entity: SyncEntity,
+    uuid: &str,
+) -> anyhow::Result<Vec<(AuditEntity, i64)>> {
+    let Some(row) = fetch_record(connection, user, entity, uuid).await? else {
+        return Ok(vec![]);
+    };
+    match (audit_entity(entity), link_owner(entity)) {
+        (Some(audited), _) => Ok(vec![(audited, row.id)]),
+        (None, Some((owner, owner_column))) => {
+            let owner_id = sqlx::query_scalar::<_, i64>(&format!(
+                "SELECT {owner_column} FROM {} WHERE rowid = ?",
+                entity.name()
+            ))
+            .bind(row.id)
+            .fetch_one(&mut *connection)
+            .await?;
+            Ok(audit_entity(owner)
+                .map(|owner| vec![(owner, owner_id)])
+                .unwrap_or_default())
+        }
+        (None, None) => Ok(vec![]),
+    }
+}
+
+/// What an uploaded change is about to touch, so it can be recorded in the audit log once it's
+/// applied.
+pub struct PendingAudit {
+    entity: SyncEntity,
+    before: Vec<(AuditEntity, i64, Option<String>)>,
+}
+
+impl PendingAudit {
+    /// Takes snapshots of what the change touches. For links, that's both the dish or meal they
+    /// belong to now, and the one `record` moves them to.
+    pub async fn start(
+        connection: &mut SqliteConnection,
+        user: &AuthUser,
+        entity: SyncEntity,
+        uuid: &str,
+        record: Option<&SyncRecord>,
+    ) -> anyhow::Result<PendingAudit> {
+        let mut targets = touched(connection, user, entity, uuid).await?;
+        let new_owner = match record {
+            Some(SyncRecord::DishIngredient { dish_uuid, .. }) => {
+                Some((SyncEntity::Dish, AuditEntity::Dish, dish_uuid))
+            }
+            Some(SyncRecord::MealDish { meal_uuid, .. })
+            | Some(SyncRecord::MealIngredient { meal_uuid, .. }) => {
+                Some((SyncEntity::Meal, AuditEntity::Meal, meal_uuid))
+            }
+            _ => None,
+        };
+        if let Some((owner, audited, owner_uuid)) = new_owner {
+            if let Some(row) = fetch_record(connection, user, owner, owner_uuid).await? {
+                if !targets.contains(&(audited, row.id)) {
+                    targets.push((audited, row.id));
+                }
+            }
+        }
+
+        let mut before = vec![];
+        for (audited, id) in targets {
+            before.push((audited, id, snapshot(&mut *connection, audited, id).await?));
+        }
+        Ok(PendingAudit { entity, before })
+    }
+
+    /// Records the change in the audit log. Rows the change created had no snapshot before it.
+    pub async fn finish(
+        self,
+        connection: &mut SqliteConnection,
+        user: &AuthUser,
+        uuid: &str,
+    ) -> anyhow::Result<()> {
+        let mut changes = self.before;
+        for (audited, id) in touched(connection, user, self.entity, uuid).await? {
+            if !changes.iter().any(|(e, i, _)| (*e, *i) == (audited, id)) {
+                changes.push((audited, id, None));
+            }
+        }
+
+        for (audited, id, before) in changes {
+            record_change(&mut *connection, user, audited, id, before).await?;
+        }
+        Ok(())
+    }
+}
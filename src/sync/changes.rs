@@ -0,0 +1,162 @@
+use std::collections::HashMap;
+
+use aide::transform::TransformOperation;
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    app_error::InternalServerError,
+    server::{ServerResponse, ServerResponseResult},
+    session::AuthUser,
+    state::AppState,
+};
+
+use super::record::{fetch_records, SyncEntity, SyncRecord};
+
+/// Most changes returned at once
+const MAX_LIMIT: i64 = 1000;
+
+#[derive(Deserialize, JsonSchema)]
+pub struct ListChangesQueryParams {
+    /// Cursor returned by the previous call. Everything is returned if not provided
+    cursor: Option<i64>,
+    /// Most changes to return. Defaults to, and can't be larger than, 1000
+    limit: Option<i64>,
+}
+
+#[derive(Serialize, JsonSchema)]
+#[serde(tag = "op")]
+pub enum FeedChange {
+    /// The row was created or updated, and now looks like `record`
+    Upsert {
+        cursor: i64,
+        uuid: String,
+        /// Id of the row in the other endpoints. Meaningless for links
+        id: i64,
+        record: SyncRecord,
+    },
+    Delete {
+        cursor: i64,
+        uuid: String,
+        entity: SyncEntity,
+    },
+}
+
+#[derive(Serialize, JsonSchema)]
+pub struct ListChangesResponse {
+    /// Only the latest change of each row, ordered by cursor
+    changes: Vec<FeedChange>,
+    /// Should be sent in the next call, and as the `base_cursor` of changes uploaded
+    cursor: i64,
+    /// Whether there are more changes after `cursor`
+    has_more: bool,
+}
+
+#[derive(sqlx::FromRow)]
+struct DatabaseChange {
+    cursor: i64,
+    entity: String,
+    entity_uuid: String,
+    operation: String,
+}
+
+/// Everything changed after `cursor` that the user can see: the household's ingredients and
+/// dishes, and the user's own meals.
+pub async fn list_changes(
+    State(AppState { connection, .. }): State<AppState>,
+    user: AuthUser,
+    Query(ListChangesQueryParams { cursor, limit }): Query<ListChangesQueryParams>,
+) -> ServerResponseResult<ListChangesResponse> {
+    let cursor = cursor.unwrap_or(0);
+    let limit = limit.unwrap_or(MAX_LIMIT).clamp(1, MAX_LIMIT);
+
+    let mut connection = connection.acquire().await?;
+
+    // Asks for one more than the limit to know if there's more
+    let mut changes = sqlx::query_as::<_, DatabaseChange>(
+        r#"
+        SELECT MAX(id) AS cursor, entity, entity_uuid, operation
+        FROM SyncChange
+        WHERE id > ? AND household_id = ? AND (user_id IS NULL OR user_id = ?)
+        GROUP BY entity, entity_uuid
+        ORDER BY cursor
+        LIMIT ?;"#,
+    )
+    .bind(cursor)
+    .bind(user.household_id)
+    .bind(user.user_id)
+    .bind(limit + 1)
+    .fetch_all(&mut *connection)
+    .await?;
+
+    let has_more = changes.len() as i64 > limit;
+    changes.truncate(limit as usize);
+
+    let mut upserted = HashMap::<SyncEntity, Vec<String>>::new();
+    for change in &changes {
+        if let (Some(entity), "Upsert") = (
+            SyncEntity::from_name(&change.entity),
+            change.operation.as_str(),
+        ) {
+            upserted
+                .entry(entity)
+                .or_default()
+                .push(change.entity_uuid.clone());
+        }
+    }
+
+    let mut records = HashMap::new();
+    for (entity, uuids) in upserted {
+        records.insert(
+            entity,
+            fetch_records(&mut connection, &user, entity, &uuids).await?,
+        );
+    }
+
+    let new_cursor = changes.last().map(|c| c.cursor).unwrap_or(cursor);
+    let changes = changes
+        .into_iter()
+        .filter_map(|change| {
+            let entity = SyncEntity::from_name(&change.entity)?;
+            // Rows that were upserted but can't be seen anymore are reported as deleted
+            let fetched = records
+                .get_mut(&entity)
+                .and_then(|records| records.remove(&change.entity_uuid));
+            Some(match fetched {
+                Some(fetched) => FeedChange::Upsert {
+                    cursor: change.cursor,
+                    uuid: change.entity_uuid,
+                    id: fetched.id,
+                    record: fetched.record,
+                },
+                None => FeedChange::Delete {
+                    cursor: change.cursor,
+                    uuid: change.entity_uuid,
+                    entity,
+                },
+            })
+        })
+        .collect();
+
+    Ok(ServerResponse::success(ListChangesResponse {
+        changes,
+        cursor: new_cursor,
+        has_more,
+    })
+    .json())
+}
+
+pub fn list_changes_docs(op: TransformOperation) -> TransformOperation {
+    op.description(
+        "Change feed of the household's ingredients, dishes and the links between them, and of \
+        the user's own meals and their links. Only the latest change of each row after `cursor` \
+        is returned, so applying the changes in order leaves the client with the server's state. \
+        Changes made through any endpoint, including uploads by this client, show up here.",
+    )
+    .response::<200, Json<ServerResponse<ListChangesResponse>>>()
+    .response::<500, Json<InternalServerError>>()
+}
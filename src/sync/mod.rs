@@ -0,0 +1,23 @@
+use aide::axum::{
+    routing::{get_with, post_with},
+    ApiRouter,
+};
+
+use crate::state::AppState;
+
+use self::{changes::list_changes_docs, post::post_sync_docs};
+
+mod audit;
+mod changes;
+mod post;
+mod record;
+
+pub fn route(state: AppState) -> ApiRouter {
+    ApiRouter::new()
+        .api_route("/", post_with(post::post_sync, post_sync_docs))
+        .api_route(
+            "/changes",
+            get_with(changes::list_changes, list_changes_docs),
+        )
+        .with_state(state)
+}
@@ -0,0 +1,155 @@
+use aide::transform::TransformOperation;
+use axum::{extract::State, Json};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{
+    app_error::InternalServerError,
+    server::{ServerResponse, ServerResponseResult},
+    session::AuthUser,
+    state::AppState,
+};
+
+use super::{
+    audit::PendingAudit,
+    record::{apply_delete, apply_upsert, SyncEntity, SyncOutcome, SyncRecord},
+};
+
+/// Most changes accepted in a single upload
+const MAX_BATCH_SIZE: usize = 500;
+
+#[derive(Deserialize, JsonSchema)]
+#[serde(tag = "op")]
+pub enum UploadChange {
+    /// Creates the row with `uuid` if it doesn't exist, or updates it to look like `record`
+    Upsert {
+        /// Lowercase UUID generated by the client when the row was created
+        uuid: String,
+        /// Cursor of the change feed the client had when it made the change
+        base_cursor: i64,
+        record: SyncRecord,
+    },
+    Delete {
+        uuid: String,
+        base_cursor: i64,
+        entity: SyncEntity,
+    },
+}
+
+#[derive(Serialize, JsonSchema)]
+#[serde(tag = "status")]
+pub enum UploadResult {
+    Applied {
+        uuid: String,
+    },
+    /// The row changed on the server after `base_cursor`, so the change was not applied.
+    /// `server` is the row as the server has it, or null if it was deleted
+    Conflict {
+        uuid: String,
+        server: Option<SyncRecord>,
+    },
+    /// The change is invalid, and was not applied
+    Rejected {
+        uuid: String,
+        reason: String,
+    },
+}
+
+#[derive(Deserialize, JsonSchema)]
+pub struct PostSyncBody {
+    changes: Vec<UploadChange>,
+}
+
+#[derive(Error, Debug)]
+enum PostSyncError {
+    #[error("At most {MAX_BATCH_SIZE} changes can be uploaded at once, but {0} were sent")]
+    BatchTooLarge(usize),
+}
+
+pub async fn post_sync(
+    State(AppState { connection, .. }): State<AppState>,
+    user: AuthUser,
+    Json(PostSyncBody { changes }): Json<PostSyncBody>,
+) -> ServerResponseResult<Vec<UploadResult>> {
+    if changes.len() > MAX_BATCH_SIZE {
+        return Err(PostSyncError::BatchTooLarge(changes.len()))?;
+    }
+
+    let mut transaction = connection.begin().await?;
+
+    let mut results = vec![];
+    for change in changes {
+        let (uuid, audit, outcome) = match change {
+            UploadChange::Upsert {
+                uuid,
+                base_cursor,
+                record,
+            } => {
+                let audit = PendingAudit::start(
+                    &mut transaction,
+                    &user,
+                    record.entity(),
+                    &uuid,
+                    Some(&record),
+                )
+                .await?;
+                let outcome =
+                    apply_upsert(&mut transaction, &user, &uuid, base_cursor, record).await?;
+                (uuid, audit, outcome)
+            }
+            UploadChange::Delete {
+                uuid,
+                base_cursor,
+                entity,
+            } => {
+                let audit =
+                    PendingAudit::start(&mut transaction, &user, entity, &uuid, None).await?;
+                let outcome =
+                    apply_delete(&mut transaction, &user, &uuid, base_cursor, entity).await?;
+                (uuid, audit, outcome)
+            }
+        };
+        if let SyncOutcome::Applied = outcome {
+            audit.finish(&mut transaction, &user, &uuid).await?;
+        }
+        results.push(match outcome {
+            SyncOutcome::Applied => UploadResult::Applied { uuid },
+            SyncOutcome::Conflict(server) => UploadResult::Conflict { uuid, server },
+            SyncOutcome::Rejected(reason) => UploadResult::Rejected {
+                uuid,
+                reason: reason.to_string(),
+            },
+        });
+    }
+
+    transaction.commit().await?;
+
+    Ok(ServerResponse::success(results).json())
+}
+
+pub fn post_sync_docs(op: TransformOperation) -> TransformOperation {
+    op.description(
+        "Uploads changes queued by a client while offline. Changes are applied in order, so a \
+        row must come before the links that reference it, and links must be deleted before the \
+        rows they reference. Each change gets its own result, in the same order.\n\n\
+        Conflict rules:\n\
+        - A change conflicts when the row it's about changed on the server (through any \
+        endpoint or client) after the change's `base_cursor`. The server always wins: the \
+        change is not applied, and the server's version of the row is returned so the client \
+        can rebase its change and upload it again with a newer `base_cursor`.\n\
+        - Upserting a record identical to the server's never conflicts, so uploads can be \
+        retried safely.\n\
+        - Deleting a row that no longer exists always succeeds, even if it was deleted after \
+        `base_cursor`.\n\
+        - Upserting a row that was deleted on the server after `base_cursor` conflicts, with a \
+        null `server`. The client should drop its change or create a new row with a new uuid.\n\
+        - Links referencing rows the server doesn't know are rejected. Two links between the \
+        same rows are rejected, the existing one should be updated instead.\n\
+        - Deleting a meal or a dish moves it to the trash, along with its links. Deleting a \
+        dish is rejected while meals, plans or templates use it. Deleting an ingredient is \
+        rejected while anything uses it.",
+    )
+    .response::<200, Json<ServerResponse<Vec<UploadResult>>>>()
+    .response::<500, Json<InternalServerError>>()
+}
@@ -0,0 +1,659 @@
+use std::collections::HashMap;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use sqlx::{prelude::FromRow, Sqlite, SqliteConnection};
+use thiserror::Error;
+
+use crate::{audit::AuditEntity, dependency::is_referenced, session::AuthUser};
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum SyncEntity {
+    Ingredient,
+    Dish,
+    Meal,
+    DishIngredient,
+    MealDish,
+    MealIngredient,
+}
+
+impl SyncEntity {
+    pub const ALL: [SyncEntity; 6] = [
+        SyncEntity::Ingredient,
+        SyncEntity::Dish,
+        SyncEntity::Meal,
+        SyncEntity::DishIngredient,
+        SyncEntity::MealDish,
+        SyncEntity::MealIngredient,
+    ];
+
+    /// Name of the entity, which is also the name of its table
+    pub fn name(self) -> &'static str {
+        match self {
+            SyncEntity::Ingredient => "Ingredient",
+            SyncEntity::Dish => "Dish",
+            SyncEntity::Meal => "Meal",
+            SyncEntity::DishIngredient => "DishIngredient",
+            SyncEntity::MealDish => "MealDish",
+            SyncEntity::MealIngredient => "MealIngredient",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<SyncEntity> {
+        SyncEntity::ALL.into_iter().find(|e| e.name() == name)
+    }
+
+    /// Selects the rows of the entity, with their columns named like the fields of `RecordRow`.
+    /// Must be followed by the id of the user or household that owns them.
+    fn select_sql(self) -> &'static str {
+        match self {
+            SyncEntity::Ingredient => {
+                r#"
+                SELECT rowid AS row_id, uuid, name
+                FROM Ingredient
+                WHERE household_id = "#
+            }
+            SyncEntity::Dish => {
+                r#"
+                SELECT rowid AS row_id, uuid, name, prep_date, total_weight, is_finished
+                FROM Dish
+                WHERE deletion_date IS NULL AND household_id = "#
+            }
+            SyncEntity::Meal => {
+                r#"
+                SELECT rowid AS row_id, uuid, eat_date, duration, description
+                FROM Meal
+                WHERE deletion_date IS NULL AND user_id = "#
+            }
+            SyncEntity::DishIngredient => {
+                r#"
+                SELECT
+                    DishIngredient.rowid AS row_id,
+                    DishIngredient.uuid,
+                    Dish.uuid AS dish_uuid,
+                    Ingredient.uuid AS ingredient_uuid,
+                    DishIngredient.weight
+                FROM DishIngredient
+                    JOIN Dish ON Dish.id = DishIngredient.dish_id
+                    JOIN Ingredient ON Ingredient.id = DishIngredient.ingredient_id
+                WHERE Dish.deletion_date IS NULL AND Dish.household_id = "#
+            }
+            SyncEntity::MealDish => {
+                r#"
+                SELECT
+                    MealDish.rowid AS row_id,
+                    MealDish.uuid,
+                    Meal.uuid AS meal_uuid,
+                    Dish.uuid AS dish_uuid,
+                    MealDish.weight
+                FROM MealDish
+                    JOIN Meal ON Meal.id = MealDish.meal_id
+                    JOIN Dish ON Dish.id = MealDish.dish_id
+                WHERE Meal.deletion_date IS NULL AND Meal.user_id = "#
+            }
+            SyncEntity::MealIngredient => {
+                r#"
+                SELECT
+                    MealIngredient.rowid AS row_id,
+                    MealIngredient.uuid,
+                    Meal.uuid AS meal_uuid,
+                    Ingredient.uuid AS ingredient_uuid,
+                    MealIngredient.weight
+                FROM MealIngredient
+                    JOIN Meal ON Meal.id = MealIngredient.meal_id
+                    JOIN Ingredient ON Ingredient.id = MealIngredient.ingredient_id
+                WHERE Meal.deletion_date IS NULL AND Meal.user_id = "#
+            }
+        }
+    }
+
+    /// The id the rows of the entity are scoped by: the user for meals, the household otherwise.
+    fn scope_id(self, user: &AuthUser) -> i64 {
+        match self {
+            SyncEntity::Meal | SyncEntity::MealDish | SyncEntity::MealIngredient => user.user_id,
+            SyncEntity::Ingredient | SyncEntity::Dish | SyncEntity::DishIngredient => {
+                user.household_id
+            }
+        }
+    }
+}
+
+/// The synced fields of a row. Links between rows reference them by uuid.
+#[derive(Deserialize, Serialize, JsonSchema, Clone, PartialEq, Debug)]
+#[serde(tag = "entity")]
+pub enum SyncRecord {
+    Ingredient {
+        name: String,
+    },
+    Dish {
+        name: Option<String>,
+        prep_date: Option<i64>,
+        total_weight: i64,
+        is_finished: i64,
+    },
+    Meal {
+        eat_date: Option<i64>,
+        duration: Option<i64>,
+        description: Option<String>,
+    },
+    DishIngredient {
+        dish_uuid: String,
+        ingredient_uuid: String,
+        weight: i64,
+    },
+    MealDish {
+        meal_uuid: String,
+        dish_uuid: String,
+        weight: i64,
+    },
+    MealIngredient {
+        meal_uuid: String,
+        ingredient_uuid: String,
+        weight: i64,
+    },
+}
+
+impl SyncRecord {
+    pub fn entity(&self) -> SyncEntity {
+        match self {
+            SyncRecord::Ingredient { .. } => SyncEntity::Ingredient,
+            SyncRecord::Dish { .. } => SyncEntity::Dish,
+            SyncRecord::Meal { .. } => SyncEntity::Meal,
+            SyncRecord::DishIngredient { .. } => SyncEntity::DishIngredient,
+            SyncRecord::MealDish { .. } => SyncEntity::MealDish,
+            SyncRecord::MealIngredient { .. } => SyncEntity::MealIngredient,
+        }
+    }
+}
+
+#[derive(FromRow)]
+struct RecordRow {
+    row_id: i64,
+    uuid: String,
+    #[sqlx(default)]
+    name: Option<String>,
+    #[sqlx(default)]
+    prep_date: Option<i64>,
+    #[sqlx(default)]
+    total_weight: Option<i64>,
+    #[sqlx(default)]
+    is_finished: Option<i64>,
+    #[sqlx(default)]
+    eat_date: Option<i64>,
+    #[sqlx(default)]
+    duration: Option<i64>,
+    #[sqlx(default)]
+    description: Option<String>,
+    #[sqlx(default)]
+    meal_uuid: Option<String>,
+    #[sqlx(default)]
+    dish_uuid: Option<String>,
+    #[sqlx(default)]
+    ingredient_uuid: Option<String>,
+    #[sqlx(default)]
+    weight: Option<i64>,
+}
+
+impl RecordRow {
+    fn into_record(self, entity: SyncEntity) -> SyncRecord {
+        match entity {
+            SyncEntity::Ingredient => SyncRecord::Ingredient {
+                name: self.name.unwrap_or_default(),
+            },
+            SyncEntity::Dish => SyncRecord::Dish {
+                name: self.name,
+                prep_date: self.prep_date,
+                total_weight: self.total_weight.unwrap_or_default(),
+                is_finished: self.is_finished.unwrap_or_default(),
+            },
+            SyncEntity::Meal => SyncRecord::Meal {
+                eat_date: self.eat_date,
+                duration: self.duration,
+                description: self.description,
+            },
+            SyncEntity::DishIngredient => SyncRecord::DishIngredient {
+                dish_uuid: self.dish_uuid.unwrap_or_default(),
+                ingredient_uuid: self.ingredient_uuid.unwrap_or_default(),
+                weight: self.weight.unwrap_or_default(),
+            },
+            SyncEntity::MealDish => SyncRecord::MealDish {
+                meal_uuid: self.meal_uuid.unwrap_or_default(),
+                dish_uuid: self.dish_uuid.unwrap_or_default(),
+                weight: self.weight.unwrap_or_default(),
+            },
+            SyncEntity::MealIngredient => SyncRecord::MealIngredient {
+                meal_uuid: self.meal_uuid.unwrap_or_default(),
+                ingredient_uuid: self.ingredient_uuid.unwrap_or_default(),
+                weight: self.weight.unwrap_or_default(),
+            },
+        }
+    }
+}
+
+async fn fetch_rows(
+    connection: &mut SqliteConnection,
+    user: &AuthUser,
+    entity: SyncEntity,
+    uuids: &[String],
+) -> anyhow::Result<Vec<RecordRow>> {
+    if uuids.is_empty() {
+        return Ok(vec![]);
+    }
+
+    Ok(sqlx::QueryBuilder::<Sqlite>::new(entity.select_sql())
+        .push_bind(entity.scope_id(user))
+        .push(format!(" AND {}.uuid IN ", entity.name()))
+        .push_tuples(uuids.iter(), |mut p, uuid| {
+            p.push_bind(uuid.clone());
+        })
+        .build_query_as::<RecordRow>()
+        .fetch_all(&mut *connection)
+        .await?)
+}
+
+/// A synced row, with the id the server knows it by.
+pub struct FetchedRecord {
+    /// Id of the row. Only meaningful for ingredients, dishes and meals, since the link tables
+    /// have no ids of their own
+    pub id: i64,
+    pub record: SyncRecord,
+}
+
+/// Fetches the records of `entity` with the given uuids that the user can see, by uuid.
+pub async fn fetch_records(
+    connection: &mut SqliteConnection,
+    user: &AuthUser,
+    entity: SyncEntity,
+    uuids: &[String],
+) -> anyhow::Result<HashMap<String, FetchedRecord>> {
+    Ok(fetch_rows(connection, user, entity, uuids)
+        .await?
+        .into_iter()
+        .map(|row| {
+            (
+                row.uuid.clone(),
+                FetchedRecord {
+                    id: row.row_id,
+                    record: row.into_record(entity),
+                },
+            )
+        })
+        .collect())
+}
+
+pub async fn fetch_record(
+    connection: &mut SqliteConnection,
+    user: &AuthUser,
+    entity: SyncEntity,
+    uuid: &str,
+) -> anyhow::Result<Option<FetchedRecord>> {
+    Ok(fetch_records(connection, user, entity, &[uuid.to_string()])
+        .await?
+        .remove(uuid))
+}
+
+#[derive(Error, Debug)]
+pub enum SyncRejection {
+    #[error("\"{0}\" is not a valid lowercase UUID")]
+    InvalidUuid(String),
+    #[error("The uuid \"{0}\" is already used by something else")]
+    UuidTaken(String),
+    #[error("Could not find {} with uuid \"{1}\"", .0.name())]
+    UnknownReference(SyncEntity, String),
+    #[error("Weight {0} is invalid. It must be larger than 0")]
+    InvalidWeight(i64),
+    #[error("The ingredient name should not be empty")]
+    NameIsEmpty,
+    #[error("The same rows are already linked, by the link with uuid \"{0}\"")]
+    DuplicateLink(String),
+    #[error("The {} is still used by other rows, which must be deleted first", .0.name())]
+    InUse(SyncEntity),
+}
+
+pub enum SyncOutcome {
+    Applied,
+    /// The row changed on the server after the client's cursor. Holds the server's version of
+    /// the row, or nothing if it was deleted
+    Conflict(Option<SyncRecord>),
+    Rejected(SyncRejection),
+}
+
+fn is_valid_uuid(uuid: &str) -> bool {
+    uuid.len() == 36
+        && uuid.char_indices().all(|(index, c)| match index {
+            8 | 13 | 18 | 23 => c == '-',
+            _ => c.is_ascii_digit() || ('a'..='f').contains(&c),
+        })
+}
+
+/// Id of the latest change to the row with `uuid`, if it ever changed.
+async fn latest_change(
+    connection: &mut SqliteConnection,
+    user: &AuthUser,
+    entity: SyncEntity,
+    uuid: &str,
+) -> anyhow::Result<Option<i64>> {
+    let entity = entity.name();
+    Ok(sqlx::query_scalar!(
+        r#"
+        SELECT MAX(id) AS "cursor?: i64"
+        FROM SyncChange
+        WHERE household_id = ? AND entity = ? AND entity_uuid = ?"#,
+        user.household_id,
+        entity,
+        uuid
+    )
+    .fetch_one(&mut *connection)
+    .await?)
+}
+
+async fn is_uuid_taken(
+    connection: &mut SqliteConnection,
+    entity: SyncEntity,
+    uuid: &str,
+) -> anyhow::Result<bool> {
+    Ok(sqlx::query_scalar::<_, i64>(&format!(
+        "SELECT COUNT(*) FROM {} WHERE uuid = ?",
+        entity.name()
+    ))
+    .bind(uuid)
+    .fetch_one(&mut *connection)
+    .await?
+        > 0)
+}
+
+enum Prepared {
+    /// The change can be applied. Holds the row, if it exists
+    Ready(Option<FetchedRecord>),
+    Done(SyncOutcome),
+}
+
+/// Finds the row a change is about, and detects conflicts with changes made after
+/// `base_cursor`.
+async fn prepare(
+    connection: &mut SqliteConnection,
+    user: &AuthUser,
+    entity: SyncEntity,
+    uuid: &str,
+    base_cursor: i64,
+) -> anyhow::Result<Prepared> {
+    if !is_valid_uuid(uuid) {
+        return Ok(Prepared::Done(SyncOutcome::Rejected(
+            SyncRejection::InvalidUuid(uuid.to_string()),
+        )));
+    }
+
+    let existing = fetch_record(connection, user, entity, uuid).await?;
+    if existing.is_none() && is_uuid_taken(connection, entity, uuid).await? {
+        return Ok(Prepared::Done(SyncOutcome::Rejected(
+            SyncRejection::UuidTaken(uuid.to_string()),
+        )));
+    }
+
+    let latest = latest_change(connection, user, entity, uuid).await?;
+    if latest.is_some_and(|cursor| cursor > base_cursor) {
+        return Ok(Prepared::Done(SyncOutcome::Conflict(
+            existing.map(|e| e.record),
+        )));
+    }
+
+    Ok(Prepared::Ready(existing))
+}
+
+/// Ids of the two rows a link connects, and the columns they go in.
+struct LinkEnds<'a> {
+    owner: (SyncEntity, &'static str, &'a str),
+    component: (SyncEntity, &'static str, &'a str),
+}
+
+async fn upsert_link(
+    connection: &mut SqliteConnection,
+    user: &AuthUser,
+    entity: SyncEntity,
+    uuid: &str,
+    existing: Option<FetchedRecord>,
+    LinkEnds { owner, component }: LinkEnds<'_>,
+    weight: i64,
+) -> anyhow::Result<SyncOutcome> {
+    if weight <= 0 {
+        return Ok(SyncOutcome::Rejected(SyncRejection::InvalidWeight(weight)));
+    }
+
+    let mut ids = vec![];
+    for (end_entity, _, end_uuid) in [owner, component] {
+        match fetch_record(connection, user, end_entity, end_uuid).await? {
+            Some(end) => ids.push(end.id),
+            None => {
+                return Ok(SyncOutcome::Rejected(SyncRejection::UnknownReference(
+                    end_entity,
+                    end_uuid.to_string(),
+                )))
+            }
+        }
+    }
+    let (table, owner_column, component_column) = (entity.name(), owner.1, component.1);
+
+    let duplicate = sqlx::query_scalar::<_, String>(&format!(
+        "SELECT uuid FROM {table} WHERE {owner_column} = ? AND {component_column} = ? AND uuid != ?"
+    ))
+    .bind(ids[0])
+    .bind(ids[1])
+    .bind(uuid)
+    .fetch_optional(&mut *connection)
+    .await?;
+    if let Some(duplicate) = duplicate {
+        return Ok(SyncOutcome::Rejected(SyncRejection::DuplicateLink(
+            duplicate,
+        )));
+    }
+
+    let sql = match existing {
+        Some(_) => format!(
+            "UPDATE {table} SET {owner_column} = ?, {component_column} = ?, weight = ? WHERE rowid = ?"
+        ),
+        None => format!(
+            "INSERT INTO {table} ({owner_column}, {component_column}, weight, uuid) VALUES (?, ?, ?, ?)"
+        ),
+    };
+    let query = sqlx::query(&sql).bind(ids[0]).bind(ids[1]).bind(weight);
+    match existing {
+        Some(existing) => query.bind(existing.id),
+        None => query.bind(uuid),
+    }
+    .execute(&mut *connection)
+    .await?;
+
+    Ok(SyncOutcome::Applied)
+}
+
+/// Creates or updates the row with `uuid`, unless it changed on the server after `base_cursor`.
+/// Uploading a record identical to the server's is never a conflict, so retries are safe.
+pub async fn apply_upsert(
+    connection: &mut SqliteConnection,
+    user: &AuthUser,
+    uuid: &str,
+    base_cursor: i64,
+    record: SyncRecord,
+) -> anyhow::Result<SyncOutcome> {
+    let entity = record.entity();
+    let existing = match prepare(connection, user, entity, uuid, base_cursor).await? {
+        Prepared::Ready(existing) => existing,
+        Prepared::Done(SyncOutcome::Conflict(Some(server))) if server == record => {
+            return Ok(SyncOutcome::Applied)
+        }
+        Prepared::Done(outcome) => return Ok(outcome),
+    };
+    let existing_id = existing.as_ref().map(|e| e.id);
+
+    match record {
+        SyncRecord::Ingredient { name } => {
+            let name = name.trim();
+            if name.is_empty() {
+                return Ok(SyncOutcome::Rejected(SyncRejection::NameIsEmpty));
+            }
+            match existing_id {
+                Some(id) => sqlx::query("UPDATE Ingredient SET name = ? WHERE id = ?")
+                    .bind(name)
+                    .bind(id),
+                None => sqlx::query(
+                    "INSERT INTO Ingredient (uuid, household_id, name) VALUES (?, ?, ?)",
+                )
+                .bind(uuid)
+                .bind(user.household_id)
+                .bind(name),
+            }
+            .execute(&mut *connection)
+            .await?;
+        }
+        SyncRecord::Dish {
+            name,
+            prep_date,
+            total_weight,
+            is_finished,
+        } => {
+            match existing_id {
+                Some(id) => sqlx::query(
+                    r#"
+                    UPDATE Dish SET name = ?, prep_date = ?, total_weight = ?, is_finished = ?
+                    WHERE id = ?"#,
+                )
+                .bind(name)
+                .bind(prep_date)
+                .bind(total_weight)
+                .bind(is_finished)
+                .bind(id),
+                None => sqlx::query(
+                    r#"
+                    INSERT INTO Dish (uuid, household_id, name, prep_date, total_weight, is_finished)
+                    VALUES (?, ?, ?, ?, ?, ?)"#,
+                )
+                .bind(uuid)
+                .bind(user.household_id)
+                .bind(name)
+                .bind(prep_date)
+                .bind(total_weight)
+                .bind(is_finished),
+            }
+            .execute(&mut *connection)
+            .await?;
+        }
+        SyncRecord::Meal {
+            eat_date,
+            duration,
+            description,
+        } => {
+            match existing_id {
+                Some(id) => sqlx::query(
+                    "UPDATE Meal SET eat_date = ?, duration = ?, description = ? WHERE id = ?",
+                )
+                .bind(eat_date)
+                .bind(duration)
+                .bind(description)
+                .bind(id),
+                None => sqlx::query(
+                    r#"
+                    INSERT INTO Meal (uuid, user_id, eat_date, duration, description)
+                    VALUES (?, ?, ?, ?, ?)"#,
+                )
+                .bind(uuid)
+                .bind(user.user_id)
+                .bind(eat_date)
+                .bind(duration)
+                .bind(description),
+            }
+            .execute(&mut *connection)
+            .await?;
+        }
+        SyncRecord::DishIngredient {
+            dish_uuid,
+            ingredient_uuid,
+            weight,
+        } => {
+            let ends = LinkEnds {
+                owner: (SyncEntity::Dish, "dish_id", &dish_uuid),
+                component: (SyncEntity::Ingredient, "ingredient_id", &ingredient_uuid),
+            };
+            return upsert_link(connection, user, entity, uuid, existing, ends, weight).await;
+        }
+        SyncRecord::MealDish {
+            meal_uuid,
+            dish_uuid,
+            weight,
+        } => {
+            let ends = LinkEnds {
+                owner: (SyncEntity::Meal, "meal_id", &meal_uuid),
+                component: (SyncEntity::Dish, "dish_id", &dish_uuid),
+            };
+            return upsert_link(connection, user, entity, uuid, existing, ends, weight).await;
+        }
+        SyncRecord::MealIngredient {
+            meal_uuid,
+            ingredient_uuid,
+            weight,
+        } => {
+            let ends = LinkEnds {
+                owner: (SyncEntity::Meal, "meal_id", &meal_uuid),
+                component: (SyncEntity::Ingredient, "ingredient_id", &ingredient_uuid),
+            };
+            return upsert_link(connection, user, entity, uuid, existing, ends, weight).await;
+        }
+    }
+
+    Ok(SyncOutcome::Applied)
+}
+
+/// Deletes the row with `uuid`, unless it changed on the server after `base_cursor`. Deleting a
+/// row that doesn't exist anymore succeeds.
+pub async fn apply_delete(
+    connection: &mut SqliteConnection,
+    user: &AuthUser,
+    uuid: &str,
+    base_cursor: i64,
+    entity: SyncEntity,
+) -> anyhow::Result<SyncOutcome> {
+    let existing = match prepare(connection, user, entity, uuid, base_cursor).await? {
+        Prepared::Ready(Some(existing)) => existing,
+        Prepared::Ready(None) | Prepared::Done(SyncOutcome::Conflict(None)) => {
+            return Ok(SyncOutcome::Applied)
+        }
+        Prepared::Done(outcome) => return Ok(outcome),
+    };
+    let id = existing.id;
+
+    let statements: &[&str] = match entity {
+        SyncEntity::Meal => &["UPDATE Meal SET deletion_date = unixepoch() * 1000 WHERE id = ?"],
+        SyncEntity::Dish => {
+            if is_referenced(connection, AuditEntity::Dish, id).await? {
+                return Ok(SyncOutcome::Rejected(SyncRejection::InUse(entity)));
+            }
+            &["UPDATE Dish SET deletion_date = unixepoch() * 1000 WHERE id = ?"]
+        }
+        SyncEntity::Ingredient => {
+            if is_referenced(connection, AuditEntity::Ingredient, id).await? {
+                return Ok(SyncOutcome::Rejected(SyncRejection::InUse(entity)));
+            }
+            &[
+                "DELETE FROM IngredientProperties WHERE ingredient_id = ?",
+                "DELETE FROM IngredientAlias WHERE ingredient_id = ?",
+                "DELETE FROM IngredientName WHERE ingredient_id = ?",
+                "DELETE FROM IngredientCategory WHERE ingredient_id = ?",
+                "DELETE FROM IngredientTag WHERE ingredient_id = ?",
+                "DELETE FROM IngredientTrackable WHERE ingredient_id = ?",
+                "UPDATE Ingredient SET generic_id = NULL WHERE generic_id = ?",
+                "DELETE FROM Ingredient WHERE id = ?",
+            ]
+        }
+        SyncEntity::DishIngredient => &["DELETE FROM DishIngredient WHERE rowid = ?"],
+        SyncEntity::MealDish => &["DELETE FROM MealDish WHERE rowid = ?"],
+        SyncEntity::MealIngredient => &["DELETE FROM MealIngredient WHERE rowid = ?"],
+    };
+
+    for statement in statements {
+        sqlx::query(statement)
+            .bind(id)
+            .execute(&mut *connection)
+            .await?;
+    }
+
+    Ok(SyncOutcome::Applied)
+}
@@ -0,0 +1,175 @@
+use std::collections::{HashMap, HashSet};
+
+use serde::Deserialize;
+use sqlx::{Executor, Sqlite, SqliteConnection};
+
+/// Turns an Open Food Facts tag such as `en:brown-rices` into a name such as `Brown rices`.
+fn category_name(tag: &str) -> String {
+    let name = tag
+        .split_once(':')
+        .map_or(tag, |(_, name)| name)
+        .replace('-', " ");
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => name,
+    }
+}
+
+/// Whether `category_id` is `ancestor_id` or one of its descendants. Categories can't be moved
+/// under themselves, which would make a loop.
+pub async fn is_in_subtree<'c>(
+    executor: impl Executor<'c, Database = Sqlite>,
+    category_id: i64,
+    ancestor_id: i64,
+) -> anyhow::Result<bool> {
+    Ok(sqlx::query_scalar!(
+        r#"
+        WITH RECURSIVE Ancestor(id) AS (
+            SELECT ?1
+            UNION
+            SELECT Category.parent_id
+            FROM Ancestor JOIN Category ON Category.id = Ancestor.id
+            WHERE Category.parent_id IS NOT NULL
+        )
+        SELECT EXISTS (SELECT 1 FROM Ancestor WHERE id = ?2) AS "is_in_subtree!: bool""#,
+        category_id,
+        ancestor_id
+    )
+    .fetch_one(executor)
+    .await?)
+}
+
+#[derive(Deserialize)]
+struct FoodFactsCategories {
+    product: FoodFactsProduct,
+}
+
+#[derive(Deserialize)]
+struct FoodFactsProduct {
+    #[serde(default)]
+    categories_hierarchy: Vec<String>,
+}
+
+/// Replaces the categories the ingredient got from Open Food Facts with the last one of the
+/// `categories_hierarchy` of its properties. Each category of the hierarchy is created in the
+/// household if needed, as a child of the one before it, unless it already has a parent.
+pub async fn seed_categories(
+    connection: &mut SqliteConnection,
+    ingredient_id: i64,
+) -> anyhow::Result<()> {
+    let properties = sqlx::query!(
+        r#"
+        SELECT Ingredient.household_id, IngredientProperties.open_food_facts_json AS "json!: String"
+        FROM IngredientProperties JOIN Ingredient ON Ingredient.id = IngredientProperties.ingredient_id
+        WHERE IngredientProperties.ingredient_id = ?"#,
+        ingredient_id
+    )
+    .fetch_one(&mut *connection)
+    .await?;
+    let hierarchy = serde_json::from_str::<FoodFactsCategories>(&properties.json)
+        .map(|categories| categories.product.categories_hierarchy)
+        .unwrap_or_default();
+
+    let mut parent_id = None;
+    for tag in &hierarchy {
+        let name = category_name(tag);
+        sqlx::query!(
+            r#"
+            INSERT INTO Category (household_id, parent_id, name, open_food_facts_tag)
+            VALUES (?, ?, ?, ?)
+            ON CONFLICT DO NOTHING"#,
+            properties.household_id,
+            parent_id,
+            name,
+            tag
+        )
+        .execute(&mut *connection)
+        .await?;
+        let category = sqlx::query!(
+            "SELECT id, parent_id FROM Category WHERE household_id = ? AND open_food_facts_tag = ?",
+            properties.household_id,
+            tag
+        )
+        .fetch_one(&mut *connection)
+        .await?;
+
+        if let (None, Some(parent_id)) = (category.parent_id, parent_id) {
+            if !is_in_subtree(&mut *connection, parent_id, category.id).await? {
+                sqlx::query!(
+                    "UPDATE Category SET parent_id = ? WHERE id = ?",
+                    parent_id,
+                    category.id
+                )
+                .execute(&mut *connection)
+                .await?;
+            }
+        }
+        parent_id = Some(category.id);
+    }
+
+    sqlx::query!(
+        "DELETE FROM IngredientCategory WHERE ingredient_id = ? AND source = 'OpenFoodFacts'",
+        ingredient_id
+    )
+    .execute(&mut *connection)
+    .await?;
+    if let Some(category_id) = parent_id {
+        sqlx::query!(
+            r#"
+            INSERT INTO IngredientCategory (ingredient_id, category_id, source)
+            VALUES (?, ?, 'OpenFoodFacts')
+            ON CONFLICT DO NOTHING"#,
+            ingredient_id,
+            category_id
+        )
+        .execute(&mut *connection)
+        .await?;
+    }
+
+    Ok(())
+}
+
+/// Fetches every category each ingredient in `ingredient_ids` is in, including the ancestors of
+/// the ones it was put in. Variants are in the categories of their generic ingredient too, so
+/// reports roll up to it. Ingredients without categories are left out of the result.
+pub async fn fetch_ingredient_categories(
+    connection: &sqlx::Pool<Sqlite>,
+    ingredient_ids: &[i64],
+) -> anyhow::Result<HashMap<i64, HashSet<i64>>> {
+    if ingredient_ids.is_empty() {
+        return Ok(HashMap::new());
+    }
+
+    Ok(sqlx::QueryBuilder::<Sqlite>::new(
+        r#"
+        WITH RECURSIVE IngredientAncestor(ingredient_id, category_id) AS (
+            SELECT Ingredient.id, IngredientCategory.category_id
+            FROM Ingredient
+                JOIN IngredientCategory
+                    ON IngredientCategory.ingredient_id IN (Ingredient.id, Ingredient.generic_id)
+            WHERE Ingredient.id IN "#,
+    )
+    .push_tuples(ingredient_ids.iter(), |mut p, id| {
+        p.push_bind(*id);
+    })
+    .push(
+        r#"
+            UNION
+            SELECT IngredientAncestor.ingredient_id, Category.parent_id
+            FROM IngredientAncestor JOIN Category ON Category.id = IngredientAncestor.category_id
+            WHERE Category.parent_id IS NOT NULL
+        )
+        SELECT ingredient_id, category_id FROM IngredientAncestor"#,
+    )
+    .build_query_as::<(i64, i64)>()
+    .fetch_all(connection)
+    .await?
+    .into_iter()
+    .fold(HashMap::new(), |mut dict, (ingredient_id, category_id)| {
+        dict.entry(ingredient_id)
+            .or_insert_with(HashSet::new)
+            .insert(category_id);
+        dict
+    }))
+}
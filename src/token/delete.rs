@@ -0,0 +1,43 @@
+use axum::extract::{Path, State};
+use schemars::JsonSchema;
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::{
+    server::{ServerResponse, ServerResponseResult},
+    session::AuthUser,
+    state::AppState,
+};
+
+#[derive(Deserialize, JsonSchema)]
+pub struct TokenId {
+    token_id: i64,
+}
+
+#[derive(Error, Debug)]
+enum DeleteTokenError {
+    #[error("Could not find token with id \"{0}\"")]
+    TokenNotFound(i64),
+}
+
+/// Revokes the token. Requests made with it are rejected from now on.
+pub async fn delete_token(
+    State(AppState { connection, .. }): State<AppState>,
+    AuthUser { user_id, .. }: AuthUser,
+    Path(TokenId { token_id }): Path<TokenId>,
+) -> ServerResponseResult<bool> {
+    let deleted = sqlx::query!(
+        "DELETE FROM ApiToken WHERE id = ? AND user_id = ?",
+        token_id,
+        user_id
+    )
+    .execute(&connection)
+    .await?
+    .rows_affected();
+
+    if deleted == 0 {
+        return Err(DeleteTokenError::TokenNotFound(token_id))?;
+    }
+
+    Ok(ServerResponse::success(true).json())
+}
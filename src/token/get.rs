@@ -0,0 +1,32 @@
+use axum::extract::State;
+
+use crate::{
+    models::ApiToken,
+    server::{ServerResponse, ServerResponseResult},
+    session::AuthUser,
+    state::AppState,
+};
+
+pub async fn list_tokens(
+    State(AppState { connection, .. }): State<AppState>,
+    AuthUser { user_id, .. }: AuthUser,
+) -> ServerResponseResult<Vec<ApiToken>> {
+    let tokens = sqlx::query_as!(
+        ApiToken,
+        r#"
+        SELECT
+            id,
+            creation_date,
+            name,
+            scope,
+            last_used_date
+        FROM ApiToken
+        WHERE user_id = ?
+        ORDER BY creation_date DESC;"#,
+        user_id
+    )
+    .fetch_all(&connection)
+    .await?;
+
+    Ok(ServerResponse::success(tokens).json())
+}
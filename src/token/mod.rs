@@ -0,0 +1,17 @@
+use aide::axum::{
+    routing::{delete, post},
+    ApiRouter,
+};
+
+use crate::state::AppState;
+
+mod delete;
+mod get;
+mod post;
+
+pub fn route(state: AppState) -> ApiRouter {
+    ApiRouter::new()
+        .api_route("/", post(post::post_token).get(get::list_tokens))
+        .api_route("/:token_id", delete(delete::delete_token))
+        .with_state(state)
+}
@@ -0,0 +1,65 @@
+use axum::{extract::State, http::StatusCode, Json};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{
+    models::ApiToken,
+    server::{ServerResponse, ServerResponseResult},
+    session::{generate_token, hash_token, AuthUser, TokenScope, API_TOKEN_PREFIX},
+    state::AppState,
+};
+
+#[derive(Deserialize, JsonSchema)]
+pub struct PostTokenBody {
+    /// What the token is used for, such as "Kitchen scale"
+    name: String,
+    scope: TokenScope,
+}
+
+#[derive(Serialize, JsonSchema)]
+pub struct PostTokenResponse {
+    /// Must be sent in the `Authorization: Bearer <token>` header. It is only shown once
+    token: String,
+    api_token: ApiToken,
+}
+
+#[derive(Error, Debug)]
+enum PostTokenError {
+    #[error("The token name should not be empty")]
+    NameIsEmpty,
+}
+
+pub async fn post_token(
+    State(AppState { connection, .. }): State<AppState>,
+    AuthUser { user_id, .. }: AuthUser,
+    Json(PostTokenBody { name, scope }): Json<PostTokenBody>,
+) -> ServerResponseResult<PostTokenResponse> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(PostTokenError::NameIsEmpty)?;
+    }
+
+    let token = format!("{API_TOKEN_PREFIX}{}", generate_token());
+    let token_hash = hash_token(&token);
+    let scope = scope.name();
+
+    let api_token = sqlx::query_as!(
+        ApiToken,
+        r#"
+        INSERT INTO ApiToken (user_id, name, scope, token_hash)
+        VALUES (?, ?, ?, ?)
+        RETURNING id, creation_date, name, scope, last_used_date;"#,
+        user_id,
+        name,
+        scope,
+        token_hash
+    )
+    .fetch_one(&connection)
+    .await?;
+
+    Ok(
+        ServerResponse::success_code(PostTokenResponse { token, api_token }, StatusCode::CREATED)
+            .json(),
+    )
+}
@@ -0,0 +1,69 @@
+use axum::extract::{Path, State};
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+use crate::{
+    audit::{record_change, snapshot, AuditEntity},
+    server::{ServerResponse, ServerResponseResult},
+    session::AuthUser,
+    state::AppState,
+};
+
+#[derive(Deserialize, JsonSchema)]
+pub struct TrackableId {
+    trackable_id: i64,
+}
+
+/// Deletes the trackable along with the amounts the ingredients and supplements had of it. The
+/// change to the ingredients is recorded in their history.
+pub async fn delete_trackable(
+    State(AppState { connection, .. }): State<AppState>,
+    user: AuthUser,
+    Path(TrackableId { trackable_id }): Path<TrackableId>,
+) -> ServerResponseResult<bool> {
+    user.check_trackable(&connection, trackable_id).await?;
+
+    let mut transaction = connection.begin().await?;
+    let ingredient_ids = sqlx::query_scalar!(
+        "SELECT ingredient_id FROM IngredientTrackable WHERE trackable_id = ?",
+        trackable_id
+    )
+    .fetch_all(&mut *transaction)
+    .await?;
+    let mut ingredients_before = vec![];
+    for ingredient_id in &ingredient_ids {
+        ingredients_before
+            .push(snapshot(&mut *transaction, AuditEntity::Ingredient, *ingredient_id).await?);
+    }
+
+    sqlx::query!(
+        "DELETE FROM IngredientTrackable WHERE trackable_id = ?",
+        trackable_id
+    )
+    .execute(&mut *transaction)
+    .await?;
+    sqlx::query!(
+        "DELETE FROM SupplementTrackable WHERE trackable_id = ?",
+        trackable_id
+    )
+    .execute(&mut *transaction)
+    .await?;
+    sqlx::query!("DELETE FROM Trackable WHERE id = ?", trackable_id)
+        .execute(&mut *transaction)
+        .await?;
+
+    for (ingredient_id, before) in ingredient_ids.into_iter().zip(ingredients_before) {
+        record_change(
+            &mut *transaction,
+            &user,
+            AuditEntity::Ingredient,
+            ingredient_id,
+            before,
+        )
+        .await?;
+    }
+
+    transaction.commit().await?;
+
+    Ok(ServerResponse::success(true).json())
+}
@@ -0,0 +1,15 @@
+use aide::axum::{routing::post, ApiRouter};
+
+use crate::state::AppState;
+
+mod delete;
+mod post;
+
+pub fn route(state: AppState) -> ApiRouter {
+    ApiRouter::new()
+        .api_route(
+            "/",
+            post(post::post_edit_trackable).delete(delete::delete_trackable),
+        )
+        .with_state(state)
+}
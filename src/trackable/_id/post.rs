@@ -0,0 +1,86 @@
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+use schemars::JsonSchema;
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::{
+    models::Trackable,
+    server::{ServerResponse, ServerResponseResult},
+    session::AuthUser,
+    state::AppState,
+};
+
+#[derive(Deserialize, JsonSchema)]
+pub struct TrackableId {
+    trackable_id: i64,
+}
+
+#[derive(Deserialize, JsonSchema)]
+pub struct PostEditTrackableBody {
+    name: String,
+    unit: String,
+    /// No limit if not provided
+    daily_limit: Option<f64>,
+}
+
+#[derive(Error, Debug)]
+enum PostEditTrackableError {
+    #[error("The name of a trackable should not be empty")]
+    EmptyName,
+    #[error("The daily limit of a trackable should not be negative")]
+    NegativeLimit,
+    #[error("There is already a trackable named \"{0}\"")]
+    TrackableExists(String),
+}
+
+/// Renames the trackable, or changes its unit or daily limit. The amounts of the ingredients are
+/// kept as they are, so they should be updated when the unit changes.
+pub async fn post_edit_trackable(
+    State(AppState { connection, .. }): State<AppState>,
+    user @ AuthUser { household_id, .. }: AuthUser,
+    Path(TrackableId { trackable_id }): Path<TrackableId>,
+    Json(PostEditTrackableBody {
+        name,
+        unit,
+        daily_limit,
+    }): Json<PostEditTrackableBody>,
+) -> ServerResponseResult<Trackable> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(PostEditTrackableError::EmptyName)?;
+    }
+    if daily_limit.is_some_and(|limit| limit < 0.0) {
+        return Err(PostEditTrackableError::NegativeLimit)?;
+    }
+    user.check_trackable(&connection, trackable_id).await?;
+    let existing = sqlx::query_scalar!(
+        "SELECT id FROM Trackable WHERE household_id = ? AND name = ? AND id != ?",
+        household_id,
+        name,
+        trackable_id
+    )
+    .fetch_optional(&connection)
+    .await?;
+    if existing.is_some() {
+        return Err(PostEditTrackableError::TrackableExists(name.to_string()))?;
+    }
+
+    let unit = unit.trim();
+    let trackable = sqlx::query_as!(
+        Trackable,
+        r#"
+        UPDATE Trackable SET name = ?, unit = ?, daily_limit = ? WHERE id = ?
+        RETURNING id as "id!", creation_date, name, unit, daily_limit;"#,
+        name,
+        unit,
+        daily_limit,
+        trackable_id
+    )
+    .fetch_one(&connection)
+    .await?;
+
+    Ok(ServerResponse::success(trackable).json())
+}
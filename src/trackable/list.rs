@@ -0,0 +1,48 @@
+use axum::extract::State;
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use crate::{
+    server::{ServerResponse, ServerResponseResult},
+    session::AuthUser,
+    state::AppState,
+};
+
+#[derive(Serialize, JsonSchema)]
+pub struct ListedTrackable {
+    id: i64,
+    creation_date: i64,
+    name: String,
+    unit: String,
+    daily_limit: Option<f64>,
+    /// How many ingredients have an amount of this trackable, not counting variants inheriting it
+    ingredient_count: i64,
+}
+
+/// Every trackable of the household.
+pub async fn list_trackables(
+    State(AppState { connection, .. }): State<AppState>,
+    AuthUser { household_id, .. }: AuthUser,
+) -> ServerResponseResult<Vec<ListedTrackable>> {
+    let trackables = sqlx::query_as!(
+        ListedTrackable,
+        r#"
+        SELECT
+            id,
+            creation_date,
+            name,
+            unit,
+            daily_limit,
+            (
+                SELECT COUNT(*) FROM IngredientTrackable WHERE trackable_id = Trackable.id
+            ) AS "ingredient_count!: i64"
+        FROM Trackable
+        WHERE household_id = ?
+        ORDER BY name;"#,
+        household_id
+    )
+    .fetch_all(&connection)
+    .await?;
+
+    Ok(ServerResponse::success(trackables).json())
+}
@@ -0,0 +1,77 @@
+use axum::{extract::State, Json};
+use schemars::JsonSchema;
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::{
+    models::Trackable,
+    server::{ServerResponse, ServerResponseResult},
+    session::AuthUser,
+    state::AppState,
+};
+
+#[derive(Deserialize, JsonSchema)]
+pub struct PostTrackableBody {
+    /// Such as `Caffeine` or `FODMAP points`
+    name: String,
+    /// Unit the amounts are in, such as `mg` or `units`
+    unit: String,
+    /// Most that should be had in a day. No limit if not provided
+    daily_limit: Option<f64>,
+}
+
+#[derive(Error, Debug)]
+enum PostTrackableError {
+    #[error("The name of a trackable should not be empty")]
+    EmptyName,
+    #[error("The daily limit of a trackable should not be negative")]
+    NegativeLimit,
+    #[error("There is already a trackable named \"{0}\"")]
+    TrackableExists(String),
+}
+
+/// Creates something to be tracked besides the built-in nutrients. Ingredients can then be given
+/// an amount of it per 100g.
+pub async fn post_trackable(
+    State(AppState { connection, .. }): State<AppState>,
+    AuthUser { household_id, .. }: AuthUser,
+    Json(PostTrackableBody {
+        name,
+        unit,
+        daily_limit,
+    }): Json<PostTrackableBody>,
+) -> ServerResponseResult<Trackable> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(PostTrackableError::EmptyName)?;
+    }
+    if daily_limit.is_some_and(|limit| limit < 0.0) {
+        return Err(PostTrackableError::NegativeLimit)?;
+    }
+    let existing = sqlx::query_scalar!(
+        "SELECT id FROM Trackable WHERE household_id = ? AND name = ?",
+        household_id,
+        name
+    )
+    .fetch_optional(&connection)
+    .await?;
+    if existing.is_some() {
+        return Err(PostTrackableError::TrackableExists(name.to_string()))?;
+    }
+
+    let unit = unit.trim();
+    let trackable = sqlx::query_as!(
+        Trackable,
+        r#"
+        INSERT INTO Trackable (household_id, name, unit, daily_limit) VALUES (?, ?, ?, ?)
+        RETURNING id, creation_date, name, unit, daily_limit;"#,
+        household_id,
+        name,
+        unit,
+        daily_limit
+    )
+    .fetch_one(&connection)
+    .await?;
+
+    Ok(ServerResponse::success(trackable).json())
+}
@@ -0,0 +1,44 @@
+use axum::extract::State;
+
+use crate::{
+    purge::{purge_dish, purge_meal},
+    server::{ServerResponse, ServerResponseResult},
+    session::AuthUser,
+    state::AppState,
+};
+
+/// Purges every dish of the household and every meal of the user that is in the trash. Returns
+/// how many were purged.
+pub async fn delete_trash(
+    State(AppState { connection, .. }): State<AppState>,
+    AuthUser {
+        user_id,
+        household_id,
+    }: AuthUser,
+) -> ServerResponseResult<usize> {
+    let mut transaction = connection.begin().await?;
+
+    let meal_ids = sqlx::query_scalar!(
+        "SELECT id FROM Meal WHERE user_id = ? AND deletion_date IS NOT NULL",
+        user_id
+    )
+    .fetch_all(&mut *transaction)
+    .await?;
+    let dish_ids = sqlx::query_scalar!(
+        "SELECT id FROM Dish WHERE household_id = ? AND deletion_date IS NOT NULL",
+        household_id
+    )
+    .fetch_all(&mut *transaction)
+    .await?;
+
+    for meal_id in &meal_ids {
+        purge_meal(&mut transaction, *meal_id).await?;
+    }
+    for dish_id in &dish_ids {
+        purge_dish(&mut transaction, *dish_id).await?;
+    }
+
+    transaction.commit().await?;
+
+    Ok(ServerResponse::success(meal_ids.len() + dish_ids.len()).json())
+}
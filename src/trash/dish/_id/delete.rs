@@ -0,0 +1,47 @@
+use axum::extract::{Path, State};
+use schemars::JsonSchema;
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::{
+    purge::purge_dish,
+    server::{ServerResponse, ServerResponseResult},
+    session::AuthUser,
+    state::AppState,
+};
+
+#[derive(Error, Debug)]
+enum DeleteTrashedDishError {
+    #[error("Could not find dish with id \"{0}\" in the trash")]
+    DishNotInTrash(i64),
+}
+
+#[derive(Deserialize, JsonSchema)]
+pub struct DishId {
+    dish_id: i64,
+}
+
+/// Purges a dish from the trash right away, instead of waiting for the retention to pass. Meals
+/// that used it lose it.
+pub async fn delete_trashed_dish(
+    State(AppState { connection, .. }): State<AppState>,
+    AuthUser { household_id, .. }: AuthUser,
+    Path(DishId { dish_id }): Path<DishId>,
+) -> ServerResponseResult<bool> {
+    let mut transaction = connection.begin().await?;
+
+    sqlx::query_scalar!(
+        "SELECT id FROM Dish WHERE id = ? AND household_id = ? AND deletion_date IS NOT NULL",
+        dish_id,
+        household_id
+    )
+    .fetch_optional(&mut *transaction)
+    .await?
+    .ok_or(DeleteTrashedDishError::DishNotInTrash(dish_id))?;
+
+    purge_dish(&mut transaction, dish_id).await?;
+
+    transaction.commit().await?;
+
+    Ok(ServerResponse::success(true).json())
+}
@@ -0,0 +1,49 @@
+use axum::extract::{Path, State};
+use schemars::JsonSchema;
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::{
+    audit::{record_change, AuditEntity},
+    models::Dish,
+    server::{ServerResponse, ServerResponseResult},
+    session::AuthUser,
+    state::AppState,
+};
+
+#[derive(Error, Debug)]
+enum RestoreDishError {
+    #[error("Could not find dish with id \"{0}\" in the trash")]
+    DishNotInTrash(i64),
+}
+
+#[derive(Deserialize, JsonSchema)]
+pub struct DishId {
+    dish_id: i64,
+}
+
+/// Takes a dish out of the trash. Meals that used it show it again.
+pub async fn post_restore_dish(
+    State(AppState { connection, .. }): State<AppState>,
+    user @ AuthUser { household_id, .. }: AuthUser,
+    Path(DishId { dish_id }): Path<DishId>,
+) -> ServerResponseResult<Dish> {
+    let mut transaction = connection.begin().await?;
+    let dish = sqlx::query_as!(
+        Dish,
+        r#"
+        UPDATE Dish SET deletion_date = NULL
+        WHERE id = ? AND household_id = ? AND deletion_date IS NOT NULL
+        RETURNING id as "id!", creation_date, prep_date, name, total_weight, is_finished, version;"#,
+        dish_id,
+        household_id
+    )
+    .fetch_optional(&mut *transaction)
+    .await?
+    .ok_or(RestoreDishError::DishNotInTrash(dish_id))?;
+
+    record_change(&mut *transaction, &user, AuditEntity::Dish, dish_id, None).await?;
+    transaction.commit().await?;
+
+    Ok(ServerResponse::success(dish).json())
+}
@@ -0,0 +1,11 @@
+use aide::axum::ApiRouter;
+
+use crate::state::AppState;
+
+mod _id;
+
+pub fn route(state: AppState) -> ApiRouter {
+    ApiRouter::new()
+        .nest_api_service("/:dish_id", _id::route(state.clone()))
+        .with_state(state)
+}
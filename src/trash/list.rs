@@ -0,0 +1,90 @@
+use axum::extract::State;
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use crate::{
+    server::{ServerResponse, ServerResponseResult},
+    session::AuthUser,
+    state::AppState,
+};
+
+#[derive(Serialize, JsonSchema)]
+pub struct TrashedDish {
+    id: i64,
+    creation_date: i64,
+    name: Option<String>,
+    prep_date: Option<i64>,
+    deletion_date: i64,
+    /// When the dish will be purged for good, unless it's restored before
+    purge_date: i64,
+}
+
+#[derive(Serialize, JsonSchema)]
+pub struct TrashedMeal {
+    id: i64,
+    creation_date: i64,
+    eat_date: Option<i64>,
+    description: Option<String>,
+    deletion_date: i64,
+    /// When the meal will be purged for good, unless it's restored before
+    purge_date: i64,
+}
+
+#[derive(Serialize, JsonSchema)]
+pub struct ListTrashResult {
+    dishes: Vec<TrashedDish>,
+    meals: Vec<TrashedMeal>,
+}
+
+/// The household's deleted dishes and the user's deleted meals, most recently deleted first.
+pub async fn list_trash(
+    State(AppState {
+        connection,
+        trash_retention,
+        ..
+    }): State<AppState>,
+    AuthUser {
+        user_id,
+        household_id,
+    }: AuthUser,
+) -> ServerResponseResult<ListTrashResult> {
+    let dishes = sqlx::query_as!(
+        TrashedDish,
+        r#"
+        SELECT
+            id,
+            creation_date,
+            name,
+            prep_date,
+            deletion_date AS "deletion_date!",
+            deletion_date + ? AS "purge_date!: i64"
+        FROM Dish
+        WHERE household_id = ? AND deletion_date IS NOT NULL
+        ORDER BY deletion_date DESC;"#,
+        trash_retention,
+        household_id
+    )
+    .fetch_all(&connection)
+    .await?;
+
+    let meals = sqlx::query_as!(
+        TrashedMeal,
+        r#"
+        SELECT
+            id,
+            creation_date,
+            eat_date,
+            description,
+            deletion_date AS "deletion_date!",
+            deletion_date + ? AS "purge_date!: i64"
+        FROM Meal
+        WHERE user_id = ? AND deletion_date IS NOT NULL
+        ORDER BY deletion_date DESC;"#,
+        trash_retention,
+        user_id
+    )
+    .fetch_all(&connection)
+    .await?;
+
+    Ok(ServerResponse::success(ListTrashResult { dishes, meals }).json())
+}
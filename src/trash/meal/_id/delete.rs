@@ -0,0 +1,46 @@
+use axum::extract::{Path, State};
+use schemars::JsonSchema;
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::{
+    purge::purge_meal,
+    server::{ServerResponse, ServerResponseResult},
+    session::AuthUser,
+    state::AppState,
+};
+
+#[derive(Error, Debug)]
+enum DeleteTrashedMealError {
+    #[error("Could not find meal with id \"{0}\" in the trash")]
+    MealNotInTrash(i64),
+}
+
+#[derive(Deserialize, JsonSchema)]
+pub struct MealId {
+    meal_id: i64,
+}
+
+/// Purges a meal from the trash right away, instead of waiting for the retention to pass.
+pub async fn delete_trashed_meal(
+    State(AppState { connection, .. }): State<AppState>,
+    AuthUser { user_id, .. }: AuthUser,
+    Path(MealId { meal_id }): Path<MealId>,
+) -> ServerResponseResult<bool> {
+    let mut transaction = connection.begin().await?;
+
+    sqlx::query_scalar!(
+        "SELECT id FROM Meal WHERE id = ? AND user_id = ? AND deletion_date IS NOT NULL",
+        meal_id,
+        user_id
+    )
+    .fetch_optional(&mut *transaction)
+    .await?
+    .ok_or(DeleteTrashedMealError::MealNotInTrash(meal_id))?;
+
+    purge_meal(&mut transaction, meal_id).await?;
+
+    transaction.commit().await?;
+
+    Ok(ServerResponse::success(true).json())
+}
@@ -0,0 +1,16 @@
+use aide::axum::{
+    routing::{delete, post},
+    ApiRouter,
+};
+
+use crate::state::AppState;
+
+mod delete;
+mod restore;
+
+pub fn route(state: AppState) -> ApiRouter {
+    ApiRouter::new()
+        .api_route("/", delete(delete::delete_trashed_meal))
+        .api_route("/restore", post(restore::post_restore_meal))
+        .with_state(state)
+}
@@ -0,0 +1,54 @@
+use axum::extract::{Path, State};
+use schemars::JsonSchema;
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::{
+    audit::{record_change, AuditEntity},
+    events::DomainEvent,
+    models::Meal,
+    server::{ServerResponse, ServerResponseResult},
+    session::AuthUser,
+    state::AppState,
+};
+
+#[derive(Error, Debug)]
+enum RestoreMealError {
+    #[error("Could not find meal with id \"{0}\" in the trash")]
+    MealNotInTrash(i64),
+}
+
+#[derive(Deserialize, JsonSchema)]
+pub struct MealId {
+    meal_id: i64,
+}
+
+/// Takes a meal out of the trash, with all of its dishes and ingredients.
+pub async fn post_restore_meal(
+    State(AppState {
+        connection, events, ..
+    }): State<AppState>,
+    user @ AuthUser { user_id, .. }: AuthUser,
+    Path(MealId { meal_id }): Path<MealId>,
+) -> ServerResponseResult<Meal> {
+    let mut transaction = connection.begin().await?;
+    let meal = sqlx::query_as!(
+        Meal,
+        r#"
+        UPDATE Meal SET deletion_date = NULL
+        WHERE id = ? AND user_id = ? AND deletion_date IS NOT NULL
+        RETURNING id as "id!", creation_date, eat_date, duration, description, version;"#,
+        meal_id,
+        user_id
+    )
+    .fetch_optional(&mut *transaction)
+    .await?
+    .ok_or(RestoreMealError::MealNotInTrash(meal_id))?;
+
+    record_change(&mut *transaction, &user, AuditEntity::Meal, meal_id, None).await?;
+    transaction.commit().await?;
+
+    events.publish(&user, DomainEvent::MealCreated { meal_id });
+
+    Ok(ServerResponse::success(meal).json())
+}
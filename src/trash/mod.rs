@@ -0,0 +1,16 @@
+use aide::axum::{routing::get, ApiRouter};
+
+use crate::state::AppState;
+
+mod delete;
+mod dish;
+mod list;
+mod meal;
+
+pub fn route(state: AppState) -> ApiRouter {
+    ApiRouter::new()
+        .api_route("/", get(list::list_trash).delete(delete::delete_trash))
+        .nest_api_service("/dish", dish::route(state.clone()))
+        .nest_api_service("/meal", meal::route(state.clone()))
+        .with_state(state)
+}